@@ -52,6 +52,40 @@ impl<'a> StmtVisitor<'a> for ItemPathFinder<'a> {
     }
 }
 
+/// Retrieves the path of items descended to a [span](Span), that is, the items whose spans cover
+/// it. Like [`get_item_path_to_offset`](get_item_path_to_offset), the path is ordered from broadest
+/// to narrowest item. For example, in
+///
+/// ```math
+/// a = 1 ^ (2 |+ 3|)
+/// ```
+///
+/// The path would be { "a = 1 ^ (2 + 3)", "1 ^ (2 + 3)", "(2 + 3)", "2 + 3" }.
+pub fn get_item_path_to_span(span: Span, program: &Ast) -> Vec<AstItem> {
+    let mut finder = ItemPathToSpanFinder { span, path: vec![] };
+    finder.visit_stmt_list(program);
+    finder.path
+}
+struct ItemPathToSpanFinder<'a> {
+    span: Span,
+    path: Vec<AstItem<'a>>,
+}
+impl<'a> StmtVisitor<'a> for ItemPathToSpanFinder<'a> {
+    fn visit_asgn(&mut self, asgn: &'a Assignment) {
+        if asgn.span.supersets(self.span) {
+            self.path.push(AstItem::Assignment(asgn));
+            visit::descend_asgn(self, asgn);
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a RcExpr) {
+        if expr.span.supersets(self.span) {
+            self.path.push(AstItem::Expr(expr));
+            visit::descend_expr(self, expr);
+        }
+    }
+}
+
 /// Finds the nearest slide [expression](libslide::Expr) around an offset
 /// position. For example, in
 ///
@@ -6,9 +6,11 @@ mod response;
 mod source_map;
 
 pub(crate) use document::Document;
-pub use document_parser::DocumentParser;
+pub use document_parser::{DocumentParser, ProgramLimits};
+pub use response::no_program_error;
 pub use source_map::SourceMap;
 
+use crate::program::AnalysisCache;
 use crate::ptr::{p, P};
 use crate::Program;
 
@@ -18,13 +20,16 @@ use tower_lsp::lsp_types::{Position, Range, Url};
 
 /// Describes a change to a [`Document`](Document).
 pub enum Change {
+    /// The [`Document`](Document) at the `Url` was opened with a language ID and content.
+    Opened(Url, String, String),
     /// The [`Document`](Document) at the `Url` was modified with new content.
     Modified(Url, String),
     /// The [`Document`](Document) at the `Url` was removed.
     Removed(Url),
 }
 
-/// A mapping between file extensions and a [parser](DocumentParser) for that file type.
+/// A mapping between LSP language IDs or file extensions and a [parser](DocumentParser) for that
+/// kind of document.
 pub type DocumentParserMap = BTreeMap<String, DocumentParser>;
 
 /// A stateful database of [`Document`](Document)s present in a session.
@@ -34,41 +39,142 @@ pub type DocumentParserMap = BTreeMap<String, DocumentParser>;
 /// queries](Self::with_program_at_uri_and_position). Thus, `DocumentRegistry` is the primary
 /// mechanism for interfacing between LSP APIs and [`Program`](crate::Program) APIs.
 pub(crate) struct DocumentRegistry {
-    /// A map of file extensions and a [parser](DocumentParser) for that file type.
+    /// A map of language IDs or file extensions and a [parser](DocumentParser) for that kind of
+    /// document.
     parsers: DocumentParserMap,
     /// The slide [context](libslide::ProgramContext) to use when processing end evaluating slide
     /// programs.
     context: P<libslide::ProgramContext>,
+    /// [Limits](ProgramLimits) beyond which programs of a document are not analyzed.
+    limits: ProgramLimits,
     /// The actual mapping of LSP text documents (represented by a `Url`) to their
     /// [`Document`](Document) representation.
     registry: HashMap<Url, Document>,
+    /// The text of all documents known to the registry, including documents that have no
+    /// [parser](DocumentParser). Used to re-parse documents when the registry is reconfigured.
+    sources: HashMap<Url, String>,
+    /// The language IDs documents were opened with. Used with precedence over file extensions to
+    /// find a document's [parser](DocumentParser).
+    language_ids: HashMap<Url, String>,
+    /// Analyses of programs in the registry, reused by unchanged programs when a document is
+    /// re-parsed. Cleared when the registry is reconfigured.
+    cache: P<AnalysisCache>,
 }
 
 impl DocumentRegistry {
-    /// Creates a new registry with a set of document parsers and slide context.
-    pub fn new(parsers: DocumentParserMap, context: P<libslide::ProgramContext>) -> Self {
+    /// Creates a new registry with a set of document parsers, slide context, and program limits.
+    pub fn new(
+        parsers: DocumentParserMap,
+        context: P<libslide::ProgramContext>,
+        limits: ProgramLimits,
+    ) -> Self {
         Self {
             parsers,
             context,
+            limits,
             registry: Default::default(),
+            sources: Default::default(),
+            language_ids: Default::default(),
+            cache: p(Default::default()),
         }
     }
 
-    /// Applies a (document change)[Change] to the registry.
-    pub fn apply_change(&mut self, apply_change: Change) {
+    /// Applies a (document change)[Change] to the registry. Returns whether the registry had a
+    /// [`Document`](Document) for the changed `Url` before the change was applied.
+    pub fn apply_change(&mut self, apply_change: Change) -> bool {
         match apply_change {
             Change::Removed(fi) => {
-                self.registry.remove(&fi);
+                self.sources.remove(&fi);
+                self.language_ids.remove(&fi);
+                self.registry.remove(&fi).is_some()
+            }
+            Change::Opened(fi, language_id, src) => {
+                self.language_ids.insert(fi.clone(), language_id);
+                self.apply_change(Change::Modified(fi, src))
             }
             Change::Modified(fi, src) => {
-                if let Some(parser) = self.get_parser(&fi) {
-                    let document = parser.parse(&src, p(fi.clone()), self.context.dupe());
-                    self.registry.insert(fi, document);
-                }
+                let previous = self.registry.remove(&fi);
+                let existed = previous.is_some();
+                self.sources.insert(fi.clone(), src);
+                self.reparse(fi, previous);
+                existed
             }
         }
     }
 
+    /// Replaces the document parsers of the registry, re-parsing documents whose parser changed.
+    /// Returns the `Url`s of the re-parsed documents.
+    pub fn set_parsers(&mut self, parsers: DocumentParserMap) -> Vec<Url> {
+        let old_parsers = std::mem::replace(&mut self.parsers, parsers);
+        self.cache.clear();
+        let changed: Vec<_> = self
+            .sources
+            .keys()
+            .filter(|uri| {
+                self.find_parser(&old_parsers, uri) != self.find_parser(&self.parsers, uri)
+            })
+            .cloned()
+            .collect();
+        for uri in changed.iter() {
+            self.parse(uri.clone());
+        }
+        changed
+    }
+
+    /// Replaces the slide context of the registry, re-parsing all parsed documents. Returns the
+    /// `Url`s of the re-parsed documents.
+    pub fn set_context(&mut self, context: P<libslide::ProgramContext>) -> Vec<Url> {
+        self.context = context;
+        self.cache.clear();
+        let parsed: Vec<_> = self.registry.keys().cloned().collect();
+        for uri in parsed.iter() {
+            self.parse(uri.clone());
+        }
+        parsed
+    }
+
+    /// Replaces the program limits of the registry, re-parsing all parsed documents. Returns the
+    /// `Url`s of the re-parsed documents.
+    pub fn set_limits(&mut self, limits: ProgramLimits) -> Vec<Url> {
+        self.limits = limits;
+        self.cache.clear();
+        let parsed: Vec<_> = self.registry.keys().cloned().collect();
+        for uri in parsed.iter() {
+            self.parse(uri.clone());
+        }
+        parsed
+    }
+
+    /// (Re-)parses the known source of the document at `fi`. If the document has no parser, it is
+    /// dropped from the registry.
+    fn parse(&mut self, fi: Url) {
+        self.reparse(fi, None);
+    }
+
+    /// Like [`parse`](Self::parse), but reuses the programs of the `previous` parse of the
+    /// document whose source text is unchanged. See
+    /// [`DocumentParser::reparse`](DocumentParser::reparse).
+    fn reparse(&mut self, fi: Url, previous: Option<Document>) {
+        let previous = previous
+            .map(|document| document.programs)
+            .unwrap_or_default();
+        let document = match (self.get_parser(&fi), self.sources.get(&fi)) {
+            (Some(parser), Some(src)) => parser.reparse(
+                src,
+                p(fi.clone()),
+                self.context.dupe(),
+                self.limits,
+                &self.cache,
+                previous,
+            ),
+            _ => {
+                self.registry.remove(&fi);
+                return;
+            }
+        };
+        self.registry.insert(fi, document);
+    }
+
     /// Retrieves the [`Document`](Document) corresponding to an LSP `Url`, if any.
     pub fn document(&self, uri: &Url) -> Option<&Document> {
         self.registry.get(uri)
@@ -90,6 +196,24 @@ impl DocumentRegistry {
         uri: &Url,
         position: Position,
         callback: impl FnOnce(&Program, usize) -> Option<ProgramResponse>,
+    ) -> Option<ProgramResponse::DocumentResponse> {
+        self.with_program_in_document_at_uri_and_position(
+            uri,
+            position,
+            |program, offset, _document_programs| callback(program, offset),
+        )
+    }
+
+    /// Like [`with_program_at_uri_and_position`](Self::with_program_at_uri_and_position), but also
+    /// provides the callback all [`Program`](Program)s in the document the program at the
+    /// `position` resides in.
+    pub fn with_program_in_document_at_uri_and_position<
+        ProgramResponse: response::IntoDocumentResponse,
+    >(
+        &self,
+        uri: &Url,
+        position: Position,
+        callback: impl FnOnce(&Program, usize, &[Program]) -> Option<ProgramResponse>,
     ) -> Option<ProgramResponse::DocumentResponse> {
         let document = self.document(uri)?;
         let offset_in_document = document.source_map.to_offset(position);
@@ -99,7 +223,7 @@ impl DocumentRegistry {
         let offset_in_program = offset_in_document - program.start;
 
         // Get the program response.
-        let program_response = callback(program, offset_in_program)?;
+        let program_response = callback(program, offset_in_program, &document.programs)?;
 
         // Marshall to absolute position in document and get the document response.
         let to_position = |offset| document.source_map.to_position(offset);
@@ -188,14 +312,30 @@ impl DocumentRegistry {
         Some(response)
     }
 
-    /// Retrieves a [`DocumentParser`](DocumentParser) for the given `Url` by its file extension,
-    /// if one is known.
+    /// Retrieves a [`DocumentParser`](DocumentParser) for the given `Url` by the language ID its
+    /// document was opened with, or otherwise by its file extension, if one is known.
     fn get_parser(&self, uri: &Url) -> Option<&DocumentParser> {
-        let ext = std::path::Path::new(uri.path())
+        self.find_parser(&self.parsers, uri)
+    }
+
+    /// Like [`get_parser`](Self::get_parser), but searches a given map of parsers.
+    fn find_parser<'a>(
+        &self,
+        parsers: &'a DocumentParserMap,
+        uri: &Url,
+    ) -> Option<&'a DocumentParser> {
+        self.language_ids
+            .get(uri)
+            .and_then(|language_id| parsers.get(language_id))
+            .or_else(|| parsers.get(Self::extension(uri)))
+    }
+
+    /// Retrieves the file extension of a `Url`, or an empty string if it has none.
+    fn extension(uri: &Url) -> &str {
+        std::path::Path::new(uri.path())
             .extension()
             .and_then(std::ffi::OsStr::to_str)
-            .unwrap_or_default();
-        self.parsers.get(ext)
+            .unwrap_or_default()
     }
 }
 
@@ -221,6 +361,7 @@ mod document_registry_tests {
         DocumentRegistry::new(
             mk_parsers(&[("slide", "(.*)"), ("math", "(.+)")]),
             p(Default::default()),
+            Default::default(),
         )
     }
 
@@ -240,6 +381,29 @@ mod document_registry_tests {
         }
     }
 
+    #[test]
+    fn get_parser_by_language_id() {
+        let mut registry = SM_registry();
+        for (fi, language_id, parser) in &[
+            ("untitled:Untitled-1", "math", Some("(.+)")),
+            ("file:///fi.txt", "slide", Some("(.*)")),
+            ("file:///fi.slide", "math", Some("(.+)")),
+            ("file:///fi.math", "plaintext", Some("(.+)")),
+            ("untitled:Untitled-2", "plaintext", None),
+        ] {
+            let fi = url(fi);
+            registry.apply_change(Change::Opened(
+                fi.clone(),
+                language_id.to_string(),
+                "1".into(),
+            ));
+            assert_eq!(
+                registry.get_parser(&fi).map(ToString::to_string),
+                parser.map(|s| s.to_owned())
+            );
+        }
+    }
+
     mod changes {
         use super::super::{Change, DocumentRegistry};
         use super::{url, SM_registry};
@@ -292,18 +456,158 @@ mod document_registry_tests {
             assert_eq!(first_program(&registry, &fi_math), "3 + 4");
 
             // Delete fi_slide: fi_math should still be registered
-            registry.apply_change(Change::Removed(fi_slide.clone()));
+            assert!(registry.apply_change(Change::Removed(fi_slide.clone())));
             assert_eq!(registry.registry.len(), 1);
             assert_eq!(first_program(&registry, &fi_math), "3 + 4");
 
             // Add fi_slide: both should be registered
-            registry.apply_change(Change::Modified(fi_slide.clone(), "1 + 10".into()));
+            assert!(!registry.apply_change(Change::Modified(fi_slide.clone(), "1 + 10".into())));
             assert_eq!(registry.registry.len(), 2);
             assert_eq!(first_program(&registry, &fi_slide), "1 + 10");
             assert_eq!(first_program(&registry, &fi_math), "3 + 4");
         }
     }
 
+    mod reuse {
+        use super::*;
+
+        /// Identifies each program of a document by the address of its source text, which a
+        /// reused program keeps and a re-parsed program allocates afresh.
+        fn program_ids(registry: &DocumentRegistry, fi: &Url) -> Vec<*const u8> {
+            registry
+                .document(fi)
+                .unwrap()
+                .programs
+                .iter()
+                .map(|program| program.source.as_ptr())
+                .collect()
+        }
+
+        fn md_registry() -> DocumentRegistry {
+            DocumentRegistry::new(
+                mk_parsers(&[("md", r"```math\n((?:.|\n)*?)\n```")]),
+                p(Default::default()),
+                Default::default(),
+            )
+        }
+
+        #[test]
+        fn edit_reuses_unchanged_programs() {
+            let mut registry = md_registry();
+            let fi_md = url("file:///test.md");
+
+            let before = "```math\n1 + 2\n```\n\n```math\na = 1 +\n```";
+            registry.apply_change(Change::Modified(fi_md.clone(), before.to_string()));
+            let ids = program_ids(&registry, &fi_md);
+
+            // Only the first program changes, moving the second program further down the
+            // document.
+            let after = "```math\n1 + 2 + 3\n```\n\n```math\na = 1 +\n```";
+            registry.apply_change(Change::Modified(fi_md.clone(), after.to_string()));
+            let new_ids = program_ids(&registry, &fi_md);
+            assert_ne!(new_ids[0], ids[0]);
+            assert_eq!(new_ids[1], ids[1]);
+
+            let document = registry.document(&fi_md).unwrap();
+            let second = &document.programs[1];
+            assert_eq!(second.start, after.find("a = 1 +").unwrap());
+            assert_eq!(&after[second.start..second.end], "a = 1 +");
+            assert_eq!(document.program_at(second.start).unwrap().source, "a = 1 +");
+        }
+
+        #[test]
+        fn edit_reuses_reordered_and_duplicate_programs() {
+            let mut registry = md_registry();
+            let fi_md = url("file:///test.md");
+
+            registry.apply_change(Change::Modified(
+                fi_md.clone(),
+                "```math\na\n```\n```math\nb\n```\n```math\na\n```".to_string(),
+            ));
+            let ids = program_ids(&registry, &fi_md);
+
+            registry.apply_change(Change::Modified(
+                fi_md.clone(),
+                "```math\nb\n```\n```math\na\n```\n```math\na\n```\n```math\na\n```".to_string(),
+            ));
+            let new_ids = program_ids(&registry, &fi_md);
+            assert_eq!(new_ids[..3], [ids[1], ids[0], ids[2]]);
+            assert!(!ids.contains(&new_ids[3]));
+        }
+
+        #[test]
+        fn settings_change_reparses_programs() {
+            let mut registry = md_registry();
+            let fi_md = url("file:///test.md");
+
+            registry.apply_change(Change::Modified(
+                fi_md.clone(),
+                "```math\n1 + 2\n```".to_string(),
+            ));
+            let ids = program_ids(&registry, &fi_md);
+
+            registry.set_context(p(Default::default()));
+            assert_ne!(program_ids(&registry, &fi_md), ids);
+        }
+    }
+
+    mod cache {
+        use super::*;
+
+        /// Analyzes all programs of a document, returning the spans of their diagnostics.
+        fn analyze_all(registry: &DocumentRegistry, fi: &Url) -> Vec<Vec<Span>> {
+            registry
+                .document(fi)
+                .unwrap()
+                .programs
+                .iter()
+                .map(|program| program.diagnostics().iter().map(|d| d.span).collect())
+                .collect()
+        }
+
+        #[test]
+        fn reuses_unchanged_programs() {
+            let mut registry = DocumentRegistry::new(
+                mk_parsers(&[("md", r"```math\n((?:.|\n)*?)\n```")]),
+                p(Default::default()),
+                Default::default(),
+            );
+            let fi_md = url("file:///test.md");
+
+            registry.apply_change(Change::Modified(
+                fi_md.clone(),
+                "```math\n1 + 2\n```\n\n```math\na = 1 +\n```".to_string(),
+            ));
+            let spans = analyze_all(&registry, &fi_md);
+            assert_eq!(spans[0], vec![]);
+            assert_eq!(spans[1].len(), 1);
+            assert_eq!(registry.cache.computed(), 2);
+
+            // Only the first program changes, moving the second program further down the
+            // document. The second program's analysis, and its program-relative spans, are reused.
+            registry.apply_change(Change::Modified(
+                fi_md.clone(),
+                "```math\n1 + 2 + 3\n```\n\n```math\na = 1 +\n```".to_string(),
+            ));
+            assert_eq!(analyze_all(&registry, &fi_md), spans);
+            assert_eq!(registry.cache.computed(), 3);
+        }
+
+        #[test]
+        fn invalidated_by_settings_change() {
+            let mut registry = SM_registry();
+            let fi_slide = url("file:///fi.slide");
+
+            registry.apply_change(Change::Modified(fi_slide.clone(), "1 + 2".into()));
+            analyze_all(&registry, &fi_slide);
+            assert_eq!(registry.cache.computed(), 1);
+
+            registry.set_context(p(Default::default()));
+            analyze_all(&registry, &fi_slide);
+            assert_eq!(registry.cache.computed(), 2);
+        }
+    }
+
     mod with_program_at {
         use super::*;
 
@@ -314,6 +618,7 @@ mod document_registry_tests {
             let mut registry = DocumentRegistry::new(
                 mk_parsers(&[("md", r"```math\n((?:.|\n)*?)\n```")]),
                 p(Default::default()),
+                Default::default(),
             );
             let fi_md = url("file:///test.md");
             let fi_content = r" // 0
@@ -41,8 +41,16 @@ impl Document {
 
     /// Retrieves diagnostics across all [Program](crate::Program)s present in this document.
     pub fn all_diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics_of_programs(0..self.programs.len())
+    }
+
+    /// Retrieves diagnostics of the [Program](crate::Program)s at the given indices of this
+    /// document. Indices past the last program are ignored.
+    pub fn diagnostics_of_programs(&self, programs: std::ops::Range<usize>) -> Vec<Diagnostic> {
         let to_position = |offset| self.source_map.to_position(offset);
-        self.programs
+        let programs =
+            programs.start.min(self.programs.len())..programs.end.min(self.programs.len());
+        self.programs[programs]
             .iter()
             .map(|p| {
                 p.diagnostics()
@@ -111,6 +119,8 @@ mod document_tests {
                 content,
                 p(Url::parse("file:///math.md").unwrap()),
                 p(Default::default()),
+                Default::default(),
+                &p(Default::default()),
             )
     }
 
@@ -156,4 +166,68 @@ mod document_tests {
         assert_eq!(document.program_at(start_p1).unwrap().source, "1 + 1");
         assert_eq!(document.program_at(start_p2).unwrap().source, "3 + 3");
     }
+
+    fn three_program_document() -> (&'static str, Document) {
+        let content = r"
+```math
+1 + 1
+```
+
+```math
+2 + 2
+```
+
+```math
+3 + 3
+```";
+        (content, math_document(content))
+    }
+
+    #[test]
+    fn get_program_at_boundaries() {
+        let (content, document) = three_program_document();
+        assert_eq!(document.programs.len(), 3);
+
+        for src in &["1 + 1", "2 + 2", "3 + 3"] {
+            let start = content.find(src).unwrap();
+            let end = start + src.len();
+
+            assert_eq!(document.program_at(start).unwrap().source, *src);
+            assert_eq!(document.program_at(end - 1).unwrap().source, *src);
+            // Programs span [start, end), so the end offset is not in the program.
+            assert!(document.program_at(end).is_none());
+        }
+
+        // Offsets before, between, and after programs are not in any program.
+        assert!(document.program_at(0).is_none());
+        let between = content.find("2 + 2").unwrap() - 2;
+        assert!(document.program_at(between).is_none());
+        assert!(document.program_at(content.len() - 1).is_none());
+    }
+
+    #[test]
+    fn get_program_including() {
+        let (content, document) = three_program_document();
+
+        let start_p2 = content.find("2 + 2").unwrap();
+        let end_p2 = start_p2 + "2 + 2".len();
+        let start_p3 = content.find("3 + 3").unwrap();
+
+        assert_eq!(
+            document.program_including(start_p2, end_p2).unwrap().source,
+            "2 + 2"
+        );
+        assert_eq!(
+            document
+                .program_including(start_p2 + 1, start_p2 + 2)
+                .unwrap()
+                .source,
+            "2 + 2"
+        );
+
+        // Ranges straddling two programs, or leaving a program, are not included in any program.
+        assert!(document.program_including(start_p2, start_p3 + 1).is_none());
+        assert!(document.program_including(start_p2, end_p2 + 1).is_none());
+        assert!(document.program_including(end_p2, start_p3).is_none());
+    }
 }
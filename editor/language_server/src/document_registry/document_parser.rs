@@ -2,17 +2,70 @@
 //! document.
 
 use super::Document;
+use crate::program::{AnalysisCache, AnalysisSkip};
 use crate::ptr::P;
 use crate::Program;
 
 use libslide::ProgramContext;
 use regex::RegexBuilder;
+use std::collections::{HashMap, VecDeque};
 use tower_lsp::lsp_types::Url;
 
 /// Responsible for parsing some kind of document into segements of slide programs.
 /// The client is responsible for determining which documents a `DocumentParser` applies to.
 #[derive(Debug)]
-pub struct DocumentParser(regex::Regex);
+pub struct DocumentParser {
+    extractor: ProgramExtractor,
+    /// Whether programs are parsed as expression patterns, like `_a + #b`, rather than as
+    /// statements.
+    expr_pat: bool,
+}
+
+/// How a [`DocumentParser`](DocumentParser) finds slide programs in a document.
+#[derive(Debug)]
+enum ProgramExtractor {
+    /// Programs are the explicit capturing group of each match of a regex.
+    Regex(regex::Regex),
+    /// Programs are the contents of reStructuredText `.. math::` directives.
+    RstMath,
+}
+
+/// Name of the builtin [`DocumentParser`](DocumentParser) for reStructuredText `.. math::`
+/// directives.
+const RST_MATH_PARSER: &str = "rst-math";
+
+/// Limits on the [`Program`](Program)s of a document beyond which programs are registered but not
+/// analyzed, so that huge generated documents do not stall the server.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProgramLimits {
+    /// Programs after the first `max_programs` programs of a document are not analyzed.
+    pub max_programs: usize,
+    /// Programs longer than `max_program_length` bytes are not analyzed.
+    pub max_program_length: usize,
+}
+
+impl Default for ProgramLimits {
+    fn default() -> Self {
+        Self {
+            max_programs: 1000,
+            max_program_length: 10_000,
+        }
+    }
+}
+
+impl ProgramLimits {
+    /// Returns why the `index`th program of a document, with source `program`, should not be
+    /// analyzed, if it exceeds a limit.
+    fn skip(&self, index: usize, program: &str) -> Option<AnalysisSkip> {
+        if index >= self.max_programs {
+            Some(AnalysisSkip::TooManyPrograms(self.max_programs))
+        } else if program.len() > self.max_program_length {
+            Some(AnalysisSkip::TooLong(self.max_program_length))
+        } else {
+            None
+        }
+    }
+}
 
 impl DocumentParser {
     /// Creates a new document parser from a multi-line regex description of what slide program
@@ -42,49 +95,231 @@ impl DocumentParser {
             )));
         }
 
-        Ok(Self(re))
+        Ok(Self {
+            extractor: ProgramExtractor::Regex(re),
+            expr_pat: false,
+        })
+    }
+
+    /// Creates a builtin document parser by name, for documents whose slide program blocks cannot
+    /// be described by a regex. Returns [None](Option::None) if there is no such builtin.
+    ///
+    /// The builtins are
+    /// - `rst-math`: the contents of reStructuredText `.. math::` directives. Content on the line
+    ///   of a directive is one program, and the indented body of the directive, after any
+    ///   `:option:` lines, is another.
+    pub fn builtin(name: &str) -> Option<DocumentParser> {
+        let extractor = match name {
+            RST_MATH_PARSER => ProgramExtractor::RstMath,
+            _ => return None,
+        };
+        Some(Self {
+            extractor,
+            expr_pat: false,
+        })
+    }
+
+    /// Makes the parser parse programs as expression patterns, like `_a + #b`, rather than as
+    /// statements.
+    pub fn expr_pat(mut self, expr_pat: bool) -> Self {
+        self.expr_pat = expr_pat;
+        self
     }
 
     /// Parses a document's source text with this document parser, returning a fresh
-    /// [`Document`](Document) with all discovered [`Program`](Program)s.
+    /// [`Document`](Document) with all discovered [`Program`](Program)s. Programs exceeding the
+    /// `limits` are not analyzed, and programs share analyses through the `cache`.
+    #[cfg(test)]
     pub(crate) fn parse(
         &self,
         document_source: &str,
         document_uri: P<Url>,
         context: P<ProgramContext>,
+        limits: ProgramLimits,
+        cache: &P<AnalysisCache>,
+    ) -> Document {
+        self.reparse(
+            document_source,
+            document_uri,
+            context,
+            limits,
+            cache,
+            Vec::new(),
+        )
+    }
+
+    /// Like [`parse`](Self::parse), but reuses the `previous` [`Program`](Program)s of the
+    /// document whose source text is unchanged, only moving them to their new offsets. A reused
+    /// program keeps any analysis already performed for it.
+    ///
+    /// The `previous` programs must have been parsed with the same document URI, context, and
+    /// limits as the new ones.
+    pub(crate) fn reparse(
+        &self,
+        document_source: &str,
+        document_uri: P<Url>,
+        context: P<ProgramContext>,
+        limits: ProgramLimits,
+        cache: &P<AnalysisCache>,
+        previous: Vec<Program>,
     ) -> Document {
+        // Program source -> previous programs with that source, in document order.
+        let mut previous_by_source = HashMap::<String, VecDeque<Program>>::new();
+        for program in previous {
+            previous_by_source
+                .entry(program.source.clone())
+                .or_default()
+                .push_back(program);
+        }
+
         let programs = self
-            .0
-            .captures_iter(&document_source)
-            .map(|segment| {
-                let program = segment
-                    .get(1)
-                    .expect("Inconsistent state: parser missing first capturing group");
-                Program::new(
-                    program.as_str().to_owned(),
-                    document_uri.dupe(),
-                    program.start(),
-                    program.end(),
-                    context.dupe(),
-                )
+            .program_ranges(document_source)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (start, end))| {
+                let source = &document_source[start..end];
+                let skip = limits.skip(i, source);
+                let reusable = previous_by_source.get_mut(source).and_then(|programs| {
+                    let reusable = programs.iter().position(|program| {
+                        program.analysis_skip() == skip && program.is_expr_pat() == self.expr_pat
+                    })?;
+                    programs.remove(reusable)
+                });
+                if let Some(mut program) = reusable {
+                    program.start = start;
+                    program.end = end;
+                    return program;
+                }
+
+                match skip {
+                    None => Program::new(
+                        source.to_owned(),
+                        document_uri.dupe(),
+                        start,
+                        end,
+                        context.dupe(),
+                    ),
+                    Some(skip) => Program::unanalyzed(
+                        source.to_owned(),
+                        document_uri.dupe(),
+                        start,
+                        end,
+                        context.dupe(),
+                        skip,
+                    ),
+                }
+                .with_expr_pat(self.expr_pat)
+                .with_cache(cache.dupe())
             })
             .collect();
 
         Document::new(&document_source, programs)
     }
+
+    /// Returns the start and end offsets of each slide program in a document, in order.
+    fn program_ranges(&self, document_source: &str) -> Vec<(usize, usize)> {
+        match &self.extractor {
+            ProgramExtractor::Regex(re) => re
+                .captures_iter(document_source)
+                .map(|segment| {
+                    let program = segment
+                        .get(1)
+                        .expect("Inconsistent state: parser missing first capturing group");
+                    (program.start(), program.end())
+                })
+                .collect(),
+            ProgramExtractor::RstMath => rst_math_ranges(document_source),
+        }
+    }
+}
+
+/// Returns the start and end offsets of the contents of each reStructuredText `.. math::`
+/// directive in a document. For example, in
+///
+/// ```rst
+/// .. math:: a + b
+///    :label: sum
+///
+///    c := a + b
+///    d := c ^ 2
+/// ```
+///
+/// the contents are `a + b` and the two lines defining `c` and `d`.
+fn rst_math_ranges(document_source: &str) -> Vec<(usize, usize)> {
+    const DIRECTIVE: &str = ".. math::";
+
+    // Each line with its offset in the document, excluding line terminators.
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in document_source.split('\n') {
+        lines.push((offset, line.trim_end_matches('\r')));
+        offset += line.len() + 1;
+    }
+    let indent = |line: &str| line.len() - line.trim_start().len();
+
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let (offset, line) = lines[i];
+        i += 1;
+        let rest = match line.trim_start().strip_prefix(DIRECTIVE) {
+            Some(rest) => rest,
+            None => continue,
+        };
+
+        let inline = rest.trim();
+        if !inline.is_empty() {
+            let start = offset + line.len() - rest.trim_start().len();
+            ranges.push((start, start + inline.len()));
+        }
+
+        // The body is the following lines indented past the directive. Options of the directive
+        // directly follow it, and are not part of the body.
+        let directive_indent = indent(line);
+        let mut in_options = true;
+        let mut body: Option<(usize, usize)> = None;
+        while i < lines.len() {
+            let (offset, line) = lines[i];
+            let content = line.trim();
+            if content.is_empty() {
+                in_options = false;
+            } else if indent(line) <= directive_indent {
+                break;
+            } else if !(in_options && content.starts_with(':')) {
+                in_options = false;
+                let start = offset + indent(line);
+                let end = start + content.len();
+                body = Some((body.map_or(start, |(start, _)| start), end));
+            }
+            i += 1;
+        }
+        ranges.extend(body);
+    }
+    ranges
 }
 
 impl std::cmp::PartialEq for DocumentParser {
-    /// Two document parsers are equal iff their regex representations are equivalent.
+    /// Two document parsers are equal iff their regex representations or builtins are equivalent
+    /// and they parse programs the same way.
     fn eq(&self, other: &Self) -> bool {
-        self.0.as_str().eq(other.0.as_str())
+        let same_extractor = match (&self.extractor, &other.extractor) {
+            (ProgramExtractor::Regex(re), ProgramExtractor::Regex(other)) => {
+                re.as_str() == other.as_str()
+            }
+            (ProgramExtractor::RstMath, ProgramExtractor::RstMath) => true,
+            _ => false,
+        };
+        same_extractor && self.expr_pat == other.expr_pat
     }
 }
 
 impl std::fmt::Display for DocumentParser {
-    /// Formats the parser as its regex representation.
+    /// Formats the parser as its regex representation, or the name of its builtin.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.0.as_str().fmt(f)
+        match &self.extractor {
+            ProgramExtractor::Regex(re) => re.as_str().fmt(f),
+            ProgramExtractor::RstMath => RST_MATH_PARSER.fmt(f),
+        }
     }
 }
 
@@ -138,6 +373,7 @@ error: unclosed character class"
     }
 
     mod parse {
+        use super::super::ProgramLimits;
         use super::DocumentParser;
         use crate::ptr::p;
         use pretty_assertions::assert_eq;
@@ -163,7 +399,13 @@ f = 9 * 8
             let context = p(libslide::ProgramContext::default());
 
             let parser = DocumentParser::build(r"```math\n((?:.|\n)*?)\n```").unwrap();
-            let document = parser.parse(document_content, uri.dupe(), context.dupe());
+            let document = parser.parse(
+                document_content,
+                uri.dupe(),
+                context.dupe(),
+                ProgramLimits::default(),
+                &p(Default::default()),
+            );
 
             assert_eq!(document.programs.len(), 2);
             let p1 = &document.programs[0];
@@ -181,5 +423,110 @@ f = 9 * 8
                 "e = a + b / c ^ d\nf = 9 * 8"
             );
         }
+
+        #[test]
+        fn parse_rst_document() {
+            let document_content = r"
+Sums
+====
+
+.. math:: 1 + 2
+
+.. math::
+   :label: definitions
+
+   a := 1 + 2
+
+   b := a ^ 2
+
+Not math.
+
+  .. math::
+
+     c := 3
+";
+            let uri = p(Url::parse("file:///test").unwrap());
+            let context = p(libslide::ProgramContext::default());
+
+            let parser = DocumentParser::builtin("rst-math").unwrap();
+            let document = parser.parse(
+                document_content,
+                uri,
+                context,
+                ProgramLimits::default(),
+                &p(Default::default()),
+            );
+
+            let programs: Vec<_> = document
+                .programs
+                .iter()
+                .map(|program| {
+                    assert_eq!(
+                        &document_content[program.start..program.end],
+                        program.source
+                    );
+                    program.source.as_str()
+                })
+                .collect();
+            assert_eq!(
+                programs,
+                vec!["1 + 2", "a := 1 + 2\n\n   b := a ^ 2", "c := 3"]
+            );
+        }
+
+        #[test]
+        fn parse_document_over_limits() {
+            let document_content = "[1 + 2] [3 + 4 + 5] [6 + 7] [8 + 9]";
+            let uri = p(Url::parse("file:///test").unwrap());
+            let context = p(libslide::ProgramContext::default());
+            let limits = ProgramLimits {
+                max_programs: 3,
+                max_program_length: 5,
+            };
+
+            let parser = DocumentParser::build(r"\[(.*?)\]").unwrap();
+            let document = parser.parse(
+                document_content,
+                uri,
+                context,
+                limits,
+                &p(Default::default()),
+            );
+
+            assert_eq!(document.programs.len(), 4);
+            let analyzed: Vec<_> = document
+                .programs
+                .iter()
+                .map(|program| program.is_analyzed())
+                .collect();
+            assert_eq!(analyzed, vec![true, false, true, false]);
+        }
+
+        #[test]
+        fn parse_expr_pat_document() {
+            let uri = p(Url::parse("file:///test").unwrap());
+            let context = p(libslide::ProgramContext::default());
+
+            let parser = DocumentParser::build("(.*)").unwrap();
+            let document = parser.parse(
+                "_a + _a",
+                uri.dupe(),
+                context.dupe(),
+                ProgramLimits::default(),
+                &p(Default::default()),
+            );
+            assert!(!document.programs[0].is_expr_pat());
+
+            let parser = DocumentParser::build("(.*)").unwrap().expr_pat(true);
+            let document = parser.parse(
+                "_a + _a",
+                uri,
+                context,
+                ProgramLimits::default(),
+                &p(Default::default()),
+            );
+            assert!(document.programs[0].is_expr_pat());
+            assert!(document.programs[0].diagnostics().is_empty());
+        }
     }
 }
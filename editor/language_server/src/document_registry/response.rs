@@ -4,6 +4,7 @@
 //! implementation of the [`ToDocumentResponse`](ToDocumentResponse) trait. All such
 //! implementations should reside in this module.
 
+use crate::evaluate::EvaluateResult;
 use crate::program::response::*;
 use std::collections::HashMap;
 use tower_lsp::lsp_types::*;
@@ -36,6 +37,34 @@ macro_rules! to_range {
 enum ServerErrorCode {
     // Rename errors
     CursorNotOverVariable = 100,
+    InvalidVariableName = 101,
+    // Evaluate errors
+    NoProgram = 200,
+    RangeNotOverItem = 201,
+    PositionNotOverItem = 202,
+}
+
+/// Returns the error for a query on a document or range that includes no slide program.
+pub fn no_program_error() -> tower_lsp::jsonrpc::Error {
+    use tower_lsp::jsonrpc::{Error, ErrorCode};
+    Error {
+        code: ErrorCode::ServerError(ServerErrorCode::NoProgram as i64),
+        message: "no slide program found".to_owned(),
+        data: None,
+    }
+}
+
+impl IntoDocumentResponse for libslide::InternedStr {
+    type DocumentResponse = libslide::InternedStr;
+
+    /// Names are independent of their position in a document, so this conversion is an identity.
+    fn into_document_response(
+        self,
+        _program_offset: usize,
+        _o2p: &impl Fn(usize) -> Position,
+    ) -> Self::DocumentResponse {
+        self
+    }
 }
 
 impl IntoDocumentResponse for ProgramLocation {
@@ -225,6 +254,7 @@ impl IntoDocumentResponse for ProgramSymbolKind {
         _o2p: &impl Fn(usize) -> Position,
     ) -> Self::DocumentResponse {
         match self {
+            Self::Program => SymbolKind::Module,
             Self::Variable => SymbolKind::Variable,
         }
     }
@@ -262,6 +292,42 @@ impl IntoDocumentResponse for Vec<ProgramSymbolInformation> {
     }
 }
 
+impl IntoDocumentResponse for ProgramDocumentSymbol {
+    type DocumentResponse = DocumentSymbol;
+
+    fn into_document_response(
+        self,
+        program_offset: usize,
+        o2p: &impl Fn(usize) -> Position,
+    ) -> Self::DocumentResponse {
+        let ProgramDocumentSymbol {
+            name,
+            kind,
+            span,
+            selection_span,
+            children,
+        } = self;
+        let children = children
+            .into_iter()
+            .map(|child| child.into_document_response(program_offset, o2p))
+            .collect::<Vec<_>>();
+        #[allow(deprecated)]
+        DocumentSymbol {
+            name,
+            detail: None,
+            kind: kind.into_document_response(program_offset, o2p),
+            deprecated: None,
+            range: to_range!(o2p, program_offset, span),
+            selection_range: to_range!(o2p, program_offset, selection_span),
+            children: if children.is_empty() {
+                None
+            } else {
+                Some(children)
+            },
+        }
+    }
+}
+
 impl IntoDocumentResponse for ProgramTextEdit {
     type DocumentResponse = TextEdit;
 
@@ -278,6 +344,20 @@ impl IntoDocumentResponse for ProgramTextEdit {
     }
 }
 
+impl IntoDocumentResponse for Vec<ProgramTextEdit> {
+    type DocumentResponse = Vec<TextEdit>;
+
+    fn into_document_response(
+        self,
+        program_offset: usize,
+        o2p: &impl Fn(usize) -> Position,
+    ) -> Self::DocumentResponse {
+        self.into_iter()
+            .map(|edit| edit.into_document_response(program_offset, o2p))
+            .collect()
+    }
+}
+
 impl IntoDocumentResponse for ProgramCanRenameResponse {
     type DocumentResponse = PrepareRenameResponse;
 
@@ -306,12 +386,16 @@ impl IntoDocumentResponse for ProgramCannotRenameBecause {
         let (code, message) = match self {
             ProgramCannotRenameBecause::CursorNotOverVariable => (
                 ServerErrorCode::CursorNotOverVariable,
-                "cursor is not over a variable",
+                "cursor is not over a variable".to_owned(),
+            ),
+            ProgramCannotRenameBecause::InvalidVariableName(name) => (
+                ServerErrorCode::InvalidVariableName,
+                format!("`{}` is not a valid variable name", name),
             ),
         };
         Error {
             code: ErrorCode::ServerError(code as i64),
-            message: message.to_owned(),
+            message,
             data: None,
         }
     }
@@ -356,6 +440,92 @@ impl IntoDocumentResponse for ProgramRenameResponse {
     }
 }
 
+impl IntoDocumentResponse for Result<Option<ProgramRenameResponse>, ProgramCannotRenameBecause> {
+    type DocumentResponse = tower_lsp::jsonrpc::Result<Option<WorkspaceEdit>>;
+
+    fn into_document_response(
+        self,
+        program_offset: usize,
+        o2p: &impl Fn(usize) -> Position,
+    ) -> Self::DocumentResponse {
+        self.map(|v| v.map(|v| v.into_document_response(program_offset, o2p)))
+            .map_err(|e| e.into_document_response(program_offset, o2p))
+    }
+}
+
+impl IntoDocumentResponse for ProgramEvaluation {
+    type DocumentResponse = EvaluateResult;
+
+    fn into_document_response(
+        self,
+        program_offset: usize,
+        o2p: &impl Fn(usize) -> Position,
+    ) -> Self::DocumentResponse {
+        let ProgramEvaluation {
+            result,
+            diagnostics,
+        } = self;
+        EvaluateResult {
+            result,
+            diagnostics: diagnostics.into_document_response(program_offset, o2p),
+        }
+    }
+}
+
+impl IntoDocumentResponse for ProgramCannotEvaluateBecause {
+    type DocumentResponse = tower_lsp::jsonrpc::Error;
+
+    fn into_document_response(
+        self,
+        _program_offset: usize,
+        _o2p: &impl Fn(usize) -> Position,
+    ) -> Self::DocumentResponse {
+        use tower_lsp::jsonrpc::{Error, ErrorCode};
+        let (code, message) = match self {
+            ProgramCannotEvaluateBecause::RangeNotOverItem => (
+                ServerErrorCode::RangeNotOverItem,
+                "range does not select a program, statement, or expression".to_owned(),
+            ),
+            ProgramCannotEvaluateBecause::PositionNotOverItem => (
+                ServerErrorCode::PositionNotOverItem,
+                "position is not over a statement".to_owned(),
+            ),
+        };
+        Error {
+            code: ErrorCode::ServerError(code as i64),
+            message,
+            data: None,
+        }
+    }
+}
+
+impl IntoDocumentResponse for Result<ProgramEvaluation, ProgramCannotEvaluateBecause> {
+    type DocumentResponse = tower_lsp::jsonrpc::Result<EvaluateResult>;
+
+    fn into_document_response(
+        self,
+        program_offset: usize,
+        o2p: &impl Fn(usize) -> Position,
+    ) -> Self::DocumentResponse {
+        self.map(|v| v.into_document_response(program_offset, o2p))
+            .map_err(|e| e.into_document_response(program_offset, o2p))
+    }
+}
+
+impl IntoDocumentResponse for Result<String, ProgramCannotEvaluateBecause> {
+    type DocumentResponse = tower_lsp::jsonrpc::Result<String>;
+
+    /// A simplified statement is independent of its position in a document, so only the error
+    /// is converted.
+    fn into_document_response(
+        self,
+        program_offset: usize,
+        o2p: &impl Fn(usize) -> Position,
+    ) -> Self::DocumentResponse {
+        self.map_err(|e| e.into_document_response(program_offset, o2p))
+    }
+}
+
 impl IntoDocumentResponse for ProgramFoldingRanges {
     type DocumentResponse = Vec<FoldingRange>;
 
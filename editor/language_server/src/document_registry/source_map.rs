@@ -1,137 +1,36 @@
 //! Module `source_map` represents a [`Document`](super::Document)'s text source and provides
 //! method to convert between offsets and positions in the source.
 
-use parking_lot::RwLock;
-use std::collections::HashMap;
+use libslide::{LineCol, SourceIndex};
 use tower_lsp::lsp_types::Position;
 
 /// Describes a source text, providing mappings between byte offsets and line/column positions in
-/// the source text.
+/// the source text. Columns are counted in bytes.
 pub struct SourceMap {
-    /// The lines in the source. Each line is represented by the byte offset of the start of the
-    /// line and the length of the line.
-    lines: Vec<(
-        /* offset of line start */ usize,
-        /* line length */ usize,
-    )>,
-    /// A cache of line/column positions -> byte offset mappings.
-    cache_position2offset: RwLock<HashMap<WrappedPosition, usize>>,
-    /// A cache of byte offset -> line/column positions mappings.
-    cache_offset2position: RwLock<HashMap<usize, WrappedPosition>>,
+    /// The line index of the source, with offsets in bytes.
+    index: SourceIndex,
 }
 
 impl SourceMap {
     /// Creates a new `SourceFile`.
     pub fn new(source: &str) -> Self {
-        let mut offset = 0;
-        let mut lines: Vec<_> = LinesWithEndings::from(source)
-            .into_iter()
-            .map(|line| {
-                let line_offset_and_width = (offset, line.len() - 1);
-                offset += line.len();
-                line_offset_and_width
-            })
-            .collect();
-        if let Some(l) = lines.last_mut() {
-            l.1 += 1;
-        }
-
         Self {
-            lines,
-            cache_offset2position: Default::default(),
-            cache_position2offset: Default::default(),
+            index: SourceIndex::new(source),
         }
     }
 
     /// Returns the byte offset corresponding to a line/column position in the source.
     pub fn to_offset(&self, position: Position) -> usize {
-        let position = WrappedPosition::from(position);
-        if !self.cache_position2offset.read().contains_key(&position) {
-            let (line_start_offset, _) = self.lines[position.line];
-            let offset = line_start_offset + position.co;
-            self.cache_position2offset.write().insert(position, offset);
-        }
-
-        *self.cache_position2offset.read().get(&position).unwrap()
+        self.index.offset(LineCol::new(
+            position.line as usize,
+            position.character as usize,
+        ))
     }
 
     /// Returns the line/column position corresponding to a byte offset in the source.
     pub fn to_position(&self, offset: usize) -> Position {
-        if !self.cache_offset2position.read().contains_key(&offset) {
-            let line = self
-                .lines
-                .binary_search_by(|&(line_start_offset, line_len)| {
-                    if offset < line_start_offset {
-                        std::cmp::Ordering::Greater
-                    } else if offset > line_start_offset + line_len {
-                        std::cmp::Ordering::Less
-                    } else {
-                        std::cmp::Ordering::Equal
-                    }
-                })
-                .expect("Offset not found!");
-            let (line_start_offset, _) = self.lines[line];
-            let position = WrappedPosition::new(line, offset - line_start_offset);
-            self.cache_offset2position.write().insert(offset, position);
-        }
-
-        (*self.cache_offset2position.read().get(&offset).unwrap()).into()
-    }
-}
-
-/// Iterator yielding every line in a string. The line includes the newline character.
-// TODO: use `str#split_inclusive` after it's made stable (https://github.com/rust-lang/rust/issues/72360)
-pub struct LinesWithEndings<'a> {
-    input: &'a str,
-}
-
-impl<'a> LinesWithEndings<'a> {
-    pub fn from(input: &'a str) -> LinesWithEndings<'a> {
-        LinesWithEndings { input }
-    }
-}
-
-impl<'a> Iterator for LinesWithEndings<'a> {
-    type Item = &'a str;
-
-    #[inline]
-    fn next(&mut self) -> Option<&'a str> {
-        if self.input.is_empty() {
-            return None;
-        }
-        let split = self
-            .input
-            .find('\n')
-            .map(|i| i + 1)
-            .unwrap_or_else(|| self.input.len());
-        let (line, rest) = self.input.split_at(split);
-        self.input = rest;
-        Some(line)
-    }
-}
-
-/// Shim for the LSP `Position` interface for use in a `SourceMap`.
-#[derive(Hash, Eq, PartialEq, Copy, Clone)]
-struct WrappedPosition {
-    line: usize,
-    co: usize,
-}
-
-impl WrappedPosition {
-    fn new(line: usize, co: usize) -> Self {
-        Self { line, co }
-    }
-}
-
-impl From<WrappedPosition> for Position {
-    fn from(pos: WrappedPosition) -> Position {
-        Position::new(pos.line as u64, pos.co as u64)
-    }
-}
-
-impl From<Position> for WrappedPosition {
-    fn from(pos: Position) -> WrappedPosition {
-        WrappedPosition::new(pos.line as usize, pos.character as usize)
+        let LineCol { line, col } = self.index.line_col(offset);
+        Position::new(line as u64, col as u64)
     }
 }
 
@@ -180,4 +79,20 @@ text";
             assert_eq!(source_map.to_offset(position), offset);
         }
     }
+
+    #[test]
+    fn source_map_crlf_multibyte() {
+        let source = "π = 1\r\nab";
+        let source_map = SourceMap::new(source);
+        let b = source.find('b').unwrap();
+        assert_eq!(source_map.to_position(b), Position::new(1, 1));
+        assert_eq!(source_map.to_offset(Position::new(1, 1)), b);
+        // `π` is two bytes.
+        assert_eq!(source_map.to_position(3), Position::new(0, 3));
+        // Positions past the end of a line are placed before its terminator.
+        assert_eq!(
+            source_map.to_offset(Position::new(0, 10)),
+            source.find('\r').unwrap()
+        );
+    }
 }
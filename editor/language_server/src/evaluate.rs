@@ -0,0 +1,116 @@
+//! Module `evaluate` describes the `slide/evaluate` command, with which clients can request the
+//! simplified form of a document or a range in it, for example to offer an "evaluate selection"
+//! command, and the `slide.simplify` command, with which clients can request the simplified form
+//! of the statement at a position.
+//!
+//! tower-lsp 0.13 does not support registering custom JSON-RPC methods, so `slide/evaluate` is
+//! served as a `workspace/executeCommand` command whose single argument is an
+//! [`EvaluateParams`](EvaluateParams) object.
+
+use libslide::{EmitConfig, EmitFormat};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tower_lsp::jsonrpc::{Error, Result};
+use tower_lsp::lsp_types::{Diagnostic, Position, Range, Url};
+
+use std::convert::TryFrom;
+
+/// Name of the command evaluating a document.
+pub const EVALUATE_COMMAND: &str = "slide/evaluate";
+
+/// Name of the command simplifying the statement at a position.
+pub const SIMPLIFY_COMMAND: &str = "slide.simplify";
+
+/// Parameters of the [`slide/evaluate`](EVALUATE_COMMAND) command.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateParams {
+    /// The document to evaluate.
+    pub uri: Url,
+    /// The range in the document to evaluate. If omitted, all programs in the document are
+    /// evaluated.
+    pub range: Option<Range>,
+    /// One of `pretty`, `s-expression`, `latex`, `debug`, or `json`. Defaults to `pretty`.
+    emit_format: Option<String>,
+    /// Emit configuration options, like `["frac", "times"]`. Defaults to the server's
+    /// configured `emit_config`.
+    emit_config: Option<Vec<String>>,
+}
+
+impl EvaluateParams {
+    /// Parses the parameters from the arguments of a `workspace/executeCommand` request.
+    pub fn from_arguments(arguments: Vec<Value>) -> Result<Self> {
+        let mut arguments = arguments.into_iter();
+        match (arguments.next(), arguments.next()) {
+            (Some(params), None) => serde_json::from_value(params)
+                .map_err(|e| Error::invalid_params(format!("invalid parameters: {}", e))),
+            _ => Err(Error::invalid_params(format!(
+                "`{}` expects exactly one argument",
+                EVALUATE_COMMAND
+            ))),
+        }
+    }
+
+    /// Returns the requested emit format, or an error if it is unknown.
+    pub fn emit_format(&self) -> Result<EmitFormat> {
+        match self.emit_format.as_deref() {
+            None => Ok(EmitFormat::Pretty),
+            Some(format) => EmitFormat::try_from(format)
+                .map_err(|_| Error::invalid_params(format!("`{}` is not an emit format", format))),
+        }
+    }
+
+    /// Returns the requested emit configuration, or `default` if none was requested. Returns an
+    /// error if any requested option is unknown.
+    pub fn emit_config(&self, default: EmitConfig) -> Result<EmitConfig> {
+        let opts = match &self.emit_config {
+            None => return Ok(default),
+            Some(opts) => opts,
+        };
+        opts.iter()
+            .map(|opt| {
+                EmitConfig::try_from(opt.as_str()).map_err(|_| {
+                    Error::invalid_params(format!("`{}` is not an emit configuration option", opt))
+                })
+            })
+            .collect()
+    }
+}
+
+/// The result of the [`slide/evaluate`](EVALUATE_COMMAND) command.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct EvaluateResult {
+    /// The simplified form of the evaluated programs or range, emitted in the requested format.
+    /// Results of multiple programs are separated by newlines.
+    pub result: String,
+    /// Diagnostics of the evaluated programs or range.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Parameters of the [`slide.simplify`](SIMPLIFY_COMMAND) command.
+pub struct SimplifyParams {
+    /// The document to simplify a statement in.
+    pub uri: Url,
+    /// The position of the statement to simplify.
+    pub position: Position,
+}
+
+impl SimplifyParams {
+    /// Parses the parameters from the arguments of a `workspace/executeCommand` request, which
+    /// are the document URI and the position, in that order.
+    pub fn from_arguments(arguments: Vec<Value>) -> Result<Self> {
+        let invalid_params =
+            |e: serde_json::Error| Error::invalid_params(format!("invalid parameters: {}", e));
+        let mut arguments = arguments.into_iter();
+        match (arguments.next(), arguments.next(), arguments.next()) {
+            (Some(uri), Some(position), None) => Ok(Self {
+                uri: serde_json::from_value(uri).map_err(invalid_params)?,
+                position: serde_json::from_value(position).map_err(invalid_params)?,
+            }),
+            _ => Err(Error::invalid_params(format!(
+                "`{}` expects a document URI and a position",
+                SIMPLIFY_COMMAND
+            ))),
+        }
+    }
+}
@@ -1,19 +1,144 @@
-//! Module `init` describes initialization options of the slide language server.
+//! Module `init` describes initialization options and runtime settings of the slide language
+//! server.
 
-use crate::document_registry::{DocumentParser, DocumentParserMap};
+use crate::document_registry::{DocumentParser, DocumentParserMap, ProgramLimits};
 
+use libslide::{EmitConfig, ProgramContext, ProgramContextBuilder};
 use serde::Deserialize;
 use serde_json::Value;
 use std::collections::BTreeMap;
+use std::convert::TryFrom;
 
 /// Initialization options available to the server.
 #[derive(Default, Debug, PartialEq)]
 pub struct InitializationOptions {
-    /// Document file extension -> [`DocumentParser`](DocumentParser) for that document.
+    /// Document language ID or file extension -> [`DocumentParser`](DocumentParser) for that
+    /// document.
     pub document_parsers: DocumentParserMap,
+    /// Initial [settings](Settings) of the server session.
+    pub settings: Settings,
 }
 
-/// A diagnostic produced while constructing [`InitializationOptions`](InitializationOptions).
+/// Settings of a server session, which may be changed by the client at runtime.
+#[derive(Debug, PartialEq)]
+pub struct Settings {
+    /// Whether slide programs should be linted.
+    pub lint: bool,
+    /// The emit configuration used when formatting slide programs.
+    pub emit_config: EmitConfig,
+    /// The scope in which references to a variable are searched for.
+    pub reference_scope: ReferenceScope,
+    /// Limits beyond which programs of a document are not analyzed.
+    pub program_limits: ProgramLimits,
+    /// Options of the [context](ProgramContext) slide programs are evaluated with. The options are
+    /// always valid, and their lint option is overridden by [`lint`](Settings::lint).
+    pub context: ProgramContextBuilder,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            lint: true,
+            emit_config: EmitConfig::default(),
+            reference_scope: ReferenceScope::default(),
+            program_limits: ProgramLimits::default(),
+            context: ProgramContextBuilder::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Applies the settings present in a [`ConfigurationChange`](ConfigurationChange) to `self`.
+    pub fn apply(&mut self, change: &ConfigurationChange) {
+        if let Some(lint) = change.lint {
+            self.lint = lint;
+        }
+        if let Some(emit_config) = change.emit_config {
+            self.emit_config = emit_config;
+        }
+        if let Some(reference_scope) = change.reference_scope {
+            self.reference_scope = reference_scope;
+        }
+        if let Some(max_programs) = change.max_programs_per_document {
+            self.program_limits.max_programs = max_programs;
+        }
+        if let Some(max_program_length) = change.max_program_length {
+            self.program_limits.max_program_length = max_program_length;
+        }
+        if let Some(context) = &change.context {
+            self.context = context.clone();
+        }
+    }
+
+    /// Returns the [context](ProgramContext) slide programs should be evaluated with.
+    pub fn program_context(&self) -> ProgramContext {
+        // The context options are validated before they are applied to the settings, and linting
+        // does not conflict with any other option.
+        self.context
+            .clone()
+            .lint(self.lint)
+            .build()
+            .expect("Settings have an invalid context")
+    }
+}
+
+/// A change of server options requested by the client via `workspace/didChangeConfiguration`.
+/// Options absent from the change keep their current values.
+#[derive(Default, Debug, PartialEq)]
+pub struct ConfigurationChange {
+    /// New document parsers, replacing all current document parsers.
+    pub document_parsers: Option<DocumentParserMap>,
+    /// See [`Settings::lint`](Settings::lint).
+    pub lint: Option<bool>,
+    /// See [`Settings::emit_config`](Settings::emit_config).
+    pub emit_config: Option<EmitConfig>,
+    /// See [`Settings::reference_scope`](Settings::reference_scope).
+    pub reference_scope: Option<ReferenceScope>,
+    /// See [`ProgramLimits::max_programs`](ProgramLimits::max_programs).
+    pub max_programs_per_document: Option<usize>,
+    /// See [`ProgramLimits::max_program_length`](ProgramLimits::max_program_length).
+    pub max_program_length: Option<usize>,
+    /// See [`Settings::context`](Settings::context).
+    pub context: Option<ProgramContextBuilder>,
+}
+
+/// The scope of programs in which a variable is considered to be the same variable.
+///
+/// Variables are document-scoped by default; that is, all variables of the same name across the
+/// programs in a document are considered to be the same variable, and references to (and renames
+/// of) a variable are searched for across all those programs. With a program scope, a variable `a`
+/// in one program is unrelated to a variable `a` in another program.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ReferenceScope {
+    /// Variables are scoped to the program they are in.
+    Program,
+    /// Variables are shared across all programs in a document.
+    Document,
+    /// Variables are shared across all programs in the workspace.
+    Workspace,
+}
+
+impl Default for ReferenceScope {
+    fn default() -> Self {
+        Self::Document
+    }
+}
+
+impl std::str::FromStr for ReferenceScope {
+    type Err = ();
+
+    fn from_str(scope: &str) -> Result<Self, Self::Err> {
+        match scope {
+            "program" => Ok(Self::Program),
+            "document" => Ok(Self::Document),
+            "workspace" => Ok(Self::Workspace),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A diagnostic produced while constructing [`InitializationOptions`](InitializationOptions) or a
+/// [`ConfigurationChange`](ConfigurationChange).
 #[derive(Debug, PartialEq)]
 pub enum InitializationDiagnostic {
     /// Entirely failed to parse the provided options from a JSON value. This likely means the
@@ -24,75 +149,247 @@ pub enum InitializationDiagnostic {
     /// Could not construct a [`DocumentParser`](DocumentParser) for a given file extension in the
     /// `document_parsers` object.
     InvalidDocumentParser(/** extension */ String, /** why */ String),
+    /// The `reference_scope` in the options JSON value is not one of "program", "document", or
+    /// "workspace".
+    InvalidReferenceScope(String),
+    /// An option in the `emit_config` list or the `emit` section of the options JSON value is not
+    /// a known emit configuration option.
+    InvalidEmitConfig(String),
+    /// An option in the `emit` section of the options JSON value is not set to a boolean.
+    InvalidEmitConfigValue(/** option */ String, /** value */ String),
+    /// The `context` section of the options JSON value does not describe a valid
+    /// [`ProgramContext`](ProgramContext).
+    InvalidContext(/** why */ String),
 }
 
-/// The raw values parsed from an initialization options JSON value, used to construct
-/// [`InitializationOptions`](InitializationOptions).
+/// The raw values parsed from an options JSON value, used to construct
+/// [`InitializationOptions`](InitializationOptions) and
+/// [`ConfigurationChange`](ConfigurationChange)s.
 #[derive(Deserialize)]
 struct SerializedInitializationOptions {
-    document_parsers: Option<BTreeMap<String, String>>,
+    document_parsers: Option<BTreeMap<String, SerializedDocumentParser>>,
+    reference_scope: Option<String>,
+    lint: Option<bool>,
+    emit_config: Option<Vec<String>>,
+    emit: Option<BTreeMap<String, Value>>,
+    max_programs_per_document: Option<usize>,
+    max_program_length: Option<usize>,
+    context: Option<Value>,
 }
 
-impl InitializationOptions {
-    /// Creates a fresh [`InitializationOptions`](InitializationOptions) from a JSON value, also
-    /// returning any [diagnostics](InitializationDiagnostic) discovered during the options'
-    /// construction.
-    pub fn from_json(json: Option<Value>) -> (Self, Vec<InitializationDiagnostic>) {
-        let opts: SerializedInitializationOptions =
-            match serde_json::from_value(json.unwrap_or_default()) {
-                Ok(opts) => opts,
-                Err(e) => {
-                    return (
-                        Default::default(),
-                        vec![InitializationDiagnostic::CouldntParse(e.to_string())],
-                    );
-                }
-            };
+/// A document parser in an options JSON value; either a regex, or an object of a regex or the name
+/// of a builtin parser and whether the programs it matches are expression patterns.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SerializedDocumentParser {
+    Regex(String),
+    Configured {
+        regex: String,
+        #[serde(default)]
+        expr_pat: bool,
+    },
+    Builtin {
+        builtin: String,
+        #[serde(default)]
+        expr_pat: bool,
+    },
+}
 
-        let SerializedInitializationOptions { document_parsers } = opts;
-        let mut diags = vec![];
-        let document_parsers = {
-            match document_parsers.as_ref() {
-                None => diags.push(InitializationDiagnostic::NoDocumentParserMap),
-                Some(d) if d.is_empty() => {
-                    diags.push(InitializationDiagnostic::NoDocumentParserMap)
-                }
-                _ => {}
-            };
+impl SerializedInitializationOptions {
+    fn from_json(json: Option<Value>) -> Result<Self, InitializationDiagnostic> {
+        serde_json::from_value(json.unwrap_or_default())
+            .map_err(|e| InitializationDiagnostic::CouldntParse(e.to_string()))
+    }
+
+    /// Builds the options present in the serialized options, adding a diagnostic to `diags` for
+    /// each invalid option. Invalid options are dropped.
+    fn build(self, diags: &mut Vec<InitializationDiagnostic>) -> ConfigurationChange {
+        let Self {
+            document_parsers,
+            reference_scope,
+            lint,
+            emit_config,
+            emit,
+            max_programs_per_document,
+            max_program_length,
+            context,
+        } = self;
+
+        let document_parsers = document_parsers.map(|document_parsers| {
             document_parsers
-                .unwrap_or_default()
                 .into_iter()
-                .filter_map(|(name, parser)| match DocumentParser::build(&parser) {
-                    Ok(parser) => Some((name, parser)),
-                    Err(why) => {
-                        diags.push(InitializationDiagnostic::InvalidDocumentParser(
-                            name,
-                            why.to_string(),
+                .filter_map(|(name, parser)| {
+                    let (parser, expr_pat) = match parser {
+                        SerializedDocumentParser::Regex(regex) => (
+                            DocumentParser::build(&regex).map_err(|e| e.to_string()),
+                            false,
+                        ),
+                        SerializedDocumentParser::Configured { regex, expr_pat } => (
+                            DocumentParser::build(&regex).map_err(|e| e.to_string()),
+                            expr_pat,
+                        ),
+                        SerializedDocumentParser::Builtin { builtin, expr_pat } => {
+                            let parser = DocumentParser::builtin(&builtin).ok_or_else(|| {
+                                format!("unknown builtin document parser `{}`", builtin)
+                            });
+                            (parser, expr_pat)
+                        }
+                    };
+                    match parser {
+                        Ok(parser) => Some((name, parser.expr_pat(expr_pat))),
+                        Err(why) => {
+                            diags.push(InitializationDiagnostic::InvalidDocumentParser(name, why));
+                            None
+                        }
+                    }
+                })
+                .collect()
+        });
+
+        let reference_scope = reference_scope.and_then(|scope| match scope.parse() {
+            Ok(scope) => Some(scope),
+            Err(()) => {
+                diags.push(InitializationDiagnostic::InvalidReferenceScope(scope));
+                None
+            }
+        });
+
+        let emit_config_list = emit_config.map(|opts| {
+            opts.into_iter()
+                .filter_map(|opt| parse_emit_config(opt, diags))
+                .collect::<EmitConfig>()
+        });
+        // The `emit` section maps emit configuration options to whether they are set, like
+        // `{ "implicit-mult": true }`.
+        let emit_section = emit.map(|opts| {
+            opts.into_iter()
+                .filter_map(|(opt, value)| match value {
+                    Value::Bool(set) => parse_emit_config(opt, diags).filter(|_| set),
+                    value => {
+                        parse_emit_config(opt.clone(), diags)?;
+                        diags.push(InitializationDiagnostic::InvalidEmitConfigValue(
+                            opt,
+                            value.to_string(),
                         ));
                         None
                     }
                 })
-                .collect()
+                .collect::<EmitConfig>()
+        });
+        let emit_config = match (emit_config_list, emit_section) {
+            (None, None) => None,
+            (list, section) => Some(list.unwrap_or_default() | section.unwrap_or_default()),
         };
 
-        let opts = Self { document_parsers };
+        // The `context` section maps options of the slide context to their values, like
+        // `{ "objective": "prefer-factored" }`.
+        let context = context.and_then(|context| {
+            let context = serde_json::from_value::<ProgramContextBuilder>(context)
+                .map_err(|e| e.to_string())
+                .and_then(|builder| match builder.clone().build() {
+                    Ok(_) => Ok(builder),
+                    Err(e) => Err(e.to_string()),
+                });
+            match context {
+                Ok(context) => Some(context),
+                Err(why) => {
+                    diags.push(InitializationDiagnostic::InvalidContext(why));
+                    None
+                }
+            }
+        });
+
+        ConfigurationChange {
+            document_parsers,
+            lint,
+            emit_config,
+            reference_scope,
+            max_programs_per_document,
+            max_program_length,
+            context,
+        }
+    }
+}
+
+/// Parses an emit configuration option, adding a diagnostic to `diags` if it is unknown.
+fn parse_emit_config(opt: String, diags: &mut Vec<InitializationDiagnostic>) -> Option<EmitConfig> {
+    match EmitConfig::try_from(opt.as_str()) {
+        Ok(config) => Some(config),
+        Err(_) => {
+            diags.push(InitializationDiagnostic::InvalidEmitConfig(opt));
+            None
+        }
+    }
+}
+
+impl InitializationOptions {
+    /// Creates a fresh [`InitializationOptions`](InitializationOptions) from a JSON value, also
+    /// returning any [diagnostics](InitializationDiagnostic) discovered during the options'
+    /// construction.
+    pub fn from_json(json: Option<Value>) -> (Self, Vec<InitializationDiagnostic>) {
+        let opts = match SerializedInitializationOptions::from_json(json) {
+            Ok(opts) => opts,
+            Err(diag) => return (Default::default(), vec![diag]),
+        };
+
+        let mut diags = vec![];
+        if opts
+            .document_parsers
+            .as_ref()
+            .map_or(true, BTreeMap::is_empty)
+        {
+            diags.push(InitializationDiagnostic::NoDocumentParserMap);
+        }
+        let change = opts.build(&mut diags);
+
+        let mut settings = Settings::default();
+        settings.apply(&change);
+        let opts = Self {
+            document_parsers: change.document_parsers.unwrap_or_default(),
+            settings,
+        };
         (opts, diags)
     }
 }
 
+impl ConfigurationChange {
+    /// Creates a [`ConfigurationChange`](ConfigurationChange) from the settings JSON value of a
+    /// `workspace/didChangeConfiguration` notification, which has the same shape as the
+    /// initialization options JSON value. Also returns any [diagnostics](InitializationDiagnostic)
+    /// discovered during the change's construction.
+    pub fn from_json(json: Value) -> (Self, Vec<InitializationDiagnostic>) {
+        let opts = match SerializedInitializationOptions::from_json(Some(json)) {
+            Ok(opts) => opts,
+            Err(diag) => return (Default::default(), vec![diag]),
+        };
+
+        let mut diags = vec![];
+        let change = opts.build(&mut diags);
+        (change, diags)
+    }
+}
+
 impl std::fmt::Display for InitializationDiagnostic {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
                 Self::CouldntParse(why) => format!("Failed to parse language server options:\n{}", why),
                 Self::NoDocumentParserMap => "No `document_parsers` in server options; slide LS will be a no-op for all documents".to_owned(),
-                Self::InvalidDocumentParser(doc, why) => format!("Failed to build parser regex for `{}`:\n{}", doc, why),
+                Self::InvalidDocumentParser(doc, why) => format!("Failed to build document parser for `{}`:\n{}", doc, why),
+                Self::InvalidReferenceScope(scope) => format!("Unknown reference scope `{}`; references will be document-scoped", scope),
+                Self::InvalidEmitConfig(opt) => format!("Unknown emit config option `{}`; it will be ignored", opt),
+                Self::InvalidEmitConfigValue(opt, value) => format!("Emit config option `{}` must be `true` or `false`, found `{}`; it will be ignored", opt, value),
+                Self::InvalidContext(why) => format!("Invalid `context` options; they will be ignored:\n{}", why),
             }.fmt(f)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{DocumentParser, InitializationDiagnostic, InitializationOptions};
+    use super::{
+        ConfigurationChange, DocumentParser, InitializationDiagnostic, InitializationOptions,
+        ProgramLimits, ReferenceScope, Settings,
+    };
+    use libslide::{EmitConfig, Objective, ProgramContext, ProgramContextBuilder, Rigor};
     use pretty_assertions::assert_eq;
     use serde_json::json;
 
@@ -102,6 +399,7 @@ mod test {
                 .into_iter()
                 .map(|(fi, re)| (fi.to_owned(), DocumentParser::build(re).unwrap()))
                 .collect(),
+            ..InitializationOptions::default()
         }
     }
 
@@ -195,4 +493,308 @@ error: unclosed character class"
         );
         assert!(diags.is_empty());
     }
+
+    #[test]
+    fn builtin_document_parser() {
+        let (opts, diags) = InitializationOptions::from_json(Some(json!({
+            "document_parsers": {
+                "rst": { "builtin": "rst-math" },
+                "tex": { "builtin": "latex-math" },
+            },
+        })));
+
+        assert_eq!(
+            opts.document_parsers.get("rst"),
+            Some(&DocumentParser::builtin("rst-math").unwrap())
+        );
+        assert_eq!(opts.document_parsers.get("tex"), None);
+        assert_eq!(
+            diags,
+            vec![InitializationDiagnostic::InvalidDocumentParser(
+                "tex".to_owned(),
+                "unknown builtin document parser `latex-math`".to_owned(),
+            )]
+        );
+    }
+
+    #[test]
+    fn expr_pat_document_parser() {
+        let (opts, diags) = InitializationOptions::from_json(Some(json!({
+            "document_parsers": {
+                "math": "(.*)",
+                "pat": { "regex": "(.*)", "expr_pat": true },
+            },
+        })));
+
+        assert_eq!(
+            opts.document_parsers.get("math"),
+            Some(&DocumentParser::build("(.*)").unwrap())
+        );
+        assert_eq!(
+            opts.document_parsers.get("pat"),
+            Some(&DocumentParser::build("(.*)").unwrap().expr_pat(true))
+        );
+        assert!(diags.is_empty());
+    }
+
+    #[test]
+    fn reference_scope() {
+        for (scope, expected) in &[
+            ("program", ReferenceScope::Program),
+            ("document", ReferenceScope::Document),
+            ("workspace", ReferenceScope::Workspace),
+        ] {
+            let (opts, diags) = InitializationOptions::from_json(Some(json!({
+                "document_parsers": {
+                    "math": "(.*)",
+                },
+                "reference_scope": scope,
+            })));
+
+            assert_eq!(opts.settings.reference_scope, *expected);
+            assert!(diags.is_empty());
+        }
+    }
+
+    #[test]
+    fn invalid_reference_scope() {
+        let (opts, diags) = InitializationOptions::from_json(Some(json!({
+            "document_parsers": {
+                "math": "(.*)",
+            },
+            "reference_scope": "galaxy",
+        })));
+
+        assert_eq!(opts.settings.reference_scope, ReferenceScope::Document);
+        assert_eq!(
+            diags,
+            vec![InitializationDiagnostic::InvalidReferenceScope(
+                "galaxy".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn settings() {
+        let (opts, diags) = InitializationOptions::from_json(Some(json!({
+            "document_parsers": {
+                "math": "(.*)",
+            },
+            "lint": false,
+            "emit_config": ["frac", "times"],
+        })));
+
+        assert_eq!(
+            opts.settings,
+            Settings {
+                lint: false,
+                emit_config: EmitConfig::FRAC | EmitConfig::TIMES,
+                reference_scope: ReferenceScope::Document,
+                program_limits: ProgramLimits::default(),
+                context: ProgramContextBuilder::default(),
+            }
+        );
+        assert!(diags.is_empty());
+    }
+
+    #[test]
+    fn invalid_emit_config() {
+        let (opts, diags) = InitializationOptions::from_json(Some(json!({
+            "document_parsers": {
+                "math": "(.*)",
+            },
+            "emit_config": ["frac", "fancy"],
+        })));
+
+        assert_eq!(opts.settings.emit_config, EmitConfig::FRAC);
+        assert_eq!(
+            diags,
+            vec![InitializationDiagnostic::InvalidEmitConfig(
+                "fancy".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn emit_section() {
+        let (opts, diags) = InitializationOptions::from_json(Some(json!({
+            "document_parsers": {
+                "math": "(.*)",
+            },
+            "emit_config": ["frac"],
+            "emit": {
+                "implicit-mult": true,
+                "define-assign": true,
+                "times": false,
+            },
+        })));
+
+        assert_eq!(
+            opts.settings.emit_config,
+            EmitConfig::FRAC | EmitConfig::IMPLICIT_MULT | EmitConfig::DEFINE_ASSIGN
+        );
+        assert!(diags.is_empty());
+    }
+
+    #[test]
+    fn invalid_emit_section() {
+        let (opts, diags) = InitializationOptions::from_json(Some(json!({
+            "document_parsers": {
+                "math": "(.*)",
+            },
+            "emit": {
+                "frac": true,
+                "fancy": true,
+                "hex": "yes",
+            },
+        })));
+
+        assert_eq!(opts.settings.emit_config, EmitConfig::FRAC);
+        assert_eq!(
+            diags,
+            vec![
+                InitializationDiagnostic::InvalidEmitConfig("fancy".to_owned()),
+                InitializationDiagnostic::InvalidEmitConfigValue(
+                    "hex".to_owned(),
+                    "\"yes\"".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn program_limits() {
+        let (opts, diags) = InitializationOptions::from_json(Some(json!({
+            "document_parsers": {
+                "math": "(.*)",
+            },
+            "max_programs_per_document": 20,
+        })));
+
+        assert_eq!(
+            opts.settings.program_limits,
+            ProgramLimits {
+                max_programs: 20,
+                ..ProgramLimits::default()
+            }
+        );
+        assert!(diags.is_empty());
+
+        let (change, diags) = ConfigurationChange::from_json(json!({
+            "max_program_length": 100,
+        }));
+        let mut settings = opts.settings;
+        settings.apply(&change);
+        assert_eq!(
+            settings.program_limits,
+            ProgramLimits {
+                max_programs: 20,
+                max_program_length: 100,
+            }
+        );
+        assert!(diags.is_empty());
+    }
+
+    #[test]
+    fn context() {
+        let (opts, diags) = InitializationOptions::from_json(Some(json!({
+            "document_parsers": {
+                "math": "(.*)",
+            },
+            "lint": false,
+            "context": {
+                "rigor": "rigorous",
+                "objective": "prefer-factored",
+            },
+        })));
+
+        assert_eq!(
+            opts.settings.program_context(),
+            ProgramContext::builder()
+                .rigor(Rigor::Rigorous)
+                .objective(Objective::PreferFactored)
+                .build()
+                .unwrap()
+        );
+        assert!(diags.is_empty());
+    }
+
+    #[test]
+    fn invalid_context() {
+        let (opts, diags) = InitializationOptions::from_json(Some(json!({
+            "document_parsers": {
+                "math": "(.*)",
+            },
+            "context": {
+                "objective": "prefer-factored",
+                "rule_denylist": ["FactorOut"],
+            },
+        })));
+
+        assert_eq!(opts.settings.context, ProgramContextBuilder::default());
+        assert_eq!(
+            diags,
+            vec![InitializationDiagnostic::InvalidContext(
+                "objective `prefer-factored` requires the rule FactorOut, but it is in the rule denylist"
+                    .to_owned()
+            )]
+        );
+
+        let (change, diags) = ConfigurationChange::from_json(json!({
+            "context": { "rigor": "strict" },
+        }));
+        assert_eq!(change.context, None);
+        assert_eq!(diags.len(), 1);
+    }
+
+    #[test]
+    fn configuration_change() {
+        let (change, diags) = ConfigurationChange::from_json(json!({
+            "lint": false,
+            "reference_scope": "document",
+        }));
+
+        assert_eq!(
+            change,
+            ConfigurationChange {
+                lint: Some(false),
+                reference_scope: Some(ReferenceScope::Document),
+                ..ConfigurationChange::default()
+            }
+        );
+        assert!(diags.is_empty());
+
+        let mut settings = Settings::default();
+        settings.apply(&change);
+        assert_eq!(
+            settings,
+            Settings {
+                lint: false,
+                emit_config: EmitConfig::default(),
+                reference_scope: ReferenceScope::Document,
+                program_limits: ProgramLimits::default(),
+                context: ProgramContextBuilder::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn configuration_change_with_document_parsers() {
+        let (change, diags) = ConfigurationChange::from_json(json!({
+            "document_parsers": {
+                "math": "(.*)",
+                "bad": "[",
+            },
+        }));
+
+        assert_eq!(
+            change.document_parsers,
+            Some(
+                vec![("math".to_owned(), DocumentParser::build("(.*)").unwrap())]
+                    .into_iter()
+                    .collect()
+            )
+        );
+        assert_eq!(diags.len(), 1);
+    }
 }
@@ -7,9 +7,11 @@ use parking_lot::{MappedRwLockReadGuard, RwLock, RwLockReadGuard};
 use tower_lsp::lsp_types::Url;
 
 use crate::ast::Ast;
-use crate::ptr::P;
+use crate::ptr::{p, P};
 
+mod cache;
 mod services;
+pub(crate) use cache::AnalysisCache;
 pub use services::response;
 
 /// Fully populated program analyses.
@@ -29,7 +31,7 @@ enum Analysis {
     /// No analyses are known or complete.
     Unknown,
     /// All analyses are complete.
-    Complete(CompletedAnalysis),
+    Complete(P<CompletedAnalysis>),
 }
 
 impl Analysis {
@@ -38,24 +40,38 @@ impl Analysis {
         Self::Unknown
     }
 
-    /// Creates a fresh [complete analysis](Analysis::Complete).
-    fn fresh(
-        original: Ast,
-        simplified: Ast,
-        diagnostics: Vec<response::ProgramDiagnostic>,
-    ) -> Self {
-        Self::Complete(CompletedAnalysis {
-            original,
-            simplified,
-            diagnostics,
-        })
-    }
-
     fn is_complete(&self) -> bool {
         matches!(self, Analysis::Complete { .. })
     }
 }
 
+/// Why a [Program](Program) is registered but not analyzed. See
+/// [`ProgramLimits`](crate::document_registry::ProgramLimits).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum AnalysisSkip {
+    /// The program comes after the first `n` programs of its document.
+    TooManyPrograms(usize),
+    /// The program is longer than `n` bytes.
+    TooLong(usize),
+}
+
+impl std::fmt::Display for AnalysisSkip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyPrograms(n) => write!(
+                f,
+                "Only the first {} programs of a document are analyzed; raise `max_programs_per_document` to analyze this program",
+                n
+            ),
+            Self::TooLong(n) => write!(
+                f,
+                "Programs longer than {} bytes are not analyzed; raise `max_program_length` to analyze this program",
+                n
+            ),
+        }
+    }
+}
+
 /// A slide program found inside a [`Document`](crate::document_registry::Document).
 /// Used to answer language queries made by a server session.
 #[derive(Debug)]
@@ -79,6 +95,16 @@ pub(crate) struct Program {
     ///
     /// Analysis is performed lazily, for example when diagnostics for the program are requested.
     analysis: RwLock<Analysis>,
+    /// Why the program is not analyzed, if it is not. Such a program has empty ASTs and a single
+    /// note diagnostic explaining the skip.
+    skip: Option<AnalysisSkip>,
+    /// Whether the program is an expression pattern, like `_a + #b`, rather than a list of
+    /// statements. An expression pattern program has empty ASTs; only its diagnostics are
+    /// analyzed.
+    expr_pat: bool,
+    /// A cache of analyses shared with other programs, if any. See
+    /// [`AnalysisCache`](AnalysisCache).
+    cache: Option<P<AnalysisCache>>,
 }
 
 impl Program {
@@ -99,9 +125,65 @@ impl Program {
             rules: build_rules(context.as_ref()).unwrap(),
             context,
             analysis: RwLock::new(Analysis::unknown()),
+            skip: None,
+            expr_pat: false,
+            cache: None,
+        }
+    }
+
+    /// Like [`new`](Self::new), but creates a program that is not analyzed because of `skip`.
+    pub fn unanalyzed(
+        source: String,
+        document_uri: P<Url>,
+        start: usize,
+        end: usize,
+        context: P<ProgramContext>,
+        skip: AnalysisSkip,
+    ) -> Self {
+        Self {
+            source,
+            document_uri,
+            start,
+            end,
+            rules: vec![],
+            context,
+            analysis: RwLock::new(Analysis::unknown()),
+            skip: Some(skip),
+            expr_pat: false,
+            cache: None,
         }
     }
 
+    /// Marks whether the program is an expression pattern rather than a list of statements.
+    pub fn with_expr_pat(mut self, expr_pat: bool) -> Self {
+        self.expr_pat = expr_pat;
+        self
+    }
+
+    /// Makes the program reuse an analysis of identical programs in `cache`, rather than analyzing
+    /// the program afresh.
+    pub fn with_cache(mut self, cache: P<AnalysisCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Whether the program is an expression pattern, like `_a + #b`.
+    pub fn is_expr_pat(&self) -> bool {
+        self.expr_pat
+    }
+
+    /// Whether the program is analyzed, rather than skipped for exceeding a
+    /// [limit](crate::document_registry::ProgramLimits).
+    #[cfg(test)]
+    pub fn is_analyzed(&self) -> bool {
+        self.skip.is_none()
+    }
+
+    /// Why the program is not analyzed, if it is not.
+    pub fn analysis_skip(&self) -> Option<AnalysisSkip> {
+        self.skip
+    }
+
     /// Returns the "original" [AST](crate::ast::AST) of the program, after parsing but before
     /// evaluation.
     pub fn original_ast(&self) -> MappedRwLockReadGuard<Ast> {
@@ -122,7 +204,7 @@ impl Program {
     fn get_analysis(&self) -> MappedRwLockReadGuard<CompletedAnalysis> {
         self.ensure_analysis();
         RwLockReadGuard::map(self.analysis.read(), |analysis| match analysis {
-            Analysis::Complete(analysis) => analysis,
+            Analysis::Complete(analysis) => analysis.as_ref(),
             Analysis::Unknown => unreachable!("Bad state: analysis is not complete"),
         })
     }
@@ -134,8 +216,51 @@ impl Program {
             return;
         }
 
+        let analysis = match (&self.cache, self.skip) {
+            (Some(cache), None) => {
+                let key = cache::CacheKey {
+                    document_uri: self.document_uri.as_ref().clone(),
+                    source: self.source.clone(),
+                    expr_pat: self.expr_pat,
+                };
+                cache.get_or_analyze(key, || self.analyze())
+            }
+            _ => p(self.analyze()),
+        };
+        *self.analysis.write() = Analysis::Complete(analysis);
+    }
+
+    /// Performs [analysis](CompletedAnalysis) of the program.
+    fn analyze(&self) -> CompletedAnalysis {
+        let empty_ast = || std::iter::empty().collect::<Ast>();
+
+        if let Some(skip) = self.skip {
+            let diagnostics = vec![services::diagnostics::skipped_analysis_diagnostic(
+                skip,
+                &self.source,
+            )];
+            return CompletedAnalysis {
+                original: empty_ast(),
+                simplified: empty_ast(),
+                diagnostics,
+            };
+        }
+
         use libslide::*;
 
+        if self.expr_pat {
+            let diagnostics = services::diagnostics::convert_diagnostics(
+                &lint(&self.source, LintMode::ExprPat),
+                "slide",
+                &self.document_uri,
+            );
+            return CompletedAnalysis {
+                original: empty_ast(),
+                simplified: empty_ast(),
+                diagnostics,
+            };
+        }
+
         // 1. Parse
         let ScanResult {
             tokens,
@@ -145,11 +270,16 @@ impl Program {
             program: original,
             diagnostics: parse_diags,
         } = parse_statements(tokens, &self.source);
-        let lint_diags = lint_stmt(&original, &self.source);
+        let lint_diags = if self.context.lints() {
+            lint_stmt(&original, &self.source)
+        } else {
+            vec![]
+        };
         // 2. Eval
         let EvaluationResult {
             simplified,
             diagnostics: eval_diags,
+            ..
         } = evaluate(original.clone(), &self.context).expect("Evaluation failed.");
 
         // 3. Diagnostics
@@ -160,8 +290,11 @@ impl Program {
             })
             .collect();
 
-        // Update fresh analysis
-        *self.analysis.write() = Analysis::fresh(original, simplified, diagnostics);
+        CompletedAnalysis {
+            original,
+            simplified,
+            diagnostics,
+        }
     }
 }
 
@@ -0,0 +1,162 @@
+//! Module `cache` describes a bounded cache of [program analyses](super::CompletedAnalysis) shared
+//! by the programs of a [`DocumentRegistry`](crate::document_registry::DocumentRegistry).
+//!
+//! Editing one slide program of a document reuses the unchanged programs of the document, but an
+//! edited program often returns to a source text it had before, like when an edit is undone.
+//! Analyses are keyed by program source text, so such programs reuse their previous analysis.
+//! Analysis spans are relative to the start of a program, so a reused analysis needs no adjustment
+//! when its program moves in the document.
+
+use super::CompletedAnalysis;
+use crate::ptr::{p, P};
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use tower_lsp::lsp_types::Url;
+
+/// The number of analyses a default [`AnalysisCache`](AnalysisCache) holds.
+const DEFAULT_CAPACITY: usize = 256;
+
+/// Identifies an analysis in an [`AnalysisCache`](AnalysisCache).
+///
+/// The slide context a program is analyzed with is not part of the key; instead, the cache is
+/// [cleared](AnalysisCache::clear) when the context changes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) struct CacheKey {
+    /// The document the program resides in. Diagnostics refer to it in related information.
+    pub document_uri: Url,
+    /// The text source of the program.
+    pub source: String,
+    /// Whether the program is an expression pattern.
+    pub expr_pat: bool,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    /// Cached analyses and the tick they were last used at.
+    analyses: HashMap<CacheKey, (P<CompletedAnalysis>, u64)>,
+    /// Monotonic counter of cache accesses, used to find the least-recently used analysis.
+    tick: u64,
+    /// The number of analyses computed by the cache, rather than reused.
+    computed: usize,
+}
+
+/// A least-recently used cache of program analyses.
+#[derive(Debug)]
+pub(crate) struct AnalysisCache {
+    capacity: usize,
+    entries: Mutex<CacheEntries>,
+}
+
+impl Default for AnalysisCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl AnalysisCache {
+    /// Creates a cache holding at most `capacity` analyses.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Default::default(),
+        }
+    }
+
+    /// Returns the analysis cached for `key`, or computes it with `analyze` and caches it.
+    ///
+    /// The cache is not locked while `analyze` runs, so programs may be analyzed in parallel.
+    pub(super) fn get_or_analyze(
+        &self,
+        key: CacheKey,
+        analyze: impl FnOnce() -> CompletedAnalysis,
+    ) -> P<CompletedAnalysis> {
+        {
+            let mut entries = self.entries.lock();
+            entries.tick += 1;
+            let tick = entries.tick;
+            if let Some((analysis, last_used)) = entries.analyses.get_mut(&key) {
+                *last_used = tick;
+                return analysis.dupe();
+            }
+        }
+
+        let analysis = p(analyze());
+
+        let mut entries = self.entries.lock();
+        entries.computed += 1;
+        if entries.analyses.len() >= self.capacity && !entries.analyses.contains_key(&key) {
+            let lru = entries
+                .analyses
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(lru) = lru {
+                entries.analyses.remove(&lru);
+            }
+        }
+        if self.capacity > 0 {
+            let tick = entries.tick;
+            entries.analyses.insert(key, (analysis.dupe(), tick));
+        }
+        analysis
+    }
+
+    /// Drops all cached analyses.
+    pub fn clear(&self) {
+        self.entries.lock().analyses.clear();
+    }
+
+    /// The number of analyses computed by the cache, rather than reused.
+    #[cfg(test)]
+    pub fn computed(&self) -> usize {
+        self.entries.lock().computed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AnalysisCache, CacheKey, CompletedAnalysis};
+    use tower_lsp::lsp_types::Url;
+
+    fn key(source: &str) -> CacheKey {
+        CacheKey {
+            document_uri: Url::parse("file:///test").unwrap(),
+            source: source.to_owned(),
+            expr_pat: false,
+        }
+    }
+
+    fn empty_analysis() -> CompletedAnalysis {
+        CompletedAnalysis {
+            original: std::iter::empty().collect(),
+            simplified: std::iter::empty().collect(),
+            diagnostics: vec![],
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = AnalysisCache::new(2);
+        cache.get_or_analyze(key("a"), empty_analysis);
+        cache.get_or_analyze(key("b"), empty_analysis);
+        cache.get_or_analyze(key("a"), empty_analysis);
+        assert_eq!(cache.computed(), 2);
+
+        // "b" is the least recently used, so it is evicted.
+        cache.get_or_analyze(key("c"), empty_analysis);
+        cache.get_or_analyze(key("a"), empty_analysis);
+        assert_eq!(cache.computed(), 3);
+        cache.get_or_analyze(key("b"), empty_analysis);
+        assert_eq!(cache.computed(), 4);
+    }
+
+    #[test]
+    fn clear() {
+        let cache = AnalysisCache::new(2);
+        cache.get_or_analyze(key("a"), empty_analysis);
+        cache.clear();
+        cache.get_or_analyze(key("a"), empty_analysis);
+        assert_eq!(cache.computed(), 2);
+    }
+}
@@ -7,6 +7,7 @@ mod actions;
 mod annotations;
 mod completions;
 mod definitions;
+mod evaluate;
 mod folding_ranges;
 mod format;
 mod highlight;
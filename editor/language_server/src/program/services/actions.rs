@@ -5,13 +5,14 @@ use crate::ast::*;
 use crate::Program;
 
 use libslide::*;
-use tower_lsp::lsp_types::Url;
+use tower_lsp::lsp_types::{DiagnosticSeverity, Url};
 
 impl Program {
-    /// Determines rewrite actions applicable over a span in a program.
-    pub fn actions(&self, span: Span) -> Vec<ProgramAction> {
+    /// Determines rewrite actions applicable over a span in a program. Rewritten items are
+    /// emitted with `config`.
+    pub fn actions(&self, span: Span, config: EmitConfig) -> Vec<ProgramAction> {
         let mut actions = self.diagnostic_actions(span);
-        if let Some(rewrite) = self.rewrite_action(span) {
+        if let Some(rewrite) = self.rewrite_action(span, config) {
             actions.push(rewrite);
         }
         actions
@@ -28,24 +29,68 @@ impl Program {
     }
 
     /// Retrieves a rewrite action for the item covering the span, if there is such an item and it
-    /// has any rewrite.
-    fn rewrite_action(&self, span: Span) -> Option<ProgramAction> {
+    /// has any rewrite. If no item exactly covers a non-empty span, the smallest item enclosing the
+    /// span is rewritten instead, as a simplification of the selection. The title of the action
+    /// previews the rewritten item, like "Simplify to 3".
+    ///
+    /// Items of statements with error diagnostics are not rewritten, since such statements may not
+    /// mean what they appear to.
+    fn rewrite_action(&self, span: Span, config: EmitConfig) -> Option<ProgramAction> {
+        let error_spans: Vec<_> = self
+            .diagnostics()
+            .iter()
+            .filter(|diag| diag.severity == DiagnosticSeverity::Error)
+            .map(|diag| diag.span)
+            .collect();
         let ast = self.original_ast();
+        let mut path = get_item_path_to_span(span, &ast);
+        let item = path.pop()?;
+        let stmt_span = path.first().unwrap_or(&item).span();
+        if error_spans.iter().any(|error| error.intersects(stmt_span)) {
+            return None;
+        }
+        let title = if item.span() == span {
+            "Simplify"
+        } else if span.lo < span.hi {
+            "Simplify selection"
+        } else {
+            return None;
+        };
+
         let simplify_expr = |e| evaluate_expr(e, &self.rules, self.context.as_ref());
-        let (span, original, simplified) = match get_item_at_span(span, &ast)? {
-            AstItem::Expr(e) => (e.span, e.to_string(), simplify_expr(e.clone()).to_string()),
-            AstItem::Assignment(a) => (
-                a.span,
-                a.to_string(),
-                a.clone().redefine_with(simplify_expr).to_string(),
-            ),
+        let (span, original, simplified, preview) = match item {
+            AstItem::Expr(e) => {
+                let simplified = simplify_expr(e.clone());
+                let preview = simplified.emit_pretty(config);
+                let mut emit = preview.clone();
+                // The simplified expression replaces only the original one, so it is parenthesized
+                // if it would otherwise group differently with the operations around it.
+                let in_operation = matches!(
+                    path.last(),
+                    Some(AstItem::Expr(parent))
+                        if matches!(parent.as_ref(), Expr::BinaryExpr(_) | Expr::UnaryExpr(_))
+                );
+                if in_operation && binding_power(&simplified) < binding_power(e) {
+                    emit = format!("({})", emit);
+                }
+                (e.span, e.emit_pretty(config), emit, preview)
+            }
+            AstItem::Assignment(a) => {
+                let simplified = a.clone().redefine_with(simplify_expr).emit_pretty(config);
+                (
+                    a.span,
+                    a.emit_pretty(config),
+                    simplified.clone(),
+                    simplified,
+                )
+            }
         };
 
         if original == simplified {
             None
         } else {
             Some(ProgramAction {
-                title: "Simplify".to_owned(),
+                title: format!("{} to {}", title, preview),
                 kind: ProgramActionKind::Rewrite,
                 resolved_diagnostic: None,
                 uri: self.document_uri.as_ref().clone(),
@@ -59,6 +104,21 @@ impl Program {
     }
 }
 
+/// How tightly an expression binds to the operations around it. Prefix signs, including those of
+/// negative constants, bind looser than exponentiation, as in `-2 ^ 2`, which is `-(2 ^ 2)`.
+fn binding_power(expr: &RcExpr) -> u8 {
+    match expr.as_ref() {
+        Expr::BinaryExpr(BinaryExpr { op, .. }) => op.precedence() * 2,
+        Expr::UnaryExpr(UnaryExpr {
+            op: UnaryOperator::Factorial,
+            ..
+        }) => 5,
+        Expr::UnaryExpr(_) => 3,
+        Expr::Const(k) if *k < 0. => 3,
+        _ => 5,
+    }
+}
+
 fn diagnostic2action(diag: &ProgramDiagnostic, document_uri: &Url) -> Option<ProgramAction> {
     let autofix = diag.autofix.as_ref()?;
     let edit = match &autofix.fix {
@@ -5,18 +5,41 @@ use super::symbols::{fmt_symbol_info, fmt_var_symbol_definition};
 use crate::Program;
 
 use libslide::collectors::collect_var_asgns;
+use libslide::{Assignment, InternedStr};
+
+use std::collections::HashMap;
 
 impl Program {
     /// Returns code completions for the context of an offset in a program.
-    pub fn completions(&self, _offset: usize) -> Vec<ProgramCompletion> {
-        let program = self.original_ast();
-        // Currently we just return all variables as completion items.
-        collect_var_asgns(&program)
+    ///
+    /// `document_programs` are all programs in the document this program resides in (including
+    /// this program). Currently we just return all variables defined in the document as completion
+    /// items, documented with their simplified definitions.
+    pub fn get_completions(
+        &self,
+        _offset: usize,
+        document_programs: &[Program],
+    ) -> Vec<ProgramCompletion> {
+        let mut definitions: HashMap<InternedStr, Vec<Assignment>> = HashMap::new();
+        for program in document_programs {
+            let simplified = program.simplified_ast();
+            for (var, asgns) in collect_var_asgns(&simplified) {
+                definitions
+                    .entry(var)
+                    .or_default()
+                    .extend(asgns.into_iter().cloned());
+            }
+        }
+
+        definitions
             .into_iter()
-            .map(|(var, asgns)| ProgramCompletion {
-                label: var.to_string(),
-                kind: ProgramCompletionKind::Variable,
-                documentation: fmt_symbol_info(fmt_var_symbol_definition(Some(asgns.as_ref()))),
+            .map(|(var, asgns)| {
+                let asgns: Vec<_> = asgns.iter().collect();
+                ProgramCompletion {
+                    label: var.to_string(),
+                    kind: ProgramCompletionKind::Variable,
+                    documentation: fmt_symbol_info(fmt_var_symbol_definition(Some(&asgns))),
+                }
             })
             .collect()
     }
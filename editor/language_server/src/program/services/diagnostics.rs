@@ -2,8 +2,10 @@
 //! [libslide diagnostics](libslide::diagnostics::Diagnostic) and LSP types.
 
 use super::response::*;
+use crate::program::AnalysisSkip;
 
 use libslide::diagnostics as s;
+use libslide::Span;
 use tower_lsp::lsp_types::*;
 
 pub fn convert_diagnostics(
@@ -26,6 +28,22 @@ pub fn convert_diagnostics(
         .collect()
 }
 
+/// Creates the note diagnostic of a program that is not analyzed because of `skip`. The
+/// diagnostic spans the whole program.
+pub fn skipped_analysis_diagnostic(skip: AnalysisSkip, source: &str) -> ProgramDiagnostic {
+    let title = skip.to_string();
+    ProgramDiagnostic {
+        span: Span::new(0, source.len()),
+        severity: DiagnosticSeverity::Information,
+        code: "skipped".to_owned(),
+        source: "slide".to_owned(),
+        display_message: title.clone(),
+        title,
+        related_information: vec![],
+        autofix: None,
+    }
+}
+
 fn flatten_diagnostic_msg(diagnostic: &s::Diagnostic) -> String {
     match &diagnostic.msg {
         Some(msg) => format!("{} \\ {}", diagnostic.title, msg),
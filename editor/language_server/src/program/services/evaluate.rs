@@ -0,0 +1,76 @@
+//! Module `evaluate` provides services for evaluating a program, or a part of it, on request.
+
+use super::response::*;
+use crate::ast::{get_item_at_span, get_item_path_to_offset, AstItem};
+use crate::Program;
+
+use libslide::{evaluate_expr, Emit, EmitConfig, EmitFormat, RcExpr, Span};
+
+impl Program {
+    /// Evaluates the program, emitting its simplified form in `format`.
+    ///
+    /// If a span is given and it does not include the entire program, only the item exactly
+    /// spanned is evaluated, and only diagnostics within the span are returned. If no such item
+    /// exists, a reason as to why is returned via an error.
+    pub fn evaluate(
+        &self,
+        span: Option<Span>,
+        format: EmitFormat,
+        config: EmitConfig,
+    ) -> Result<ProgramEvaluation, ProgramCannotEvaluateBecause> {
+        let span = match span {
+            Some(span) if !span.supersets(self.content_span()) => span,
+            _ => {
+                return Ok(ProgramEvaluation {
+                    result: self.simplified_ast().emit(format, config),
+                    diagnostics: self.diagnostics().to_vec(),
+                })
+            }
+        };
+
+        let evaluate = |expr: RcExpr| evaluate_expr(expr, &self.rules, self.context.as_ref());
+        let result = match get_item_at_span(span, &self.original_ast()) {
+            Some(AstItem::Assignment(asgn)) => {
+                asgn.clone().redefine_with(evaluate).emit(format, config)
+            }
+            Some(AstItem::Expr(expr)) => evaluate(expr.clone()).emit(format, config),
+            None => return Err(ProgramCannotEvaluateBecause::RangeNotOverItem),
+        };
+        let diagnostics = self
+            .diagnostics()
+            .iter()
+            .filter(|diag| span.supersets(diag.span))
+            .cloned()
+            .collect();
+
+        Ok(ProgramEvaluation {
+            result,
+            diagnostics,
+        })
+    }
+
+    /// Simplifies the statement an offset is in, emitting it in the pretty format with `config`.
+    /// If the offset is not over a statement, a reason as to why is returned via an error.
+    pub fn simplify_at(
+        &self,
+        offset: usize,
+        config: EmitConfig,
+    ) -> Result<String, ProgramCannotEvaluateBecause> {
+        let evaluate = |expr: RcExpr| evaluate_expr(expr, &self.rules, self.context.as_ref());
+        let ast = self.original_ast();
+        match get_item_path_to_offset(offset, &ast).first() {
+            Some(AstItem::Assignment(asgn)) => {
+                Ok((*asgn).clone().redefine_with(evaluate).emit_pretty(config))
+            }
+            Some(AstItem::Expr(expr)) => Ok(evaluate((*expr).clone()).emit_pretty(config)),
+            None => Err(ProgramCannotEvaluateBecause::PositionNotOverItem),
+        }
+    }
+
+    /// Returns the span of the program's source, excluding leading and trailing whitespace.
+    fn content_span(&self) -> Span {
+        let lo = self.source.len() - self.source.trim_start().len();
+        let hi = self.source.trim_end().len().max(lo);
+        Span::new(lo, hi)
+    }
+}
@@ -4,15 +4,17 @@
 use super::response::*;
 use crate::Program;
 
-use libslide::visit::StmtVisitor;
+use libslide::visit::{self, StmtVisitor};
 use libslide::*;
 
 impl Program {
     /// Returns [span](Span)s of foldable ranges in a program.
-    /// Such ranges generally correspond to expressions and definitions in a program.
+    /// Such ranges are statements and parenthesized or bracketed expressions spanning multiple
+    /// lines, ordered outermost-first.
     pub fn folding_ranges(&self) -> ProgramFoldingRanges {
         let ast = self.original_ast();
         let mut ranges_collector = FoldingRangeCollector {
+            source: &self.source,
             folding_ranges: vec![],
         };
         ranges_collector.visit_stmt_list(&ast);
@@ -20,11 +22,33 @@ impl Program {
     }
 }
 
-struct FoldingRangeCollector {
+struct FoldingRangeCollector<'a> {
+    source: &'a str,
     folding_ranges: Vec<Span>,
 }
-impl<'a> StmtVisitor<'a> for FoldingRangeCollector {
+impl<'a> FoldingRangeCollector<'a> {
+    fn add_if_multiline(&mut self, span: Span) {
+        if let Some(text) = self.source.get(span.lo..span.hi) {
+            // Chained assignments are parsed as statements that may share a span.
+            if text.contains('\n') && self.folding_ranges.last() != Some(&span) {
+                self.folding_ranges.push(span);
+            }
+        }
+    }
+}
+impl<'a> StmtVisitor<'a> for FoldingRangeCollector<'a> {
     fn visit_stmt(&mut self, stmt: &'a Stmt) {
-        self.folding_ranges.push(*stmt.span());
+        self.add_if_multiline(*stmt.span());
+        visit::descend_stmt(self, stmt);
+    }
+
+    fn visit_parend(&mut self, expr: &'a RcExpr, span: Span) {
+        self.add_if_multiline(span);
+        visit::descend_parend(self, expr, span);
+    }
+
+    fn visit_bracketed(&mut self, expr: &'a RcExpr, span: Span) {
+        self.add_if_multiline(span);
+        visit::descend_bracketed(self, expr, span);
     }
 }
@@ -4,23 +4,76 @@ use super::response::*;
 use crate::ast::{get_item_at_span, AstItem};
 use crate::Program;
 
-use libslide::{Emit, EmitConfig, Span};
+use libslide::visit::StmtVisitor;
+use libslide::{Emit, EmitConfig, Span, Stmt};
 
 impl Program {
-    /// Formats the program.
-    pub fn format(&self, config: EmitConfig) -> ProgramTextEdit {
-        ProgramTextEdit {
-            span: (0, self.end - self.start).into(),
-            edit: self.original_ast().emit_pretty(config),
-        }
+    /// Formats the program, returning an edit for each statement whose formatted form differs
+    /// from its source. Whitespace between statements is left untouched.
+    pub fn format(&self, config: EmitConfig) -> Vec<ProgramTextEdit> {
+        self.format_stmts_in((0, self.end - self.start).into(), config)
     }
 
-    /// Formats a span in the program, if the span exactly includes something that can be formatted.
-    pub fn format_span(&self, span: Span, config: EmitConfig) -> Option<ProgramTextEdit> {
+    /// Formats a span in the program.
+    ///
+    /// If the span fully includes any statements, those statements are formatted. Otherwise, if
+    /// the span exactly includes something that can be formatted, that item is formatted. Only
+    /// items whose formatted form differs from their source are edited.
+    pub fn format_span(&self, span: Span, config: EmitConfig) -> Option<Vec<ProgramTextEdit>> {
+        let edits = self.format_stmts_in(span, config);
+        if !edits.is_empty() {
+            return Some(edits);
+        }
+
         let edit = match get_item_at_span(span, &self.original_ast())? {
             AstItem::Assignment(asgn) => asgn.emit_pretty(config),
             AstItem::Expr(expr) => expr.emit_pretty(config),
         };
-        Some(ProgramTextEdit { span, edit })
+        self.edit_if_changed(span, edit).map(|edit| vec![edit])
+    }
+
+    /// Returns edits for the statements fully inside `span` whose formatted form differs from
+    /// their source.
+    fn format_stmts_in(&self, span: Span, config: EmitConfig) -> Vec<ProgramTextEdit> {
+        let ast = self.original_ast();
+        let mut grouper = StmtGrouper { groups: vec![] };
+        grouper.visit_stmt_list(&ast);
+
+        grouper
+            .groups
+            .into_iter()
+            .filter(|(stmts_span, _)| span.supersets(*stmts_span))
+            .filter_map(|(stmts_span, stmts)| {
+                let formatted: Vec<_> = stmts
+                    .into_iter()
+                    .map(|stmt| stmt.kind.emit_pretty(config))
+                    .collect();
+                self.edit_if_changed(stmts_span, formatted.join("\n"))
+            })
+            .collect()
+    }
+
+    /// Returns an edit replacing `span` with `edit`, unless that is what the span already holds.
+    fn edit_if_changed(&self, span: Span, edit: String) -> Option<ProgramTextEdit> {
+        if self.source.get(span.lo..span.hi) == Some(edit.as_str()) {
+            None
+        } else {
+            Some(ProgramTextEdit { span, edit })
+        }
+    }
+}
+
+/// Groups the statements of a program by the source they were parsed from. A chained assignment
+/// like `a = b = 1` is parsed as multiple statements whose spans overlap; such statements are
+/// grouped together under the span of the first.
+struct StmtGrouper<'a> {
+    groups: Vec<(Span, Vec<&'a Stmt>)>,
+}
+impl<'a> StmtVisitor<'a> for StmtGrouper<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match self.groups.last_mut() {
+            Some((span, stmts)) if span.supersets(*stmt.span()) => stmts.push(stmt),
+            _ => self.groups.push((*stmt.span(), vec![stmt])),
+        }
     }
 }
@@ -6,21 +6,75 @@ use crate::ast;
 use crate::Program;
 
 use collectors::collect_var_asgns;
+use libslide::diagnostics::Diagnostic;
+use libslide::scanner::TokenType;
 use libslide::*;
 
+use core::convert::TryFrom;
 use tower_lsp::lsp_types::*;
 
 impl Program {
     /// Returns hover info for an expression.
+    /// - If the offset is over the token of a binary or unary operator, documentation for the
+    ///   operator is returned.
     /// - If the expression is a variable,
     ///   - if the variable is defined, its simplified definition(s) are returned.
     ///   - if the variable is not defined, an "unknown" marker is returned.
     /// - Otherwise, a simplified version of the hovered expression is returned.
+    ///
+    /// If the program is an expression pattern, hover info is only returned for metavariables,
+    /// like `_a`, describing what they match.
+    ///
+    /// If the offset is within the span of any diagnostics, the title and explanation of each
+    /// diagnostic are appended to the hover info as markdown.
     pub fn get_hover_info(&self, offset: usize) -> Option<ProgramHoverResponse> {
+        let hover = if self.is_expr_pat() {
+            self.get_pattern_hover_info(offset)
+        } else {
+            self.get_expr_hover_info(offset)
+        };
+        let diagnostics: Vec<_> = self
+            .diagnostics()
+            .iter()
+            .filter(|diag| diag.span.contains(offset))
+            .map(|diag| (diag.span, fmt_diagnostic_info(&diag.code, &diag.title)))
+            .collect();
+        if diagnostics.is_empty() {
+            return hover;
+        }
+
+        let (mut sections, span) = match hover {
+            Some(ProgramHoverResponse { contents, span }) => (vec![to_markdown(contents)], span),
+            None => (vec![], diagnostics[0].0),
+        };
+        sections.extend(diagnostics.into_iter().map(|(_, info)| info));
+
+        Some(ProgramHoverResponse {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: sections.join("\n\n---\n\n"),
+            }),
+            span,
+        })
+    }
+
+    /// Returns hover info for the expression or operator at an offset.
+    fn get_expr_hover_info(&self, offset: usize) -> Option<ProgramHoverResponse> {
         let program_ast = self.original_ast();
         let tightest_expr = ast::get_tightest_expr(offset, &program_ast)?;
         let span = tightest_expr.span;
 
+        let operator_hover = match tightest_expr.as_ref() {
+            Expr::BinaryExpr(BinaryExpr { op, .. }) => self.get_operator_hover_info(offset, *op),
+            Expr::UnaryExpr(UnaryExpr { op, .. }) => {
+                self.get_unary_operator_hover_info(offset, span, *op)
+            }
+            _ => None,
+        };
+        if operator_hover.is_some() {
+            return operator_hover;
+        }
+
         // Now the fun part: actually figure out the hover result.
         let simplified_ast = self.simplified_ast();
         let var_asgns = collect_var_asgns(&simplified_ast);
@@ -40,4 +94,130 @@ impl Program {
             span,
         })
     }
+
+    /// Returns a description of what the metavariable at an offset matches, if any.
+    fn get_pattern_hover_info(&self, offset: usize) -> Option<ProgramHoverResponse> {
+        let token = self.token_at(offset)?;
+        let matches = match token.ty {
+            TokenType::AnyPattern(_) => "any expression",
+            TokenType::ConstPattern(_) => "a constant",
+            TokenType::VariablePattern(_) => "a variable",
+            _ => return None,
+        };
+
+        Some(ProgramHoverResponse {
+            contents: HoverContents::Scalar(MarkedString::String(format!(
+                "`{}`: matches {}",
+                token, matches
+            ))),
+            span: token.span,
+        })
+    }
+
+    /// Returns the token at an offset in the program, if any.
+    fn token_at(&self, offset: usize) -> Option<Token> {
+        let ScanResult { tokens, .. } = scan(&*self.source);
+        tokens.into_iter().find(|tok| tok.span.contains(offset))
+    }
+
+    /// Returns documentation for the binary operator `op` if the offset is over its token.
+    fn get_operator_hover_info(
+        &self,
+        offset: usize,
+        op: BinaryOperator,
+    ) -> Option<ProgramHoverResponse> {
+        let token = self.token_at(offset)?;
+        if BinaryOperator::try_from(&token).ok()? != op {
+            return None;
+        }
+
+        let description = match op {
+            BinaryOperator::Plus => "addition",
+            BinaryOperator::Minus => "subtraction",
+            BinaryOperator::PlusMinus => "addition or subtraction",
+            BinaryOperator::Mult => "multiplication",
+            BinaryOperator::Div => "division",
+            BinaryOperator::Mod => "modulo",
+            BinaryOperator::Exp => "exponentiation",
+        };
+        let associativity = if op.is_right_associative() {
+            "right"
+        } else {
+            "left"
+        };
+
+        Some(ProgramHoverResponse {
+            contents: HoverContents::Scalar(MarkedString::String(format!(
+                "`{}`: {} (precedence {}, {}-associative)",
+                op,
+                description,
+                op.precedence(),
+                associativity
+            ))),
+            span: token.span,
+        })
+    }
+
+    /// Returns documentation for the unary operator `op` of the unary expression spanning
+    /// `expr_span` if the offset is over the operator's token.
+    fn get_unary_operator_hover_info(
+        &self,
+        offset: usize,
+        expr_span: Span,
+        op: UnaryOperator,
+    ) -> Option<ProgramHoverResponse> {
+        let token = self.token_at(offset)?;
+        // The operator of a prefix expression is its first token, and that of a postfix
+        // expression is its last.
+        let is_op_token = if op.is_postfix() {
+            token.span.hi == expr_span.hi
+        } else {
+            token.span.lo == expr_span.lo
+        };
+        if !is_op_token || token.to_string() != op.to_string() {
+            return None;
+        }
+
+        let (description, fixity) = match op {
+            UnaryOperator::SignPositive => ("identity", "prefix, binds more loosely than `^`"),
+            UnaryOperator::SignNegative => ("negation", "prefix, binds more loosely than `^`"),
+            UnaryOperator::Factorial => ("factorial", "postfix, binds more tightly than `^`"),
+        };
+
+        Some(ProgramHoverResponse {
+            contents: HoverContents::Scalar(MarkedString::String(format!(
+                "`{}`: {} ({})",
+                op, description, fixity
+            ))),
+            span: token.span,
+        })
+    }
+}
+
+/// Formats the title and explanation of a diagnostic as markdown.
+fn fmt_diagnostic_info(code: &str, title: &str) -> String {
+    let header = format!("**{}**: {}", code, title);
+    match Diagnostic::explanation(code) {
+        Some(explanation) => format!("{}\n\n{}", header, explanation.trim_end()),
+        None => header,
+    }
+}
+
+/// Converts hover contents to markdown.
+fn to_markdown(contents: HoverContents) -> String {
+    let marked_string = |s| match s {
+        MarkedString::String(s) => s,
+        MarkedString::LanguageString(LanguageString { language, value }) => {
+            format!("```{}\n{}\n```", language, value)
+        }
+    };
+    match contents {
+        HoverContents::Scalar(s) => marked_string(s),
+        HoverContents::Array(ss) => ss
+            .into_iter()
+            .map(marked_string)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        HoverContents::Markup(MarkupContent { value, .. }) => value,
+    }
 }
@@ -18,9 +18,19 @@ impl Program {
         offset: usize,
         include_declaration: bool,
     ) -> Option<Vec<ProgramLocation>> {
+        let seeking = self.get_var_at(offset)?;
+        Some(self.get_references_to(seeking, include_declaration))
+    }
+
+    /// Returns all references to a variable in the program.
+    /// If `include_declaration` is false, declaration references are excluded.
+    pub fn get_references_to(
+        &self,
+        seeking: InternedStr,
+        include_declaration: bool,
+    ) -> Vec<ProgramLocation> {
         let uri = self.document_uri.as_ref();
-        let references = self.get_kinded_references(offset)?;
-        let references = references
+        self.get_kinded_references_to(seeking)
             .into_iter()
             .filter_map(|rk| match rk {
                 ReferenceKind::Definition(_) if !include_declaration => None,
@@ -29,24 +39,29 @@ impl Program {
                     span: *rk.span(),
                 }),
             })
-            .collect();
+            .collect()
+    }
 
-        Some(references)
+    /// Returns the variable an offset is over, if any.
+    pub fn get_var_at(&self, offset: usize) -> Option<InternedStr> {
+        let program_ast = self.original_ast();
+        ast::get_tightest_expr(offset, &program_ast)?.get_var()
     }
 
     pub fn get_kinded_references(&self, offset: usize) -> Option<Vec<ReferenceKind>> {
-        let program_ast = &self.original_ast();
-        let tightest_expr = ast::get_tightest_expr(offset, program_ast)?;
-        let seeking = tightest_expr.get_var()?;
+        let seeking = self.get_var_at(offset)?;
+        Some(self.get_kinded_references_to(seeking))
+    }
 
+    fn get_kinded_references_to(&self, seeking: InternedStr) -> Vec<ReferenceKind> {
         let mut reference_finder = ReferenceFinder {
             seeking,
             is_declaration: false,
             refs: vec![],
         };
-        reference_finder.visit_stmt_list(program_ast);
+        reference_finder.visit_stmt_list(&self.original_ast());
 
-        Some(reference_finder.refs)
+        reference_finder.refs
     }
 }
 
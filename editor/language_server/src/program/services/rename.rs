@@ -27,32 +27,65 @@ impl Program {
         }
     }
 
-    /// Retrieves edits to rename a variable across a program.
-    pub fn get_rename_edits(&self, offset: usize, rename: String) -> Option<ProgramRenameResponse> {
-        let ast = self.original_ast();
+    /// Retrieves edits to rename the variable at an offset across a program to `new_name`. If
+    /// `new_name` is not a legal variable name, a reason as to why is returned via an error.
+    pub fn rename(
+        &self,
+        offset: usize,
+        new_name: String,
+    ) -> Result<Option<ProgramRenameResponse>, ProgramCannotRenameBecause> {
+        if !is_valid_var_name(&new_name) {
+            return Err(ProgramCannotRenameBecause::InvalidVariableName(new_name));
+        }
+
         // Only variables can be renamed.
-        let orig_var_name = get_tightest_expr(offset, &ast)?.get_var()?;
+        match self.get_var_at(offset) {
+            Some(name) => self.rename_var(name, new_name),
+            None => Ok(None),
+        }
+    }
+
+    /// Retrieves edits to rename all occurrences of the variable `var` in a program to `new_name`,
+    /// or `None` if the variable does not occur in the program. If `new_name` is not a legal
+    /// variable name, a reason as to why is returned via an error.
+    pub fn rename_var(
+        &self,
+        var: InternedStr,
+        new_name: String,
+    ) -> Result<Option<ProgramRenameResponse>, ProgramCannotRenameBecause> {
+        if !is_valid_var_name(&new_name) {
+            return Err(ProgramCannotRenameBecause::InvalidVariableName(new_name));
+        }
 
         let mut collector = NamedVarCollector {
-            name: orig_var_name,
+            name: var,
             locations: vec![],
         };
-        collector.visit_stmt_list(&ast);
+        collector.visit_stmt_list(&self.original_ast());
+        if collector.locations.is_empty() {
+            return Ok(None);
+        }
 
-        Some(ProgramRenameResponse {
+        Ok(Some(ProgramRenameResponse {
             uri: (*self.document_uri).clone(),
             edits: collector
                 .locations
                 .into_iter()
                 .map(|span| ProgramTextEdit {
                     span,
-                    edit: rename.to_string(),
+                    edit: new_name.clone(),
                 })
                 .collect(),
-        })
+        }))
     }
 }
 
+/// Slide variables are non-empty and composed only of alphabetic characters, so in particular they
+/// cannot start with a digit or contain pattern sigils (`$`, `#`, `_`).
+fn is_valid_var_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(char::is_alphabetic)
+}
+
 struct NamedVarCollector {
     name: InternedStr,
     locations: Vec<Span>,
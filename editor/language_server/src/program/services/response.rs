@@ -48,6 +48,7 @@ pub struct ProgramDiagnostic {
 }
 
 pub enum ProgramSymbolKind {
+    Program,
     Variable,
 }
 
@@ -58,6 +59,14 @@ pub struct ProgramSymbolInformation {
     pub location: ProgramLocation,
 }
 
+pub struct ProgramDocumentSymbol {
+    pub name: String,
+    pub kind: ProgramSymbolKind,
+    pub span: Span,
+    pub selection_span: Span,
+    pub children: Vec<ProgramDocumentSymbol>,
+}
+
 pub struct ProgramTextEdit {
     pub span: Span,
     pub edit: String,
@@ -65,6 +74,7 @@ pub struct ProgramTextEdit {
 
 pub enum ProgramCannotRenameBecause {
     CursorNotOverVariable,
+    InvalidVariableName(String),
 }
 
 pub struct ProgramCanRenameResponse {
@@ -77,6 +87,16 @@ pub struct ProgramRenameResponse {
     pub edits: Vec<ProgramTextEdit>,
 }
 
+pub struct ProgramEvaluation {
+    pub result: String,
+    pub diagnostics: Vec<ProgramDiagnostic>,
+}
+
+pub enum ProgramCannotEvaluateBecause {
+    RangeNotOverItem,
+    PositionNotOverItem,
+}
+
 pub struct ProgramFoldingRanges(pub Vec<Span>);
 
 pub struct ProgramSelectionRanges(pub Vec<Span>);
@@ -34,6 +34,41 @@ impl Program {
             })
             .collect()
     }
+
+    /// Returns a symbol for the slide program as a whole, whose children are symbols for each
+    /// variable definition in the program. The program symbol is named by the first line of the
+    /// program.
+    ///
+    /// Returns `None` if the program is empty.
+    pub fn get_document_symbol(&self) -> Option<ProgramDocumentSymbol> {
+        let name = self.source.lines().map(str::trim).find(|l| !l.is_empty())?;
+        let span = Span::new(0, self.end - self.start);
+
+        let ast = self.original_ast();
+        let mut children: Vec<_> = collect_var_asgns(&ast)
+            .into_iter()
+            .flat_map(|(var, definitions)| {
+                definitions
+                    .into_iter()
+                    .map(move |asgn| ProgramDocumentSymbol {
+                        name: var.to_string(),
+                        kind: ProgramSymbolKind::Variable,
+                        span: asgn.span,
+                        selection_span: asgn.lhs.span,
+                        children: vec![],
+                    })
+            })
+            .collect();
+        children.sort_by_key(|symbol| symbol.span.lo);
+
+        Some(ProgramDocumentSymbol {
+            name: name.to_owned(),
+            kind: ProgramSymbolKind::Program,
+            span,
+            selection_span: span,
+            children,
+        })
+    }
 }
 
 /// Pretty-formats a [variable](Expr::Var) symbol's definition given its assignment values.
@@ -4,29 +4,57 @@
 #![deny(missing_docs)]
 #![doc(html_logo_url = "https://raw.githubusercontent.com/yslide/slide/base/assets/logo.png")]
 
-use libslide::*;
-
 use parking_lot::{
-    MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    MappedRwLockReadGuard, MappedRwLockWriteGuard, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard,
 };
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
 
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
 mod ast;
 mod document_registry;
+mod evaluate;
 mod init;
 mod program;
 mod ptr;
 
-use document_registry::{Change, DocumentRegistry};
-use init::InitializationOptions;
+use document_registry::{no_program_error, Change, DocumentRegistry};
+use evaluate::{
+    EvaluateParams, EvaluateResult, SimplifyParams, EVALUATE_COMMAND, SIMPLIFY_COMMAND,
+};
+use init::{ConfigurationChange, InitializationOptions, ReferenceScope, Settings};
 use program::Program;
 use ptr::p;
 
 #[cfg(test)]
 mod tests;
 
+/// How long to wait for further changes to a document before analyzing it.
+const ANALYSIS_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// How many programs of a document are analyzed at a time. The document registry is unlocked
+/// between chunks, so that the analysis of a huge document does not block other requests.
+const ANALYSIS_CHUNK_SIZE: usize = 50;
+
+/// A change of a document's content whose diagnostics have not yet been published.
+struct PendingChange {
+    /// Identifies the change among all changes received by the server.
+    id: u64,
+    version: Option<i64>,
+    /// Whether the change, or a change it superseded, dropped a parsed document from the registry,
+    /// so that its old diagnostics must be cleared.
+    clears_diagnostics: bool,
+}
+
+/// The latest change of each document whose diagnostics have not yet been published.
+type PendingChanges = HashMap<Url, PendingChange>;
+
 /// A slide language server.
 pub struct SlideLS {
     /// LSP client the server communicates with.
@@ -36,9 +64,15 @@ pub struct SlideLS {
     ////// The following fields are always correctly set after `initialize`. //////
     ///////////////////////////////////////////////////////////////////////////////
     /// The database of documents known to the server session.
-    document_registry: RwLock<Option<DocumentRegistry>>,
+    document_registry: Arc<RwLock<Option<DocumentRegistry>>>,
     /// The [LSP client's](Self::client) capabilities.
     client_caps: RwLock<Option<ClientCapabilities>>,
+    /// User settings of the server session.
+    settings: RwLock<Settings>,
+    /// Document changes waiting for their diagnostics to be published.
+    pending_changes: Arc<Mutex<PendingChanges>>,
+    /// Source of [pending change](PendingChange) ids.
+    change_counter: AtomicU64,
 }
 
 impl SlideLS {
@@ -46,13 +80,17 @@ impl SlideLS {
     pub fn new(client: Client) -> Self {
         Self {
             client,
-            document_registry: RwLock::new(None),
+            document_registry: Arc::new(RwLock::new(None)),
             client_caps: RwLock::new(None),
+            settings: RwLock::new(Settings::default()),
+            pending_changes: Default::default(),
+            change_counter: AtomicU64::new(0),
         }
     }
 
-    /// Returns capabilities of the language server.
-    pub fn capabilities() -> ServerCapabilities {
+    /// Returns capabilities of the language server, given the capabilities of its client.
+    /// Capabilities the client cannot make use of, like rename support, are not advertised.
+    pub fn capabilities(client_caps: &ClientCapabilities) -> ServerCapabilities {
         let text_document_sync = Some(TextDocumentSyncCapability::Options(
             TextDocumentSyncOptions {
                 open_close: Some(true),
@@ -68,13 +106,29 @@ impl SlideLS {
         let workspace_symbol_provider = Some(true);
         let document_formatting_provider = Some(true);
         let document_range_formatting_provider = Some(true);
-        let rename_provider = Some(RenameProviderCapability::Options(RenameOptions {
-            prepare_provider: Some(true),
-            work_done_progress_options: WorkDoneProgressOptions::default(),
-        }));
+        let rename_provider = client_caps
+            .text_document
+            .as_ref()
+            .and_then(|td| td.rename.as_ref())
+            .map(|rename| {
+                if rename.prepare_support.unwrap_or(false) {
+                    RenameProviderCapability::Options(RenameOptions {
+                        prepare_provider: Some(true),
+                        work_done_progress_options: WorkDoneProgressOptions::default(),
+                    })
+                } else {
+                    RenameProviderCapability::Simple(true)
+                }
+            });
         let folding_range_provider = Some(FoldingRangeProviderCapability::Simple(true));
         let selection_range_provider = Some(SelectionRangeProviderCapability::Simple(true));
-        let code_action_provider = Some(CodeActionProviderCapability::Simple(true));
+        let code_action_provider = Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![
+                CodeActionKind::QUICKFIX,
+                CodeActionKind::REFACTOR_REWRITE,
+            ]),
+            work_done_progress_options: WorkDoneProgressOptions::default(),
+        }));
         let completion_provider = Some(CompletionOptions {
             resolve_provider: Some(false),
             trigger_characters: Some(
@@ -88,6 +142,10 @@ impl SlideLS {
         let code_lens_provider = Some(CodeLensOptions {
             resolve_provider: Some(true),
         });
+        let execute_command_provider = Some(ExecuteCommandOptions {
+            commands: vec![EVALUATE_COMMAND.to_owned(), SIMPLIFY_COMMAND.to_owned()],
+            work_done_progress_options: WorkDoneProgressOptions::default(),
+        });
 
         ServerCapabilities {
             text_document_sync,
@@ -105,25 +163,136 @@ impl SlideLS {
             document_range_formatting_provider,
             rename_provider,
             folding_range_provider,
+            execute_command_provider,
             ..ServerCapabilities::default()
         }
     }
 
-    /// Records a document content change.
-    async fn change(&self, fi: Url, text: String, version: Option<i64>) {
-        self.registry_mut()
-            .apply_change(Change::Modified(fi.clone(), text));
+    /// Records a document content change. `language_id` is given when the change opens the
+    /// document.
+    ///
+    /// The change is applied to the document registry right away, so that requests made after
+    /// it see the new content. Programs are analyzed lazily, so only the diagnostics of the
+    /// document are computed and published, in a separate task, once no newer change of the
+    /// document has arrived for [`ANALYSIS_DEBOUNCE`](ANALYSIS_DEBOUNCE); rapid edits do not each
+    /// re-evaluate the document. Diagnostics of superseded changes are not published.
+    async fn change(
+        &self,
+        fi: Url,
+        language_id: Option<String>,
+        text: String,
+        version: Option<i64>,
+    ) {
+        let id = self.change_counter.fetch_add(1, Ordering::Relaxed);
+        {
+            // The pending changes stay locked until the change is recorded, so that analysis of a
+            // superseded change does not publish diagnostics of the new content.
+            let mut pending_changes = self.pending_changes.lock();
+            let change = match language_id {
+                Some(language_id) => Change::Opened(fi.clone(), language_id, text),
+                None => Change::Modified(fi.clone(), text),
+            };
+            let (existed, exists) = {
+                let mut registry = self.registry_mut();
+                let existed = registry.apply_change(change);
+                (existed, registry.document(&fi).is_some())
+            };
+
+            let superseded = pending_changes.remove(&fi);
+            let change = PendingChange {
+                id,
+                version,
+                clears_diagnostics: !exists
+                    && (existed || superseded.map_or(false, |c| c.clears_diagnostics)),
+            };
+            pending_changes.insert(fi.clone(), change);
+        }
+
+        tokio::spawn(analyze_change(
+            self.client.clone(),
+            Arc::clone(&self.document_registry),
+            Arc::clone(&self.pending_changes),
+            fi,
+            id,
+        ));
+    }
+
+    /// Publishes the current diagnostics of documents, e.g. after they were re-parsed.
+    async fn publish_diagnostics(&self, uris: Vec<Url>) {
+        for uri in uris {
+            // Documents that are no longer parsed have no diagnostics.
+            let diags = self
+                .registry()
+                .document(&uri)
+                .map(|d| d.all_diagnostics())
+                .unwrap_or_default();
+            self.client.publish_diagnostics(uri, diags, None).await;
+        }
+    }
+
+    /// Records the closing of a document, clearing its diagnostics.
+    async fn close(&self, fi: Url) {
+        let existed = {
+            // Cancel analysis of the document's pending change, if any.
+            let mut pending_changes = self.pending_changes.lock();
+            pending_changes.remove(&fi);
+            self.registry_mut()
+                .apply_change(Change::Removed(fi.clone()))
+        };
+        if existed {
+            self.client.publish_diagnostics(fi, vec![], None).await;
+        }
+    }
 
-        let document_diagnostics = self.registry().document(&fi).map(|d| d.all_diagnostics());
-        if let Some(diags) = document_diagnostics {
-            self.client.publish_diagnostics(fi, diags, version).await;
+    /// Evaluates the programs in a document, or the program in a range of it, as requested by
+    /// the [`slide/evaluate`](EVALUATE_COMMAND) command.
+    fn evaluate(&self, params: EvaluateParams) -> Result<EvaluateResult> {
+        let format = params.emit_format()?;
+        let config = params.emit_config(self.settings.read().emit_config)?;
+
+        let registry = self.registry();
+        let evaluations = match params.range {
+            Some(range) => registry
+                .with_program_at_uri_and_range(&params.uri, range, |program, span| {
+                    Some(program.evaluate(Some(span), format, config))
+                })
+                .map(|evaluation| vec![evaluation]),
+            None => registry.with_programs_at_uri(&params.uri, |program| {
+                Some(program.evaluate(None, format, config))
+            }),
+        };
+        let evaluations = evaluations
+            .unwrap_or_default()
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+        if evaluations.is_empty() {
+            return Err(no_program_error());
         }
+
+        let (results, diagnostics): (Vec<_>, Vec<_>) = evaluations
+            .into_iter()
+            .map(
+                |EvaluateResult {
+                     result,
+                     diagnostics,
+                 }| (result, diagnostics),
+            )
+            .unzip();
+        Ok(EvaluateResult {
+            result: results.join("\n"),
+            diagnostics: diagnostics.concat(),
+        })
     }
 
-    /// Records the closing of a document.
-    fn close(&self, fi: &Url) {
-        self.registry_mut()
-            .apply_change(Change::Removed(fi.clone()));
+    /// Simplifies the statement at a position, as requested by the
+    /// [`slide.simplify`](SIMPLIFY_COMMAND) command.
+    fn simplify(&self, params: SimplifyParams) -> Result<String> {
+        let config = self.settings.read().emit_config;
+        self.registry()
+            .with_program_at_uri_and_position(&params.uri, params.position, |program, offset| {
+                Some(program.simplify_at(offset, config))
+            })
+            .unwrap_or_else(|| Err(no_program_error()))
     }
 
     /// Retrieves the LSP client's capabilities.
@@ -142,27 +311,110 @@ impl SlideLS {
     }
 }
 
+/// Waits out the [debounce period](ANALYSIS_DEBOUNCE) of the pending change `id` of a document,
+/// then analyzes the document and publishes its diagnostics. Does nothing if the change was
+/// superseded or its document was closed in the meantime.
+///
+/// The document's programs are analyzed in [chunks](ANALYSIS_CHUNK_SIZE).
+async fn analyze_change(
+    client: Client,
+    document_registry: Arc<RwLock<Option<DocumentRegistry>>>,
+    pending_changes: Arc<Mutex<PendingChanges>>,
+    fi: Url,
+    id: u64,
+) {
+    tokio::time::delay_for(ANALYSIS_DEBOUNCE).await;
+
+    let (version, clears_diagnostics, num_programs) = {
+        let pending_changes = pending_changes.lock();
+        let change = match pending_changes.get(&fi) {
+            Some(change) if change.id == id => change,
+            _ => return,
+        };
+        let registry = RwLockReadGuard::map(document_registry.read(), |r| r.as_ref().unwrap());
+        let num_programs = registry.document(&fi).map(|d| d.programs.len());
+        (change.version, change.clears_diagnostics, num_programs)
+    };
+
+    // Documents that were dropped by the change publish an empty diagnostic set, so that the
+    // client clears their old diagnostics.
+    let num_programs = match num_programs {
+        Some(num_programs) => num_programs,
+        None => {
+            if take_pending_change(&pending_changes, &fi, id) && clears_diagnostics {
+                client.publish_diagnostics(fi, vec![], version).await;
+            }
+            return;
+        }
+    };
+
+    let mut diags = vec![];
+    let mut num_analyzed = 0;
+    for chunk_start in (0..num_programs).step_by(ANALYSIS_CHUNK_SIZE) {
+        let chunk_end = (chunk_start + ANALYSIS_CHUNK_SIZE).min(num_programs);
+        let chunk_diags = {
+            // Stop if the document was changed or closed meanwhile; the newer change is analyzed,
+            // or the closed document's diagnostics cleared, on its own.
+            let superseded = pending_changes.lock().get(&fi).map(|change| change.id) != Some(id);
+            let registry = RwLockReadGuard::map(document_registry.read(), |r| r.as_ref().unwrap());
+            match registry.document(&fi) {
+                Some(document) if !superseded => {
+                    document.diagnostics_of_programs(chunk_start..chunk_end)
+                }
+                _ => break,
+            }
+        };
+        diags.extend(chunk_diags);
+        num_analyzed = chunk_end;
+
+        tokio::task::yield_now().await;
+    }
+
+    if num_analyzed == num_programs && take_pending_change(&pending_changes, &fi, id) {
+        client.publish_diagnostics(fi, diags, version).await;
+    }
+}
+
+/// Removes the pending change `id` of a document, returning whether it was still the document's
+/// latest change.
+fn take_pending_change(pending_changes: &Mutex<PendingChanges>, fi: &Url, id: u64) -> bool {
+    let mut pending_changes = pending_changes.lock();
+    let latest = pending_changes.get(fi).map(|change| change.id) == Some(id);
+    if latest {
+        pending_changes.remove(fi);
+    }
+    latest
+}
+
 #[tower_lsp::async_trait]
 impl LanguageServer for SlideLS {
     async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
-        let (InitializationOptions { document_parsers }, diags) =
-            InitializationOptions::from_json(params.initialization_options);
+        let (
+            InitializationOptions {
+                document_parsers,
+                settings,
+            },
+            diags,
+        ) = InitializationOptions::from_json(params.initialization_options);
         for diag in diags {
             self.client
                 .log_message(MessageType::Error, diag.to_string())
                 .await;
         }
 
-        // TODO: make this a user option
-        let context = ProgramContext::default().lint(true);
-        let document_registry = DocumentRegistry::new(document_parsers, p(context));
+        let context = settings.program_context();
+        let document_registry =
+            DocumentRegistry::new(document_parsers, p(context), settings.program_limits);
+
+        let capabilities = SlideLS::capabilities(&params.capabilities);
 
         // Update fresh instance options
         *self.document_registry.write() = Some(document_registry);
         *self.client_caps.write() = Some(params.capabilities);
+        *self.settings.write() = settings;
 
         Ok(InitializeResult {
-            capabilities: SlideLS::capabilities(),
+            capabilities,
             ..InitializeResult::default()
         })
     }
@@ -177,11 +429,53 @@ impl LanguageServer for SlideLS {
         Ok(())
     }
 
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        let (change, diags) = ConfigurationChange::from_json(params.settings);
+        for diag in diags {
+            self.client
+                .log_message(MessageType::Error, diag.to_string())
+                .await;
+        }
+
+        let (context, relimit, program_limits) = {
+            let mut settings = self.settings.write();
+            let old_context = settings.program_context();
+            let old_program_limits = settings.program_limits;
+            settings.apply(&change);
+            let context = Some(settings.program_context()).filter(|ctxt| *ctxt != old_context);
+            let relimit = settings.program_limits != old_program_limits;
+            (context, relimit, settings.program_limits)
+        };
+
+        let mut reparsed = {
+            let mut registry = self.registry_mut();
+            let mut reparsed = match change.document_parsers {
+                Some(parsers) => registry.set_parsers(parsers),
+                None => vec![],
+            };
+            if let Some(context) = context {
+                reparsed.extend(registry.set_context(p(context)));
+            }
+            if relimit {
+                reparsed.extend(registry.set_limits(program_limits));
+            }
+            reparsed
+        };
+        reparsed.sort();
+        reparsed.dedup();
+
+        self.publish_diagnostics(reparsed).await;
+    }
+
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         let TextDocumentItem {
-            uri, text, version, ..
+            uri,
+            language_id,
+            text,
+            version,
         } = params.text_document;
-        self.change(uri, text, Some(version)).await;
+        self.change(uri, Some(language_id), text, Some(version))
+            .await;
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
@@ -190,12 +484,12 @@ impl LanguageServer for SlideLS {
         // so here we assume the only change passed is a change of the entire document's content.
         let TextDocumentContentChangeEvent { text, .. } =
             params.content_changes.into_iter().next().unwrap();
-        self.change(uri, text, version).await;
+        self.change(uri, None, text, version).await;
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let TextDocumentIdentifier { uri } = params.text_document;
-        self.close(&uri);
+        self.close(uri).await;
     }
 
     async fn goto_definition(
@@ -251,11 +545,30 @@ impl LanguageServer for SlideLS {
             ..
         } = params;
 
-        let references =
-            self.registry()
-                .with_program_at_uri_and_position(&uri, position, |program, offset| {
+        let registry = self.registry();
+        let reference_scope = self.settings.read().reference_scope;
+        let references = match reference_scope {
+            ReferenceScope::Program => {
+                registry.with_program_at_uri_and_position(&uri, position, |program, offset| {
                     program.get_references(offset, include_declaration)
-                });
+                })
+            }
+            scope => {
+                // Variables of the same name are shared across all programs in the scope, so find
+                // the variable at the position and then its references in all those programs.
+                let var =
+                    registry.with_program_at_uri_and_position(&uri, position, |program, offset| {
+                        program.get_var_at(offset)
+                    });
+                let get_references =
+                    |program: &Program| Some(program.get_references_to(var?, include_declaration));
+                let references = match scope {
+                    ReferenceScope::Document => registry.with_programs_at_uri(&uri, get_references),
+                    _ => registry.with_all_programs(get_references),
+                };
+                var.and(references).map(|refs| refs.concat())
+            }
+        };
 
         Ok(references)
     }
@@ -287,10 +600,24 @@ impl LanguageServer for SlideLS {
             ..
         } = params;
 
-        let symbols = self
-            .registry()
-            .with_programs_at_uri(&uri, |program| Some(program.get_symbols(None)));
-        let symbols = symbols.map(|s| DocumentSymbolResponse::Flat(s.concat()));
+        let supports_hierarchy = self
+            .client_capabilities()
+            .text_document
+            .as_ref()
+            .and_then(|td| td.document_symbol.as_ref())
+            .and_then(|ds| ds.hierarchical_document_symbol_support)
+            .unwrap_or(false);
+
+        let registry = self.registry();
+        let symbols = if supports_hierarchy {
+            registry
+                .with_programs_at_uri(&uri, |program| program.get_document_symbol())
+                .map(DocumentSymbolResponse::Nested)
+        } else {
+            registry
+                .with_programs_at_uri(&uri, |program| Some(program.get_symbols(None)))
+                .map(|s| DocumentSymbolResponse::Flat(s.concat()))
+        };
 
         Ok(symbols)
     }
@@ -315,10 +642,11 @@ impl LanguageServer for SlideLS {
             ..
         } = params;
 
-        let formattings = self.registry().with_programs_at_uri(&uri, |program| {
-            // TODO: use user emit config
-            Some(program.format(EmitConfig::default()))
-        });
+        let emit_config = self.settings.read().emit_config;
+        let formattings = self
+            .registry()
+            .with_programs_at_uri(&uri, |program| Some(program.format(emit_config)))
+            .map(|edits| edits.concat());
 
         Ok(formattings)
     }
@@ -333,13 +661,12 @@ impl LanguageServer for SlideLS {
             ..
         } = params;
 
-        let formatting = self
-            .registry()
-            .with_program_at_uri_and_range(&uri, range, |program, span| {
-                // TODO: use user emit config
-                program.format_span(span, EmitConfig::default())
-            })
-            .map(|f| vec![f]);
+        let emit_config = self.settings.read().emit_config;
+        let formatting =
+            self.registry()
+                .with_program_at_uri_and_range(&uri, range, |program, span| {
+                    program.format_span(span, emit_config)
+                });
 
         Ok(formatting)
     }
@@ -373,13 +700,47 @@ impl LanguageServer for SlideLS {
             ..
         } = params;
 
-        let renames =
-            self.registry()
+        let registry = self.registry();
+        let reference_scope = self.settings.read().reference_scope;
+        match reference_scope {
+            ReferenceScope::Program => registry
                 .with_program_at_uri_and_position(&uri, position, |program, offset| {
-                    program.get_rename_edits(offset, new_name)
-                });
-
-        Ok(renames)
+                    Some(program.rename(offset, new_name))
+                })
+                .unwrap_or(Ok(None)),
+            scope => {
+                // Like references, a variable is renamed across all programs in the scope.
+                let var = match registry.with_program_at_uri_and_position(
+                    &uri,
+                    position,
+                    |program, offset| program.get_var_at(offset),
+                ) {
+                    Some(var) => var,
+                    None => return Ok(None),
+                };
+                let rename = |program: &Program| Some(program.rename_var(var, new_name.clone()));
+                let renames = match scope {
+                    ReferenceScope::Document => registry.with_programs_at_uri(&uri, rename),
+                    _ => registry.with_all_programs(rename),
+                };
+
+                let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+                for rename in renames.unwrap_or_default() {
+                    let program_changes = rename?.and_then(|edit| edit.changes);
+                    for (uri, edits) in program_changes.into_iter().flatten() {
+                        changes.entry(uri).or_default().extend(edits);
+                    }
+                }
+                // As with a program scope, there is no edit if nothing is renamed.
+                if changes.is_empty() {
+                    return Ok(None);
+                }
+                Ok(Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    ..WorkspaceEdit::default()
+                }))
+            }
+        }
     }
 
     async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
@@ -424,10 +785,11 @@ impl LanguageServer for SlideLS {
             ..
         } = params;
 
+        let emit_config = self.settings.read().emit_config;
         let actions =
             self.registry()
                 .with_program_at_uri_and_range(&uri, range, |program, span| {
-                    Some(program.actions(span))
+                    Some(program.actions(span, emit_config))
                 });
         let actions = actions.map(|actions| {
             actions
@@ -445,11 +807,15 @@ impl LanguageServer for SlideLS {
             position,
         } = params.text_document_position;
 
-        let completions =
-            self.registry()
-                .with_program_at_uri_and_position(&uri, position, |program, offset| {
-                    Some(program.completions(offset))
-                });
+        let completions = self
+            .registry()
+            .with_program_in_document_at_uri_and_position(
+                &uri,
+                position,
+                |program, offset, document_programs| {
+                    Some(program.get_completions(offset, document_programs))
+                },
+            );
 
         Ok(completions)
     }
@@ -468,6 +834,28 @@ impl LanguageServer for SlideLS {
     async fn code_lens_resolve(&self, code_lens: CodeLens) -> Result<CodeLens> {
         Ok(code_lens)
     }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<Value>> {
+        let ExecuteCommandParams {
+            command, arguments, ..
+        } = params;
+
+        match command.as_str() {
+            EVALUATE_COMMAND => {
+                let result = self.evaluate(EvaluateParams::from_arguments(arguments)?)?;
+                // An `EvaluateResult` is always representable as JSON.
+                Ok(Some(serde_json::to_value(result).unwrap()))
+            }
+            SIMPLIFY_COMMAND => {
+                let result = self.simplify(SimplifyParams::from_arguments(arguments)?)?;
+                Ok(Some(Value::String(result)))
+            }
+            _ => Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                "unknown command `{}`",
+                command
+            ))),
+        }
+    }
 }
 
 #[tokio::main]
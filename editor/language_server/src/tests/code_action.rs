@@ -1,7 +1,9 @@
 use super::mocks::*;
+use super::utils::*;
 use crate::document_registry::SourceMap;
 
 use pretty_assertions::assert_eq;
+use serde_json::Value;
 use tower_lsp::lsp_types::*;
 
 async fn drive_code_action_test(content: &str, range: &Range, check: impl FnOnce(Vec<CodeAction>)) {
@@ -66,3 +68,248 @@ a := 1 + +++2 / 5
     })
     .await;
 }
+
+#[tokio::test]
+async fn unmatched_delimiter_quickfix() {
+    let content = r"
+a := 1 + 2)
+";
+    let sm = SourceMap::new(content);
+    let start = content.find(')').unwrap();
+    let range = Range::new(sm.to_position(start), sm.to_position(start + 1));
+
+    drive_code_action_test(content, &range, |actions| {
+        let quickfixes: Vec<_> = actions
+            .into_iter()
+            .filter(|a| a.kind == Some(CodeActionKind::QUICKFIX))
+            .collect();
+        assert_eq!(quickfixes.len(), 1);
+
+        let fix = &quickfixes[0];
+        assert_eq!(fix.diagnostics.as_ref().map(|d| d[0].range), Some(range));
+        let edits = fix
+            .edit
+            .as_ref()
+            .and_then(|e| e.changes.as_ref())
+            .and_then(|c| c.get(&default_file()))
+            .unwrap();
+        assert_eq!(edits, &vec![TextEdit::new(range, "".to_owned())]);
+        assert_eq!(fix.is_preferred, Some(false));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn mismatched_delimiter_quickfix() {
+    let content = r"
+a := [1 + 2)
+";
+    let sm = SourceMap::new(content);
+    let start = content.find(')').unwrap();
+    let range = Range::new(sm.to_position(start), sm.to_position(start + 1));
+
+    drive_code_action_test(content, &range, |actions| {
+        let quickfixes: Vec<_> = actions
+            .into_iter()
+            .filter(|a| a.kind == Some(CodeActionKind::QUICKFIX))
+            .collect();
+        assert_eq!(quickfixes.len(), 1);
+
+        let fix = &quickfixes[0];
+        assert_eq!(
+            fix.diagnostics.as_ref().and_then(|d| d[0].code.clone()),
+            Some(NumberOrString::String("P0003".to_owned()))
+        );
+        let edits = fix
+            .edit
+            .as_ref()
+            .and_then(|e| e.changes.as_ref())
+            .and_then(|c| c.get(&default_file()))
+            .unwrap();
+        assert_eq!(edits, &vec![TextEdit::new(range, "]".to_owned())]);
+        assert_eq!(fix.is_preferred, Some(true));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn simplify_rewrite() {
+    let content = r"
+a := 1 + 2 * 3
+";
+    let sm = SourceMap::new(content);
+    let start = content.find("1 + 2 * 3").unwrap();
+    let range = Range::new(sm.to_position(start), sm.to_position(start + 9));
+
+    drive_code_action_test(content, &range, |actions| {
+        assert_eq!(actions.len(), 1);
+
+        let rewrite = &actions[0];
+        assert_eq!(rewrite.title, "Simplify to 7");
+        assert_eq!(rewrite.kind, Some(CodeActionKind::REFACTOR_REWRITE));
+        assert_eq!(rewrite.diagnostics, None);
+        let edits = rewrite
+            .edit
+            .as_ref()
+            .and_then(|e| e.changes.as_ref())
+            .and_then(|c| c.get(&default_file()))
+            .unwrap();
+        assert_eq!(edits, &vec![TextEdit::new(range, "7".to_owned())]);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn simplify_selection() {
+    let content = r"
+1 + (2 + 3) * 4
+max(-1, -2) ^ 2
+";
+    let sm = SourceMap::new(content);
+    let range = |over: &str| {
+        let start = content.find(over).unwrap();
+        Range::new(sm.to_position(start), sm.to_position(start + over.len()))
+    };
+    let check_rewrite = |title: &'static str, edited: Range, edit: &'static str| {
+        move |actions: Vec<CodeAction>| {
+            assert_eq!(actions.len(), 1);
+
+            let rewrite = &actions[0];
+            assert_eq!(rewrite.title, title);
+            assert_eq!(rewrite.kind, Some(CodeActionKind::REFACTOR_REWRITE));
+            let edits = rewrite
+                .edit
+                .as_ref()
+                .and_then(|e| e.changes.as_ref())
+                .and_then(|c| c.get(&default_file()))
+                .unwrap();
+            assert_eq!(edits, &vec![TextEdit::new(edited, edit.to_owned())]);
+        }
+    };
+
+    // Only the selected subexpression is simplified.
+    drive_code_action_test(
+        content,
+        &range("2 + 3"),
+        check_rewrite("Simplify to 5", range("2 + 3"), "5"),
+    )
+    .await;
+
+    // A selection not aligned with a subexpression snaps to the smallest one enclosing it.
+    drive_code_action_test(
+        content,
+        &range("+ 3)"),
+        check_rewrite("Simplify selection to 5", range("(2 + 3)"), "5"),
+    )
+    .await;
+
+    // Simplified subexpressions keep grouping with the operations around them.
+    drive_code_action_test(
+        content,
+        &range("(-1, -"),
+        check_rewrite("Simplify selection to -1", range("max(-1, -2)"), "(-1)"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn simplify_previews_result() {
+    let content = r"
+a := 1 + 2
+b = x + x
+";
+    let sm = SourceMap::new(content);
+    let range = |over: &str| {
+        let start = content.find(over).unwrap();
+        Range::new(sm.to_position(start), sm.to_position(start + over.len()))
+    };
+    let check_preview = |title: &'static str| {
+        move |actions: Vec<CodeAction>| {
+            let rewrites: Vec<_> = actions
+                .into_iter()
+                .filter(|a| a.kind == Some(CodeActionKind::REFACTOR_REWRITE))
+                .collect();
+            assert_eq!(rewrites.len(), 1);
+            assert_eq!(rewrites[0].title, title);
+            assert_eq!(rewrites[0].is_preferred, Some(true));
+        }
+    };
+
+    drive_code_action_test(content, &range("1 + 2"), check_preview("Simplify to 3")).await;
+    drive_code_action_test(
+        content,
+        &range("b = x + x"),
+        check_preview("Simplify to b = x * 2"),
+    )
+    .await;
+}
+
+/// Applies the rewrite action offered over `over` in `content`, if any, returning the rewritten
+/// document.
+async fn apply_rewrite(initialization_options: Value, content: &str, over: &str) -> Option<String> {
+    let mut service = MockService::new(false, initialization_options).await;
+    let file = default_file();
+
+    service.did_open(&file, content).await;
+
+    let actions = service
+        .code_action(&file, &range_of(over, content))
+        .await
+        .unwrap_or_default();
+    let rewrite = actions.into_iter().find_map(|action| match action {
+        CodeActionOrCommand::CodeAction(action)
+            if action.kind == Some(CodeActionKind::REFACTOR_REWRITE) =>
+        {
+            Some(action)
+        }
+        _ => None,
+    });
+    let rewritten = rewrite.map(|rewrite| {
+        let changes = rewrite.edit.and_then(|edit| edit.changes).unwrap();
+        assert_eq!(changes.len(), 1);
+        apply_edits(content, &changes[&file])
+    });
+
+    service.shutdown().await;
+    rewritten
+}
+
+#[tokio::test]
+async fn apply_simplify_rewrite() {
+    let content = "a := 1 + 2 * 3\nb := a + (2 + 3) * 4";
+
+    assert_eq!(
+        apply_rewrite(default_initialization_options(), content, "1 + 2 * 3").await,
+        Some("a := 7\nb := a + (2 + 3) * 4".to_owned())
+    );
+    assert_eq!(
+        apply_rewrite(default_initialization_options(), content, "+ 3)").await,
+        Some("a := 1 + 2 * 3\nb := a + 5 * 4".to_owned())
+    );
+}
+
+#[tokio::test]
+async fn apply_simplify_rewrite_with_emit_config() {
+    let mut initialization_options = default_initialization_options();
+    initialization_options["emit_config"] = serde_json::json!(["define-assign"]);
+
+    assert_eq!(
+        apply_rewrite(initialization_options, "b = x + x", "b = x + x").await,
+        Some("b := x * 2".to_owned())
+    );
+}
+
+#[tokio::test]
+async fn no_simplify_rewrite_of_statements_with_errors() {
+    let content = "a := 1 + 1 / 0\nb := 1 + 2";
+
+    assert_eq!(
+        apply_rewrite(default_initialization_options(), content, "1 + 1 / 0").await,
+        None
+    );
+    // Other statements of the program are still rewritten.
+    assert_eq!(
+        apply_rewrite(default_initialization_options(), content, "1 + 2").await,
+        Some("a := 1 + 1 / 0\nb := 3".to_owned())
+    );
+}
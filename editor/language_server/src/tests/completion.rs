@@ -65,3 +65,53 @@ completions_tests! {
     //      d := 1 + 2
     //  "
 }
+
+#[tokio::test]
+async fn completions_across_programs() {
+    let parsers = markdown_math_document_parsers();
+    let mut service = MockService::new(/* link support */ false, parsers).await;
+    let file = markdown_file();
+
+    let DecorationResult { cursor, text, .. } = process_decorations(
+        r"
+# Definitions
+
+```math
+a := 1 + 2
+b := a + 4
+```
+
+# Usage
+
+```math
+c := a + ¦b
+```
+",
+    );
+    service.did_open(&file, &text).await;
+
+    let mut completions = match service
+        .completion(&file, cursor.expect("No cursor found"))
+        .await
+    {
+        Some(CompletionResponse::Array(completions)) => completions,
+        _ => unreachable!("Expected an array of completions"),
+    };
+    completions.sort_by(|a, b| a.label.cmp(&b.label));
+
+    let completions: Vec<_> = completions
+        .into_iter()
+        .map(|c| (c.label, c.documentation))
+        .collect();
+    let doc = |d: &str| Some(Documentation::String(d.to_owned()));
+    assert_eq!(
+        completions,
+        vec![
+            ("a".to_owned(), doc("= 3")),
+            ("b".to_owned(), doc("= a + 4")),
+            ("c".to_owned(), doc("= a + b")),
+        ]
+    );
+
+    service.shutdown().await;
+}
@@ -0,0 +1,113 @@
+use super::mocks::*;
+
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tower_lsp::lsp_types::*;
+
+fn codes(diagnostics: &PublishDiagnosticsParams) -> Vec<NumberOrString> {
+    diagnostics
+        .diagnostics
+        .iter()
+        .filter_map(|d| d.code.clone())
+        .collect()
+}
+
+fn lint_codes() -> Vec<NumberOrString> {
+    vec![NumberOrString::String("L0002".to_owned())]
+}
+
+#[tokio::test]
+async fn toggle_lint() {
+    let mut service = MockService::default().await;
+
+    let file = default_file();
+    let text = "a := 5 + ++5";
+
+    let diagnostics = service.did_open(&file, text).await;
+    assert_eq!(codes(&diagnostics), lint_codes());
+
+    // Disabling linting re-publishes the document's diagnostics, without lints.
+    let republished = service
+        .did_change_configuration(json!({ "lint": false }), 1)
+        .await;
+    assert_eq!(republished.len(), 1);
+    assert_eq!(republished[0].uri, file);
+    assert!(republished[0].diagnostics.is_empty());
+
+    let diagnostics = service.did_change(&file, text).await;
+    assert!(diagnostics.diagnostics.is_empty());
+
+    // And enabling it brings the lints back.
+    let republished = service
+        .did_change_configuration(json!({ "lint": true }), 1)
+        .await;
+    assert_eq!(codes(&republished[0]), lint_codes());
+
+    let diagnostics = service.did_change(&file, text).await;
+    assert_eq!(codes(&diagnostics), lint_codes());
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn change_document_parsers() {
+    let mut service = MockService::new(false, markdown_math_document_parsers()).await;
+
+    let file = markdown_file();
+    let text = "```math\na := 5 + ++5\n```";
+
+    let diagnostics = service.did_open(&file, text).await;
+    assert_eq!(codes(&diagnostics), lint_codes());
+
+    // The markdown document no longer has a parser, so it has no diagnostics.
+    let republished = service
+        .did_change_configuration(default_initialization_options(), 1)
+        .await;
+    assert_eq!(republished[0].uri, file);
+    assert!(republished[0].diagnostics.is_empty());
+    assert_eq!(service.formatting(&file).await, None);
+
+    let republished = service
+        .did_change_configuration(markdown_math_document_parsers(), 1)
+        .await;
+    assert_eq!(codes(&republished[0]), lint_codes());
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn change_emit_config() {
+    let mut service = MockService::default().await;
+
+    let file = default_file();
+    service.did_open(&file, "2 * a").await;
+
+    let republished = service
+        .did_change_configuration(json!({ "emit_config": ["implicit-mult"] }), 0)
+        .await;
+    assert!(republished.is_empty());
+
+    let edits = service.formatting(&file).await.unwrap();
+    assert_eq!(edits[0].new_text, "2a");
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn emit_section_define_assign() {
+    let mut service = MockService::default().await;
+
+    let file = default_file();
+    service.did_open(&file, "a = 1 + 2").await;
+    assert_eq!(service.formatting(&file).await, Some(vec![]));
+
+    service
+        .did_change_configuration(json!({ "emit": { "define-assign": true } }), 0)
+        .await;
+
+    let edits = service.formatting(&file).await.unwrap();
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].new_text, "a := 1 + 2");
+
+    service.shutdown().await;
+}
@@ -2,6 +2,7 @@ use super::mocks::*;
 use super::utils::range_of;
 
 use pretty_assertions::assert_eq;
+use serde_json::json;
 use tower_lsp::lsp_types::*;
 
 macro_rules! related_info {
@@ -59,7 +60,7 @@ async fn open_and_change_with_diagnostics() {
     let file = default_file();
     let text = r#"
     a := 1 + 2
-    c := 5 + ++5 + /
+    c := 5 + ++5 * /
     "#;
 
     let diagnostics = service.did_open(&file, text).await;
@@ -69,7 +70,16 @@ async fn open_and_change_with_diagnostics() {
         diagnostics.diagnostics,
         diagnostics! {
             text,
-            "/", [Error P0002] "Expected an expression, found / \\ expected an expression";; vec![],
+            "*", [Error P0002] "Expected an operand after `*` \\ operator missing an operand";; related_info! { text,
+                file@"/", "found `/`"
+            },
+            "/", [Error P0002] "Expected an operand after `/` \\ operator missing an operand";; vec![
+                DiagnosticRelatedInformation {
+                    // The end of the program, after its trailing whitespace.
+                    location: Location::new(file.clone(), Range::new(Position::new(3, 4), Position::new(3, 4))),
+                    message: "found end of file".to_string(),
+                }
+            ],
             "++5", [Warning L0002] "Trivially reducible unary operator chain";; related_info! { text,
             }
         }
@@ -105,3 +115,196 @@ async fn open_and_change_with_diagnostics() {
 
     service.shutdown().await;
 }
+
+#[tokio::test]
+async fn publish_diagnostics_of_latest_change_only() {
+    let mut service = MockService::default().await;
+
+    let file = default_file();
+    service.did_open(&file, "a := 1").await;
+
+    let text = "a := 5 + ++5";
+    let diagnostics = service
+        .did_change_rapidly(&file, &["a := 1 +", "a := 1 + /", text])
+        .await;
+
+    assert_eq!(diagnostics.uri, file);
+    assert_eq!(diagnostics.version, Some(3));
+    assert_eq!(
+        diagnostics.diagnostics,
+        diagnostics! {
+            text,
+            "++5", [Warning L0002] "Trivially reducible unary operator chain";; related_info! { text,
+            }
+        }
+    );
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn requests_see_changes_before_diagnostics_are_published() {
+    let mut service = MockService::default().await;
+
+    let file = default_file();
+    service.did_open(&file, "1 +").await;
+
+    // The change is answered by requests right away; only its diagnostics are debounced.
+    service
+        .did_change_without_diagnostics(&file, "3    * 4")
+        .await;
+    let edits = service.formatting(&file).await.unwrap();
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].new_text, "3 * 4");
+
+    let diagnostics = service.get_diagnostics().await;
+    assert_eq!(diagnostics.uri, file);
+    assert!(diagnostics.diagnostics.is_empty());
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn clear_diagnostics_of_document_without_programs() {
+    let mut service = MockService::new(false, markdown_math_document_parsers()).await;
+
+    let file = markdown_file();
+    let diagnostics = service
+        .did_open(&file, "# Math\n\n```math\na := 1 +\n```")
+        .await;
+    assert_eq!(diagnostics.uri, file);
+    assert!(!diagnostics.diagnostics.is_empty());
+
+    // Deleting the last math block leaves the document with no programs, and no diagnostics.
+    let diagnostics = service.did_change(&file, "# Math\n\nNo more math.").await;
+    assert_eq!(diagnostics.uri, file);
+    assert!(diagnostics.diagnostics.is_empty());
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn diagnose_document_by_language_id() {
+    let parsers = serde_json::json!({
+        "document_parsers": {
+            "markdown": r"```math\n((?:.|\n)*?)\n```",
+        },
+    });
+    let mut service = MockService::new(false, parsers).await;
+
+    // An untitled document has no file extension, but its language ID finds its parser.
+    let file = Url::parse("untitled:Untitled-1").unwrap();
+    let text = "# Math\n\n```math\na := 1 + /\n```";
+    let diagnostics = service
+        .did_open_with_language_id(&file, "markdown", text)
+        .await;
+    assert_eq!(diagnostics.uri, file);
+    assert_eq!(
+        diagnostics.diagnostics,
+        diagnostics! {
+            text,
+            "+", [Error P0002] "Expected an operand after `+` \\ operator missing an operand";; related_info! { text,
+                file@"/", "found `/`"
+            },
+            "/", [Error P0002] "Expected an operand after `/` \\ operator missing an operand";; vec![
+                DiagnosticRelatedInformation {
+                    // The end of the program, which spans the newline closing its math block.
+                    location: Location::new(file.clone(), Range::new(Position::new(3, 10), Position::new(4, 0))),
+                    message: "found end of file".to_string(),
+                }
+            ]
+        }
+    );
+
+    // Changes of the document reuse the language ID it was opened with.
+    let diagnostics = service
+        .did_change(&file, "# Math\n\n```math\na := 1 + 2\n```")
+        .await;
+    assert_eq!(diagnostics.uri, file);
+    assert!(diagnostics.diagnostics.is_empty());
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn clear_diagnostics_on_close() {
+    let mut service = MockService::default().await;
+
+    let file = default_file();
+    let diagnostics = service.did_open(&file, "a := 1 +").await;
+    assert_eq!(diagnostics.uri, file);
+    assert!(!diagnostics.diagnostics.is_empty());
+
+    let diagnostics = service.did_close(&file).await;
+    assert_eq!(diagnostics.uri, file);
+    assert!(diagnostics.diagnostics.is_empty());
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn skip_analysis_of_programs_over_limits() {
+    let options = json!({
+        "document_parsers": {
+            "md": r"```math\n((?:.|\n)*?)\n```",
+        },
+        "max_programs_per_document": 2,
+    });
+    let mut service = MockService::new(false, options).await;
+
+    let file = markdown_file();
+    let text = "```math\n1 + 1\n```\n\n```math\n2 + 2\n```\n\n```math\n3 + /\n```";
+    let diagnostics = service.did_open(&file, text).await;
+    assert_eq!(
+        diagnostics.diagnostics,
+        diagnostics! {
+            text,
+            "3 + /", [Information skipped] "Only the first 2 programs of a document are analyzed; raise `max_programs_per_document` to analyze this program";; vec![]
+        }
+    );
+
+    let republished = service
+        .did_change_configuration(json!({ "max_program_length": 3 }), 1)
+        .await;
+    let too_long = "Programs longer than 3 bytes are not analyzed; raise `max_program_length` to analyze this program";
+    let too_many = "Only the first 2 programs of a document are analyzed; raise `max_programs_per_document` to analyze this program";
+    let messages: Vec<_> = republished[0]
+        .diagnostics
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(messages, vec![too_long, too_long, too_many]);
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn crlf_and_unicode_whitespace() {
+    let mut service = MockService::default().await;
+
+    let file = default_file();
+    let text = "a = 1\u{a0}+\r\nb\u{a0}=\u{a0}2\r\nc = b\u{2003}@\r\n";
+
+    let diagnostics = service.did_open(&file, text).await;
+
+    let ranges: Vec<_> = diagnostics
+        .diagnostics
+        .into_iter()
+        .map(|diag| (diag.code.unwrap(), diag.range))
+        .collect();
+    assert_eq!(
+        ranges,
+        vec![
+            (
+                NumberOrString::String("S0001".to_owned()),
+                range_of("@", text)
+            ),
+            (
+                NumberOrString::String("P0002".to_owned()),
+                range_of("+", text)
+            ),
+        ]
+    );
+
+    service.shutdown().await;
+}
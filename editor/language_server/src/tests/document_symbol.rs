@@ -76,3 +76,75 @@ a + d
 ```
 "
 }
+
+#[tokio::test]
+async fn hierarchical_symbols_in_document() {
+    let capabilities = serde_json::json!({
+        "textDocument": {
+            "documentSymbol": {
+                "hierarchicalDocumentSymbolSupport": true,
+            },
+        },
+    });
+    let mut service =
+        MockService::with_capabilities(capabilities, markdown_math_document_parsers()).await;
+    let file = markdown_file();
+
+    let text = r"# Hello world
+
+```math
+a = 1 + 2
+b := a
+```
+
+## Othello
+
+```math
+
+1 + 3
+a = 3
+```";
+    service.did_open(&file, text).await;
+
+    let range = |subtext: &str, len: usize| {
+        let start = text.find(subtext).unwrap();
+        Range::new(to_position(start, text), to_position(start + len, text))
+    };
+    let program = |name: &str, source: &str, children: Vec<DocumentSymbol>| {
+        #[allow(deprecated)]
+        DocumentSymbol {
+            name: name.to_owned(),
+            detail: None,
+            kind: SymbolKind::Module,
+            deprecated: None,
+            range: range(source, source.len()),
+            selection_range: range(source, source.len()),
+            children: Some(children),
+        }
+    };
+    let variable = |name: &str, stmt: &str| {
+        #[allow(deprecated)]
+        DocumentSymbol {
+            name: name.to_owned(),
+            detail: None,
+            kind: SymbolKind::Variable,
+            deprecated: None,
+            range: range(stmt, stmt.len()),
+            selection_range: range(stmt, name.len()),
+            children: None,
+        }
+    };
+
+    let expected_symbols = DocumentSymbolResponse::Nested(vec![
+        program(
+            "a = 1 + 2",
+            "a = 1 + 2\nb := a",
+            vec![variable("a", "a = 1 + 2"), variable("b", "b := a")],
+        ),
+        program("1 + 3", "\n1 + 3\na = 3", vec![variable("a", "a = 3")]),
+    ]);
+
+    assert_eq!(service.document_symbol(&file).await, Some(expected_symbols));
+
+    service.shutdown().await;
+}
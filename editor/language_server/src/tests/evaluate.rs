@@ -0,0 +1,126 @@
+use super::mocks::*;
+
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tower_lsp::jsonrpc::ErrorCode;
+use tower_lsp::lsp_types::*;
+
+#[tokio::test]
+async fn evaluate_program() {
+    let mut service = MockService::default().await;
+    let file = default_file();
+    service.did_open(&file, "a = 1 + 2\na * 2").await;
+
+    let evaluation = service.evaluate(json!({ "uri": file })).await.unwrap();
+    assert_eq!(evaluation.result, "a = 3\na * 2");
+    assert!(evaluation.diagnostics.is_empty());
+
+    let evaluation = service
+        .evaluate(json!({ "uri": file, "emitFormat": "s-expression" }))
+        .await
+        .unwrap();
+    assert_eq!(evaluation.result, "(= a 3)\n(* a 2)");
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn evaluate_range() {
+    let mut service = MockService::default().await;
+    let file = default_file();
+    service
+        .did_open(&file, "a := 2 * (1 + 2)\nb := 1 / 0")
+        .await;
+
+    // (1 + 2)
+    let range = Range::new(Position::new(0, 9), Position::new(0, 16));
+    let evaluation = service
+        .evaluate(json!({ "uri": file, "range": range, "emitFormat": "latex" }))
+        .await
+        .unwrap();
+    assert_eq!(evaluation.result, "$3$");
+    assert!(evaluation.diagnostics.is_empty());
+
+    // b := 1 / 0
+    let range = Range::new(Position::new(1, 0), Position::new(1, 10));
+    let evaluation = service
+        .evaluate(json!({ "uri": file, "range": range }))
+        .await
+        .unwrap();
+    assert_eq!(evaluation.result, "b := inf");
+    let codes: Vec<_> = evaluation
+        .diagnostics
+        .into_iter()
+        .filter_map(|d| d.code)
+        .collect();
+    assert_eq!(codes, vec![NumberOrString::String("V0002".to_owned())]);
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn evaluate_range_without_program() {
+    let mut service = MockService::new(false, markdown_math_document_parsers()).await;
+    let file = markdown_file();
+    service
+        .did_open(&file, "# Title\n```math\n1 + 2\n```")
+        .await;
+
+    let range = Range::new(Position::new(0, 0), Position::new(0, 7));
+    let err = service
+        .evaluate(json!({ "uri": file, "range": range }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::ServerError(200));
+
+    let range = Range::new(Position::new(2, 0), Position::new(2, 5));
+    let evaluation = service
+        .evaluate(json!({ "uri": file, "range": range }))
+        .await
+        .unwrap();
+    assert_eq!(evaluation.result, "3");
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn evaluate_invalid_params() {
+    let mut service = MockService::default().await;
+    let file = default_file();
+    service.did_open(&file, "1 + 2").await;
+
+    let err = service
+        .evaluate(json!({ "uri": file, "emitFormat": "binary" }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidParams);
+
+    let err = service
+        .evaluate(json!({ "uri": file, "emitConfig": ["frac", "bad"] }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidParams);
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn simplify_statement_at_position() {
+    let mut service = MockService::default().await;
+    let file = default_file();
+    service.did_open(&file, "a = 1 + 2\n2 * (b + b)\n\n").await;
+
+    let simplified = service.simplify(&file, Position::new(0, 5)).await.unwrap();
+    assert_eq!(simplified, "a = 3");
+
+    let simplified = service.simplify(&file, Position::new(1, 6)).await.unwrap();
+    assert_eq!(simplified, "b * 4");
+
+    let err = service
+        .simplify(&file, Position::new(2, 0))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::ServerError(202));
+
+    service.shutdown().await;
+}
@@ -11,6 +11,7 @@ async fn drive_folding_ranges_tests(content: &str, expected_ranges: &[Range]) {
     service.did_open(&file, content).await;
 
     let folding_ranges = service.folding_range(&file).await.unwrap();
+    assert_eq!(folding_ranges.len(), expected_ranges.len());
     for (range, expected_range) in folding_ranges.iter().zip(expected_ranges) {
         assert_eq!(range.start_line, expected_range.start.line);
         assert_eq!(range.start_character, Some(expected_range.start.character));
@@ -46,8 +47,27 @@ def  :=   234
     let expected_ranges = &[
         Range::new(Position::new(4, 0), Position::new(6, 5)),
         Range::new(Position::new(7, 0), Position::new(9, 5)),
-        Range::new(Position::new(13, 0), Position::new(13, 15)),
         Range::new(Position::new(15, 0), Position::new(17, 6)),
     ];
     drive_folding_ranges_tests(content, expected_ranges).await;
 }
+
+#[tokio::test]
+async fn folding_range_grouped_expressions() {
+    let content = r"
+```math
+x := (1 +
+  2) * [3 -
+  (4
+   + 5)]
+y := (6 + 7)
+```
+";
+    let expected_ranges = &[
+        Range::new(Position::new(2, 0), Position::new(5, 8)),
+        Range::new(Position::new(2, 5), Position::new(3, 4)),
+        Range::new(Position::new(3, 7), Position::new(5, 8)),
+        Range::new(Position::new(4, 2), Position::new(5, 7)),
+    ];
+    drive_folding_ranges_tests(content, expected_ranges).await;
+}
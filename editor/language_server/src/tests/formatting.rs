@@ -11,6 +11,7 @@ async fn drive_formatting_test(content: &str, expected_edits: &[(Position, Posit
     service.did_open(&file, content).await;
 
     let edits = service.formatting(&file).await.unwrap();
+    assert_eq!(edits.len(), expected_edits.len());
     for (expected_edit, edit) in expected_edits.iter().zip(edits) {
         let &(start, end, expected_edit) = expected_edit;
         assert_eq!(edit.range.start, start);
@@ -40,11 +41,55 @@ def  :=   234
 ";
     let expected_edits = &[
         (Position::new(4, 0), Position::new(6, 5), "1 + 2 / 3 ^ 4"),
+        (Position::new(10, 0), Position::new(10, 15), "a = b + c"),
         (
-            Position::new(10, 0),
+            Position::new(11, 0),
             Position::new(12, 6),
-            "a = b + c\ndef := 234 * 78",
+            "def := 234 * 78",
         ),
     ];
     drive_formatting_test(content, expected_edits).await;
 }
+
+#[tokio::test]
+async fn formatting_only_changed_statements() {
+    let content = r"
+```math
+a = b + c
+
+b   =   2
+
+
+c := a * b
+```
+";
+    let expected_edits = &[(Position::new(4, 0), Position::new(4, 9), "b = 2")];
+    drive_formatting_test(content, expected_edits).await;
+}
+
+#[tokio::test]
+async fn formatting_chained_assignment() {
+    let content = r"
+```math
+a =  b = 1 +  2
+```
+";
+    let expected_edits = &[(
+        Position::new(2, 0),
+        Position::new(2, 15),
+        "a = 1 + 2\nb = 1 + 2",
+    )];
+    drive_formatting_test(content, expected_edits).await;
+}
+
+#[tokio::test]
+async fn formatting_already_formatted() {
+    let content = r"
+```math
+a = b + c
+
+c := a * b
+```
+";
+    drive_formatting_test(content, &[]).await;
+}
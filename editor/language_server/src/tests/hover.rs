@@ -40,15 +40,9 @@ hover_tests! {
     simple_expr: r"
         a := ¦1 + 2
               ~@[= 1]"
-    binary_operator: r"
-        a := 1 ¦+ 2
-             ~~~~~~@[= 3]"
     inside_binary_expression: r"
         a := 1 ¦ + 2
              ~~~~~~~@[= 3]"
-    unary_expression: r"
-        a := ¦++2
-              ~~~@[= 2]"
     paren: r"
         ¦(1 + 5)
          ~~~~~~~@[= 6]"
@@ -59,11 +53,171 @@ hover_tests! {
         a := ¦c
               ~@[= ???]"
     multidefined_var: r"
-        ¦a := c
+        a := c
+        ¦a := 2c
          ~@[= c<|>= c * 2]
-         a := 2c
     "
     no_hover: r"
         a :¦= b
     "
 }
+
+macro_rules! operator_hover_tests {
+    ($($name:ident: $text:expr)*) => {$(
+        #[tokio::test]
+        async fn $name() {
+            let mut service = MockService::default().await;
+            let file = default_file();
+
+            let DecorationResult { decorations, cursor, text } = process_decorations($text);
+            service.did_open(&file, &text).await;
+
+            let hover_info = service.hover(&file, cursor.expect("cursor not found")).await.unwrap();
+
+            let (expected_range, expected_content) = decorations.into_iter().next().unwrap();
+            let expected_content = HoverContents::Scalar(MarkedString::String(
+                expected_content.expect("Expected hover contents!"),
+            ));
+
+            assert_eq!(hover_info.range, Some(expected_range));
+            assert_eq!(hover_info.contents, expected_content);
+
+            service.shutdown().await;
+        }
+    )*}
+}
+
+operator_hover_tests! {
+    mult_operator: r"
+        2 ¦* 3
+          ~~@[`*`: multiplication (precedence 1, left-associative)]"
+    plus_operator: r"
+        a := 1 ¦+ 2
+               ~~@[`+`: addition (precedence 0, left-associative)]"
+    // Columns are counted in bytes, and `±` is two bytes long.
+    plus_minus_operator: r"
+        a := 1 ¦± 2
+               ~~~@[`±`: addition or subtraction (precedence 0, left-associative)]"
+    exp_operator: r"
+        a := b ¦^ 2
+               ~~@[`^`: exponentiation (precedence 2, right-associative)]"
+    unary_operator: r"
+        a := ¦-2
+             ~~@[`-`: negation (prefix, binds more loosely than `^`)]"
+    factorial_operator: r"
+        a := b¦!
+              ~~@[`!`: factorial (postfix, binds more tightly than `^`)]"
+}
+
+#[tokio::test]
+async fn rst_math_directive() {
+    let mut service = MockService::new(false, rst_math_document_parsers()).await;
+    let file = rst_file();
+
+    let DecorationResult {
+        decorations,
+        cursor,
+        text,
+    } = process_decorations(
+        r"
+Sums
+====
+
+.. math::
+   :label: sum
+
+   ¦a := 5 + 6
+    ~@[= 11]",
+    );
+    service.did_open(&file, &text).await;
+
+    let hover = service
+        .hover(&file, cursor.expect("cursor not found"))
+        .await
+        .unwrap();
+
+    let (expected_range, expected_content) = decorations.into_iter().next().unwrap();
+    let expected_content = HoverContents::Scalar(MarkedString::LanguageString(LanguageString {
+        language: "math".to_string(),
+        value: expected_content.expect("Expected hover contents!"),
+    }));
+
+    assert_eq!(hover.range, Some(expected_range));
+    assert_eq!(hover.contents, expected_content);
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn diagnostic_explanation() {
+    let mut service = MockService::default().await;
+    let file = default_file();
+
+    let DecorationResult {
+        decorations,
+        cursor,
+        text,
+    } = process_decorations(
+        r"
+        a := ++¦2
+               ~~",
+    );
+    service.did_open(&file, &text).await;
+
+    let hover = service
+        .hover(&file, cursor.expect("cursor not found"))
+        .await
+        .unwrap();
+
+    let explanation = libslide::diagnostics::Diagnostic::explanation("L0002").unwrap();
+    let expected_content = HoverContents::Markup(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: format!(
+            "```math\n= 2\n```\n\n---\n\n**L0002**: Trivially reducible unary operator chain\n\n{}",
+            explanation.trim_end()
+        ),
+    });
+
+    assert_eq!(hover.range, Some(decorations[0].0));
+    assert_eq!(hover.contents, expected_content);
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn pattern_metavariables() {
+    let options = serde_json::json!({
+        "document_parsers": {
+            "slide": { "regex": r"((?:.|\n)*)", "expr_pat": true },
+        },
+    });
+    let mut service = MockService::new(false, options).await;
+    let file = default_file();
+    // Each pattern is used twice, so that no single-use lint is shown with its hover info.
+    let text = "_a + #b * $c - _a * #b * $c";
+    service.did_open(&file, text).await;
+
+    for (offset, metavariable, matches) in &[
+        (0, "_a", "any expression"),
+        (5, "#b", "a constant"),
+        (10, "$c", "a variable"),
+    ] {
+        let hover = service
+            .hover(&file, Position::new(0, *offset))
+            .await
+            .unwrap();
+        let range = Range::new(
+            Position::new(0, *offset),
+            Position::new(0, offset + metavariable.len() as u64),
+        );
+        let expected_content = HoverContents::Scalar(MarkedString::String(format!(
+            "`{}`: matches {}",
+            metavariable, matches
+        )));
+
+        assert_eq!(hover.range, Some(range));
+        assert_eq!(hover.contents, expected_content);
+    }
+
+    service.shutdown().await;
+}
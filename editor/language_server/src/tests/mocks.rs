@@ -2,6 +2,7 @@
 //! Extended from https://github.com/wasm-lsp/wasm-language-server/blob/main/crates/testing/src/lsp.rs
 
 use serde_json::Value;
+use std::time::Duration;
 use tokio::stream::StreamExt;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{LspService, MessageStream};
@@ -31,9 +32,22 @@ pub fn markdown_math_document_parsers() -> Value {
     })
 }
 
+pub fn rst_file() -> Url {
+    Url::parse("file:///fi.rst").unwrap()
+}
+
+pub fn rst_math_document_parsers() -> Value {
+    serde_json::json!({
+        "document_parsers": {
+            "rst": { "builtin": "rst-math" },
+        },
+    })
+}
+
 pub struct MockService {
     service: Spawn<LspService>,
     msg_stream: MessageStream,
+    server_capabilities: ServerCapabilities,
 }
 
 impl MockService {
@@ -43,22 +57,34 @@ impl MockService {
 
     /// Creates a new slide language service and initializes it.
     pub async fn new(link_support: bool, initialization_options: Value) -> Self {
+        Self::with_capabilities(
+            initialize::capabilities(link_support),
+            initialization_options,
+        )
+        .await
+    }
+
+    /// Creates a new slide language service and initializes it with the given client
+    /// capabilities.
+    pub async fn with_capabilities(capabilities: Value, initialization_options: Value) -> Self {
         let (service, msg_stream) = LspService::new(crate::SlideLS::new);
         let service = Spawn::new(service);
         let mut service = Self {
             service,
             msg_stream,
+            server_capabilities: ServerCapabilities::default(),
         };
 
         service.assert_ready();
 
         // Initialize
-        service
-            .send_recv(
-                initialize::request(link_support, initialization_options),
-                Some(initialize::response()),
-            )
-            .await;
+        let response = service
+            .send(initialize::request(capabilities, initialization_options))
+            .await
+            .unwrap();
+        assert_eq!(response["id"], 1);
+        service.server_capabilities =
+            serde_json::from_value(response["result"]["capabilities"].clone()).unwrap();
         // Mark initialized
         service.send_recv(initialized::notification(), None).await;
         // Skip "server initialized" message
@@ -67,6 +93,11 @@ impl MockService {
         service
     }
 
+    /// Capabilities the server advertised when it was initialized.
+    pub fn server_capabilities(&self) -> &ServerCapabilities {
+        &self.server_capabilities
+    }
+
     pub async fn shutdown(&mut self) {
         self.assert_ready();
         self.send_recv(shutdown::request(), Some(shutdown::response()))
@@ -90,7 +121,7 @@ impl MockService {
         assert_eq!(self.send(send).await, recv);
     }
 
-    async fn get_diagnostics(&mut self) -> PublishDiagnosticsParams {
+    pub async fn get_diagnostics(&mut self) -> PublishDiagnosticsParams {
         let diagnostics = self.msg_stream.next().await.unwrap();
         serde_json::from_value(
             serde_json::to_value(&diagnostics)
@@ -102,10 +133,26 @@ impl MockService {
         .unwrap()
     }
 
+    /// Opens a document with the language ID of its file extension, like `markdown` for `.md`
+    /// files.
     pub async fn did_open(&mut self, uri: &Url, text: &str) -> PublishDiagnosticsParams {
+        let language_id = if uri.path().ends_with(".md") {
+            "markdown"
+        } else {
+            "slide"
+        };
+        self.did_open_with_language_id(uri, language_id, text).await
+    }
+
+    pub async fn did_open_with_language_id(
+        &mut self,
+        uri: &Url,
+        language_id: &str,
+        text: &str,
+    ) -> PublishDiagnosticsParams {
         self.assert_ready();
         self.send_recv(
-            text_document::did_open::notification(uri, "slide", 0, text),
+            text_document::did_open::notification(uri, language_id, 0, text),
             None,
         )
         .await;
@@ -124,6 +171,71 @@ impl MockService {
         self.get_diagnostics().await
     }
 
+    /// Sends a change of a document without waiting for its diagnostics, which are received with
+    /// [`get_diagnostics`](Self::get_diagnostics).
+    pub async fn did_change_without_diagnostics(&mut self, uri: &Url, text: &str) {
+        self.assert_ready();
+        self.send_recv(
+            text_document::did_change::notification::entire(uri, text),
+            None,
+        )
+        .await;
+    }
+
+    /// Sends changes of a document in rapid succession, with versions counting up from 1.
+    /// Returns the diagnostics published for the changes, asserting that only one set is.
+    pub async fn did_change_rapidly(
+        &mut self,
+        uri: &Url,
+        texts: &[&str],
+    ) -> PublishDiagnosticsParams {
+        for (i, text) in texts.iter().enumerate() {
+            self.assert_ready();
+            self.send_recv(
+                text_document::did_change::notification::versioned(uri, i as i64 + 1, text),
+                None,
+            )
+            .await;
+        }
+
+        let diagnostics = self.get_diagnostics().await;
+        let next_msg = tokio::time::timeout(Duration::from_millis(500), self.msg_stream.next());
+        assert!(
+            next_msg.await.is_err(),
+            "diagnostics were published for a superseded change"
+        );
+        diagnostics
+    }
+
+    pub async fn did_close(&mut self, uri: &Url) -> PublishDiagnosticsParams {
+        self.assert_ready();
+        self.send_recv(text_document::did_close::notification(uri), None)
+            .await;
+
+        self.get_diagnostics().await
+    }
+
+    /// Changes the server configuration, returning the diagnostics published for the
+    /// `num_reparsed` documents re-parsed due to the change.
+    pub async fn did_change_configuration(
+        &mut self,
+        settings: Value,
+        num_reparsed: usize,
+    ) -> Vec<PublishDiagnosticsParams> {
+        self.assert_ready();
+        self.send_recv(
+            workspace::did_change_configuration::notification(settings),
+            None,
+        )
+        .await;
+
+        let mut diagnostics = Vec::with_capacity(num_reparsed);
+        for _ in 0..num_reparsed {
+            diagnostics.push(self.get_diagnostics().await);
+        }
+        diagnostics
+    }
+
     pub async fn definition(
         &mut self,
         uri: &Url,
@@ -226,13 +338,17 @@ impl MockService {
         uri: &Url,
         position: &Position,
         new_name: &str,
-    ) -> Option<WorkspaceEdit> {
+    ) -> tower_lsp::jsonrpc::Result<Option<WorkspaceEdit>> {
         self.assert_ready();
-        let hover_resp = self
+        let resp = self
             .send(text_document::rename::request(uri, position, new_name))
             .await
             .unwrap();
-        serde_json::from_value(hover_resp.get("result").unwrap().clone()).ok()
+        if let Some(result) = resp.get("result") {
+            Ok(serde_json::from_value(result.clone()).ok())
+        } else {
+            Err(serde_json::from_value(resp.get("error").unwrap().clone()).unwrap())
+        }
     }
 
     pub async fn folding_range(&mut self, uri: &Url) -> Option<Vec<FoldingRange>> {
@@ -288,6 +404,45 @@ impl MockService {
         serde_json::from_value(code_lens_response.get("result").unwrap().clone()).ok()
     }
 
+    pub async fn evaluate(
+        &mut self,
+        params: Value,
+    ) -> tower_lsp::jsonrpc::Result<crate::evaluate::EvaluateResult> {
+        self.assert_ready();
+        let resp = self
+            .send(workspace::execute_command::request(
+                crate::evaluate::EVALUATE_COMMAND,
+                vec![params],
+            ))
+            .await
+            .unwrap();
+        if let Some(result) = resp.get("result") {
+            Ok(serde_json::from_value(result.clone()).unwrap())
+        } else {
+            Err(serde_json::from_value(resp.get("error").unwrap().clone()).unwrap())
+        }
+    }
+
+    pub async fn simplify(
+        &mut self,
+        uri: &Url,
+        position: Position,
+    ) -> tower_lsp::jsonrpc::Result<String> {
+        self.assert_ready();
+        let resp = self
+            .send(workspace::execute_command::request(
+                crate::evaluate::SIMPLIFY_COMMAND,
+                vec![serde_json::json!(uri), serde_json::json!(position)],
+            ))
+            .await
+            .unwrap();
+        if let Some(result) = resp.get("result") {
+            Ok(serde_json::from_value(result.clone()).unwrap())
+        } else {
+            Err(serde_json::from_value(resp.get("error").unwrap().clone()).unwrap())
+        }
+    }
+
     pub async fn workspace_symbol(&mut self, query: &str) -> Option<Vec<SymbolInformation>> {
         self.assert_ready();
         let hover_resp = self.send(workspace::symbol::request(query)).await.unwrap();
@@ -309,29 +464,26 @@ pub mod exit {
 pub mod initialize {
     use serde_json::{json, Value};
 
-    pub fn request(link_support: bool, initialization_options: Value) -> Value {
+    pub fn capabilities(link_support: bool) -> Value {
         json!({
-            "jsonrpc": "2.0",
-            "method": "initialize",
-            "params": {
-                "capabilities": {
-                    "textDocument": {
-                        "definition": {
-                            "linkSupport": link_support,
-                        },
-                    },
+            "textDocument": {
+                "definition": {
+                    "linkSupport": link_support,
+                },
+                "rename": {
+                    "prepareSupport": true,
                 },
-                "initializationOptions": initialization_options,
             },
-            "id": 1,
         })
     }
 
-    pub fn response() -> Value {
+    pub fn request(capabilities: Value, initialization_options: Value) -> Value {
         json!({
             "jsonrpc": "2.0",
-            "result": {
-                "capabilities": crate::SlideLS::capabilities(),
+            "method": "initialize",
+            "params": {
+                "capabilities": capabilities,
+                "initializationOptions": initialization_options,
             },
             "id": 1,
         })
@@ -393,6 +545,24 @@ pub mod text_document {
                     },
                 })
             }
+
+            pub fn versioned<S: AsRef<str>>(uri: &Url, version: i64, text: S) -> Value {
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": "textDocument/didChange",
+                    "params": {
+                        "textDocument": {
+                            "uri": uri,
+                            "version": version,
+                        },
+                        "contentChanges": [
+                            {
+                                "text": text.as_ref(),
+                            }
+                        ],
+                    },
+                })
+            }
         }
     }
 
@@ -421,6 +591,23 @@ pub mod text_document {
         }
     }
 
+    pub mod did_close {
+        use serde_json::{json, Value};
+        use tower_lsp::lsp_types::*;
+
+        pub fn notification(uri: &Url) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didClose",
+                "params": {
+                    "textDocument": {
+                        "uri": uri,
+                    },
+                },
+            })
+        }
+    }
+
     pub mod definition {
         use serde_json::{json, Value};
         use tower_lsp::lsp_types::*;
@@ -729,4 +916,34 @@ mod workspace {
             })
         }
     }
+
+    pub mod execute_command {
+        use serde_json::{json, Value};
+
+        pub fn request(command: &str, arguments: Vec<Value>) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "workspace/executeCommand",
+                "params": {
+                    "command": command,
+                    "arguments": arguments,
+                },
+                "id": 1,
+            })
+        }
+    }
+
+    pub mod did_change_configuration {
+        use serde_json::{json, Value};
+
+        pub fn notification(settings: Value) -> Value {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "workspace/didChangeConfiguration",
+                "params": {
+                    "settings": settings,
+                },
+            })
+        }
+    }
 }
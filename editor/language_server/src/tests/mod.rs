@@ -6,9 +6,11 @@ mod utils;
 mod code_action;
 mod code_lens;
 mod completion;
+mod configuration;
 mod definitions;
 mod diagnostics;
 mod document_symbol;
+mod evaluate;
 mod folding_range;
 mod formatting;
 mod highlight;
@@ -67,7 +67,7 @@ def  :=   234
         service, file, content, sm,
         "1    + 2\n / 3\n  ^ 4" => Some("1 + 2 / 3 ^ 4")
         "1    + 2"              => None
-        "3"                     => Some("3")
+        "3"                     => None
         " 3"                    => None
         "a     = b +   c"       => Some("a = b + c")
         "b +   c"               => Some("b + c")
@@ -47,3 +47,77 @@ references_tests! {
         a := a + c + a
     "
 }
+
+async fn drive_markdown_references_test(reference_scope: &str, text: &str) {
+    let mut initialization_options = markdown_math_document_parsers();
+    initialization_options["reference_scope"] = serde_json::json!(reference_scope);
+    let mut service = MockService::new(false, initialization_options).await;
+    let file = markdown_file();
+
+    let DecorationResult {
+        decorations,
+        cursor,
+        text,
+    } = process_decorations(text);
+    service.did_open(&file, &text).await;
+
+    let expected_ranges = decorations
+        .into_iter()
+        .map(|(r, _)| Location::new(file.clone(), r))
+        .collect::<Vec<_>>();
+
+    let references = service
+        .references(&file, cursor.expect("cursor not found"), true)
+        .await;
+
+    assert_eq!(references, Some(expected_ranges));
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn program_scoped_references() {
+    drive_markdown_references_test(
+        "program",
+        r"
+# One
+
+```math
+a := 1
+```
+
+# Two
+
+```math
+b := ¦a + 2
+      ~
+c := a
+     ~
+```",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn document_scoped_references() {
+    drive_markdown_references_test(
+        "document",
+        r"
+# One
+
+```math
+a := 1
+~
+```
+
+# Two
+
+```math
+b := ¦a + 2
+      ~
+c := a
+     ~
+```",
+    )
+    .await;
+}
@@ -28,7 +28,7 @@ macro_rules! rename_tests {
                 })
             };
 
-            let edits = service.rename(&file, &cursor.expect("cursor not found"), $new_name).await;
+            let edits = service.rename(&file, &cursor.expect("cursor not found"), $new_name).await.unwrap();
 
             assert_eq!(edits, expected_edits);
 
@@ -60,4 +60,193 @@ rename_tests! {
         d * e ^ a
                 ~@[f]
     "
+    rename_var_many_occurrences => "x", r"
+        b := 1 + 2
+        ~@[x]
+        a := b + b ^ b
+             ~@[x]
+                 ~@[x]
+                     ~@[x]
+        ¦b - a
+        ~~@[x]
+    "
+}
+
+macro_rules! invalid_rename_tests {
+    ($($name:ident => $new_name:expr)*) => {$(
+        #[tokio::test]
+        async fn $name() {
+            let mut service = MockService::default().await;
+            let file = default_file();
+
+            let DecorationResult { cursor, text, .. } = process_decorations("¦a + b");
+            service.did_open(&file, &text).await;
+
+            let err = service
+                .rename(&file, &cursor.expect("cursor not found"), $new_name)
+                .await
+                .unwrap_err();
+
+            assert_eq!(err.message, format!("`{}` is not a valid variable name", $new_name));
+
+            service.shutdown().await;
+        }
+    )*}
+}
+
+invalid_rename_tests! {
+    invalid_rename_empty => ""
+    invalid_rename_leading_digit => "1a"
+    invalid_rename_var_pattern => "$a"
+    invalid_rename_const_pattern => "#a"
+    invalid_rename_any_pattern => "_a"
+    invalid_rename_operator => "a+b"
+}
+
+async fn drive_markdown_rename_test(reference_scope: &str, new_name: &str, text: &str) {
+    let mut initialization_options = markdown_math_document_parsers();
+    initialization_options["reference_scope"] = serde_json::json!(reference_scope);
+    let mut service = MockService::new(false, initialization_options).await;
+    let file = markdown_file();
+
+    let DecorationResult {
+        decorations,
+        cursor,
+        text,
+    } = process_decorations(text);
+    service.did_open(&file, &text).await;
+
+    let expected_edits = decorations
+        .into_iter()
+        .map(|(range, rename)| TextEdit {
+            range,
+            new_text: rename.expect("no rename given"),
+        })
+        .collect::<Vec<_>>();
+    let mut changes = HashMap::new();
+    changes.insert(file.clone(), expected_edits);
+    let expected_edits = WorkspaceEdit {
+        changes: Some(changes),
+        document_changes: None,
+    };
+
+    let edits = service
+        .rename(&file, &cursor.expect("cursor not found"), new_name)
+        .await
+        .unwrap();
+
+    assert_eq!(edits, Some(expected_edits));
+
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn program_scoped_rename() {
+    drive_markdown_rename_test(
+        "program",
+        "x",
+        r"
+# One
+
+```math
+a := 1
+```
+
+# Two
+
+```math
+b := ¦a + 2
+      ~@[x]
+c := a
+     ~@[x]
+```",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn document_scoped_rename() {
+    drive_markdown_rename_test(
+        "document",
+        "x",
+        r"
+# One
+
+```math
+a := 1
+~@[x]
+```
+
+# Two
+
+```math
+b := ¦a + 2
+      ~@[x]
+c := a
+     ~@[x]
+```",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn scoped_rename_without_matches() {
+    for reference_scope in &["document", "workspace"] {
+        let mut initialization_options = markdown_math_document_parsers();
+        initialization_options["reference_scope"] = serde_json::json!(reference_scope);
+        let mut service = MockService::new(false, initialization_options).await;
+        let file = markdown_file();
+
+        let DecorationResult { cursor, text, .. } = process_decorations(
+            r"
+# One
+
+```math
+a := ¦1
+```
+
+# Two
+
+```math
+b := a + 2
+```",
+        );
+        service.did_open(&file, &text).await;
+
+        let edits = service
+            .rename(&file, &cursor.expect("cursor not found"), "x")
+            .await
+            .unwrap();
+
+        assert_eq!(edits, None);
+
+        service.shutdown().await;
+    }
+}
+
+#[tokio::test]
+async fn rename_provider_follows_client_support() {
+    let rename_provider = |capabilities| async move {
+        let mut service =
+            MockService::with_capabilities(capabilities, default_initialization_options()).await;
+        let rename_provider = service.server_capabilities().rename_provider.clone();
+        service.shutdown().await;
+        rename_provider
+    };
+
+    assert_eq!(rename_provider(serde_json::json!({})).await, None);
+    assert_eq!(
+        rename_provider(serde_json::json!({ "textDocument": { "rename": {} } })).await,
+        Some(RenameProviderCapability::Simple(true))
+    );
+    assert_eq!(
+        rename_provider(serde_json::json!({
+            "textDocument": { "rename": { "prepareSupport": true } }
+        }))
+        .await,
+        Some(RenameProviderCapability::Options(RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: WorkDoneProgressOptions::default(),
+        }))
+    );
 }
@@ -1,5 +1,7 @@
 //! Module `utils` provides test utilities for the slide language server.
 
+use crate::document_registry::SourceMap;
+
 use pretty_assertions::assert_eq;
 use tower_lsp::lsp_types::*;
 
@@ -26,7 +28,7 @@ pub fn range_of(subtext: &str, text: &str) -> Range {
         .next()
         .expect("Subtext not found.")
         .0;
-    let span = (span_start, span_start + subtext.chars().count());
+    let span = (span_start, span_start + subtext.len());
     Range::new(to_position(span.0, text), to_position(span.1, text))
 }
 
@@ -132,6 +134,26 @@ pub fn process_decorations(text: &str) -> DecorationResult {
     }
 }
 
+/// Applies text edits to a source text, as a client would apply the edits of a workspace edit.
+pub fn apply_edits(text: &str, edits: &[TextEdit]) -> String {
+    let source_map = SourceMap::new(text);
+    let mut edits: Vec<_> = edits
+        .iter()
+        .map(|edit| {
+            let start = source_map.to_offset(edit.range.start);
+            let end = source_map.to_offset(edit.range.end);
+            (start, end, edit.new_text.as_str())
+        })
+        .collect();
+    // Apply later edits first, so that the offsets of earlier edits stay valid.
+    edits.sort_by_key(|&(start, ..)| std::cmp::Reverse(start));
+    let mut text = text.to_owned();
+    for (start, end, new_text) in edits {
+        text.replace_range(start..end, new_text);
+    }
+    text
+}
+
 pub fn cmp_document_symbols(s1: DocumentSymbolResponse, s2: DocumentSymbolResponse) {
     match (s1, s2) {
         (DocumentSymbolResponse::Flat(s1), DocumentSymbolResponse::Flat(s2)) => cmp_symbols(s1, s2),
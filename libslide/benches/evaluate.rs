@@ -0,0 +1,46 @@
+#[macro_use]
+extern crate criterion;
+extern crate libslide;
+
+use criterion::{black_box, Criterion};
+use libslide::{build_rules, evaluate_with_rules, parse_statements, scan, ProgramContext};
+
+const INPUT: [&str; 4] = [
+    "x + 0 + y * 1 + 0",
+    "1 * (a + 0) * (b + 0 + c) * 1",
+    "a + b + c + d + e + f + 0",
+    "(x + 0) * (y * 1 + z * 1) + 0 * w",
+];
+
+fn bench_evaluate(c: &mut Criterion) {
+    let ctxt = ProgramContext::default();
+    let rules = build_rules(&ctxt).unwrap();
+    let programs: Vec<_> = INPUT
+        .iter()
+        .map(|program| parse_statements(scan(*program).tokens, program).program)
+        .collect();
+
+    c.bench_function("evaluate", |b| {
+        b.iter(|| {
+            for program in programs.iter() {
+                evaluate_with_rules(black_box(program.clone()), &rules, &ctxt);
+            }
+        })
+    });
+}
+
+fn bench_evaluate_repeated(c: &mut Criterion) {
+    let ctxt = ProgramContext::default();
+    let rules = build_rules(&ctxt).unwrap();
+    // A program whose statements repeat a few expressions, as generated documents often do.
+    let input: Vec<_> = (0..100).map(|i| INPUT[i % INPUT.len()]).collect();
+    let input = input.join("\n");
+    let program = parse_statements(scan(input.as_str()).tokens, &input).program;
+
+    c.bench_function("evaluate_repeated", |b| {
+        b.iter(|| evaluate_with_rules(black_box(program.clone()), &rules, &ctxt))
+    });
+}
+
+criterion_group!(evaluate_benches, bench_evaluate, bench_evaluate_repeated);
+criterion_main!(evaluate_benches);
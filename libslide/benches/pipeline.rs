@@ -0,0 +1,138 @@
+#[macro_use]
+extern crate criterion;
+extern crate libslide;
+
+use criterion::{black_box, Criterion};
+use libslide::{
+    _validate, build_rules, evaluate_with_rules, parse_statements, scan, ProgramContext,
+};
+
+/// A document-sized program, like the slide code blocks of a markdown file.
+const DOCUMENT: &str = r"a = 1 + 2 * 3
+b = a ^ 2 - 4
+c := (x + 1) * (x - 1)
+d := c / (x - 1)
+f(x) = x ^ 2 + 2x + 1
+f(3) + f(a)
+e = 10 / 4 + 3!
+(a + b) * (a - b)
+g = max(a, b, 3) - min(1, 2)
+h := y + 0 + y * 1 - 0
+i = [x + 1] * (x + 1) / (x + 1)
+j = 2 ^ 10 % 7
+k = -(x - y) + -(y - x)
+l = (x ^ 3 - 1) / (x - 1)
+m = x * x * x / x
+n = 1 / 2 + 1 / 3 + 1 / 6
+o := a * x + b * x + c
+p = (y + 2) ^ 2 - (y + 2) * (y + 2)
+q = sqrt(16) + root(3, 27)
+r = -(-(-(x)))
+";
+
+/// `1 + b + 3 + d + ... + j`, alternating constants and variables.
+fn additive_chain(terms: usize) -> String {
+    let vars = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"];
+    (1..=terms)
+        .map(|i| match i % 2 {
+            0 => vars[i % vars.len()].to_owned(),
+            _ => i.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// `(((x - 0) + 1) * 2 - 2) ...`, nested `depth` times.
+fn deep_nesting(depth: usize) -> String {
+    (0..depth).fold("x".to_owned(), |expr, i| match i % 2 {
+        0 => format!("({} - {})", expr, i),
+        _ => format!("({} + {}) * 2", expr, i),
+    })
+}
+
+/// Statements sharing the same few subexpressions.
+fn repeated_subexpressions(stmts: usize) -> String {
+    let vars = ["a", "b", "c", "d", "e", "f", "g", "h"];
+    (0..stmts)
+        .map(|i| {
+            format!(
+                "{} = (x + 1) * (y - 2) + (x + 1) ^ 2 - (y - 2) / (x + 1)",
+                vars[i % vars.len()]
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+const POLYNOMIAL_DIVISIONS: &str = r"(x^4 - 1) / (x - 1)
+(x^3 - 2x^2 + x) / (x - 1)
+(x^2 - 2x) / (x + 1)
+(x^6 - 1) / (x^2 - 1)
+(2x^3 + 3x^2 - 2x - 3) / (x^2 - 1)";
+
+fn programs() -> Vec<(&'static str, String)> {
+    vec![
+        ("additive_chain", additive_chain(16)),
+        ("deep_nesting", deep_nesting(20)),
+        ("repeated_subexpressions", repeated_subexpressions(20)),
+        ("polynomial_division", POLYNOMIAL_DIVISIONS.to_owned()),
+        ("document", DOCUMENT.to_owned()),
+    ]
+}
+
+fn bench_scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scan");
+    for (name, program) in programs() {
+        group.bench_function(name, |b| b.iter(|| scan(black_box(program.as_str()))));
+    }
+    group.finish();
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, program) in programs() {
+        let tokens = scan(program.as_str()).tokens;
+        group.bench_function(name, |b| {
+            b.iter(|| parse_statements(black_box(tokens.clone()), &program))
+        });
+    }
+    group.finish();
+}
+
+fn bench_evaluate(c: &mut Criterion) {
+    let ctxt = ProgramContext::default();
+    let rules = build_rules(&ctxt).unwrap();
+    let mut group = c.benchmark_group("evaluate");
+    // Evaluating the larger programs takes long enough that fewer samples suffice.
+    group.sample_size(10);
+    for (name, program) in programs() {
+        let parsed = parse_statements(scan(program.as_str()).tokens, &program).program;
+        group.bench_function(name, |b| {
+            b.iter(|| evaluate_with_rules(black_box(parsed.clone()), &rules, &ctxt))
+        });
+    }
+    group.finish();
+}
+
+fn bench_validate(c: &mut Criterion) {
+    let ctxt = ProgramContext::default();
+    let rules = build_rules(&ctxt).unwrap();
+    let mut group = c.benchmark_group("validate");
+    for (name, program) in programs() {
+        let parsed = parse_statements(scan(program.as_str()).tokens, &program).program;
+        let simplified = evaluate_with_rules(parsed, &rules, &ctxt).simplified;
+        group.bench_function(name, |b| {
+            b.iter(|| _validate(black_box(&simplified), &rules, &ctxt))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    pipeline_benches,
+    bench_scan,
+    bench_parse,
+    bench_evaluate,
+    bench_validate
+);
+criterion_main!(pipeline_benches);
@@ -1,17 +1,32 @@
 //! Common types used by libslide.
 
-use crate::evaluator_rules::RuleName;
+#[macro_use]
+mod errors;
+pub(crate) use errors::ConfigErrors;
+use errors::*;
 
+mod source_index;
+pub use source_index::{LineCol, LineColSpan, SourceIndex};
+
+use crate::diagnostics::{Diagnostic, DiagnosticRecord};
+use crate::evaluator_rules::{RuleName, RuleSetPreset};
+use crate::grammar::{BinaryExpr, BinaryOperator, Call, Expr, Interval, UnaryExpr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
 
-/// Describes the character span of a substring in a text.
+/// Describes the byte span of a substring in a text.
 ///
-/// For example, in "abcdef", "bcd" has the span (1, 4).
+/// For example, in "abcdef", "bcd" has the span (1, 4), and in "1 × 2", "2" has the span (5, 6).
 #[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Span {
-    /// Inclusive lower bound index of the span, in terms of number of chars
+    /// Inclusive lower bound index of the span, in terms of number of bytes
     pub lo: usize,
-    /// Exclusive upper bound index of the span, in terms of number of chars
+    /// Exclusive upper bound index of the span, in terms of number of bytes
     pub hi: usize,
 }
 
@@ -37,17 +52,37 @@ impl Span {
         }
     }
 
+    /// Returns the smallest span covering both this span and `other`, regardless of their order.
+    pub(crate) fn union(&self, other: Span) -> Span {
+        Self {
+            lo: self.lo.min(other.lo),
+            hi: self.hi.max(other.hi),
+        }
+    }
+
+    /// Returns the substring of `content` the span covers. The span is clipped to the end of
+    /// `content`, which the span of an end-of-file token reaches past.
     pub(crate) fn over<'a>(&self, content: &'a str) -> &'a str {
-        let mut indices = content.char_indices().map(|(i, _)| i);
-        let lo = indices.nth(self.lo).unwrap();
-        let hi = vec![lo]
-            .into_iter()
-            .chain(indices)
-            .nth(self.hi - self.lo)
-            .unwrap_or_else(|| content.len());
+        let lo = self.lo.min(content.len());
+        let hi = self.hi.min(content.len());
         &content[lo..hi]
     }
 
+    /// Returns the span without any whitespace at the ends of the substring of `content` it covers.
+    /// The span is returned as it is if it is not within `content`, or covers only whitespace.
+    pub(crate) fn trim(&self, content: &str) -> Span {
+        if self.hi > content.len() {
+            return *self;
+        }
+        let text = self.over(content);
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return *self;
+        }
+        let lo = self.lo + (text.len() - text.trim_start().len());
+        Span::new(lo, lo + trimmed.len())
+    }
+
     /// Returns `true` iff the span contains `pos`.
     pub fn contains(&self, pos: usize) -> bool {
         self.lo <= pos && self.hi > pos
@@ -67,6 +102,22 @@ impl Span {
         // 3----4
         !(self.lo >= other.hi || other.lo >= self.hi)
     }
+
+    /// Returns the [line and column](LineCol) positions of the bounds of the span in `source`.
+    ///
+    /// ```
+    /// use libslide::{LineCol, Span};
+    ///
+    /// let range = Span::new(4, 9).to_range("a = 1\nb = 2 +");
+    /// assert_eq!(range.start, LineCol::new(0, 4));
+    /// assert_eq!(range.end, LineCol::new(1, 3));
+    /// ```
+    ///
+    /// This indexes the lines of `source` on each call; to map many spans in the same source, build
+    /// a [`SourceIndex`](SourceIndex) once and use [`SourceIndex::span`](SourceIndex::span).
+    pub fn to_range(&self, source: &str) -> LineColSpan {
+        SourceIndex::new(source).span(*self)
+    }
 }
 
 impl PartialOrd for Span {
@@ -116,29 +167,65 @@ impl From<Span> for (usize, usize) {
 }
 
 /// A context for evaluating a slide program.
+///
+/// A context is created with a [`ProgramContextBuilder`](ProgramContextBuilder), which validates
+/// the combination of its options, or with [`ProgramContext::default`](ProgramContext::default).
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "ProgramContextBuilder"))]
 pub struct ProgramContext {
     /// Rules that should not be included in the evaluation of an expression.
     pub(crate) rule_denylist: Vec<RuleName>,
 
+    /// The preset of rules an expression is evaluated with.
+    pub(crate) rule_set: RuleSetPreset,
+
     /// Whether an expression should always be flattened before it is further evaluated.
     pub(crate) always_flatten: bool,
 
     /// Whether "lint"-like diagnostics should be emitted.
     pub(crate) lint: bool,
+
+    /// The canonical form of a term multiplied by itself.
+    pub(crate) square_form: SquareForm,
+
+    /// How rigorously simplifications must hold for all values of their variables.
+    pub(crate) rigor: Rigor,
+
+    /// What evaluation optimizes for when choosing between candidate forms of an expression.
+    pub(crate) objective: Objective,
+
+    /// Whether evaluations to non-integer constants are errors.
+    pub(crate) strict_integers: bool,
 }
 
 impl Default for ProgramContext {
     fn default() -> Self {
         Self {
             rule_denylist: vec![],
+            rule_set: RuleSetPreset::Full,
             always_flatten: true,
             lint: false,
+            square_form: SquareForm::Power,
+            rigor: Rigor::Pragmatic,
+            objective: Objective::OpCount,
+            strict_integers: false,
         }
     }
 }
 
 impl ProgramContext {
+    /// Returns a [`ProgramContextBuilder`](ProgramContextBuilder) with the options of the default
+    /// context.
+    pub fn builder() -> ProgramContextBuilder {
+        ProgramContextBuilder::default()
+    }
+
+    /// Whether "lint"-like diagnostics should be emitted.
+    pub fn lints(&self) -> bool {
+        self.lint
+    }
+
     /// Set rules to exclude in evaluation.
     pub fn with_denylist<T>(mut self, rule_denylist: T) -> Self
     where
@@ -148,6 +235,13 @@ impl ProgramContext {
         self
     }
 
+    /// Sets the preset of rules an expression is evaluated with. Rules required by other options,
+    /// like the [`PreferFactored`](Objective::PreferFactored) objective, are added to any preset.
+    pub fn rule_set(mut self, rule_set: RuleSetPreset) -> Self {
+        self.rule_set = rule_set;
+        self
+    }
+
     /// Whether expressions should always be flattened during evaluation.
     pub fn always_flatten(mut self, flatten: bool) -> Self {
         self.always_flatten = flatten;
@@ -159,12 +253,513 @@ impl ProgramContext {
         self.lint = lint;
         self
     }
+
+    /// Sets the canonical form of a term multiplied by itself.
+    pub fn square_form(mut self, square_form: SquareForm) -> Self {
+        self.square_form = square_form;
+        self
+    }
+
+    /// Sets how rigorously simplifications must hold for all values of their variables.
+    pub fn rigor(mut self, rigor: Rigor) -> Self {
+        self.rigor = rigor;
+        self
+    }
+
+    /// Sets what evaluation optimizes for when choosing between candidate forms of an expression.
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Sets whether evaluations to non-integer constants, like `1 / 2`, are errors.
+    pub fn strict_integers(mut self, strict_integers: bool) -> Self {
+        self.strict_integers = strict_integers;
+        self
+    }
+}
+
+/// Builds a [`ProgramContext`](ProgramContext), validating that its options are compatible.
+///
+/// ```
+/// use libslide::evaluator_rules::RuleName;
+/// use libslide::{ConfigError, Objective, ProgramContext};
+///
+/// let context = ProgramContext::builder().lint(true).build();
+/// assert!(context.is_ok());
+///
+/// let context = ProgramContext::builder()
+///     .objective(Objective::PreferFactored)
+///     .with_denylist(vec![RuleName::FactorOut])
+///     .build();
+/// assert!(matches!(context, Err(ConfigError::RequiredRuleDenied { .. })));
+/// ```
+///
+/// With the `serde` feature, the builder can be deserialized from a configuration file or other
+/// serialized settings. Options absent from the serialized form keep their default values.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct ProgramContextBuilder {
+    rule_denylist: Vec<RuleName>,
+    rule_set: RuleSetPreset,
+    always_flatten: bool,
+    lint: bool,
+    square_form: SquareForm,
+    rigor: Rigor,
+    objective: Objective,
+    strict_integers: bool,
+}
+
+impl Default for ProgramContextBuilder {
+    fn default() -> Self {
+        let ProgramContext {
+            rule_denylist,
+            rule_set,
+            always_flatten,
+            lint,
+            square_form,
+            rigor,
+            objective,
+            strict_integers,
+        } = ProgramContext::default();
+        Self {
+            rule_denylist,
+            rule_set,
+            always_flatten,
+            lint,
+            square_form,
+            rigor,
+            objective,
+            strict_integers,
+        }
+    }
+}
+
+impl ProgramContextBuilder {
+    /// See [`ProgramContext::with_denylist`](ProgramContext::with_denylist).
+    pub fn with_denylist<T>(mut self, rule_denylist: T) -> Self
+    where
+        T: Into<Vec<RuleName>>,
+    {
+        self.rule_denylist = rule_denylist.into();
+        self
+    }
+
+    /// See [`ProgramContext::rule_set`](ProgramContext::rule_set).
+    pub fn rule_set(mut self, rule_set: RuleSetPreset) -> Self {
+        self.rule_set = rule_set;
+        self
+    }
+
+    /// See [`ProgramContext::always_flatten`](ProgramContext::always_flatten).
+    pub fn always_flatten(mut self, flatten: bool) -> Self {
+        self.always_flatten = flatten;
+        self
+    }
+
+    /// See [`ProgramContext::lint`](ProgramContext::lint).
+    pub fn lint(mut self, lint: bool) -> Self {
+        self.lint = lint;
+        self
+    }
+
+    /// See [`ProgramContext::square_form`](ProgramContext::square_form).
+    pub fn square_form(mut self, square_form: SquareForm) -> Self {
+        self.square_form = square_form;
+        self
+    }
+
+    /// See [`ProgramContext::rigor`](ProgramContext::rigor).
+    pub fn rigor(mut self, rigor: Rigor) -> Self {
+        self.rigor = rigor;
+        self
+    }
+
+    /// See [`ProgramContext::objective`](ProgramContext::objective).
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// See [`ProgramContext::strict_integers`](ProgramContext::strict_integers).
+    pub fn strict_integers(mut self, strict_integers: bool) -> Self {
+        self.strict_integers = strict_integers;
+        self
+    }
+
+    /// Builds the [`ProgramContext`](ProgramContext), or returns the first
+    /// [`ConfigError`](ConfigError) found among its options.
+    pub fn build(self) -> Result<ProgramContext, ConfigError> {
+        // Options that add a rule to evaluation conflict with denying that rule.
+        let mut required_rules = vec![];
+        if self.square_form == SquareForm::Multiply {
+            let value = self.square_form.to_string();
+            required_rules.push((RuleName::ExpandSquare, "square_form", value));
+        }
+        match self.objective {
+            Objective::PreferFactored => {
+                let value = self.objective.to_string();
+                required_rules.push((RuleName::FactorOut, "objective", value));
+            }
+            Objective::PreferExpanded => {
+                let value = self.objective.to_string();
+                required_rules.push((RuleName::DistributeMultiplication, "objective", value));
+            }
+            Objective::OpCount | Objective::Depth => {}
+        }
+        if let Some((rule, option, value)) = required_rules
+            .into_iter()
+            .find(|(rule, _, _)| self.rule_denylist.contains(rule))
+        {
+            return Err(ConfigError::RequiredRuleDenied {
+                rule,
+                option,
+                value,
+            });
+        }
+
+        let Self {
+            rule_denylist,
+            rule_set,
+            always_flatten,
+            lint,
+            square_form,
+            rigor,
+            objective,
+            strict_integers,
+        } = self;
+        Ok(ProgramContext {
+            rule_denylist,
+            rule_set,
+            always_flatten,
+            lint,
+            square_form,
+            rigor,
+            objective,
+            strict_integers,
+        })
+    }
+}
+
+impl TryFrom<ProgramContextBuilder> for ProgramContext {
+    type Error = ConfigError;
+
+    fn try_from(builder: ProgramContextBuilder) -> Result<Self, Self::Error> {
+        builder.build()
+    }
+}
+
+/// An invalid combination of [`ProgramContext`](ProgramContext) options.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// An option requires a rule that is in the rule denylist. For example, the
+    /// [`PreferFactored`](Objective::PreferFactored) objective requires the
+    /// [`FactorOut`](RuleName::FactorOut) rule.
+    RequiredRuleDenied {
+        /// The denied rule.
+        rule: RuleName,
+        /// The name of the option requiring the rule, like `objective`.
+        option: &'static str,
+        /// The value of the option requiring the rule, like `prefer-factored`.
+        value: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RequiredRuleDenied {
+                rule,
+                option,
+                value,
+            } => write!(
+                f,
+                "{} `{}` requires the rule {:?}, but it is in the rule denylist",
+                option, value, rule
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl ConfigError {
+    /// Creates a diagnostic for this error in a configuration file, like a `slide.toml`. `span` is
+    /// the span of the option causing the error in the file.
+    pub fn to_diagnostic(&self, span: Span) -> Diagnostic {
+        match self {
+            Self::RequiredRuleDenied { .. } => RequiredRuleDenied!(span, self),
+        }
+    }
+}
+
+/// Creates a diagnostic for a configuration file that cannot be read as slide options, like one
+/// with an unknown option or an option of the wrong type. `span` is the span of the invalid part of
+/// the file, and `reason` describes why it is invalid.
+pub fn invalid_config_diagnostic(span: Span, reason: String) -> Diagnostic {
+    InvalidConfig!(span, reason)
+}
+
+/// The canonical form of a term multiplied by itself, which evaluation simplifies the other form
+/// to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum SquareForm {
+    /// `x^2`. `x * x` is simplified to `x^2`.
+    Power,
+    /// `x * x`. `x^2` is simplified to `x * x`.
+    Multiply,
+}
+
+/// How rigorously simplifications must hold for all values of their variables.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Rigor {
+    /// Simplifications may assume variables take values for which the original expression is
+    /// defined. For example, `x / x` is simplified to `1`, assuming `x` is nonzero.
+    Pragmatic,
+    /// Simplifications must hold for all values of their variables. For example, `x / x` is not
+    /// simplified, since it is undefined when `x` is zero.
+    Rigorous,
+}
+
+/// What evaluation optimizes for when choosing between candidate forms of an expression, like the
+/// symmetric forms of a rule's target or the forms an expression takes across passes of the
+/// evaluator's rules.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Objective {
+    /// Prefers expressions with the fewest operations, as measured by
+    /// [`Expr::complexity`](Expr::complexity).
+    OpCount,
+    /// Prefers the shallowest expressions, as measured by [`Expr::depth`](Expr::depth), then
+    /// those with the fewest operations.
+    Depth,
+    /// Prefers factored forms, like `a * (b + c)`, over expanded forms, like `a * b + a * c`.
+    /// Evaluation also factors out common factors.
+    PreferFactored,
+    /// Prefers expanded forms, like `a * b + a * c`, over factored forms, like `a * (b + c)`.
+    /// Evaluation also distributes multiplication over addition.
+    PreferExpanded,
+}
+
+impl fmt::Display for SquareForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Power => "power",
+            Self::Multiply => "multiply",
+        }
+        .fmt(f)
+    }
+}
+
+impl fmt::Display for Rigor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pragmatic => "pragmatic",
+            Self::Rigorous => "rigorous",
+        }
+        .fmt(f)
+    }
+}
+
+impl fmt::Display for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OpCount => "op-count",
+            Self::Depth => "depth",
+            Self::PreferFactored => "prefer-factored",
+            Self::PreferExpanded => "prefer-expanded",
+        }
+        .fmt(f)
+    }
+}
+
+impl Objective {
+    /// Returns the cost of an expression under the objective. Expressions of lower cost are
+    /// preferred; ties are broken by [complexity](Expr::complexity).
+    ///
+    /// | objective        | primary cost                                     |
+    /// |:---------------- |:------------------------------------------------ |
+    /// | `OpCount`        | none                                             |
+    /// | `Depth`          | depth                                            |
+    /// | `PreferFactored` | number of multiplications                        |
+    /// | `PreferExpanded` | number of additions or subtractions multiplied by a term |
+    pub fn cost(&self, expr: &Expr) -> (usize, usize) {
+        let primary = match self {
+            Self::OpCount => 0,
+            Self::Depth => expr.depth(),
+            Self::PreferFactored => count_ops(expr, false, &|op, _| op == BinaryOperator::Mult),
+            Self::PreferExpanded => count_ops(expr, false, &|op, in_mult| {
+                in_mult && matches!(op, BinaryOperator::Plus | BinaryOperator::Minus)
+            }),
+        };
+        (primary, expr.complexity())
+    }
+}
+
+/// Counts the binary operations in `expr` matching `counted`, which is given an operator and
+/// whether the operation is an operand of a multiplication.
+fn count_ops(expr: &Expr, in_mult: bool, counted: &dyn Fn(BinaryOperator, bool) -> bool) -> usize {
+    match expr {
+        Expr::Const(_) | Expr::Var(_) => 0,
+        Expr::BinaryExpr(BinaryExpr { op, lhs, rhs }) => {
+            let is_mult = *op == BinaryOperator::Mult;
+            counted(*op, in_mult) as usize
+                + count_ops(lhs, is_mult, counted)
+                + count_ops(rhs, is_mult, counted)
+        }
+        Expr::UnaryExpr(UnaryExpr { rhs, .. }) => count_ops(rhs, false, counted),
+        Expr::Parend(inner) | Expr::Bracketed(inner) => count_ops(inner, in_mult, counted),
+        Expr::Call(Call { args, .. }) => {
+            args.iter().map(|arg| count_ops(arg, false, counted)).sum()
+        }
+        Expr::Interval(Interval { lo, hi }) => {
+            count_ops(lo, false, counted) + count_ops(hi, false, counted)
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
+    mod program_context_builder {
+        use super::super::{ConfigError, Objective, ProgramContext, SquareForm};
+        use crate::evaluator_rules::{RuleName, RuleSetPreset};
+
+        #[test]
+        fn default() {
+            assert_eq!(
+                ProgramContext::builder().build(),
+                Ok(ProgramContext::default())
+            );
+        }
+
+        #[test]
+        fn denied_rule_unrelated_to_options() {
+            let context = ProgramContext::builder()
+                .objective(Objective::PreferFactored)
+                .with_denylist(vec![RuleName::DistributeMultiplication])
+                .build();
+            assert!(context.is_ok());
+        }
+
+        #[test]
+        fn required_rule_denied() {
+            let cases = vec![
+                (
+                    ProgramContext::builder()
+                        .square_form(SquareForm::Multiply)
+                        .with_denylist(vec![RuleName::ExpandSquare]),
+                    RuleName::ExpandSquare,
+                    "square_form `multiply` requires the rule ExpandSquare, but it is in the rule denylist",
+                ),
+                (
+                    ProgramContext::builder()
+                        .objective(Objective::PreferFactored)
+                        .with_denylist(vec![RuleName::Add, RuleName::FactorOut]),
+                    RuleName::FactorOut,
+                    "objective `prefer-factored` requires the rule FactorOut, but it is in the rule denylist",
+                ),
+                (
+                    ProgramContext::builder()
+                        .objective(Objective::PreferExpanded)
+                        .with_denylist(vec![RuleName::DistributeMultiplication]),
+                    RuleName::DistributeMultiplication,
+                    "objective `prefer-expanded` requires the rule DistributeMultiplication, but it is in the rule denylist",
+                ),
+            ];
+            for (builder, denied, message) in cases {
+                let err = builder.build().unwrap_err();
+                assert!(
+                    matches!(err, ConfigError::RequiredRuleDenied { rule, .. } if rule == denied)
+                );
+                assert_eq!(err.to_string(), message);
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn toml_round_trip() {
+            use crate::Rigor;
+
+            let context = ProgramContext::builder()
+                .with_denylist(vec![RuleName::Add])
+                .rule_set(RuleSetPreset::Basic)
+                .lint(true)
+                .square_form(SquareForm::Multiply)
+                .rigor(Rigor::Rigorous)
+                .objective(Objective::PreferExpanded)
+                .strict_integers(true)
+                .build()
+                .unwrap();
+            let toml = toml::to_string(&context).unwrap();
+            assert_eq!(
+                toml,
+                r#"rule_denylist = ["Add"]
+rule_set = "basic"
+always_flatten = true
+lint = true
+square_form = "multiply"
+rigor = "rigorous"
+objective = "prefer-expanded"
+strict_integers = true
+"#
+            );
+            assert_eq!(toml::from_str::<ProgramContext>(&toml).unwrap(), context);
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn toml_defaults_absent_options() {
+            let context: ProgramContext = toml::from_str("lint = true").unwrap();
+            assert_eq!(context, ProgramContext::default().lint(true));
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn toml_invalid() {
+            let err = toml::from_str::<ProgramContext>(
+                r#"objective = "prefer-factored"
+rule_denylist = ["FactorOut"]"#,
+            )
+            .unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "objective `prefer-factored` requires the rule FactorOut, but it is in the rule denylist"
+            );
+
+            assert!(toml::from_str::<ProgramContext>("rigor = \"strict\"").is_err());
+            assert!(toml::from_str::<ProgramContext>("rule_set = \"minimal\"").is_err());
+            assert!(toml::from_str::<ProgramContext>("unknown = 1").is_err());
+        }
+    }
+
+    mod objective {
+        use super::super::Objective;
+        use crate::parse_expr;
+
+        #[test]
+        fn cost() {
+            let expr = parse_expr!("(x + 1) * 2 * y");
+            assert_eq!(expr.complexity(), 8);
+            assert_eq!(expr.depth(), 5);
+            for (objective, expected) in &[
+                (Objective::OpCount, (0, 8)),
+                (Objective::Depth, (5, 8)),
+                (Objective::PreferFactored, (2, 8)),
+                (Objective::PreferExpanded, (1, 8)),
+            ] {
+                assert_eq!(objective.cost(&expr), *expected, "{:?}", objective);
+            }
+        }
+    }
+
     mod span {
-        use super::super::Span;
+        use super::super::{LineCol, Span};
 
         #[test]
         fn intersects() {
@@ -177,5 +772,24 @@ mod test {
                 assert_eq!(Span::from(s1).intersects(Span::from(s2)), expected);
             }
         }
+
+        #[test]
+        fn to_range() {
+            let source = "a = 1\nb = a +\r\n  2\n\nc";
+            for &(span, start, end) in &[
+                ((0, 5), (0, 0), (0, 5)),
+                ((4, 11), (0, 4), (1, 5)),
+                ((6, 18), (1, 0), (2, 3)),
+                ((12, 15), (1, 6), (2, 0)),
+                ((17, 18), (2, 2), (2, 3)),
+                ((19, 21), (3, 0), (4, 1)),
+                ((20, 21), (4, 0), (4, 1)),
+                ((21, 30), (4, 1), (4, 1)),
+            ] {
+                let range = Span::from(span).to_range(source);
+                assert_eq!(range.start, LineCol::new(start.0, start.1), "{:?}", span);
+                assert_eq!(range.end, LineCol::new(end.0, end.1), "{:?}", span);
+            }
+        }
     }
 }
@@ -0,0 +1,82 @@
+//! Diagnostic errors of slide configuration files.
+
+use crate::diagnostics::{DiagnosticRecord, DiagnosticRegistry};
+
+macro_rules! define_errors {
+    ($($(#[doc = $doc:expr])+ $code:ident: $error:ident $gen_macro:tt)*) => {$(
+        $(#[doc = $doc])+
+        pub(crate) struct $error;
+
+        impl DiagnosticRecord for $error {
+            const CODE: &'static str = stringify!($code);
+            const EXPLANATION: &'static str = concat!($($doc, "\n"),+);
+        })*
+
+        /// Diagnostic errors of slide configuration files.
+        pub struct ConfigErrors;
+
+        impl DiagnosticRegistry for ConfigErrors {
+            #[allow(clippy::vec_init_then_push)]
+            fn codes_with_explanations() -> Vec<(&'static str, &'static str)> {
+                let mut vec = Vec::new();
+                $(vec.push(($error::CODE, $error::EXPLANATION));)*
+                vec
+            }
+        }
+
+        $(
+            macro_rules! $error $gen_macro
+        )*
+    };
+}
+
+define_errors! {
+    ///This error is fired on a configuration file of slide options, like a `slide.toml`, that
+    ///cannot be read as slide options. For example, the file
+    ///
+    ///```toml
+    ///output_form = "html"
+    ///```
+    ///
+    ///is invalid because "html" is not an emit format, and the file
+    ///
+    ///```toml
+    ///[context]
+    ///rigour = "rigorous"
+    ///```
+    ///
+    ///is invalid because "rigour" is not an option of the evaluation context.
+    ///
+    ///Options of a configuration file have the names and values of their command line flags, with
+    ///dashes in names replaced by underscores. Options of the evaluation context go in a
+    ///`[context]` table.
+    C0001: InvalidConfig {
+        ($span:expr, $reason:expr) => {
+            Diagnostic::span_err($span, "Invalid config file", InvalidConfig::CODE, $reason)
+        }
+    }
+
+    ///This error is fired on a configuration file of slide options with an option that requires a
+    ///simplification rule that the file also denies. For example, the file
+    ///
+    ///```toml
+    ///[context]
+    ///objective = "prefer-factored"
+    ///rule_denylist = ["FactorOut"]
+    ///```
+    ///
+    ///is invalid because preferring factored results requires factoring terms out, which the
+    ///denied "FactorOut" rule does.
+    ///
+    ///To fix this error, remove either the option or the rule from the rule denylist.
+    C0002: RequiredRuleDenied {
+        ($span:expr, $error:expr) => {
+            Diagnostic::span_err(
+                $span,
+                "Option requires a denied rule",
+                RequiredRuleDenied::CODE,
+                $error.to_string(),
+            )
+        }
+    }
+}
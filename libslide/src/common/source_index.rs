@@ -0,0 +1,189 @@
+//! Mappings between offsets and line/column positions in a source text.
+
+use super::Span;
+
+/// A 0-indexed line and column in a source text.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub struct LineCol {
+    /// 0-indexed line.
+    pub line: usize,
+    /// 0-indexed column, counted in bytes.
+    pub col: usize,
+}
+
+impl LineCol {
+    /// Creates a new position at `line` and `col`.
+    pub fn new(line: usize, col: usize) -> Self {
+        Self { line, col }
+    }
+}
+
+/// The [line and column](LineCol) positions of the bounds of a [Span].
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub struct LineColSpan {
+    /// Position of the inclusive lower bound of the span.
+    pub start: LineCol,
+    /// Position of the exclusive upper bound of the span.
+    pub end: LineCol,
+}
+
+/// An index of the lines of a source text, mapping byte offsets in the source, like those of
+/// [Span]s, to line/column positions and back. An index is built once per source, after which each
+/// mapping is a binary search.
+///
+/// Lines are terminated by `\n` or `\r\n`; the terminator belongs to the line it ends.
+#[derive(Clone, Debug)]
+pub struct SourceIndex {
+    /// The offset of the start of each line, and the length of the line excluding its terminator.
+    lines: Vec<(usize, usize)>,
+    /// The length of the source.
+    len: usize,
+}
+
+impl SourceIndex {
+    /// Indexes the lines of `source`.
+    pub fn new(source: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut line_start = 0;
+        for (offset, _) in source.match_indices('\n') {
+            let line = &source[line_start..offset];
+            let len = line.strip_suffix('\r').unwrap_or(line).len();
+            lines.push((line_start, len));
+            line_start = offset + 1;
+        }
+        lines.push((line_start, source.len() - line_start));
+
+        Self {
+            lines,
+            len: source.len(),
+        }
+    }
+
+    /// Returns the line/column position of `offset`. Offsets past the end of the source are
+    /// positioned at its end.
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let offset = offset.min(self.len);
+        let line = match self
+            .lines
+            .binary_search_by_key(&offset, |&(start, _)| start)
+        {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+        LineCol::new(line, offset - self.lines[line].0)
+    }
+
+    /// Returns the line/column positions of the bounds of `span`.
+    pub fn span(&self, span: Span) -> LineColSpan {
+        LineColSpan {
+            start: self.line_col(span.lo),
+            end: self.line_col(span.hi),
+        }
+    }
+
+    /// Returns the offset of a line/column position. Positions past the end of a line are placed at
+    /// the end of the line, before its terminator, and positions past the last line at the end of
+    /// the source.
+    pub fn offset(&self, pos: LineCol) -> usize {
+        match self.lines.get(pos.line) {
+            Some(&(start, len)) => start + pos.col.min(len),
+            None => self.len,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineCol, SourceIndex};
+    use crate::Span;
+
+    /// Checks that each offset in the source of `index` maps to the position at the same index in
+    /// `positions`, and back.
+    fn check(index: &SourceIndex, positions: &[(usize, usize)]) {
+        for (offset, &(line, col)) in positions.iter().enumerate() {
+            let pos = LineCol::new(line, col);
+            assert_eq!(index.line_col(offset), pos, "offset {}", offset);
+            assert_eq!(index.offset(pos), offset, "{:?}", pos);
+        }
+    }
+
+    #[test]
+    fn lines() {
+        let index = SourceIndex::new("ab\nc\n\nd");
+        check(
+            &index,
+            &[
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 1),
+                (2, 0),
+                (3, 0),
+                (3, 1),
+            ],
+        );
+    }
+
+    #[test]
+    fn crlf() {
+        let index = SourceIndex::new("ab\r\nc\r\n\r\nd");
+        // The `\n` of a `\r\n` is after the end of its line, so it has no position of its own.
+        assert_eq!(index.line_col(3), LineCol::new(0, 3));
+        assert_eq!(index.offset(LineCol::new(0, 3)), 2);
+        assert_eq!(index.line_col(4), LineCol::new(1, 0));
+        assert_eq!(index.line_col(7), LineCol::new(2, 0));
+        assert_eq!(index.line_col(9), LineCol::new(3, 0));
+        assert_eq!(index.offset(LineCol::new(3, 0)), 9);
+        assert_eq!(index.offset(LineCol::new(1, 5)), 5);
+    }
+
+    #[test]
+    fn multibyte_chars() {
+        let source = "π + ππ\nπ +";
+        let index = SourceIndex::new(source);
+        // `π` is two bytes.
+        assert_eq!(
+            index.line_col(source.find('\n').unwrap()),
+            LineCol::new(0, 9)
+        );
+        assert_eq!(
+            index.line_col(source.rfind('+').unwrap()),
+            LineCol::new(1, 3)
+        );
+        assert_eq!(index.offset(LineCol::new(1, 3)), source.rfind('+').unwrap());
+    }
+
+    #[test]
+    fn multibyte_crlf() {
+        let source = "é\r\nü";
+        let index = SourceIndex::new(source);
+        assert_eq!(index.line_col(2), LineCol::new(0, 2));
+        assert_eq!(index.line_col(4), LineCol::new(1, 0));
+        assert_eq!(index.offset(LineCol::new(0, 9)), 2);
+    }
+
+    #[test]
+    fn past_end() {
+        let index = SourceIndex::new("1 + 2\n3 +");
+        assert_eq!(index.line_col(9), LineCol::new(1, 3));
+        assert_eq!(index.line_col(20), LineCol::new(1, 3));
+        assert_eq!(index.offset(LineCol::new(5, 0)), 9);
+    }
+
+    #[test]
+    fn span() {
+        let index = SourceIndex::new("a = 1\nb = 2 +");
+        let span = index.span(Span::new(4, 9));
+        assert_eq!(span.start, LineCol::new(0, 4));
+        assert_eq!(span.end, LineCol::new(1, 3));
+    }
+
+    #[test]
+    fn empty_source() {
+        let index = SourceIndex::new("");
+        assert_eq!(index.line_col(0), LineCol::new(0, 0));
+        assert_eq!(index.line_col(1), LineCol::new(0, 0));
+        assert_eq!(index.offset(LineCol::new(0, 1)), 0);
+    }
+}
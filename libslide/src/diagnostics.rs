@@ -7,13 +7,13 @@
 //!   they want
 //! - easily transformable into some output form by downstream customers (namely the slide app)
 
-use crate::common::Span;
-use crate::{LintConfig, ParseErrors, PartialEvaluatorErrors, ScanErrors};
+use crate::common::{LineColSpan, SourceIndex, Span};
+use crate::{ConfigErrors, LintConfig, ParseErrors, PartialEvaluatorErrors, ScanErrors};
 
 use std::collections::HashMap;
 
 /// The kind of a slide diagnostic.
-#[derive(PartialEq)]
+#[derive(PartialEq, Debug)]
 pub enum DiagnosticKind {
     /// An error diagnostic. Generally, this diagnostic should be emitted for unrecoverable errors.
     /// In other cases, a warning or a note may be more applicable.
@@ -31,6 +31,7 @@ pub enum DiagnosticKind {
 }
 
 /// A secondary diagnostic associated with a primary `Diagnostic`.
+#[derive(Debug)]
 pub struct AssociatedDiagnostic {
     /// The diagnostic kind.
     pub kind: DiagnosticKind,
@@ -95,6 +96,7 @@ impl Autofix {
 }
 
 /// A diagnostic for slide source code.
+#[derive(Debug)]
 pub struct Diagnostic {
     /// The diagnostic kind.
     pub kind: DiagnosticKind,
@@ -203,6 +205,8 @@ impl Diagnostic {
     with_diag! {
         /// Adds a note to the diagnostic.
         with_note as Note
+        /// Adds a help message to the diagnostic.
+        with_help as Help
     }
 
     with_spanned_diag! {
@@ -223,6 +227,13 @@ impl Diagnostic {
             ..self
         }
     }
+
+    /// Returns the 0-indexed `(line, column)` positions of the start and end of the diagnostic's
+    /// span, in the source indexed by `index`.
+    pub fn line_col(&self, index: &SourceIndex) -> ((usize, usize), (usize, usize)) {
+        let LineColSpan { start, end } = index.span(self.span);
+        ((start.line, start.col), (end.line, end.col))
+    }
 }
 
 macro_rules! include_diagnostic_registries {
@@ -234,6 +245,11 @@ macro_rules! include_diagnostic_registries {
                 $(map.extend($registry::codes_with_explanations());)*
                 map
             }
+
+            /// The explanation of the diagnostic with code `code`, if there is such a diagnostic.
+            pub fn explanation(code: &str) -> Option<&'static str> {
+                Self::all_codes_with_explanations().get(code).copied()
+            }
         }
 
         #[cfg(test)]
@@ -250,7 +266,15 @@ macro_rules! include_diagnostic_registries {
                 assert_eq!(vec.len(), Diagnostic::all_codes_with_explanations().len());
             }
 
-            /// Each code must be of form Sdddd, where S is L/S/P and d is a digit.
+            #[test]
+            fn explanation() {
+                assert!(Diagnostic::explanation("P0001")
+                    .unwrap()
+                    .contains("not connected to the rest of a primary statement"));
+                assert_eq!(Diagnostic::explanation("P9999"), None);
+            }
+
+            /// Each code must be of form Sdddd, where S is L/S/P/V/C and d is a digit.
             #[test]
             fn check_format() {
                 let codes = Diagnostic::all_codes_with_explanations();
@@ -259,7 +283,7 @@ macro_rules! include_diagnostic_registries {
                     assert_eq!(code.len(), 5);
                     assert!(matches!(
                         code.chars().next(),
-                        Some('L') | Some('S') | Some('P') | Some('V')
+                        Some('L') | Some('S') | Some('P') | Some('V') | Some('C')
                     ));
                     for ch in code.chars().skip(1) {
                         assert!(matches!(ch, '0'..='9'));
@@ -295,4 +319,19 @@ include_diagnostic_registries! {
     ParseErrors
     ScanErrors
     PartialEvaluatorErrors
+    ConfigErrors
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_statements, scan, SourceIndex};
+
+    #[test]
+    fn line_col() {
+        let program = "π = 1\r\nππ = 2 +";
+        let parsed = parse_statements(scan(program).tokens, program);
+        let index = SourceIndex::new(program);
+        // `π` is two bytes.
+        assert_eq!(parsed.diagnostics[0].line_col(&index), ((1, 9), (1, 10)));
+    }
 }
@@ -1,11 +1,19 @@
 //! Emit strategies for the libslide grammar IR.
 
 use crate::grammar::*;
+use crate::math::{continued_fraction, dec2frac_within};
+use crate::Span;
 
+use core::convert::TryFrom;
 use core::fmt;
+use core::str::FromStr;
+use std::error::Error;
+
+mod latex_check;
+pub use latex_check::check_latex;
 
 /// The format in which a slide grammar should be emitted.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum EmitFormat {
     /// Canonical, human-readable form.
     /// For example, `1+1` is output as `1 + 1`.
@@ -19,20 +27,76 @@ pub enum EmitFormat {
     /// Slide internal debug form.
     /// NB: this form is not stable, and no assumptions should be made about it.
     Debug,
+    /// JSON form of the syntax tree, with the kind and span of each node.
+    /// For example, `1+1` is output as
+    /// `{"kind":"BinaryExpr","op":"+","lhs":{"kind":"Const","value":1,"span":{"lo":0,"hi":1}},...}`.
+    Json,
 }
 
 impl From<String> for EmitFormat {
+    /// Converts an emit format name already known to be valid, as one validated by a command line
+    /// parser. Use [`try_from`](EmitFormat::try_from) to convert an unvalidated name.
     fn from(ef: String) -> Self {
-        match ef.as_ref() {
+        Self::try_from(ef.as_str()).unwrap_or_else(|err| unreachable!("{}", err))
+    }
+}
+
+impl TryFrom<&str> for EmitFormat {
+    type Error = UnknownEmitOption;
+
+    fn try_from(ef: &str) -> Result<Self, Self::Error> {
+        Ok(match ef {
             "pretty" => EmitFormat::Pretty,
             "s-expression" => EmitFormat::SExpression,
             "latex" => EmitFormat::Latex,
             "debug" => EmitFormat::Debug,
-            _ => unreachable!(),
+            "json" => EmitFormat::Json,
+            _ => return Err(UnknownEmitOption::Format(ef.to_owned())),
+        })
+    }
+}
+
+impl FromStr for EmitFormat {
+    type Err = UnknownEmitOption;
+
+    fn from_str(ef: &str) -> Result<Self, Self::Err> {
+        Self::try_from(ef)
+    }
+}
+
+impl fmt::Display for EmitFormat {
+    /// Formats the emit format as the name it is parsed from.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EmitFormat::Pretty => "pretty",
+            EmitFormat::SExpression => "s-expression",
+            EmitFormat::Latex => "latex",
+            EmitFormat::Debug => "debug",
+            EmitFormat::Json => "json",
+        })
+    }
+}
+
+/// An unknown [emit format](EmitFormat) or [emit config](EmitConfig) option name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnknownEmitOption {
+    /// An unknown emit format name.
+    Format(String),
+    /// An unknown emit config option name.
+    Config(String),
+}
+
+impl fmt::Display for UnknownEmitOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Format(name) => write!(f, r#"Unknown emit format "{}""#, name),
+            Self::Config(name) => write!(f, r#"Unknown emit config option "{}""#, name),
         }
     }
 }
 
+impl Error for UnknownEmitOption {}
+
 bitflags::bitflags! {
     /// Configuration options for emitting a slide grammar.
     #[derive(Default)]
@@ -52,26 +116,61 @@ bitflags::bitflags! {
         /// Emits integers as hex decimals, where possible.
         /// Applies to pretty emit.
         const HEX = 32;
+        /// Emits integer exponents as Unicode superscripts, where possible.
+        /// For example, `x^2` can be emitted as `x²`.
+        /// Applies to pretty emit.
+        const SUPERSCRIPT = 64;
+        /// Emits all assignments with the define-assign operator `:=`.
+        const DEFINE_ASSIGN = 128;
+        /// Emits binary operators without surrounding spaces.
+        /// For example, `1 + 2 * 3` can be emitted as `1+2*3`.
+        /// Applies to pretty emit.
+        const COMPACT = 256;
+        /// Emits exponentiations without spaces around `^`, keeping them around other operators.
+        /// For example, `1 + 3 ^ 4` can be emitted as `1 + 3^4`.
+        /// Applies to pretty emit.
+        const TIGHT_EXP = 512;
+        /// Emits constant results as continued fractions.
+        /// For example, `43/19` can be emitted as `[2; 3, 1, 4]`. Results that are not constants
+        /// are emitted as usual.
+        /// Applies to pretty emit.
+        const CONTINUED_FRACTION = 1024;
     }
 }
 
 impl From<Vec<String>> for EmitConfig {
+    /// Converts emit config option names already known to be valid, as ones validated by a
+    /// command line parser. Use [`try_from`](EmitConfig::try_from) to convert an unvalidated name.
     fn from(opts: Vec<String>) -> Self {
         let mut config = EmitConfig::default();
         for opt in opts {
-            config |= match opt.as_ref() {
-                "frac" => EmitConfig::FRAC,
-                "implicit-mult" => EmitConfig::IMPLICIT_MULT,
-                "times" => EmitConfig::TIMES,
-                "div" => EmitConfig::DIV,
-                "hex" => EmitConfig::HEX,
-                _ => unreachable!(),
-            }
+            config |= Self::try_from(opt.as_str()).unwrap_or_else(|err| unreachable!("{}", err));
         }
         config
     }
 }
 
+impl TryFrom<&str> for EmitConfig {
+    type Error = UnknownEmitOption;
+
+    /// Converts a single emit config option name to its config flag.
+    fn try_from(opt: &str) -> Result<Self, Self::Error> {
+        Ok(match opt {
+            "frac" => EmitConfig::FRAC,
+            "implicit-mult" => EmitConfig::IMPLICIT_MULT,
+            "times" => EmitConfig::TIMES,
+            "div" => EmitConfig::DIV,
+            "hex" => EmitConfig::HEX,
+            "superscript" => EmitConfig::SUPERSCRIPT,
+            "define-assign" => EmitConfig::DEFINE_ASSIGN,
+            "compact" => EmitConfig::COMPACT,
+            "tight-exp" => EmitConfig::TIGHT_EXP,
+            "continued-fraction" => EmitConfig::CONTINUED_FRACTION,
+            _ => return Err(UnknownEmitOption::Config(opt.to_owned())),
+        })
+    }
+}
+
 /// Implements the emission of a type in an [EmitFormat](self::EmitFormat).
 pub trait Emit
 where
@@ -90,17 +189,35 @@ where
             EmitFormat::SExpression => self.emit_s_expression(config),
             EmitFormat::Latex => self.emit_wrapped_latex(config),
             EmitFormat::Debug => self.emit_debug(config),
+            EmitFormat::Json => self.emit_json(config),
         }
     }
 
     /// Emit `self` with the [pretty emit format](EmitFormat::Pretty)
     fn emit_pretty(&self, config: EmitConfig) -> String;
 
+    /// Same as [emit_pretty](Emit::emit_pretty), but breaks lines longer than `max_width`
+    /// characters before the additions and subtractions of an expression, indenting the
+    /// continuation lines. A line with no such operator to break at may still be longer than
+    /// `max_width`.
+    ///
+    /// By default, `self` is emitted without breaking any lines.
+    fn emit_pretty_wrapped(&self, config: EmitConfig, _max_width: usize) -> String {
+        self.emit_pretty(config)
+    }
+
     /// Emit `self` with the [debug emit format](EmitFormat::Debug)
     fn emit_debug(&self, _config: EmitConfig) -> String {
         format!("{:#?}", self)
     }
 
+    /// Emit `self` with the [JSON emit format](EmitFormat::Json).
+    ///
+    /// By default, `self` is emitted as a JSON string of its pretty form.
+    fn emit_json(&self, config: EmitConfig) -> String {
+        json_str(&self.emit_pretty(config))
+    }
+
     /// Emit `self` with the [s_expression emit format](EmitFormat::SExpression)
     fn emit_s_expression(&self, config: EmitConfig) -> String;
 
@@ -178,6 +295,10 @@ impl Emit for StmtList {
         join_emits(self.iter(), |s| s.emit_pretty(config))
     }
 
+    fn emit_pretty_wrapped(&self, config: EmitConfig, max_width: usize) -> String {
+        join_emits(self.iter(), |s| s.emit_pretty_wrapped(config, max_width))
+    }
+
     fn emit_s_expression(&self, config: EmitConfig) -> String {
         join_emits(self.iter(), |s| s.emit_s_expression(config))
     }
@@ -186,6 +307,11 @@ impl Emit for StmtList {
         join_emits(self.iter(), |s| s.emit_latex(config))
     }
 
+    fn emit_json(&self, config: EmitConfig) -> String {
+        let stmts: Vec<_> = self.iter().map(|s| s.emit_json(config)).collect();
+        format!("[{}]", stmts.join(","))
+    }
+
     fn emit_wrapped_latex(&self, config: EmitConfig) -> String {
         let latex = self.emit_latex(config);
         let lines: Vec<_> = latex.lines().collect();
@@ -206,11 +332,21 @@ fmt_emit_impl!(StmtKind);
 impl Emit for StmtKind {
     fn emit_pretty(&self, config: EmitConfig) -> String {
         match self {
-            Self::Expr(expr) => expr.emit_pretty(config),
+            Self::Expr(expr) => {
+                emit_continued_fraction(expr, config).unwrap_or_else(|| expr.emit_pretty(config))
+            }
             Self::Assignment(asgn) => asgn.emit_pretty(config),
         }
     }
 
+    fn emit_pretty_wrapped(&self, config: EmitConfig, max_width: usize) -> String {
+        match self {
+            Self::Expr(expr) => emit_continued_fraction(expr, config)
+                .unwrap_or_else(|| expr.emit_pretty_wrapped(config, max_width)),
+            Self::Assignment(asgn) => asgn.emit_pretty_wrapped(config, max_width),
+        }
+    }
+
     fn emit_s_expression(&self, config: EmitConfig) -> String {
         match self {
             Self::Expr(expr) => expr.emit_s_expression(config),
@@ -224,6 +360,13 @@ impl Emit for StmtKind {
             Self::Assignment(asgn) => asgn.emit_latex(config),
         }
     }
+
+    fn emit_json(&self, config: EmitConfig) -> String {
+        match self {
+            Self::Expr(expr) => expr.emit_json(config),
+            Self::Assignment(asgn) => asgn.emit_json(config),
+        }
+    }
 }
 
 fmt_emit_impl!(Stmt);
@@ -232,6 +375,10 @@ impl Emit for Stmt {
         vert_lines(self.vw()) + &self.kind.emit_pretty(config)
     }
 
+    fn emit_pretty_wrapped(&self, config: EmitConfig, max_width: usize) -> String {
+        vert_lines(self.vw()) + &self.kind.emit_pretty_wrapped(config, max_width)
+    }
+
     fn emit_s_expression(&self, config: EmitConfig) -> String {
         vert_lines(self.vw()) + &self.kind.emit_s_expression(config)
     }
@@ -239,12 +386,17 @@ impl Emit for Stmt {
     fn emit_latex(&self, config: EmitConfig) -> String {
         vert_lines(self.vw()) + &self.kind.emit_latex(config)
     }
+
+    fn emit_json(&self, config: EmitConfig) -> String {
+        self.kind.emit_json(config)
+    }
 }
 
 fmt_emit_impl!(AssignmentOp);
 impl Emit for AssignmentOp {
-    fn emit_pretty(&self, _config: EmitConfig) -> String {
+    fn emit_pretty(&self, config: EmitConfig) -> String {
         match self {
+            AssignmentOp::Equal(_) if config.contains(EmitConfig::DEFINE_ASSIGN) => ":=",
             AssignmentOp::Equal(_) => "=",
             AssignmentOp::AssignDefine(_) => ":=",
         }
@@ -267,10 +419,24 @@ impl Emit for Assignment {
             "{} {} {}",
             self.lhs.emit_pretty(config),
             self.asgn_op.emit_pretty(config),
-            self.rhs.emit_pretty(config)
+            emit_continued_fraction(&self.rhs, config)
+                .unwrap_or_else(|| self.rhs.emit_pretty(config))
         )
     }
 
+    fn emit_pretty_wrapped(&self, config: EmitConfig, max_width: usize) -> String {
+        let lhs = format!(
+            "{} {} ",
+            self.lhs.emit_pretty(config),
+            self.asgn_op.emit_pretty(config)
+        );
+        let offset = lhs.chars().count();
+        let rhs = emit_continued_fraction(&self.rhs, config).unwrap_or_else(|| {
+            emit_pretty_wrapped_at(self.rhs.as_ref(), config, max_width, offset)
+        });
+        lhs + &rhs
+    }
+
     fn emit_s_expression(&self, config: EmitConfig) -> String {
         format!(
             "({} {} {})",
@@ -288,6 +454,15 @@ impl Emit for Assignment {
             self.rhs.emit_latex(config)
         )
     }
+
+    fn emit_json(&self, config: EmitConfig) -> String {
+        let fields = [
+            ("op", self.asgn_op.emit_json(config)),
+            ("lhs", self.lhs.emit_json(config)),
+            ("rhs", self.rhs.emit_json(config)),
+        ];
+        json_node("Assignment", &fields, Some(self.span))
+    }
 }
 
 fmt_emit_impl!(Expr);
@@ -308,9 +483,15 @@ impl Emit for Expr {
             Self::UnaryExpr(unary_expr) => unary_expr.emit_pretty(config),
             Self::Parend(expr) => normal_wrap!((expr.emit_pretty(config))),
             Self::Bracketed(expr) => normal_wrap!([expr.emit_pretty(config)]),
+            Self::Call(call) => call.emit_pretty(config),
+            Self::Interval(interval) => interval.emit_pretty(config),
         }
     }
 
+    fn emit_pretty_wrapped(&self, config: EmitConfig, max_width: usize) -> String {
+        emit_pretty_wrapped_at(self, config, max_width, 0)
+    }
+
     fn emit_s_expression(&self, config: EmitConfig) -> String {
         match self {
             Self::Const(konst) => konst.to_string(),
@@ -319,6 +500,8 @@ impl Emit for Expr {
             Self::UnaryExpr(unary_expr) => unary_expr.emit_s_expression(config),
             Self::Parend(inner) => normal_wrap!((inner.emit_s_expression(config))),
             Self::Bracketed(inner) => normal_wrap!([inner.emit_s_expression(config)]),
+            Self::Call(call) => call.emit_s_expression(config),
+            Self::Interval(interval) => interval.emit_s_expression(config),
         }
     }
 
@@ -334,8 +517,118 @@ impl Emit for Expr {
             Self::UnaryExpr(unary_expr) => unary_expr.emit_latex(config),
             Self::Parend(expr) => latex_wrap!((expr.emit_latex(config))),
             Self::Bracketed(expr) => latex_wrap!([expr.emit_latex(config)]),
+            Self::Call(call) => call.emit_latex(config),
+            Self::Interval(interval) => interval.emit_latex(config),
         }
     }
+
+    fn emit_json(&self, config: EmitConfig) -> String {
+        self.emit_json_at(None, config)
+    }
+}
+
+impl Expr {
+    /// Emits the expression as a JSON node, which is located at `span` if it is known.
+    pub(crate) fn emit_json_at(&self, span: Option<Span>, config: EmitConfig) -> String {
+        let (kind, fields) = match self {
+            Self::Const(num) => ("Const", vec![("value", json_num(*num))]),
+            Self::Var(var) => ("Var", vec![("name", json_str(var.as_ref()))]),
+            Self::BinaryExpr(binary_expr) => ("BinaryExpr", binary_expr.json_fields(config)),
+            Self::UnaryExpr(unary_expr) => ("UnaryExpr", unary_expr.json_fields(config)),
+            Self::Parend(expr) => ("Parend", vec![("expr", expr.emit_json(config))]),
+            Self::Bracketed(expr) => ("Bracketed", vec![("expr", expr.emit_json(config))]),
+            Self::Call(call) => ("Call", call.json_fields(config)),
+            Self::Interval(interval) => ("Interval", interval.json_fields(config)),
+        };
+        json_node(kind, &fields, span)
+    }
+}
+
+fmt_emit_impl!(Call);
+impl Emit for Call {
+    fn emit_pretty(&self, config: EmitConfig) -> String {
+        let args: Vec<_> = self.args.iter().map(|a| a.emit_pretty(config)).collect();
+        format!("{}({})", self.func, args.join(", "))
+    }
+
+    fn emit_s_expression(&self, config: EmitConfig) -> String {
+        let mut items = vec![self.func.to_string()];
+        items.extend(self.args.iter().map(|a| a.emit_s_expression(config)));
+        normal_wrap!((items.join(" ")))
+    }
+
+    fn emit_latex(&self, config: EmitConfig) -> String {
+        let func = match (self.func.as_ref(), self.args.as_slice()) {
+            // Roots are written with radicals rather than as function applications.
+            ("sqrt", [x]) => return format!("\\sqrt{{{}}}", x.emit_latex(config)),
+            ("root", [n, x]) => {
+                let (n, x) = (n.emit_latex(config), x.emit_latex(config));
+                return format!("\\sqrt[{}]{{{}}}", n, x);
+            }
+            ("sum", [i, lo, hi, body]) => {
+                let [i, lo, hi] = [i, lo, hi].map(|e| e.emit_latex(config));
+                // Delimit a compound summand, which would otherwise read as a term after the sum.
+                let body = match body.as_ref() {
+                    Expr::BinaryExpr(_) => latex_wrap!((body.emit_latex(config))),
+                    _ => body.emit_latex(config),
+                };
+                return format!("\\sum_{{{}={}}}^{{{}}} {}", i, lo, hi, body);
+            }
+            // LaTeX has builtin operators for these functions.
+            ("min", _) | ("max", _) => format!("\\{}", self.func),
+            (other, _) => format!("\\operatorname{{{}}}", other),
+        };
+        let args: Vec<_> = self.args.iter().map(|a| a.emit_latex(config)).collect();
+        format!("{}{}", func, latex_wrap!((args.join(", "))))
+    }
+
+    fn emit_json(&self, config: EmitConfig) -> String {
+        json_node("Call", &self.json_fields(config), None)
+    }
+}
+
+impl Call {
+    fn json_fields(&self, config: EmitConfig) -> Vec<(&'static str, String)> {
+        let args: Vec<_> = self.args.iter().map(|a| a.emit_json(config)).collect();
+        vec![
+            ("func", json_str(self.func.as_ref())),
+            ("args", format!("[{}]", args.join(","))),
+        ]
+    }
+}
+
+fmt_emit_impl!(Interval);
+impl Emit for Interval {
+    fn emit_pretty(&self, config: EmitConfig) -> String {
+        let (lo, hi) = (self.lo.emit_pretty(config), self.hi.emit_pretty(config));
+        normal_wrap!([format!("{}, {}", lo, hi)])
+    }
+
+    fn emit_s_expression(&self, config: EmitConfig) -> String {
+        let (lo, hi) = (
+            self.lo.emit_s_expression(config),
+            self.hi.emit_s_expression(config),
+        );
+        normal_wrap!((format!("interval {} {}", lo, hi)))
+    }
+
+    fn emit_latex(&self, config: EmitConfig) -> String {
+        let (lo, hi) = (self.lo.emit_latex(config), self.hi.emit_latex(config));
+        latex_wrap!([format!("{}, {}", lo, hi)])
+    }
+
+    fn emit_json(&self, config: EmitConfig) -> String {
+        json_node("Interval", &self.json_fields(config), None)
+    }
+}
+
+impl Interval {
+    fn json_fields(&self, config: EmitConfig) -> Vec<(&'static str, String)> {
+        vec![
+            ("lo", self.lo.emit_json(config)),
+            ("hi", self.hi.emit_json(config)),
+        ]
+    }
 }
 
 fmt_emit_impl!(BinaryOperator);
@@ -344,6 +637,7 @@ impl Emit for BinaryOperator {
         match self {
             Self::Plus => "+",
             Self::Minus => "-",
+            Self::PlusMinus => "±",
             Self::Mult => "*",
             Self::Div => "/",
             Self::Mod => "%",
@@ -360,6 +654,7 @@ impl Emit for BinaryOperator {
         match self {
             Self::Plus => "+",
             Self::Minus => "-",
+            Self::PlusMinus => "\\pm",
             Self::Mult if config.contains(EmitConfig::TIMES) => "\\times",
             Self::Mult => "*",
             Self::Div if config.contains(EmitConfig::DIV) => "\\div",
@@ -392,22 +687,55 @@ macro_rules! format_binary_operand {
             // - if the child op precedence is less than the parent op, we must always parenthesize
             //   it ([1])
             // - if the op precedences are equivalent, then
-            //   - if the child is on the LHS, we can always unwrap it
+            //   - if the child is on the LHS, we can unwrap it unless the parent op is
+            //     right-associative; (2 ^ 3) ^ 4 is not 2 ^ 3 ^ 4
             //   - if the child is on the RHS, we parenthesize it unless the parent op is
-            //     associative
-            //
-            // I think this is enough, but maybe we're overlooking left/right associativity?
+            //     associative and the child can be regrouped with it; 2 * (3 % 4 * 5) is not
+            //     2 * 3 % 4 * 5
             $E::BinaryExpr(child) => {
-                if child.op.precedence() < $parent_expr.op.precedence()
-                    || ($is_right_operand
-                        && child.op.precedence() == $parent_expr.op.precedence()
-                        && !$parent_expr.op.is_associative())
-                {
+                let parent_op = $parent_expr.op;
+                let same_precedence = child.op.precedence() == parent_op.precedence();
+                let wrap = child.op.precedence() < parent_op.precedence()
+                    || (same_precedence
+                        && if $is_right_operand {
+                            // The operands at the child's precedence that would be regrouped
+                            // with the parent are those on the child's left spine.
+                            let mut regroupable = parent_op.is_associative();
+                            let mut spine = Some(child);
+                            while let Some(expr) = spine.filter(|_| regroupable) {
+                                regroupable = expr.op != BinaryOperator::Mod;
+                                spine = match expr.lhs.as_ref() {
+                                    $E::BinaryExpr(lhs)
+                                        if lhs.op.precedence() == parent_op.precedence() =>
+                                    {
+                                        Some(lhs)
+                                    }
+                                    _ => None,
+                                };
+                            }
+                            !regroupable
+                        } else {
+                            parent_op.is_right_associative()
+                        });
+                if wrap {
                     $wrap!(($emit(child, $config)))
                 } else {
                     $emit(child, $config)
                 }
             }
+            // Prefix operators bind more loosely than ^, so (-2) ^ 2 is not -2 ^ 2.
+            $E::UnaryExpr(child)
+                if !$is_right_operand
+                    && $parent_expr.op == BinaryOperator::Exp
+                    && !child.op.is_postfix() =>
+            {
+                $wrap!(($emit(child, $config)))
+            }
+            $E::Const(n)
+                if !$is_right_operand && $parent_expr.op == BinaryOperator::Exp && *n < 0. =>
+            {
+                $wrap!(($emit($operand.as_ref(), $config)))
+            }
             expr => $emit(expr, $config),
         }
     };
@@ -421,6 +749,166 @@ macro_rules! can_fold_mult {
     };
 }
 
+/// Returns the spacing around a binary operator in pretty emit.
+fn binary_op_spacing(op: BinaryOperator, config: EmitConfig) -> &'static str {
+    let tight_exp = op == BinaryOperator::Exp && config.contains(EmitConfig::TIGHT_EXP);
+    if config.contains(EmitConfig::COMPACT) || tight_exp {
+        ""
+    } else {
+        " "
+    }
+}
+
+/// The largest magnitude of a constant emitted as a continued fraction. Larger constants cannot be
+/// converted to a fraction without overflowing.
+const MAX_CONTINUED_FRACTION: f64 = i32::MAX as f64;
+
+/// Emits a constant result as a continued fraction, like `[2; 3, 1, 4]` for `43/19`, if the
+/// [continued fraction](EmitConfig::CONTINUED_FRACTION) emit is configured. Returns `None` if the
+/// result is not a constant, or is not close to a fraction.
+fn emit_continued_fraction(result: &Expr, config: EmitConfig) -> Option<String> {
+    match result {
+        Expr::Const(num)
+            if config.contains(EmitConfig::CONTINUED_FRACTION)
+                && num.abs() <= MAX_CONTINUED_FRACTION =>
+        {
+            // A constant is only as precise as the last place its magnitude leaves it.
+            let precision = f64::EPSILON * num.abs().max(1.);
+            let fraction = dec2frac_within(*num, 1_000_000, precision).ok()?;
+            let terms: Vec<_> = continued_fraction(fraction)
+                .into_iter()
+                .map(|term| term.to_string())
+                .collect();
+            Some(match terms.split_first() {
+                Some((first, [])) => format!("[{}]", first),
+                Some((first, rest)) => format!("[{}; {}]", first, rest.join(", ")),
+                None => return None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Converts an emitted integer to its Unicode superscript form, if the emitted string is a
+/// non-negative integer.
+fn to_superscript(num: &str) -> Option<String> {
+    if num.is_empty() {
+        return None;
+    }
+    num.chars()
+        .map(|c| {
+            Some(match c {
+                '0' => '⁰',
+                '1' => '¹',
+                '2' => '²',
+                '3' => '³',
+                '4' => '⁴',
+                '5' => '⁵',
+                '6' => '⁶',
+                '7' => '⁷',
+                '8' => '⁸',
+                '9' => '⁹',
+                _ => return None,
+            })
+        })
+        .collect()
+}
+
+/// Indentation of the continuation lines of a wrapped expression.
+const CONTINUATION_INDENT: &str = "    ";
+
+/// Emits `expr` in the pretty format, breaking it before the additions and subtractions joining
+/// its top-level terms wherever a line would be longer than `max_width` characters. `offset` is
+/// the number of characters preceding `expr` on its first line.
+fn emit_pretty_wrapped_at(
+    expr: &Expr,
+    config: EmitConfig,
+    max_width: usize,
+    offset: usize,
+) -> String {
+    let pretty = expr.emit_pretty(config);
+    if offset + pretty.chars().count() <= max_width {
+        return pretty;
+    }
+
+    // Collect the terms of the sum along its left spine, like `a`, `+ b`, `- c` in `a + b - c`,
+    // parenthesized as they would be in the emit of the whole sum.
+    let mut links = vec![];
+    let mut first = expr;
+    while let Expr::BinaryExpr(sum) = first {
+        if !matches!(sum.op, BinaryOperator::Plus | BinaryOperator::Minus) {
+            break;
+        }
+        let term =
+            format_binary_operand!(Expr, sum, &sum.rhs, true, emit_pretty, normal_wrap, config);
+        let space = binary_op_spacing(sum.op, config);
+        links.push(format!("{}{}{}", sum.op.emit_pretty(config), space, term));
+        first = sum.lhs.as_ref();
+    }
+    if links.is_empty() {
+        return pretty;
+    }
+    links.reverse();
+
+    let space = binary_op_spacing(BinaryOperator::Plus, config);
+    let mut lines = vec![first.emit_pretty(config)];
+    let mut line_width = offset + lines[0].chars().count();
+    for link in links {
+        let link_width = link.chars().count();
+        if line_width + space.len() + link_width > max_width {
+            lines.push(format!("{}{}", CONTINUATION_INDENT, link));
+            line_width = CONTINUATION_INDENT.len() + link_width;
+        } else {
+            let line = lines.last_mut().unwrap();
+            line.push_str(space);
+            line.push_str(&link);
+            line_width += space.len() + link_width;
+        }
+    }
+    lines.join("\n")
+}
+
+/// Emits a string as a JSON string literal.
+fn json_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Emits a number as a JSON number. JSON has no representation of infinite or NaN numbers, so
+/// those are emitted as strings.
+fn json_num(n: f64) -> String {
+    if n.is_finite() {
+        n.to_string()
+    } else {
+        json_str(&n.to_string())
+    }
+}
+
+/// Emits a syntax node of some kind as a JSON object. `fields` are already emitted as JSON.
+fn json_node(kind: &str, fields: &[(&str, String)], span: Option<Span>) -> String {
+    let mut members = vec![format!("\"kind\":{}", json_str(kind))];
+    members.extend(
+        fields
+            .iter()
+            .map(|(name, value)| format!("{}:{}", json_str(name), value)),
+    );
+    if let Some(Span { lo, hi }) = span {
+        members.push(format!("\"span\":{{\"lo\":{},\"hi\":{}}}", lo, hi));
+    }
+    format!("{{{}}}", members.join(","))
+}
+
 macro_rules! display_binary_expr {
     ($iexpr:ident, $expr:ident) => {
         fmt_emit_impl!(BinaryExpr<$iexpr>);
@@ -445,6 +933,10 @@ macro_rules! display_binary_expr {
                     normal_wrap,
                     config
                 );
+                let spaced = || {
+                    let space = binary_op_spacing(self.op, config);
+                    format!("{}{}{}{}{}", lhs, space, op, space, rhs)
+                };
 
                 match self.op {
                     BinaryOperator::Mult
@@ -453,7 +945,13 @@ macro_rules! display_binary_expr {
                     {
                         format!("{}{}", lhs, rhs)
                     }
-                    _ => format!("{} {} {}", lhs, op, rhs),
+                    BinaryOperator::Exp if config.contains(EmitConfig::SUPERSCRIPT) => {
+                        match to_superscript(&rhs) {
+                            Some(sup) => format!("{}{}", lhs, sup),
+                            None => spaced(),
+                        }
+                    }
+                    _ => spaced(),
                 }
             }
 
@@ -488,6 +986,20 @@ macro_rules! display_binary_expr {
                     _ => format!("{} {} {}", lhs, op, rhs),
                 }
             }
+
+            fn emit_json(&self, config: EmitConfig) -> String {
+                json_node("BinaryExpr", &self.json_fields(config), None)
+            }
+        }
+
+        impl BinaryExpr<$iexpr> {
+            fn json_fields(&self, config: EmitConfig) -> Vec<(&'static str, String)> {
+                vec![
+                    ("op", self.op.emit_json(config)),
+                    ("lhs", self.lhs.emit_json(config)),
+                    ("rhs", self.rhs.emit_json(config)),
+                ]
+            }
         }
     };
 }
@@ -500,6 +1012,7 @@ impl Emit for UnaryOperator {
         match self {
             Self::SignPositive => "+",
             Self::SignNegative => "-",
+            Self::Factorial => "!",
         }
         .to_owned()
     }
@@ -520,9 +1033,19 @@ macro_rules! display_unary_expr {
             fn emit_pretty(&self, config: EmitConfig) -> String {
                 let format_arg = |arg: &$iexpr| match arg.as_ref() {
                     $expr::BinaryExpr(l) => normal_wrap!((l.emit_pretty(config))),
+                    // -(5!) is -5!, but (-5)! must keep its parentheses.
+                    $expr::UnaryExpr(u) if self.op.is_postfix() && !u.op.is_postfix() => {
+                        normal_wrap!((u.emit_pretty(config)))
+                    }
+                    $expr::Const(n) if self.op.is_postfix() && *n < 0. => normal_wrap!((n)),
                     expr => expr.emit_pretty(config),
                 };
-                format!("{}{}", self.op.emit_pretty(config), format_arg(&self.rhs))
+                let (op, arg) = (self.op.emit_pretty(config), format_arg(&self.rhs));
+                if self.op.is_postfix() {
+                    format!("{}{}", arg, op)
+                } else {
+                    format!("{}{}", op, arg)
+                }
             }
 
             fn emit_s_expression(&self, config: EmitConfig) -> String {
@@ -536,9 +1059,31 @@ macro_rules! display_unary_expr {
             fn emit_latex(&self, config: EmitConfig) -> String {
                 let format_arg = |arg: &$iexpr| match arg.as_ref() {
                     $expr::BinaryExpr(l) => latex_wrap!((l.emit_latex(config))),
+                    $expr::UnaryExpr(u) if self.op.is_postfix() && !u.op.is_postfix() => {
+                        latex_wrap!((u.emit_latex(config)))
+                    }
+                    $expr::Const(n) if self.op.is_postfix() && *n < 0. => latex_wrap!((n)),
                     expr => expr.emit_latex(config),
                 };
-                format!("{}{}", self.op.emit_latex(config), format_arg(&self.rhs))
+                let (op, arg) = (self.op.emit_latex(config), format_arg(&self.rhs));
+                if self.op.is_postfix() {
+                    format!("{}{}", arg, op)
+                } else {
+                    format!("{}{}", op, arg)
+                }
+            }
+
+            fn emit_json(&self, config: EmitConfig) -> String {
+                json_node("UnaryExpr", &self.json_fields(config), None)
+            }
+        }
+
+        impl UnaryExpr<$iexpr> {
+            fn json_fields(&self, config: EmitConfig) -> Vec<(&'static str, String)> {
+                vec![
+                    ("op", self.op.emit_json(config)),
+                    ("rhs", self.rhs.emit_json(config)),
+                ]
             }
         }
     };
@@ -583,4 +1128,432 @@ impl Emit for ExprPat {
             Self::Bracketed(inner) => latex_wrap!([inner.emit_latex(config)]),
         }
     }
+
+    fn emit_json(&self, config: EmitConfig) -> String {
+        self.emit_json_at(None, config)
+    }
+}
+
+impl ExprPat {
+    /// Emits the expression pattern as a JSON node, which is located at `span` if it is known.
+    pub(crate) fn emit_json_at(&self, span: Option<Span>, config: EmitConfig) -> String {
+        let (kind, fields) = match self {
+            Self::Const(num) => ("Const", vec![("value", json_num(*num))]),
+            Self::VarPat(pat) => ("VarPat", vec![("name", json_str(pat))]),
+            Self::ConstPat(pat) => ("ConstPat", vec![("name", json_str(pat))]),
+            Self::AnyPat(pat) => ("AnyPat", vec![("name", json_str(pat))]),
+            Self::BinaryExpr(binary_expr) => ("BinaryExpr", binary_expr.json_fields(config)),
+            Self::UnaryExpr(unary_expr) => ("UnaryExpr", unary_expr.json_fields(config)),
+            Self::Parend(expr) => ("Parend", vec![("expr", expr.emit_json(config))]),
+            Self::Bracketed(expr) => ("Bracketed", vec![("expr", expr.emit_json(config))]),
+        };
+        json_node(kind, &fields, span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_latex, Emit, EmitConfig, EmitFormat, UnknownEmitOption};
+    use crate::grammar::{Expr, RcExpr, StmtKind};
+    use crate::parse_stmt;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn emit_format_display_from_str() {
+        for format in &[
+            EmitFormat::Pretty,
+            EmitFormat::SExpression,
+            EmitFormat::Latex,
+            EmitFormat::Debug,
+            EmitFormat::Json,
+        ] {
+            assert_eq!(format.to_string().parse::<EmitFormat>(), Ok(*format));
+            assert_eq!(EmitFormat::from(format.to_string()), *format);
+        }
+    }
+
+    #[test]
+    fn emit_format_try_from_unknown() {
+        let err = EmitFormat::try_from("xml").unwrap_err();
+        assert_eq!(err, UnknownEmitOption::Format("xml".to_owned()));
+        assert_eq!(err.to_string(), r#"Unknown emit format "xml""#);
+    }
+
+    #[test]
+    fn emit_config_try_from() {
+        assert_eq!(EmitConfig::try_from("frac"), Ok(EmitConfig::FRAC));
+        assert_eq!(
+            EmitConfig::from(vec!["times".to_owned(), "hex".to_owned()]),
+            EmitConfig::TIMES | EmitConfig::HEX
+        );
+
+        let err = EmitConfig::try_from("xml").unwrap_err();
+        assert_eq!(err, UnknownEmitOption::Config("xml".to_owned()));
+        assert_eq!(err.to_string(), r#"Unknown emit config option "xml""#);
+    }
+
+    macro_rules! emit_stmt_list_tests {
+        ($($name:ident: $format:expr => $result:expr)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let stmts = parse_stmt!("a = 1 + 2\nb := a * 3");
+                assert_eq!(stmts.emit($format, EmitConfig::default()), $result);
+            }
+        )*
+        }
+    }
+
+    emit_stmt_list_tests! {
+        stmt_list_pretty:       EmitFormat::Pretty      => "a = 1 + 2\nb := a * 3"
+        stmt_list_s_expression: EmitFormat::SExpression => "(= a (+ 1 2))\n(:= b (* a 3))"
+        stmt_list_latex:        EmitFormat::Latex       => "\\begin{gathered}\na = 1 + 2\\\\\nb := a * 3\n\\end{gathered}"
+    }
+
+    /// Configs changing how LaTeX is emitted.
+    const LATEX_CONFIGS: &[EmitConfig] = &[
+        EmitConfig::FRAC,
+        EmitConfig::TIMES,
+        EmitConfig::DIV,
+        EmitConfig::IMPLICIT_MULT,
+    ];
+
+    /// Asserts that `emit` is well-formed LaTeX for every combination of [LATEX_CONFIGS].
+    fn assert_latex_well_formed(program: &str, emit: impl Fn(EmitConfig) -> String) {
+        for i in 0..(1 << LATEX_CONFIGS.len()) {
+            let config = LATEX_CONFIGS
+                .iter()
+                .enumerate()
+                .filter(|(bit, _)| i & (1 << bit) != 0)
+                .fold(EmitConfig::default(), |config, (_, flag)| config | *flag);
+            let latex = emit(config);
+            assert_eq!(
+                check_latex(&latex),
+                Ok(()),
+                "{} emitted as {} with {:?}",
+                program,
+                latex,
+                config
+            );
+        }
+    }
+
+    #[test]
+    fn emit_latex_well_formed() {
+        for program in &[
+            "1 + 2 - 3",
+            "1 ± 2 - (3 ± x)",
+            "a = 1 * x",
+            "a := b % 3",
+            "x % (y % z) % 2",
+            "-x + +y - -(-z)",
+            "(-2) ^ 2 + -2 ^ 2",
+            "x! + (-x)! + (x + 1)! + 5!!",
+            "1 / 2 / (3 / 4)",
+            "1 / (2 / (3 / (4 / x)))",
+            "(1 / 2) ^ (3 / 4) ^ 5",
+            "(x ^ 2) ^ 3 + x ^ 2 ^ 3",
+            "2x + 2(x + 1) + 2[x + 1] + (x)(y)",
+            "[1, 2] + [x, x ^ 2]",
+            "sqrt(x / 2) + root(3, x ^ 2)",
+            "max(1, x / 2) + min(x) + f(x, y % 2)",
+            "sum(i, 1, n, i ^ 2 / 2)",
+            "1 / 0 + -(1 / 0)",
+            "a = 1\nb := a + 2\n\nc = b % 2",
+        ] {
+            let stmts = parse_stmt!(*program);
+            assert_latex_well_formed(program, |config| stmts.emit_wrapped_latex(config));
+        }
+    }
+
+    #[test]
+    fn emit_latex_well_formed_patterns() {
+        for program in &[
+            "$a + #b - _c",
+            "$a % #b % _c",
+            "-$a + +#b + _c!",
+            "$a / (#b / _c) ^ 2",
+            "($a / 2) ^ (#b / 3)",
+            "[$a + 1] * (#b)",
+        ] {
+            let pat = crate::parse_expression_pattern(crate::scan(*program).tokens).program;
+            assert_latex_well_formed(program, |config| pat.emit_wrapped_latex(config));
+        }
+    }
+
+    #[test]
+    fn emit_pretty_wrapped() {
+        let stmts = parse_stmt!("1000 + 2000 * b + 3000 - 4000 * c + 5000 + 6000 - (7000 + d)");
+        assert_eq!(
+            stmts.emit_pretty_wrapped(EmitConfig::default(), 24),
+            "1000 + 2000 * b + 3000\n    - 4000 * c + 5000\n    + 6000 - (7000 + d)"
+        );
+        for line in stmts.emit_pretty_wrapped(EmitConfig::default(), 24).lines() {
+            assert!(line.len() <= 24, "{}", line);
+        }
+    }
+
+    #[test]
+    fn emit_pretty_wrapped_assignment() {
+        let stmts = parse_stmt!("abc = 1000 + 2000 + 3000 + 4000\n1 + 2");
+        assert_eq!(
+            stmts.emit_pretty_wrapped(EmitConfig::default(), 18),
+            "abc = 1000 + 2000\n    + 3000 + 4000\n1 + 2"
+        );
+    }
+
+    #[test]
+    fn emit_pretty_wrapped_unbreakable() {
+        let stmts = parse_stmt!("1000 * 2000 * 3000 + 4000");
+        assert_eq!(
+            stmts.emit_pretty_wrapped(EmitConfig::default(), 10),
+            "1000 * 2000 * 3000\n    + 4000"
+        );
+        let stmts = parse_stmt!("1000 * 2000 * 3000");
+        assert_eq!(
+            stmts.emit_pretty_wrapped(EmitConfig::default(), 10),
+            "1000 * 2000 * 3000"
+        );
+    }
+
+    #[test]
+    fn emit_pretty_wrapped_fits() {
+        let stmts = parse_stmt!("1 + 2 + 3");
+        assert_eq!(
+            stmts.emit_pretty_wrapped(EmitConfig::default(), 9),
+            stmts.emit_pretty(EmitConfig::default())
+        );
+    }
+
+    #[test]
+    fn emit_continued_fraction() {
+        for (konst, emitted) in &[
+            (3., "[3]"),
+            (0.5, "[0; 2]"),
+            (43. / 19., "[2; 3, 1, 4]"),
+            (415. / 93., "[4; 2, 6, 7]"),
+            (-43. / 19., "[-3; 1, 2, 1, 4]"),
+        ] {
+            let stmt = StmtKind::Expr(rc_expr!(Expr::Const(*konst), crate::DUMMY_SP));
+            assert_eq!(stmt.emit_pretty(EmitConfig::CONTINUED_FRACTION), *emitted);
+        }
+    }
+
+    #[test]
+    fn emit_continued_fraction_only_constant_results() {
+        for (program, emitted) in &[
+            ("a = 0.5", "a = [0; 2]"),
+            ("x + 0.5", "x + 0.5"),
+            ("a = x + 0.5", "a = x + 0.5"),
+        ] {
+            let stmts = parse_stmt!(*program);
+            assert_eq!(stmts.emit_pretty(EmitConfig::CONTINUED_FRACTION), *emitted);
+        }
+    }
+
+    #[test]
+    fn emit_operator_spacing() {
+        let stmts = parse_stmt!("1 + 2 * 3^4");
+        for (config, emitted) in &[
+            (EmitConfig::default(), "1 + 2 * 3 ^ 4"),
+            (EmitConfig::COMPACT, "1+2*3^4"),
+            (EmitConfig::TIGHT_EXP, "1 + 2 * 3^4"),
+            (EmitConfig::COMPACT | EmitConfig::TIGHT_EXP, "1+2*3^4"),
+        ] {
+            assert_eq!(stmts.emit(EmitFormat::Pretty, *config), *emitted);
+        }
+    }
+
+    #[test]
+    fn emit_operator_spacing_unary() {
+        let stmts = parse_stmt!("-1 - -2 * (-3)^4!");
+        assert_eq!(stmts.emit_pretty(EmitConfig::COMPACT), "-1--2*(-3)^4!");
+        assert_eq!(
+            stmts.emit_pretty(EmitConfig::TIGHT_EXP),
+            "-1 - -2 * (-3)^4!"
+        );
+    }
+
+    #[test]
+    fn emit_pretty_wrapped_compact() {
+        let stmts = parse_stmt!("1000 + 2000 * b + 3000 - 4000 * c");
+        assert_eq!(
+            stmts.emit_pretty_wrapped(EmitConfig::COMPACT, 16),
+            "1000+2000*b+3000\n    -4000*c"
+        );
+    }
+
+    #[test]
+    fn emit_define_assign() {
+        let stmts = parse_stmt!("a = 1 + 2\nb := a * 3");
+        assert_eq!(
+            stmts.emit(EmitFormat::Pretty, EmitConfig::DEFINE_ASSIGN),
+            "a := 1 + 2\nb := a * 3"
+        );
+    }
+
+    macro_rules! emit_latex_tests {
+        ($($name:ident: $program:expr => $result:expr)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let stmts = parse_stmt!($program);
+                assert_eq!(stmts.emit(EmitFormat::Latex, EmitConfig::default()), $result);
+            }
+        )*
+        }
+    }
+
+    emit_latex_tests! {
+        latex_sqrt:             "sqrt(a + 1)"   => "$\\sqrt{a + 1}$"
+        latex_root:             "root(3, a)"    => "$\\sqrt[3]{a}$"
+        latex_sum:              "sum(i, 1, n, i)" => "$\\sum_{i=1}^{n} i$"
+        latex_sum_compound:     "sum(i, 1, n, 2 * i)" => "$\\sum_{i=1}^{n} \\left(2 * i\\right)$"
+        latex_max:              "max(a, 1)"     => "$\\max\\left(a, 1\\right)$"
+        latex_factorial:        "5!"            => "$5!$"
+        latex_factorial_paren:  "(a + 1)!"      => "$\\left(a + 1\\right)!$"
+        latex_plus_minus:       "1 ± (a - 2)"   => "$1 \\pm \\left(a - 2\\right)$"
+    }
+
+    #[test]
+    fn emit_interval() {
+        let stmts = parse_stmt!("[x - 1, 2]");
+        for (format, emitted) in &[
+            (EmitFormat::Pretty, "[x - 1, 2]"),
+            (EmitFormat::SExpression, "(interval (- x 1) 2)"),
+            (EmitFormat::Latex, "$\\left[x - 1, 2\\right]$"),
+        ] {
+            assert_eq!(stmts.emit(*format, EmitConfig::default()), *emitted);
+        }
+    }
+
+    #[test]
+    fn emit_json() {
+        let stmts = parse_stmt!("1 + 2 * 3");
+        assert_eq!(
+            stmts.emit(EmitFormat::Json, EmitConfig::default()),
+            concat!(
+                r#"[{"kind":"BinaryExpr","op":"+","#,
+                r#""lhs":{"kind":"Const","value":1,"span":{"lo":0,"hi":1}},"#,
+                r#""rhs":{"kind":"BinaryExpr","op":"*","#,
+                r#""lhs":{"kind":"Const","value":2,"span":{"lo":4,"hi":5}},"#,
+                r#""rhs":{"kind":"Const","value":3,"span":{"lo":8,"hi":9}},"#,
+                r#""span":{"lo":4,"hi":9}},"#,
+                r#""span":{"lo":0,"hi":9}}]"#,
+            )
+        );
+    }
+
+    #[test]
+    fn emit_json_assignment() {
+        let stmts = parse_stmt!("a = sqrt(x)");
+        assert_eq!(
+            stmts.emit(EmitFormat::Json, EmitConfig::default()),
+            concat!(
+                r#"[{"kind":"Assignment","op":"=","#,
+                r#""lhs":{"kind":"Var","name":"a","span":{"lo":0,"hi":1}},"#,
+                r#""rhs":{"kind":"Call","func":"sqrt","#,
+                r#""args":[{"kind":"Var","name":"x","span":{"lo":9,"hi":10}}],"#,
+                r#""span":{"lo":4,"hi":11}},"#,
+                r#""span":{"lo":0,"hi":11}}]"#,
+            )
+        );
+    }
+    mod round_trip {
+        use crate::grammar::*;
+        use crate::{parse_expr, Emit, DUMMY_SP};
+        use proptest::prelude::*;
+
+        fn binary(op: BinaryOperator, lhs: RcExpr, rhs: RcExpr) -> RcExpr {
+            RcExpr::binary(BinaryExpr { op, lhs, rhs }, DUMMY_SP)
+        }
+
+        fn unary(op: UnaryOperator, rhs: RcExpr) -> RcExpr {
+            RcExpr::unary(UnaryExpr { op, rhs }, DUMMY_SP)
+        }
+
+        fn arb_expr() -> impl Strategy<Value = RcExpr> {
+            let leaf = prop_oneof![
+                (0..100u32).prop_map(|n| rc_expr!(Expr::Const(n as f64), DUMMY_SP)),
+                (0..16u32).prop_map(|n| rc_expr!(Expr::Const(n as f64 / 4.), DUMMY_SP)),
+                prop::sample::select(vec!["a", "b", "x", "y"])
+                    .prop_map(|v| rc_expr!(Expr::Var(intern_str!(v)), DUMMY_SP)),
+            ];
+            leaf.prop_recursive(6, 64, 2, |inner| {
+                let binary_op = prop::sample::select(vec![
+                    BinaryOperator::Plus,
+                    BinaryOperator::Minus,
+                    BinaryOperator::Mult,
+                    BinaryOperator::Div,
+                    BinaryOperator::Mod,
+                    BinaryOperator::Exp,
+                ]);
+                let unary_op = prop::sample::select(vec![
+                    UnaryOperator::SignPositive,
+                    UnaryOperator::SignNegative,
+                    UnaryOperator::Factorial,
+                ]);
+                prop_oneof![
+                    (binary_op, inner.clone(), inner.clone())
+                        .prop_map(|(op, lhs, rhs)| binary(op, lhs, rhs)),
+                    (unary_op, inner).prop_map(|(op, rhs)| unary(op, rhs)),
+                ]
+            })
+        }
+
+        /// Normalizes an expression to the form it takes after being emitted and parsed again.
+        /// Parentheses are dropped, and since the emitter does not parenthesize the right operand
+        /// of an associative operator, `a + (b - c)` is regrouped as `(a + b) - c`.
+        fn normalize(expr: &RcExpr) -> RcExpr {
+            match expr.as_ref() {
+                Expr::Parend(inner) | Expr::Bracketed(inner) => normalize(inner),
+                Expr::BinaryExpr(BinaryExpr { op, lhs, rhs }) => {
+                    regroup(*op, normalize(lhs), normalize(rhs))
+                }
+                Expr::UnaryExpr(UnaryExpr { op, rhs }) => unary(*op, normalize(rhs)),
+                _ => expr.clone(),
+            }
+        }
+
+        fn regroup(op: BinaryOperator, lhs: RcExpr, rhs: RcExpr) -> RcExpr {
+            match rhs.as_ref() {
+                Expr::BinaryExpr(child)
+                    if matches!(op, BinaryOperator::Plus | BinaryOperator::Mult)
+                        && child.op.precedence() == op.precedence()
+                        && !has_mod_on_left_spine(&rhs) =>
+                {
+                    let lhs = regroup(op, lhs, child.lhs.clone());
+                    regroup(child.op, lhs, child.rhs.clone())
+                }
+                _ => binary(op, lhs, rhs),
+            }
+        }
+
+        fn has_mod_on_left_spine(expr: &RcExpr) -> bool {
+            match expr.as_ref() {
+                Expr::BinaryExpr(BinaryExpr { op, lhs, .. }) => match op {
+                    BinaryOperator::Mod => true,
+                    BinaryOperator::Mult | BinaryOperator::Div => has_mod_on_left_spine(lhs),
+                    _ => false,
+                },
+                _ => false,
+            }
+        }
+
+        proptest! {
+            #[test]
+            fn emit_pretty_round_trips(expr in arb_expr()) {
+                let emitted = expr.to_string();
+                let reparsed = parse_expr!(emitted.as_str());
+                prop_assert_eq!(normalize(&reparsed), normalize(&expr), "emitted as {}", emitted);
+            }
+
+            #[test]
+            fn emit_latex_well_formed(expr in arb_expr()) {
+                super::assert_latex_well_formed(&expr.to_string(), |config| {
+                    expr.emit_wrapped_latex(config)
+                });
+            }
+        }
+    }
 }
@@ -0,0 +1,460 @@
+//! A lightweight checker for the LaTeX [emitted](super::Emit::emit_wrapped_latex) by slide.
+//!
+//! The checker does not typeset anything. It only checks the structure of LaTeX emit that would
+//! keep it from compiling: unbalanced groups, delimiters, environments, and math modes; commands
+//! slide does not emit, or that are missing arguments; unescaped special characters; and doubled
+//! scripts.
+
+/// Commands slide emits, with the number of mandatory arguments each takes.
+const COMMANDS: &[(&str, usize)] = &[
+    ("bmod", 0),
+    ("div", 0),
+    ("frac", 2),
+    ("infty", 0),
+    ("max", 0),
+    ("min", 0),
+    ("operatorname", 1),
+    ("pm", 0),
+    ("sqrt", 1),
+    ("sum", 0),
+    ("times", 0),
+    ("to", 0),
+];
+
+/// Environments slide emits. An environment outside of math mode is typeset as display math.
+const ENVIRONMENTS: &[&str] = &["gathered"];
+
+/// Characters escaped with a backslash to be typeset literally, and spacing control symbols.
+const CONTROL_SYMBOLS: &str = "$#_{}%&,;! ";
+
+/// Characters that must be escaped to be typeset literally.
+const SPECIAL_CHARS: &str = "#%&~";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    /// A command, like `\frac`, or a control symbol, like `\#`, without its backslash.
+    Command(&'a str),
+    Open,
+    Close,
+    Math,
+    /// A superscript or subscript operator.
+    Script(char),
+    Char(char),
+}
+
+/// Splits LaTeX into [`Token`](Token)s and their byte offsets, dropping whitespace.
+fn tokenize(latex: &str) -> Result<Vec<(usize, Token<'_>)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = latex.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        let token = match c {
+            '\\' => {
+                let name_start = offset + 1;
+                let mut name_end = name_start;
+                while let Some((i, c)) = chars.peek().copied() {
+                    if !c.is_ascii_alphabetic() {
+                        break;
+                    }
+                    name_end = i + 1;
+                    chars.next();
+                }
+                if name_end == name_start {
+                    match chars.next() {
+                        Some((i, c)) => name_end = i + c.len_utf8(),
+                        None => return Err(at(offset, "unterminated command `\\`")),
+                    }
+                }
+                Token::Command(&latex[name_start..name_end])
+            }
+            '{' => Token::Open,
+            '}' => Token::Close,
+            '$' => Token::Math,
+            '^' | '_' => Token::Script(c),
+            c if c.is_whitespace() => continue,
+            c => Token::Char(c),
+        };
+        tokens.push((offset, token));
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GroupKind<'a> {
+    /// Inline math, `$...$`.
+    Math,
+    /// A braced group, `{...}`.
+    Brace,
+    /// A `\left ... \right` pair.
+    Left,
+    /// A `\begin{...} ... \end{...}` environment.
+    Env(&'a str),
+    /// The optional argument of `\sqrt`, `[...]`.
+    Optional,
+}
+
+/// What a group is to its parent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Role {
+    /// An argument of a command.
+    Arg,
+    /// The argument of a superscript or subscript.
+    Script(char),
+    /// A group of its own.
+    Plain,
+}
+
+#[derive(Debug)]
+struct Group<'a> {
+    kind: GroupKind<'a>,
+    role: Role,
+    start: usize,
+    /// The last command in the group that still expects mandatory arguments, and how many.
+    command: Option<(&'a str, usize)>,
+    /// Whether `[` begins the optional argument of the last command in the group.
+    optional_allowed: bool,
+    /// A script operator in the group awaiting its argument.
+    script: Option<char>,
+    /// The scripts already applied to the last atom of the group.
+    atom_scripts: Vec<char>,
+}
+
+impl<'a> Group<'a> {
+    fn new(kind: GroupKind<'a>, role: Role, start: usize) -> Self {
+        Self {
+            kind,
+            role,
+            start,
+            command: None,
+            optional_allowed: false,
+            script: None,
+            atom_scripts: Vec::new(),
+        }
+    }
+
+    /// Ensures nothing in the group awaits an argument, as it is about to close at `offset`.
+    fn check_complete(&self, offset: usize) -> Result<(), String> {
+        if let Some((command, _)) = self.command {
+            return Err(at(offset, &format!("missing argument to `\\{}`", command)));
+        }
+        if let Some(script) = self.script {
+            return Err(at(offset, &format!("missing argument to `{}`", script)));
+        }
+        Ok(())
+    }
+
+    /// Starts a new atom in the group.
+    fn new_atom(&mut self) {
+        self.atom_scripts.clear();
+        self.optional_allowed = false;
+    }
+}
+
+/// Checks that LaTeX [emitted](super::Emit::emit_wrapped_latex) by slide is well-formed, returning
+/// a description of the first problem found otherwise.
+///
+/// Math is expected between `$`s or in a [`gathered`] environment, and may be separated by plain
+/// text. The checker knows only the commands and environments slide emits.
+///
+/// [`gathered`]: https://ctan.org/pkg/amsmath
+pub fn check_latex(latex: &str) -> Result<(), String> {
+    let tokens = tokenize(latex)?;
+    let mut stack: Vec<Group> = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let (offset, token) = tokens[i];
+        i += 1;
+
+        let group = match stack.last_mut() {
+            Some(group) => group,
+            // Text mode: only plain text, inline math, and display math environments.
+            None => match token {
+                Token::Math => {
+                    stack.push(Group::new(GroupKind::Math, Role::Plain, offset));
+                    continue;
+                }
+                Token::Command("begin") => {
+                    let env = environment(&tokens, &mut i, offset)?;
+                    stack.push(Group::new(GroupKind::Env(env), Role::Plain, offset));
+                    continue;
+                }
+                Token::Char(c) if c.is_ascii() && !SPECIAL_CHARS.contains(c) => continue,
+                Token::Char(c) => return Err(unexpected_char(offset, c)),
+                _ => return Err(at(offset, "expected math mode")),
+            },
+        };
+
+        // The argument of a script is a group or a single token.
+        if let Some(script) = group.script.take() {
+            match token {
+                Token::Open => {
+                    stack.push(Group::new(GroupKind::Brace, Role::Script(script), offset));
+                }
+                Token::Char(c) if c.is_ascii_alphanumeric() => group.atom_scripts.push(script),
+                Token::Command(command) if arity(command) == Some(0) => {
+                    group.atom_scripts.push(script)
+                }
+                _ => return Err(at(offset, &format!("missing argument to `{}`", script))),
+            }
+            continue;
+        }
+
+        // Commands expecting arguments take them as braced groups.
+        if let Some((command, args)) = group.command {
+            match token {
+                Token::Open => {
+                    group.command = if args > 1 {
+                        Some((command, args - 1))
+                    } else {
+                        None
+                    };
+                    stack.push(Group::new(GroupKind::Brace, Role::Arg, offset));
+                }
+                Token::Char('[') if group.optional_allowed => {
+                    group.optional_allowed = false;
+                    stack.push(Group::new(GroupKind::Optional, Role::Arg, offset));
+                }
+                _ => return Err(at(offset, &format!("missing argument to `\\{}`", command))),
+            }
+            continue;
+        }
+
+        match token {
+            Token::Math => {
+                if group.kind != GroupKind::Math {
+                    return Err(at(offset, &format!("`$` closes {}", describe(group))));
+                }
+                close(&mut stack, offset)?;
+            }
+            Token::Open => {
+                group.new_atom();
+                stack.push(Group::new(GroupKind::Brace, Role::Plain, offset));
+            }
+            Token::Close => {
+                if group.kind != GroupKind::Brace {
+                    return Err(at(offset, &format!("`}}` closes {}", describe(group))));
+                }
+                close(&mut stack, offset)?;
+            }
+            Token::Char(']') if group.kind == GroupKind::Optional => close(&mut stack, offset)?,
+            Token::Script(script) => {
+                if group.atom_scripts.contains(&script) {
+                    let kind = if script == '^' {
+                        "superscript"
+                    } else {
+                        "subscript"
+                    };
+                    return Err(at(offset, &format!("double {}", kind)));
+                }
+                group.script = Some(script);
+            }
+            Token::Command("left") => {
+                delimiter(&tokens, &mut i, offset)?;
+                group.new_atom();
+                stack.push(Group::new(GroupKind::Left, Role::Plain, offset));
+            }
+            Token::Command("right") => {
+                if group.kind != GroupKind::Left {
+                    return Err(at(offset, &format!("`\\right` closes {}", describe(group))));
+                }
+                delimiter(&tokens, &mut i, offset)?;
+                close(&mut stack, offset)?;
+            }
+            Token::Command("begin") => {
+                let env = environment(&tokens, &mut i, offset)?;
+                group.new_atom();
+                stack.push(Group::new(GroupKind::Env(env), Role::Plain, offset));
+            }
+            Token::Command("end") => {
+                let env = environment(&tokens, &mut i, offset)?;
+                if group.kind != GroupKind::Env(env) {
+                    let msg = format!("`\\end{{{}}}` closes {}", env, describe(group));
+                    return Err(at(offset, &msg));
+                }
+                close(&mut stack, offset)?;
+            }
+            Token::Command("\\") => {
+                if !matches!(group.kind, GroupKind::Env(_)) {
+                    return Err(at(offset, "line break outside of an environment"));
+                }
+                group.new_atom();
+            }
+            Token::Command(command) => {
+                let args = match arity(command) {
+                    Some(args) => args,
+                    None => return Err(at(offset, &format!("unknown command `\\{}`", command))),
+                };
+                group.new_atom();
+                if args > 0 {
+                    group.command = Some((command, args));
+                    group.optional_allowed = command == "sqrt";
+                }
+            }
+            Token::Char(c) if c.is_ascii() && !SPECIAL_CHARS.contains(c) => group.new_atom(),
+            Token::Char(c) => return Err(unexpected_char(offset, c)),
+        }
+    }
+
+    match stack.last() {
+        Some(group) => Err(at(group.start, &format!("unclosed {}", describe(group)))),
+        None => Ok(()),
+    }
+}
+
+/// Closes the innermost group at `offset`, completing the argument, script, or atom it is of its
+/// parent.
+fn close(stack: &mut Vec<Group>, offset: usize) -> Result<(), String> {
+    let group = stack.pop().unwrap();
+    group.check_complete(offset)?;
+    if let Some(parent) = stack.last_mut() {
+        match group.role {
+            Role::Script(script) => parent.atom_scripts.push(script),
+            Role::Arg => {}
+            Role::Plain => parent.atom_scripts.clear(),
+        }
+    }
+    Ok(())
+}
+
+/// Consumes the delimiter following a `\left` or `\right` at `offset`.
+fn delimiter(tokens: &[(usize, Token)], i: &mut usize, offset: usize) -> Result<(), String> {
+    let is_delimiter = match tokens.get(*i) {
+        Some((_, Token::Char(c))) => "()[].|/".contains(*c),
+        Some((_, Token::Command(command))) => ["{", "}", "|"].contains(command),
+        _ => false,
+    };
+    if !is_delimiter {
+        return Err(at(offset, "missing delimiter"));
+    }
+    *i += 1;
+    Ok(())
+}
+
+/// Consumes the `{name}` of the environment following a `\begin` or `\end` at `offset`, returning
+/// the name.
+fn environment<'a>(
+    tokens: &[(usize, Token<'a>)],
+    i: &mut usize,
+    offset: usize,
+) -> Result<&'a str, String> {
+    let env = match tokens.get(*i..*i + 3) {
+        Some([(_, Token::Open), (_, Token::Char(_)), _]) => {
+            let name: String = tokens[*i + 1..]
+                .iter()
+                .take_while(|(_, token)| matches!(token, Token::Char(c) if c.is_ascii_alphabetic()))
+                .map(|(_, token)| match token {
+                    Token::Char(c) => *c,
+                    _ => unreachable!(),
+                })
+                .collect();
+            *i += 1 + name.len();
+            ENVIRONMENTS.iter().find(|env| **env == name)
+        }
+        _ => None,
+    };
+    match (env, tokens.get(*i)) {
+        (Some(env), Some((_, Token::Close))) => {
+            *i += 1;
+            Ok(env)
+        }
+        _ => Err(at(offset, "unknown environment")),
+    }
+}
+
+/// Returns the number of mandatory arguments of a command, if it is known.
+fn arity(command: &str) -> Option<usize> {
+    if command.len() == 1 && CONTROL_SYMBOLS.contains(command) {
+        return Some(0);
+    }
+    COMMANDS
+        .iter()
+        .find(|(name, _)| *name == command)
+        .map(|(_, args)| *args)
+}
+
+/// Describes a group for an error message.
+fn describe(group: &Group) -> String {
+    match group.kind {
+        GroupKind::Math => "`$`".to_owned(),
+        GroupKind::Brace => "`{`".to_owned(),
+        GroupKind::Left => "`\\left`".to_owned(),
+        GroupKind::Env(env) => format!("`\\begin{{{}}}`", env),
+        GroupKind::Optional => "`[`".to_owned(),
+    }
+}
+
+fn unexpected_char(offset: usize, c: char) -> String {
+    if c.is_ascii() {
+        at(offset, &format!("unescaped `{}`", c))
+    } else {
+        at(offset, &format!("non-ASCII character `{}`", c))
+    }
+}
+
+/// Locates an error message at a byte offset.
+fn at(offset: usize, msg: &str) -> String {
+    format!("{} at byte {}", msg, offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_latex;
+
+    #[test]
+    fn well_formed() {
+        for latex in &[
+            "$1 + 2$",
+            "$\\frac{1}{x^{2}}$",
+            "$\\sqrt[3]{x} + \\sqrt{\\left(x + 1\\right)}$",
+            "$\\left[1, 2\\right] \\bmod 3$",
+            "$\\sum_{i=1}^{n} i$",
+            "$\\operatorname{f}\\left(x\\right)$",
+            "$x^{2}_{1}$",
+            "$\\#a \\to 1$",
+            "$\\$a + \\_b$",
+            "$1$ -> $2$",
+            "\\begin{gathered}\na = 1\\\\\n\\\\\nb = 2\n\\end{gathered}",
+        ] {
+            assert_eq!(check_latex(latex), Ok(()), "{}", latex);
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        for (latex, err) in &[
+            ("\\frac{1}{2}", "expected math mode at byte 0"),
+            ("#a -> $1$", "unescaped `#` at byte 0"),
+            ("$1 + 2", "unclosed `$` at byte 0"),
+            ("$#a$", "unescaped `#` at byte 1"),
+            ("$x % 2$", "unescaped `%` at byte 3"),
+            ("$x^{2$", "`$` closes `{` at byte 5"),
+            ("$x}$", "`}` closes `$` at byte 2"),
+            ("$\\left(x$", "`$` closes `\\left` at byte 8"),
+            ("$\\left(x}$", "`}` closes `\\left` at byte 8"),
+            ("$\\left x\\right)$", "missing delimiter at byte 1"),
+            ("$x\\right)$", "`\\right` closes `$` at byte 2"),
+            ("$\\frac{1}$", "missing argument to `\\frac` at byte 9"),
+            ("$\\frac{1}2$", "missing argument to `\\frac` at byte 9"),
+            ("$x^$", "missing argument to `^` at byte 3"),
+            ("$x^{2}^{3}$", "double superscript at byte 6"),
+            ("$x_1_2$", "double subscript at byte 4"),
+            ("$\\alpha$", "unknown command `\\alpha` at byte 1"),
+            (
+                "$a \\\\ b$",
+                "line break outside of an environment at byte 3",
+            ),
+            ("$x^{\\left(1}\\right)$", "`}` closes `\\left` at byte 11"),
+            (
+                "$\\begin{align}x\\end{align}$",
+                "unknown environment at byte 1",
+            ),
+            (
+                "\\begin{gathered}x",
+                "unclosed `\\begin{gathered}` at byte 0",
+            ),
+            ("$x$ \\", "unterminated command `\\` at byte 4"),
+            ("$αβ$", "non-ASCII character `α` at byte 1"),
+        ] {
+            assert_eq!(check_latex(latex), Err(err.to_string()), "{}", latex);
+        }
+    }
+}
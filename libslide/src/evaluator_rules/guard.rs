@@ -0,0 +1,233 @@
+use super::pattern_match::PatternMatch;
+use crate::grammar::*;
+
+use core::fmt;
+use std::error::Error;
+
+/// A side condition on the constant patterns of a [`PatternMap`](super::rule::PatternMap), written
+/// after `where` in the rule's string form. A rule with a guard is only applied when the constants
+/// bound to its patterns satisfy all of the guard's conditions.
+///
+/// A guard is of the form
+///
+/// ```text
+/// <cond> [and <cond>]*
+/// ```
+///
+/// where each `<cond>` is one of
+///
+/// | condition           | holds when `#a` is    |
+/// |:------------------- |:--------------------- |
+/// | `#a <op> <number>`  | compared by `<op>`, one of `==`, `!=`, `<`, `<=`, `>`, `>=` |
+/// | `#a is nonzero`     | not zero              |
+/// | `#a is integer`     | an integer            |
+#[derive(Clone, Debug, PartialEq)]
+pub struct Guard {
+    conditions: Vec<Condition>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Condition {
+    Compare {
+        pat: String,
+        op: Comparison,
+        rhs: f64,
+    },
+    Nonzero(String),
+    Integer(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn from_str(op: &str) -> Option<Self> {
+        Some(match op {
+            "==" => Self::Eq,
+            "!=" => Self::Ne,
+            "<" => Self::Lt,
+            "<=" => Self::Le,
+            ">" => Self::Gt,
+            ">=" => Self::Ge,
+            _ => return None,
+        })
+    }
+
+    fn holds(self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Self::Eq => (lhs - rhs).abs() < std::f64::EPSILON,
+            Self::Ne => (lhs - rhs).abs() >= std::f64::EPSILON,
+            Self::Lt => lhs < rhs,
+            Self::Le => lhs <= rhs,
+            Self::Gt => lhs > rhs,
+            Self::Ge => lhs >= rhs,
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        })
+    }
+}
+
+impl Condition {
+    fn parse(cond: &str) -> Result<Self, InvalidGuard> {
+        let invalid = || InvalidGuard::Syntax(cond.to_owned());
+        let words: Vec<_> = cond.split_whitespace().collect();
+        let (pat, rest) = match words.as_slice() {
+            [pat, rest @ ..] if pat.starts_with('#') && pat.len() > 1 => (pat.to_string(), rest),
+            _ => return Err(invalid()),
+        };
+        match rest {
+            ["is", "nonzero"] => Ok(Self::Nonzero(pat)),
+            ["is", "integer"] => Ok(Self::Integer(pat)),
+            [op, rhs] => {
+                let op = Comparison::from_str(op).ok_or_else(invalid)?;
+                let rhs = rhs.parse().map_err(|_| invalid())?;
+                Ok(Self::Compare { pat, op, rhs })
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    fn pat(&self) -> &str {
+        match self {
+            Self::Compare { pat, .. } | Self::Nonzero(pat) | Self::Integer(pat) => pat,
+        }
+    }
+
+    fn holds(&self, konst: f64) -> bool {
+        match self {
+            Self::Compare { op, rhs, .. } => op.holds(konst, *rhs),
+            Self::Nonzero(_) => konst != 0.,
+            Self::Integer(_) => konst.fract() == 0.,
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Compare { pat, op, rhs } => write!(f, "{} {} {}", pat, op, rhs),
+            Self::Nonzero(pat) => write!(f, "{} is nonzero", pat),
+            Self::Integer(pat) => write!(f, "{} is integer", pat),
+        }
+    }
+}
+
+impl Guard {
+    /// Parses the string form of a guard, the part of a rule after `where`.
+    pub fn from_str(guard: &str) -> Result<Self, InvalidGuard> {
+        let conditions = guard
+            .split(" and ")
+            .map(Condition::parse)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { conditions })
+    }
+
+    /// The constant patterns the guard's conditions are on, like `#a`.
+    pub fn pats(&self) -> impl Iterator<Item = &str> {
+        self.conditions.iter().map(Condition::pat)
+    }
+
+    /// Checks whether the constants bound to the guard's patterns in `repls` satisfy all of the
+    /// guard's conditions. A condition on a pattern not bound to a constant does not hold.
+    pub fn holds(&self, repls: &PatternMatch<RcExpr>) -> bool {
+        self.holds_for(|pat| {
+            repls
+                .get(&ExprPat::ConstPat(pat.to_owned()))
+                .and_then(|konst| konst.get_const())
+        })
+    }
+
+    /// Like [`holds`](Self::holds), but looks up the constant bound to a pattern with `konst_of`.
+    pub(crate) fn holds_for(&self, konst_of: impl Fn(&str) -> Option<f64>) -> bool {
+        self.conditions
+            .iter()
+            .all(|cond| konst_of(cond.pat()).map_or(false, |konst| cond.holds(konst)))
+    }
+}
+
+impl fmt::Display for Guard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let conditions = self
+            .conditions
+            .iter()
+            .map(|cond| cond.to_string())
+            .collect::<Vec<_>>();
+        f.write_str(&conditions.join(" and "))
+    }
+}
+
+/// An error in the guard of a rule.
+#[derive(Debug, PartialEq)]
+pub enum InvalidGuard {
+    /// A condition that is not of any known form.
+    Syntax(String),
+    /// A condition on a pattern that is not a constant pattern of the rule's source.
+    UnboundPattern(String),
+}
+
+impl fmt::Display for InvalidGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(cond) => write!(f, r#"Could not parse guard condition "{}""#, cond),
+            Self::UnboundPattern(pat) => write!(
+                f,
+                r#"Guard condition on "{}" must be on a constant pattern of the rule's source"#,
+                pat
+            ),
+        }
+    }
+}
+
+impl Error for InvalidGuard {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        for guard in &[
+            "#b != 0",
+            "#a >= -1.5",
+            "#a is nonzero",
+            "#a is integer and #b < 10",
+        ] {
+            assert_eq!(Guard::from_str(guard).unwrap().to_string(), *guard);
+        }
+    }
+
+    #[test]
+    fn parse_error() {
+        for (guard, cond) in &[
+            ("$b != 0", "$b != 0"),
+            ("#b ~ 0", "#b ~ 0"),
+            ("#b != zero", "#b != zero"),
+            ("#a is positive", "#a is positive"),
+            ("#a is integer and", "#a is integer and"),
+            ("#a is integer and #b", "#b"),
+        ] {
+            assert_eq!(
+                Guard::from_str(guard).unwrap_err(),
+                InvalidGuard::Syntax(cond.to_string())
+            );
+        }
+    }
+}
@@ -3,12 +3,18 @@
 //! [Grammar]: crate::Grammar
 //! [partial_evaluator]: crate::partial_evaluator
 
+mod guard;
 mod pattern_match;
 mod registry;
 mod rule;
+#[cfg(test)]
+mod soundness;
 mod unbuilt_rule;
 
+pub use pattern_match::match_pattern;
 pub use registry::BuildRuleErrors;
 pub use registry::RuleName;
 pub use registry::RuleSet;
+pub use registry::RuleSetPreset;
+pub(crate) use registry::MAX_FACTORIAL_OPERAND;
 pub use rule::Rule;
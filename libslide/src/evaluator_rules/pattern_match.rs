@@ -1,5 +1,7 @@
+use crate::grammar::visit::ExprPatVisitor;
 use crate::grammar::*;
-use crate::utils::hash;
+use crate::utils::{get_flattened_binary_args, hash, unflatten_binary_expr, UnflattenStrategy};
+use crate::Span;
 
 use std::collections::HashMap;
 
@@ -7,21 +9,64 @@ use std::collections::HashMap;
 ///
 /// The rhs of a rule may be transfomed with an instance of `PatternMatch` to obtain the result of a
 /// rule applied on a target expression.
+#[derive(Clone)]
 pub struct PatternMatch<E: RcExpression> {
     map: HashMap<
         u64, // pointer to rule pattern, like #a
         E,   // target expr,             like 10
     >,
+    /// Replacements for [rest patterns](is_rest_pat), like `_a...`.
+    rests: HashMap<
+        u64,                      // pointer to rest pattern, like _a...
+        (BinaryOperator, Vec<E>), // target operands and their operator, like + and [x, y]
+    >,
 }
 
 impl<E: RcExpression> Default for PatternMatch<E> {
     fn default() -> Self {
         Self {
             map: HashMap::new(),
+            rests: HashMap::new(),
         }
     }
 }
 
+/// Whether a pattern is a rest pattern, like `_a...`. A rest pattern is an any pattern suffixed by
+/// `...` that matches the remaining operands of a `+` or `*` chain it is an operand of.
+pub fn is_rest_pat(pat: &ExprPat) -> bool {
+    matches!(pat, ExprPat::AnyPat(name) if name.ends_with("..."))
+}
+
+/// If `pat` is a `+` or `*` chain with a [rest pattern](is_rest_pat) operand, like `_a... + 0`,
+/// returns the chain's operator and flattened operands.
+pub fn get_nary_operands(pat: &RcExprPat) -> Option<(BinaryOperator, Vec<RcExprPat>)> {
+    fn flatten(pat: &RcExprPat, op: BinaryOperator, operands: &mut Vec<RcExprPat>) {
+        match pat.as_ref() {
+            ExprPat::BinaryExpr(child) if child.op == op => {
+                flatten(&child.lhs, op, operands);
+                flatten(&child.rhs, op, operands);
+            }
+            _ => operands.push(pat.clone()),
+        }
+    }
+
+    let op = match pat.as_ref() {
+        ExprPat::BinaryExpr(BinaryExpr { op, .. })
+            if *op == BinaryOperator::Plus || *op == BinaryOperator::Mult =>
+        {
+            *op
+        }
+        _ => return None,
+    };
+    let mut operands = Vec::new();
+    flatten(pat, op, &mut operands);
+    if operands.iter().any(|operand| is_rest_pat(operand)) {
+        Some((op, operands))
+    } else {
+        None
+    }
+}
+
 pub trait MatchRule<E: RcExpression> {
     /// Pattern matches a rule template against an expression. If successful, the results of the
     /// matching are returned as a mapping of rule to target expressions replacements.
@@ -33,6 +78,10 @@ pub trait MatchRule<E: RcExpression> {
 
 impl MatchRule<RcExpr> for PatternMatch<RcExpr> {
     fn match_rule(rule: RcExprPat, target: RcExpr) -> Option<PatternMatch<RcExpr>> {
+        if let Some((op, operands)) = get_nary_operands(&rule) {
+            return Self::match_nary(op, &operands, target);
+        }
+
         match (rule.as_ref(), target.as_ref()) {
             // The happiest path -- if a pattern matches an expression, return replacements for it!
             (ExprPat::VarPat(_), Expr::Var(_))
@@ -120,6 +169,84 @@ impl MatchRule<RcExprPat> for PatternMatch<RcExprPat> {
     }
 }
 
+impl PatternMatch<RcExpr> {
+    /// Matches the flattened operands of an n-ary pattern, like `_a... + 0`, against the flattened
+    /// operands of a `op` chain in the target. Each operand pattern other than the rest pattern
+    /// must match a distinct operand of the target, in any order; the rest pattern matches the
+    /// remaining operands, of which there must be at least one.
+    fn match_nary(op: BinaryOperator, operands: &[RcExprPat], target: RcExpr) -> Option<Self> {
+        let (rests, pats): (Vec<_>, Vec<_>) =
+            operands.iter().partition(|operand| is_rest_pat(operand));
+        let rest = match rests.as_slice() {
+            [rest] => *rest,
+            // Which operands multiple rest patterns in one chain match is ambiguous.
+            _ => return None,
+        };
+        let args = match target.as_ref() {
+            Expr::BinaryExpr(BinaryExpr { op: target_op, .. }) if *target_op == op => {
+                get_flattened_binary_args(target, op)
+            }
+            _ => return None,
+        };
+        if args.len() <= pats.len() {
+            return None;
+        }
+
+        let mut used = vec![false; args.len()];
+        let mut repls = Self::match_operands(&pats, &args, &mut used, Self::default())?;
+        let rest_args = args
+            .into_iter()
+            .zip(used)
+            .filter(|(_, used)| !used)
+            .map(|(arg, _)| arg)
+            .collect();
+        repls.rests.insert(hash(rest.as_ref()), (op, rest_args));
+        Some(repls)
+    }
+
+    /// Matches each of `pats` against a distinct operand of `args` not already `used`,
+    /// backtracking until all patterns match with replacements consistent with `repls`.
+    fn match_operands(
+        pats: &[&RcExprPat],
+        args: &[RcExpr],
+        used: &mut [bool],
+        repls: Self,
+    ) -> Option<Self> {
+        let (pat, pats) = match pats.split_first() {
+            Some(split) => split,
+            None => return Some(repls),
+        };
+        for (i, arg) in args.iter().enumerate() {
+            if used[i] {
+                continue;
+            }
+            let merged = Self::match_rule((*pat).clone(), arg.clone())
+                .and_then(|matched| Self::try_merge(repls.clone(), matched));
+            if let Some(merged) = merged {
+                used[i] = true;
+                if let Some(repls) = Self::match_operands(pats, args, used, merged) {
+                    return Some(repls);
+                }
+                used[i] = false;
+            }
+        }
+        None
+    }
+
+    /// Like [`transform`](Transformer::transform), but the result, and expressions instantiated
+    /// from the pattern itself (rather than replaced by a matched target expression), are given
+    /// `span`.
+    ///
+    /// When a rule is applied on a target expression, the spans of the rule's RHS pattern point
+    /// into the rule's definition rather than the source program. Transforming the RHS at the span
+    /// of the target expression keeps the result pointing to the source it was derived from. This
+    /// holds even when the RHS is a lone pattern, as in `_a + 0 -> _a`, where the result covers
+    /// the whole `_a + 0` it rewrote rather than only `_a`.
+    pub fn transform_at(&self, item: RcExprPat, span: Span) -> RcExpr {
+        transform_pat(self, item, Some(span), &mut HashMap::new()).with_span(span)
+    }
+}
+
 impl Transformer<RcExprPat, RcExpr> for PatternMatch<RcExpr> {
     /// Transforms a pattern expression into an expression by replacing patterns with target
     /// expressions known by the [`PatternMatch`].
@@ -129,66 +256,69 @@ impl Transformer<RcExprPat, RcExpr> for PatternMatch<RcExpr> {
     ///
     /// [`PatternMatch`]: PatternMatch
     fn transform(&self, item: RcExprPat) -> RcExpr {
-        fn transform(
-            repls: &PatternMatch<RcExpr>,
-            item: RcExprPat,
-            cache: &mut HashMap<u64, RcExpr>,
-        ) -> RcExpr {
-            if let Some(result) = cache.get(&hash(item.as_ref())) {
-                return result.clone();
-            }
+        // Expr pointer -> transformed expression. Assumes that transient expressions of the same
+        // value are reference counters pointing to the same underlying expression. This is done
+        // via common subexpression elimination during parsing.
+        let mut cache = HashMap::new();
+        transform_pat(self, item, None, &mut cache)
+    }
+}
 
-            let og_span = item.span;
-            let transformed: RcExpr = match item.as_ref() {
-                ExprPat::VarPat(_) | ExprPat::ConstPat(_) | ExprPat::AnyPat(_) => {
-                    match repls.map.get(&hash(item.as_ref())) {
-                        Some(transformed) => transformed.clone(),
+/// Transforms a pattern expression into an expression with the replacements in `repls`. If `span`
+/// is given, it is used as the span of all expressions instantiated from the pattern.
+fn transform_pat(
+    repls: &PatternMatch<RcExpr>,
+    item: RcExprPat,
+    span: Option<Span>,
+    cache: &mut HashMap<u64, RcExpr>,
+) -> RcExpr {
+    if let Some(result) = cache.get(&hash(item.as_ref())) {
+        return result.clone();
+    }
 
-                        // A pattern can only be transformed into an expression if it has an
-                        // expression replacement. Patterns are be validated before transformation,
-                        // so this branch should never be hit.
-                        None => unreachable!(),
-                    }
-                }
+    let og_span = span.unwrap_or(item.span);
+    let transformed: RcExpr = match item.as_ref() {
+        ExprPat::VarPat(_) | ExprPat::ConstPat(_) | ExprPat::AnyPat(_) => {
+            match repls.get_rolled(&item) {
+                Some(transformed) => transformed,
 
-                ExprPat::Const(f) => rc_expr!(Expr::Const(*f), og_span),
-                ExprPat::BinaryExpr(binary_expr) => rc_expr!(
-                    Expr::BinaryExpr(BinaryExpr {
-                        op: binary_expr.op,
-                        lhs: transform(repls, binary_expr.lhs.clone(), cache),
-                        rhs: transform(repls, binary_expr.rhs.clone(), cache),
-                    }),
-                    og_span
-                ),
-                ExprPat::UnaryExpr(unary_expr) => rc_expr!(
-                    Expr::UnaryExpr(UnaryExpr {
-                        op: unary_expr.op,
-                        rhs: transform(repls, unary_expr.rhs.clone(), cache),
-                    }),
-                    og_span
-                ),
-                ExprPat::Parend(expr) => {
-                    let inner = transform(repls, expr.clone(), cache);
-                    rc_expr!(Expr::Parend(inner), og_span)
-                }
-                ExprPat::Bracketed(expr) => {
-                    let inner = transform(repls, expr.clone(), cache);
-                    rc_expr!(Expr::Bracketed(inner), og_span)
-                }
-            };
+                // A pattern can only be transformed into an expression if it has an
+                // expression replacement. Patterns are be validated before transformation,
+                // so this branch should never be hit.
+                None => unreachable!(),
+            }
+        }
 
-            let result = cache
-                .entry(hash(item.as_ref()))
-                .or_insert_with(|| transformed);
-            result.clone()
+        ExprPat::Const(f) => rc_expr!(Expr::Const(*f), og_span),
+        ExprPat::BinaryExpr(binary_expr) => rc_expr!(
+            Expr::BinaryExpr(BinaryExpr {
+                op: binary_expr.op,
+                lhs: transform_pat(repls, binary_expr.lhs.clone(), span, cache),
+                rhs: transform_pat(repls, binary_expr.rhs.clone(), span, cache),
+            }),
+            og_span
+        ),
+        ExprPat::UnaryExpr(unary_expr) => rc_expr!(
+            Expr::UnaryExpr(UnaryExpr {
+                op: unary_expr.op,
+                rhs: transform_pat(repls, unary_expr.rhs.clone(), span, cache),
+            }),
+            og_span
+        ),
+        ExprPat::Parend(expr) => {
+            let inner = transform_pat(repls, expr.clone(), span, cache);
+            rc_expr!(Expr::Parend(inner), og_span)
+        }
+        ExprPat::Bracketed(expr) => {
+            let inner = transform_pat(repls, expr.clone(), span, cache);
+            rc_expr!(Expr::Bracketed(inner), og_span)
         }
+    };
 
-        // Expr pointer -> transformed expression. Assumes that transient expressions of the same
-        // value are reference counters pointing to the same underlying expression. This is done
-        // via common subexpression elimination during parsing.
-        let mut cache = HashMap::new();
-        transform(self, item, &mut cache)
-    }
+    let result = cache
+        .entry(hash(item.as_ref()))
+        .or_insert_with(|| transformed);
+    result.clone()
 }
 
 impl Transformer<RcExprPat, RcExprPat> for PatternMatch<RcExprPat> {
@@ -205,8 +335,8 @@ impl Transformer<RcExprPat, RcExprPat> for PatternMatch<RcExprPat> {
             let og_span = item.span;
             let transformed: RcExprPat = match item.as_ref() {
                 ExprPat::VarPat(_) | ExprPat::ConstPat(_) | ExprPat::AnyPat(_) => {
-                    match repls.map.get(&hash(item.as_ref())) {
-                        Some(transformed) => transformed.clone(),
+                    match repls.get_rolled(&item) {
+                        Some(transformed) => transformed,
                         None => unreachable!(),
                     }
                 }
@@ -251,11 +381,57 @@ impl Transformer<RcExprPat, RcExprPat> for PatternMatch<RcExprPat> {
     }
 }
 
+/// Pattern matches an expression pattern against an expression. If the match is successful,
+/// returns the expression bound to each pattern of the expression pattern, like `$a` -> `x`, in the
+/// order the patterns first appear in the expression pattern.
+pub fn match_pattern(pat: RcExprPat, expr: RcExpr) -> Option<Vec<(String, RcExpr)>> {
+    let repls = PatternMatch::match_rule(pat.clone(), expr)?;
+
+    let mut collector = PatternCollector::default();
+    collector.visit_expr_pat(&pat);
+    let bindings = collector
+        .pats
+        .into_iter()
+        .map(|pat| {
+            let bound = repls.get_rolled(&pat).unwrap();
+            (pat.to_string(), bound)
+        })
+        .collect();
+    Some(bindings)
+}
+
+/// Collects the distinct patterns in an expression pattern, in the order they first appear.
+#[derive(Default)]
+struct PatternCollector {
+    pats: Vec<RcExprPat>,
+}
+impl<'a> ExprPatVisitor<'a> for PatternCollector {
+    fn visit_expr_pat(&mut self, expr_pat: &'a RcExprPat) {
+        match expr_pat.as_ref() {
+            ExprPat::VarPat(_) | ExprPat::ConstPat(_) | ExprPat::AnyPat(_) => {
+                if !self.pats.iter().any(|pat| pat == expr_pat) {
+                    self.pats.push(expr_pat.clone());
+                }
+            }
+            _ => visit::descend_expr_pat(self, expr_pat),
+        }
+    }
+}
+
 impl<E: RcExpression + Eq> PatternMatch<E> {
     /// Merges two `PatternMatch`. If the `PatternMatch` are of incompatible state (i.e. contain
     /// different mappings), merging fails and nothing is returned.
     fn try_merge(left: PatternMatch<E>, right: PatternMatch<E>) -> Option<PatternMatch<E>> {
         let mut replacements = left;
+        for (from, to_r) in right.rests.into_iter() {
+            match replacements.rests.get(&from) {
+                Some(to_l) if *to_l != to_r => return None,
+                Some(_) => {}
+                None => {
+                    replacements.rests.insert(from, to_r);
+                }
+            }
+        }
         for (from, to_r) in right.map.into_iter() {
             if let Some(to_l) = replacements.map.get(&from) {
                 if to_r != *to_l {
@@ -270,6 +446,22 @@ impl<E: RcExpression + Eq> PatternMatch<E> {
         Some(replacements)
     }
 
+    /// Returns the expression matched by a pattern, like `#a`.
+    pub fn get(&self, pat: &ExprPat) -> Option<&E> {
+        self.map.get(&hash(pat))
+    }
+
+    /// Like [`get`](Self::get), but the operands matched by a [rest pattern](is_rest_pat) are
+    /// rolled back up into a chain of their operator.
+    fn get_rolled(&self, pat: &RcExprPat) -> Option<E> {
+        let pat = hash(pat.as_ref());
+        self.map.get(&pat).cloned().or_else(|| {
+            self.rests
+                .get(&pat)
+                .map(|(op, args)| unflatten_binary_expr(args, *op, UnflattenStrategy::Left))
+        })
+    }
+
     fn insert(&mut self, k: &RcExprPat, v: E) -> Option<E> {
         self.map.insert(hash(k.as_ref()), v)
     }
@@ -400,4 +592,71 @@ mod tests {
             bracketed_on_parend:           "[$a + #b]" => "(x + 0)" => None
         }
     }
+
+    mod match_pattern {
+        use super::*;
+
+        fn match_pattern(rule: &str, target: &str) -> Option<Vec<String>> {
+            let bindings = super::match_pattern(parse_rule(rule), parse_expr!(target))?;
+            Some(
+                bindings
+                    .into_iter()
+                    .map(|(pat, expr)| format!("{} -> {}", pat, expr))
+                    .collect(),
+            )
+        }
+
+        #[test]
+        fn bindings_in_order_of_appearance() {
+            assert_eq!(
+                match_pattern("_c * $a + #b + _c", "(1 + 2) * x + 0 + (1 + 2)"),
+                Some(vec![
+                    "_c -> (1 + 2)".to_owned(),
+                    "$a -> x".to_owned(),
+                    "#b -> 0".to_owned()
+                ])
+            );
+        }
+
+        #[test]
+        fn no_patterns() {
+            assert_eq!(match_pattern("1 + 2", "1 + 2"), Some(vec![]));
+        }
+
+        #[test]
+        fn no_match() {
+            assert_eq!(match_pattern("$a + #b", "x + y"), None);
+        }
+
+        macro_rules! match_nary_tests {
+            ($($name:ident: $rule:expr => $target:expr => $expected_bindings:expr)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let expected: Option<Vec<&str>> = $expected_bindings;
+                    assert_eq!(
+                        match_pattern($rule, $target),
+                        expected.map(|b| b.into_iter().map(String::from).collect())
+                    );
+                }
+            )*
+            }
+        }
+
+        match_nary_tests! {
+            nary_rest_on_chain:     "_a... + 0" => "x + 0 + y" => Some(vec!["_a... -> x + y"])
+            nary_any_order:         "_a... + 0" => "0 + x"     => Some(vec!["_a... -> x"])
+            nary_mult:              "_a... * 1" => "x * 1 * y" => Some(vec!["_a... -> x * y"])
+            nary_wrong_op:          "_a... + 0" => "x * 0"     => None
+            nary_no_rest_operands:  "_a... + 0" => "0"         => None
+            nary_unmatched_operand: "_a... + 0" => "x + 1"     => None
+            nary_nested:            "1 + _a... * 2" => "1 + x * y * 2" => Some(vec!["_a... -> x * y"])
+            nary_consistent_bindings:
+                "_a... + $b * 2 + $b" => "y + x * 2 + z + x"
+                => Some(vec!["_a... -> y + z", "$b -> x"])
+            nary_inconsistent_bindings:
+                "_a... + $b * 2 + $b" => "y + x * 2 + z"
+                => None
+        }
+    }
 }
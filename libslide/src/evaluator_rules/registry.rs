@@ -4,6 +4,7 @@ use super::rule::*;
 use super::unbuilt_rule::UnbuiltRule;
 use crate::utils::indent;
 use fn_rules::*;
+pub(crate) use fn_rules::MAX_FACTORIAL_OPERAND;
 
 use core::fmt;
 use std::cmp::Ordering;
@@ -13,6 +14,7 @@ use std::error::Error;
 macro_rules! define_rules {
     ($($(#[doc = $doc:expr])+ $kind:ident: $def:expr)*) => {
         #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         /// Built-in rewrite rule names.
         pub enum RuleName {
             $(
@@ -43,6 +45,13 @@ define_rules! {
     Multiply: F(multiply)
     /// Binary division.
     Divide: F(divide)
+    /// Cancels the greatest common divisor of polynomials in a division, like
+    /// `(x^2 - 1) / (x + 1) -> x - 1`, assuming the divisor is nonzero. Not applied under
+    /// [rigorous](crate::Rigor::Rigorous) evaluation.
+    CancelPolynomials: F(cancel_polynomials)
+    /// Divides polynomials into a quotient plus the remainder over the divisor, like
+    /// `(x^2 - 2x) / (x + 1) -> x - 3 + 3 / (x + 1)`.
+    DividePolynomials: F(divide_polynomials)
     /// Binary modulo.
     Modulo: F(modulo)
     /// Binary exponentiation.
@@ -51,12 +60,21 @@ define_rules! {
     Posate: F(posate)
     /// Unary negation.
     Negate: F(negate)
+    /// Factorials of non-negative integers, like `5!`, up to `170!`.
+    Factorial: F(factorial)
+    /// Builtin function calls, like `max(a, b)` or `root(3, 27)`.
+    EvaluateCall: F(evaluate_call)
+    /// Folds an expression built only of constants to its value, like `(1 + 2) * 3! -> 18`.
+    FoldConstants: F(fold_constants)
     /// The multiplicative identity `a*1=a`.
-    MultiplicateIdentity: S("_a * 1 -> _a")
+    MultiplicateIdentity: S("_a... * 1 -> _a...")
     /// The additive identity `a+0=a`.
-    AdditiveIdentity: S("_a + 0 -> _a")
+    AdditiveIdentity: S("_a... + 0 -> _a...")
     /// The additive inverse `a+(-a)=0`.
     AdditiveInverse: S("_a - _a -> 0")
+    /// The multiplicative inverse `a/a=1`, assuming `a` is nonzero. Not applied under
+    /// [rigorous](crate::Rigor::Rigorous) evaluation.
+    CancelDivision: S("_a / _a -> 1")
     /// The equivalent additive identity `a-0=a`.
     SubtractiveIdentity: S("_a - 0 -> _a")
     /// The commutative axiom with constants.
@@ -73,9 +91,10 @@ define_rules! {
         "_a * 1 / _b -> _a / _b",
         "_a * (1 / _b) -> _a / _b",
     ])
+    /// Folds the multiplication of a term by itself to a square, like `x * x -> x^2`.
+    FoldSquare: S("_a * _a -> _a^2")
     /// Exponentiation axioms.
     FoldExponents: M(&[
-        "_a * _a -> _a^2",
         "_a * _a^_b -> _a^(_b + 1)",
         "_a^_b * _a -> _a^(_b + 1)",
         "_a^_b * _a^_c -> _a^(_b + _c)",
@@ -83,8 +102,77 @@ define_rules! {
         "_a^_b / _a -> _a^(_b - 1)",
         "_a^_b / _a^_c -> _a^(_b - _c)",
     ])
-    /// Exponentiation identity `a^0=a`.
-    ExponentiativeIdentity: S("_a^0 -> _a")
+    /// Exponentiation identity `a^0=1`.
+    ExponentiativeIdentity: S("_a^0 -> 1")
+    /// Expands a square to the multiplication of a term by itself, like `x^2 -> x * x`. The
+    /// inverse of [`FoldSquare`](RuleName::FoldSquare).
+    ExpandSquare: F(expand_square)
+    /// Distributes multiplication over addition, like `a * (b + c) -> a * b + a * c`. This
+    /// conflicts with factoring, so it is not applied by default.
+    DistributeMultiplication: S("_a * (_b + _c) -> _a * _b + _a * _c")
+    /// Factors a factor common to all terms out of a sum, like `a * b + a * c -> a * (b + c)`. The
+    /// inverse of [`DistributeMultiplication`](RuleName::DistributeMultiplication), so it is not
+    /// applied by default either.
+    FactorOut: F(factor_out)
+}
+
+/// Named rules that are not in the [default rule set](RuleSet::default), and must be
+/// [inserted](RuleSet::insert) to be used.
+const OPT_IN_RULES: &[RuleName] = &[
+    RuleName::ExpandSquare,
+    RuleName::DistributeMultiplication,
+    RuleName::FactorOut,
+];
+
+/// Named pattern rules that may rewrite an expression to a costlier one, as stepping stones to
+/// simpler forms. See [`PatternMap::may_grow`](PatternMap::may_grow).
+const GROWING_RULES: &[RuleName] = &[RuleName::DistributeMultiplication];
+
+/// Named rules of the [basic](RuleSetPreset::Basic) preset.
+const BASIC_RULES: &[RuleName] = &[
+    RuleName::UnwrapExplicitParens,
+    RuleName::UnwrapExplicitBrackets,
+    RuleName::Add,
+    RuleName::Subtract,
+    RuleName::Multiply,
+    RuleName::Divide,
+    RuleName::Modulo,
+    RuleName::Exponentiate,
+    RuleName::Posate,
+    RuleName::Negate,
+    RuleName::Factorial,
+    RuleName::EvaluateCall,
+    RuleName::FoldConstants,
+    RuleName::MultiplicateIdentity,
+    RuleName::AdditiveIdentity,
+    RuleName::SubtractiveIdentity,
+    RuleName::ExponentiativeIdentity,
+];
+
+/// A named selection of the rules in a [`RuleSet`](RuleSet). See [`RuleSet::preset`](RuleSet::preset).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum RuleSetPreset {
+    /// Only rules that fold constants, like `1 + 2 -> 3`, and apply identities, like
+    /// `a * 1 -> a`.
+    ///
+    /// The preset only selects rules. Expressions are still
+    /// [flattened](crate::ProgramContext::always_flatten) before the rules apply, and flattening
+    /// also combines like terms, like `x + x + 2x -> x * 4`; turn flattening off to keep them.
+    Basic,
+    /// All rules in the rule set.
+    Full,
+}
+
+impl fmt::Display for RuleSetPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Basic => "basic",
+            Self::Full => "full",
+        }
+        .fmt(f)
+    }
 }
 
 impl PartialOrd for RuleName {
@@ -99,35 +187,94 @@ impl Ord for RuleName {
     }
 }
 
-/// Set of unbuilt rules.
+/// A set of rewrite rules to evaluate slide programs with. A rule set may contain
+/// [named built-in rules](RuleName) and custom [`Rule`](Rule)s, and is [built](RuleSet::build)
+/// into a list of rules that can be passed to [`evaluate_with_rules`](crate::evaluate_with_rules).
+///
+/// For example, to evaluate a program without constant folding:
+///
+/// ```
+/// use libslide::evaluator_rules::{RuleName, RuleSet};
+/// use libslide::{evaluate_with_rule_set, parse_statements, scan, ProgramContext};
+///
+/// let mut rule_set = RuleSet::default_rules();
+/// rule_set.remove(RuleName::Add);
+/// rule_set.remove(RuleName::FoldConstants);
+///
+/// let program = parse_statements(scan("1 + 1").tokens, "1 + 1").program;
+/// // Normalization folds constants too, so it must be disabled as well.
+/// let ctxt = ProgramContext::default().always_flatten(false);
+/// let result = evaluate_with_rule_set(program, &rule_set, &ctxt).unwrap();
+/// assert_eq!(result.simplified.to_string(), "1 + 1");
+/// ```
 pub struct RuleSet {
     rules: HashMap<RuleName, UnbuiltRule>,
-    custom_rules: Vec<UnbuiltRule>,
+    custom_rules: Vec<Rule>,
 }
 
 impl Default for RuleSet {
     /// Constructs the default rule set.
     fn default() -> Self {
         Self {
-            rules: get_all_rules(),
+            rules: get_all_rules()
+                .into_iter()
+                .filter(|(name, _)| !OPT_IN_RULES.contains(name))
+                .collect(),
             custom_rules: Vec::new(),
         }
     }
 }
 
 impl RuleSet {
+    /// Constructs a rule set with no rules.
+    pub fn empty() -> Self {
+        Self {
+            rules: HashMap::new(),
+            custom_rules: Vec::new(),
+        }
+    }
+
+    /// Constructs the default rule set, with all named rules but those that must be
+    /// [inserted](Self::insert) explicitly.
+    pub fn default_rules() -> Self {
+        Self::default()
+    }
+
+    /// Restricts the named rules of the rule set to those in a preset. Custom rules are kept, and
+    /// rules [inserted](Self::insert) afterwards are added regardless of the preset.
+    ///
+    /// ```
+    /// use libslide::evaluator_rules::{RuleName, RuleSet, RuleSetPreset};
+    ///
+    /// let rule_set = RuleSet::default().preset(RuleSetPreset::Basic);
+    /// let names: Vec<_> = rule_set.iter().filter_map(|(name, _)| name).collect();
+    /// assert!(names.contains(&RuleName::FoldConstants));
+    /// assert!(!names.contains(&RuleName::CancelPolynomials));
+    /// ```
+    pub fn preset(mut self, preset: RuleSetPreset) -> Self {
+        match preset {
+            RuleSetPreset::Basic => self.rules.retain(|name, _| BASIC_RULES.contains(name)),
+            RuleSetPreset::Full => {}
+        }
+        self
+    }
+
     /// Creates a list of `Rules`s from the unbuilt rule set.
     pub fn build(&self) -> Result<Vec<Rule>, BuildRuleErrors> {
-        // Order rules deterministically -- first order by name, then add custom rules.
-        let mut unbuilt_named_rules: Vec<(&RuleName, &UnbuiltRule)> = self.rules.iter().collect();
-        unbuilt_named_rules.sort_by(|&(a, _), &(b, _)| a.cmp(b));
-        let all_rules: Vec<_> = unbuilt_named_rules
+        Ok(self
+            .build_named()?
             .into_iter()
-            .map(|(rn, rule)| (Some(rn), rule))
-            .chain(self.custom_rules.iter().map(|r| (None, r)))
-            .collect();
+            .map(|(_, rule)| rule)
+            .collect())
+    }
 
-        let num_rules = all_rules.iter().clone().fold(0, |sum, (_, ur)| match ur {
+    /// Like [`build`](Self::build), but pairs each built rule with the name of the rule it was
+    /// built from. Custom rules have no name.
+    ///
+    /// Built rules also carry their [name](Rule::name) themselves.
+    pub fn build_named(&self) -> Result<Vec<(Option<RuleName>, Rule)>, BuildRuleErrors> {
+        let named_rules = self.sorted_named_rules();
+        let num_rules = named_rules.iter().fold(0, |sum, (_, ur)| match ur {
             // Building a string rule actually generates two versions:
             // 1. The "raw" form of the string rule
             // 2. A version of the (1) boostrapped with a set of rules, possibly including (1)
@@ -135,36 +282,53 @@ impl RuleSet {
             UnbuiltRule::S(_) => sum + 2,
             UnbuiltRule::M(v) => sum + 2 * v.len(),
             _ => sum + 1,
-        });
+        }) + 2 * self.custom_rules.len();
 
         let mut built_rules = Vec::with_capacity(num_rules);
         let mut errors: Vec<Box<dyn Error>> = Vec::new();
         let bootstrapping_rules = Self::get_bootstrapping_rules();
         let bootstrap_blacklist = Self::get_boostrap_blacklist();
-        let mut mk_str_rule =
-            |built_rules: &mut Vec<Rule>, rule_name: Option<&RuleName>, rule: &'static str| {
-                let pm = PatternMap::from_str(rule);
-                if let Err(err) = pm.validate() {
-                    errors.push(err.into());
-                    return;
-                }
+        let mut mk_pm_rule = |built_rules: &mut Vec<(Option<RuleName>, Rule)>,
+                              rule_name: Option<RuleName>,
+                              pm: PatternMap| {
+            if let Err(err) = pm.validate() {
+                errors.push(err);
+                return;
+            }
 
-                if !bootstrap_blacklist.contains(&rule_name.copied()) {
-                    let bootstrapped_pm = pm.bootstrap(&bootstrapping_rules);
-                    built_rules.push(Rule::PatternMap(bootstrapped_pm));
-                }
-                built_rules.push(Rule::PatternMap(pm));
-            };
+            if !bootstrap_blacklist.contains(&rule_name) {
+                let bootstrapped_pm = pm.bootstrap(&bootstrapping_rules);
+                let rule = Rule::PatternMap(bootstrapped_pm).named(rule_name);
+                built_rules.push((rule_name, rule));
+            }
+            built_rules.push((rule_name, Rule::PatternMap(pm).named(rule_name)));
+        };
 
-        for (rule_name, unbuilt_rule) in all_rules.into_iter() {
+        // Order rules deterministically -- first order by name, then add custom rules.
+        for (rule_name, unbuilt_rule) in named_rules.into_iter() {
+            let may_grow = GROWING_RULES.contains(&rule_name);
+            let rule_name = Some(rule_name);
             match unbuilt_rule {
-                UnbuiltRule::S(rule) => mk_str_rule(&mut built_rules, rule_name, rule),
+                UnbuiltRule::S(rule) => mk_pm_rule(
+                    &mut built_rules,
+                    rule_name,
+                    PatternMap::from_str(rule).growing(may_grow),
+                ),
                 UnbuiltRule::M(rules) => {
                     for rule in rules.iter() {
-                        mk_str_rule(&mut built_rules, rule_name, rule);
+                        let pm = PatternMap::from_str(rule).growing(may_grow);
+                        mk_pm_rule(&mut built_rules, rule_name, pm);
                     }
                 }
-                UnbuiltRule::F(f) => built_rules.push(Rule::from_fn(*f)),
+                UnbuiltRule::F(f) => {
+                    built_rules.push((rule_name, Rule::from_fn(*f).named(rule_name)))
+                }
+            }
+        }
+        for rule in self.custom_rules.iter() {
+            match rule {
+                Rule::PatternMap(pm) => mk_pm_rule(&mut built_rules, None, pm.clone()),
+                Rule::Evaluate(..) => built_rules.push((None, rule.clone().named(None))),
             }
         }
 
@@ -175,15 +339,53 @@ impl RuleSet {
         Ok(built_rules)
     }
 
+    /// Lists the rules in the rule set, in the order they are applied, as the names of named
+    /// rules paired with the display strings of their definitions. Named rules defined by multiple
+    /// string rules are listed once per string rule. Custom rules have no name.
+    pub fn iter(&self) -> impl Iterator<Item = (Option<RuleName>, String)> + '_ {
+        let named = self
+            .sorted_named_rules()
+            .into_iter()
+            .flat_map(|(name, unbuilt_rule)| {
+                match unbuilt_rule {
+                    UnbuiltRule::S(rule) => vec![rule.to_string()],
+                    UnbuiltRule::M(rules) => rules.iter().map(|rule| rule.to_string()).collect(),
+                    UnbuiltRule::F(_) => vec![format!("{:?}", name)],
+                }
+                .into_iter()
+                .map(move |rule| (Some(name), rule))
+            });
+        let custom = self
+            .custom_rules
+            .iter()
+            .map(|rule| (None, rule.to_string()));
+        named.chain(custom)
+    }
+
     /// Remove a named rule from the rule set.
-    pub fn remove(&mut self, rule: &RuleName) {
-        self.rules.remove(rule);
+    pub fn remove(&mut self, rule: RuleName) {
+        self.rules.remove(&rule);
+    }
+
+    /// Insert a named rule into the rule set.
+    pub fn insert(&mut self, rule: RuleName) {
+        if let Some(unbuilt_rule) = get_all_rules().remove(&rule) {
+            self.rules.insert(rule, unbuilt_rule);
+        }
+    }
+
+    /// Add a custom rule, like one created by [`Rule::from_pat_str`](Rule::from_pat_str) or
+    /// [`Rule::from_fn`](Rule::from_fn), to the rule set. Custom rules are applied after all named
+    /// rules, in the order they are added.
+    pub fn push(&mut self, rule: Rule) {
+        self.custom_rules.push(rule);
     }
 
-    /// Insert a custom unbuilt rule into the rule set.
-    #[allow(unused)] // Used in testing. TODO: enable
-    fn insert_custom<T: Into<UnbuiltRule>>(&mut self, rule: T) {
-        self.custom_rules.push(rule.into());
+    /// Named rules in the rule set, in the order they are applied.
+    fn sorted_named_rules(&self) -> Vec<(RuleName, &UnbuiltRule)> {
+        let mut named_rules: Vec<_> = self.rules.iter().map(|(rn, ur)| (*rn, ur)).collect();
+        named_rules.sort_by_key(|(rn, _)| *rn);
+        named_rules
     }
 
     /// Retrieves a set of rules to be used in bootstrapping other rules.
@@ -247,6 +449,15 @@ impl Error for BuildRuleErrors {}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{evaluate_with_rules, parse_stmt, ProgramContext};
+
+    fn eval_with(rule_set: &RuleSet, program: &str) -> String {
+        let rules = rule_set.build().unwrap();
+        let ctxt = ProgramContext::default();
+        evaluate_with_rules(parse_stmt!(program), &rules, &ctxt)
+            .simplified
+            .to_string()
+    }
 
     #[test]
     fn builds_rules() {
@@ -255,14 +466,171 @@ mod tests {
 
         assert!(built_rules
             .into_iter()
-            .any(|s| s.to_string() == "_a + 0 -> _a"));
+            .any(|s| s.to_string() == "_a... + 0 -> _a..."));
+    }
+
+    #[test]
+    fn opt_in_rules() {
+        let mut rule_set = RuleSet::default();
+        let has_expand_square = |rule_set: &RuleSet| {
+            rule_set
+                .build_named()
+                .unwrap()
+                .into_iter()
+                .any(|(name, _)| name == Some(RuleName::ExpandSquare))
+        };
+        assert!(!has_expand_square(&rule_set));
+
+        rule_set.insert(RuleName::ExpandSquare);
+        assert!(has_expand_square(&rule_set));
+    }
+
+    #[test]
+    fn basic_preset() {
+        let basic = RuleSet::default().preset(RuleSetPreset::Basic);
+        let full = RuleSet::default().preset(RuleSetPreset::Full);
+        for (program, basic_result, full_result) in &[
+            ("1 + 2 * 3!", "13", "13"),
+            ("x ^ 0 + y * 1 - 0", "y + 1", "y + 1"),
+            ("y * (1 / x)", "y * 1 / x", "y / x"),
+            ("x^2 * x^3 / x", "1 / x * x ^ 2 * x ^ 3", "x ^ 4"),
+            (
+                "(x^2 - 1) / (x + 1)",
+                "(-1 + x ^ 2) * 1 / (x + 1)",
+                "x + -1",
+            ),
+        ] {
+            assert_eq!(eval_with(&basic, program), *basic_result, "{}", program);
+            assert_eq!(eval_with(&full, program), *full_result, "{}", program);
+        }
+
+        // Flattening combines like terms regardless of the preset.
+        assert_eq!(eval_with(&basic, "x + x + 2x"), "x * 4");
+        let rules = basic.build().unwrap();
+        let ctxt = ProgramContext::default().always_flatten(false);
+        let unflattened = evaluate_with_rules(parse_stmt!("x + x + 2x"), &rules, &ctxt);
+        assert_eq!(unflattened.simplified.to_string(), "x + x + x * 2");
+    }
+
+    #[test]
+    fn preset_keeps_custom_and_inserted_rules() {
+        let mut rule_set = RuleSet::empty();
+        rule_set.push(Rule::from_pat_str("_a - _a -> 0"));
+        let mut rule_set = rule_set.preset(RuleSetPreset::Basic);
+        rule_set.insert(RuleName::FactorOut);
+        assert_eq!(
+            rule_set.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            vec![Some(RuleName::FactorOut), None]
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert!(RuleSet::empty().build().unwrap().is_empty());
+        assert_eq!(RuleSet::empty().iter().count(), 0);
+    }
+
+    #[test]
+    fn iter() {
+        let mut rule_set = RuleSet::empty();
+        rule_set.insert(RuleName::AdditiveIdentity);
+        rule_set.insert(RuleName::Add);
+        rule_set.insert(RuleName::FoldDivision);
+        rule_set.push(Rule::from_pat_str("_a - _a -> 0"));
+
+        assert_eq!(
+            rule_set.iter().collect::<Vec<_>>(),
+            vec![
+                (Some(RuleName::Add), "Add".to_owned()),
+                (
+                    Some(RuleName::AdditiveIdentity),
+                    "_a... + 0 -> _a...".to_owned()
+                ),
+                (
+                    Some(RuleName::FoldDivision),
+                    "_a * 1 / _b -> _a / _b".to_owned()
+                ),
+                (
+                    Some(RuleName::FoldDivision),
+                    "_a * (1 / _b) -> _a / _b".to_owned()
+                ),
+                (None, "_a - _a -> 0".to_owned()),
+            ]
+        );
+
+        rule_set.remove(RuleName::FoldDivision);
+        assert_eq!(rule_set.iter().count(), 3);
+    }
+
+    #[test]
+    fn push_bootstraps_pattern_rules() {
+        let mut rule_set = RuleSet::empty();
+        rule_set.push(Rule::from_pat_str("_a * (_b + _c) -> _a * _b + _a * _c"));
+        let built_rules: Vec<_> = rule_set
+            .build()
+            .unwrap()
+            .into_iter()
+            .map(|rule| format!("{:?}", rule))
+            .collect();
+
+        // The bootstrapped rule matches `_b + _c` without explicit parentheses.
+        assert_eq!(built_rules.len(), 2);
+        assert!(!built_rules[0].contains("Parend"));
+        assert!(built_rules[1].contains("Parend"));
+    }
+
+    #[test]
+    fn built_rules_are_named() {
+        let mut rule_set = RuleSet::default();
+        rule_set.push(Rule::from_pat_str("_a - _a -> 0"));
+        let built_rules = rule_set.build_named().unwrap();
+
+        assert!(built_rules.iter().all(|(name, rule)| rule.name() == *name));
+        assert_eq!(built_rules.last().unwrap().1.name(), None);
+    }
+
+    mod distribute_multiplication {
+        use super::*;
+
+        #[test]
+        fn applies() {
+            let mut rule_set = RuleSet::default();
+            rule_set.insert(RuleName::DistributeMultiplication);
+            assert_eq!(eval_with(&rule_set, "2 * (x + 3)"), "6 + x * 2");
+            assert_eq!(eval_with(&rule_set, "a * (b + c)"), "a * b + a * c");
+            assert_eq!(eval_with(&rule_set, "y + 2 * (x + 3)"), "y + 6 + x * 2");
+        }
+
+        #[test]
+        fn off_by_default() {
+            assert_eq!(eval_with(&RuleSet::default(), "2 * (x + 3)"), "2 * (x + 3)");
+        }
+    }
+
+    mod factor_out {
+        use super::*;
+
+        #[test]
+        fn applies() {
+            let mut rule_set = RuleSet::default();
+            rule_set.insert(RuleName::FactorOut);
+            assert_eq!(eval_with(&rule_set, "2x + 2y"), "2 * (x + y)");
+            assert_eq!(eval_with(&rule_set, "x*y + x*z"), "x * (y + z)");
+            assert_eq!(eval_with(&rule_set, "x*y + x"), "x * (y + 1)");
+            assert_eq!(eval_with(&rule_set, "x*y + z"), "z + x * y");
+        }
+
+        #[test]
+        fn off_by_default() {
+            assert_eq!(eval_with(&RuleSet::default(), "x*y + x*z"), "x * y + x * z");
+        }
     }
 
     #[test]
     fn fail_build_rules() {
         let mut rule_set = RuleSet::default();
-        rule_set.insert_custom("_a -> _b");
-        rule_set.insert_custom("$a -> $a - _c");
+        rule_set.push(Rule::from_pat_str("_a -> _b"));
+        rule_set.push(Rule::from_pat_str("$a -> $a - _c"));
         let err = rule_set.build().expect_err("");
 
         assert_eq!(
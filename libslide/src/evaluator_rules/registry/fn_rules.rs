@@ -1,6 +1,8 @@
 use crate::grammar::*;
 use crate::math::*;
+use crate::partial_evaluator::flatten::flatten_expr;
 use crate::utils::*;
+use crate::{Rigor, Span};
 
 macro_rules! get_binary_args {
     ($expr:expr, $op:pat) => {
@@ -40,17 +42,20 @@ pub(super) fn add(expr: RcExpr) -> Option<RcExpr> {
     let span = expr.span;
     let mut args = get_flattened_binary_args!(expr, BinaryOperator::Plus)?;
     let mut konst = 0.;
+    // Span of the constants folded together.
+    let mut konst_span: Option<Span> = None;
     let mut i = 0;
     for _ in 0..args.len() {
         match args[i].as_ref() {
             Expr::Const(f) => {
                 konst += f;
+                konst_span = Some(konst_span.map_or(args[i].span, |s| s.union(args[i].span)));
                 args.swap_remove(i);
             }
             _ => i += 1,
         }
     }
-    args.push(rc_expr!(konst.into(), span));
+    args.push(rc_expr!(konst.into(), konst_span.unwrap_or(span)));
 
     Some(unflatten_binary_expr(
         &args,
@@ -68,17 +73,20 @@ pub(super) fn multiply(expr: RcExpr) -> Option<RcExpr> {
     let span = expr.span;
     let mut args = get_flattened_binary_args!(expr, BinaryOperator::Mult)?;
     let mut konst = 1.;
+    // Span of the constants folded together.
+    let mut konst_span: Option<Span> = None;
     let mut i = 0;
     for _ in 0..args.len() {
         match args[i].as_ref() {
             Expr::Const(f) => {
                 konst *= f;
+                konst_span = Some(konst_span.map_or(args[i].span, |s| s.union(args[i].span)));
                 args.swap_remove(i);
             }
             _ => i += 1,
         }
     }
-    args.push(rc_expr!(konst.into(), span));
+    args.push(rc_expr!(konst.into(), konst_span.unwrap_or(span)));
 
     Some(unflatten_binary_expr(
         &args,
@@ -88,42 +96,105 @@ pub(super) fn multiply(expr: RcExpr) -> Option<RcExpr> {
 }
 
 pub(super) fn divide(expr: RcExpr) -> Option<RcExpr> {
-    let og_span = expr.span;
+    let (l, r) = get_binary_args!(expr, BinaryOperator::Div)?;
+    Some(rc_expr!(Expr::Const(l / r), expr.span))
+}
+
+/// A division of polynomials relative to the same term.
+struct PolyDivision<'a> {
+    lhs: &'a RcExpr,
+    rhs: &'a RcExpr,
+    numerator: Poly,
+    denominator: Poly,
+    relative_to: RcExpr,
+}
+
+/// Converts the numerator and denominator of a division into polynomials relative to the same
+/// term, if they are polynomials.
+fn poly_division(expr: &RcExpr) -> Option<PolyDivision<'_>> {
     match expr.as_ref() {
         Expr::BinaryExpr(BinaryExpr {
             op: BinaryOperator::Div,
             lhs,
             rhs,
-        }) => match (lhs.as_ref(), rhs.as_ref()) {
-            (Expr::Const(l), Expr::Const(r)) => Some(rc_expr!(Expr::Const(l / r), og_span)),
-            _ => {
-                // Now we try to convert the numerator/denominator into polynomials and cancel them.
-                let (numerator, relative_to) = Poly::from_expr(lhs.clone(), None).ok()?;
-                let relative_to = match relative_to {
-                    Some(e) => e,
-                    // Cancelling should only work with term'd polynomials. If the expression has
-                    // no terms for whatever reason, let another rule take care of it.
-                    None => return None,
-                };
-                let (denominator, _) =
-                    Poly::from_expr(rhs.clone(), Some(relative_to.clone())).ok()?;
-                let (_, numerator, denominator) = gcd_poly_zz_heu(numerator, denominator).ok()?;
-
-                // Woo! The polynomials have a gcd we can cancel them with.
-                let numer_expr = numerator.to_expr(relative_to.clone(), lhs.span);
-                if denominator.is_one() {
-                    Some(numer_expr)
-                } else {
-                    let denom_expr = denominator.to_expr(relative_to, rhs.span);
-                    let division = BinaryExpr::div(numer_expr, denom_expr);
-                    Some(rc_expr!(Expr::BinaryExpr(division), og_span))
-                }
-            }
-        },
+        }) if !(lhs.is_constant_expr() && rhs.is_constant_expr()) => {
+            let (numerator, relative_to) = Poly::from_expr(lhs.clone(), None).ok()?;
+            // Polynomial division should only work with term'd polynomials. If the numerator has
+            // no terms for whatever reason, let another rule take care of it.
+            let relative_to = relative_to?;
+            let (denominator, _) = Poly::from_expr(rhs.clone(), Some(relative_to.clone())).ok()?;
+            Some(PolyDivision {
+                lhs,
+                rhs,
+                numerator,
+                denominator,
+                relative_to,
+            })
+        }
         _ => None,
     }
 }
 
+/// Cancels the greatest common divisor of the polynomials in a division, like
+/// `(x^2 - 1) / (x + 1) -> x - 1`.
+pub(super) fn cancel_polynomials(expr: RcExpr) -> Option<RcExpr> {
+    let PolyDivision {
+        lhs,
+        rhs,
+        numerator,
+        denominator,
+        relative_to,
+    } = poly_division(&expr)?;
+    let (gcd, mut numerator, mut denominator) = gcd_poly_zz_heu(numerator, denominator).ok()?;
+    if gcd.is_one() {
+        return None;
+    }
+    // Keep the leading coefficient of the denominator positive, like `(-x - 1) / (-x + 2)` ->
+    // `(x + 1) / (x - 2)`.
+    if denominator.lc() < 0 {
+        numerator = numerator.mul_scalar(-1);
+        denominator = denominator.mul_scalar(-1);
+    }
+
+    // Woo! The polynomials have a gcd we can cancel them with.
+    let numer_expr = numerator.to_expr(relative_to.clone(), lhs.span);
+    if denominator.is_one() {
+        Some(numer_expr)
+    } else {
+        let denom_expr = denominator.to_expr(relative_to, rhs.span);
+        let division = BinaryExpr::div(numer_expr, denom_expr);
+        Some(rc_expr!(Expr::BinaryExpr(division), expr.span))
+    }
+}
+
+/// Divides the polynomials in a division into a quotient plus the remainder over the divisor, like
+/// `(x^2 - 2x) / (x + 1) -> x - 3 + 3 / (x + 1)`. Divisions without a remainder are left to
+/// [cancel_polynomials], since they drop the values for which the divisor is zero.
+pub(super) fn divide_polynomials(expr: RcExpr) -> Option<RcExpr> {
+    let PolyDivision {
+        lhs,
+        rhs,
+        numerator,
+        denominator,
+        relative_to,
+    } = poly_division(&expr)?;
+    let (quotient, remainder) = numerator.div(denominator).ok()?;
+    if quotient.is_zero() || remainder.is_zero() {
+        return None;
+    }
+
+    let quotient_expr = quotient.to_expr(relative_to.clone(), lhs.span);
+    let remainder_expr = remainder.to_expr(relative_to, lhs.span);
+    let fraction = rc_expr!(
+        Expr::BinaryExpr(BinaryExpr::div(remainder_expr, rhs.clone())),
+        expr.span
+    );
+    Some(rc_expr!(
+        Expr::BinaryExpr(BinaryExpr::add(quotient_expr, fraction)),
+        expr.span
+    ))
+}
+
 pub(super) fn modulo(expr: RcExpr) -> Option<RcExpr> {
     let (l, r) = get_binary_args!(expr, BinaryOperator::Mod)?;
     Some(rc_expr!(Expr::Const(l % r), expr.span))
@@ -134,6 +205,59 @@ pub(super) fn exponentiate(expr: RcExpr) -> Option<RcExpr> {
     Some(rc_expr!(Expr::Const(l.powf(*r)), expr.span))
 }
 
+/// Expands a square to the multiplication of a term by itself, like `x^2 -> x * x`.
+pub(super) fn expand_square(expr: RcExpr) -> Option<RcExpr> {
+    match expr.as_ref() {
+        Expr::BinaryExpr(BinaryExpr {
+            op: BinaryOperator::Exp,
+            lhs,
+            rhs,
+        }) if rhs.get_const() == Some(2.) => Some(rc_expr!(
+            Expr::BinaryExpr(BinaryExpr::mult(lhs.clone(), lhs.clone())),
+            expr.span
+        )),
+        _ => None,
+    }
+}
+
+/// Factors a factor common to all addends out of a sum, like `a * b + a * c -> a * (b + c)`.
+pub(super) fn factor_out(expr: RcExpr) -> Option<RcExpr> {
+    let span = expr.span;
+    let addends = get_flattened_binary_args!(expr, BinaryOperator::Plus)?;
+    let addend_factors: Vec<_> = addends
+        .into_iter()
+        .map(|addend| get_flattened_binary_args(addend, BinaryOperator::Mult))
+        .collect();
+
+    // Pick the first factor of the first addend present in all other addends. Factoring out `1`
+    // does not simplify anything.
+    let (first, rest) = addend_factors.split_first()?;
+    let common = first.iter().find(|factor| {
+        factor.get_const() != Some(1.) && rest.iter().all(|factors| factors.contains(factor))
+    })?;
+
+    let remainders: Vec<_> = addend_factors
+        .iter()
+        .map(|factors| {
+            let mut factors = factors.clone();
+            let i = factors.iter().position(|factor| factor == common).unwrap();
+            let removed = factors.remove(i);
+            if factors.is_empty() {
+                rc_expr!(Expr::Const(1.), removed.span)
+            } else {
+                unflatten_binary_expr(&factors, BinaryOperator::Mult, UnflattenStrategy::Left)
+            }
+        })
+        .collect();
+    let sum = unflatten_binary_expr(&remainders, BinaryOperator::Plus, UnflattenStrategy::Left);
+    let sum = rc_expr!(Expr::Parend(sum), span);
+
+    Some(rc_expr!(
+        Expr::BinaryExpr(BinaryExpr::mult(common.clone(), sum)),
+        span
+    ))
+}
+
 pub(super) fn posate(expr: RcExpr) -> Option<RcExpr> {
     get_unary_arg!(expr, UnaryOperator::SignPositive)
 }
@@ -144,3 +268,185 @@ pub(super) fn negate(expr: RcExpr) -> Option<RcExpr> {
         _ => None,
     }
 }
+
+pub(super) fn factorial(expr: RcExpr) -> Option<RcExpr> {
+    let n = get_unary_arg!(expr, UnaryOperator::Factorial)?.get_const()?;
+    Some(rc_expr!(Expr::Const(factorial_of(n)?), expr.span))
+}
+
+/// The largest number whose factorial is representable; `171!` overflows to infinity.
+pub(crate) const MAX_FACTORIAL_OPERAND: f64 = 170.;
+
+/// Computes `n!`, if it is defined and representable.
+///
+/// Numbers are 64-bit floats, so large factorials cannot be exact. Factorials up to `34!` are
+/// computed exactly and rounded once to the nearest float; larger ones are accumulated in floats,
+/// and may be off in their last few bits.
+fn factorial_of(n: f64) -> Option<f64> {
+    // Factorials of negative, non-integer, or too large numbers are reported by the evaluator's
+    // validation.
+    if n < 0. || n.fract() != 0. || n > MAX_FACTORIAL_OPERAND {
+        return None;
+    }
+    let n = n as u32;
+    let mut exact: u128 = 1;
+    let mut i = 2;
+    while i <= n {
+        match exact.checked_mul(i.into()) {
+            Some(product) => exact = product,
+            None => break,
+        }
+        i += 1;
+    }
+    Some((i..=n).fold(exact as f64, |result, i| result * f64::from(i)))
+}
+
+/// Folds an expression built only of constants, like `(1 + 2) * 3!`, to its value in one step.
+pub(super) fn fold_constants(expr: RcExpr) -> Option<RcExpr> {
+    if expr.is_const() || !expr.is_constant_expr() {
+        return None;
+    }
+    Some(rc_expr!(Expr::Const(constant_value(&expr)?), expr.span))
+}
+
+/// Evaluates an expression built only of constants, if every operation in it can be evaluated
+/// the way the rule for that operation would evaluate it.
+fn constant_value(expr: &RcExpr) -> Option<f64> {
+    match expr.as_ref() {
+        Expr::Const(c) => Some(*c),
+        Expr::BinaryExpr(BinaryExpr { op, lhs, rhs }) => {
+            let (l, r) = (constant_value(lhs)?, constant_value(rhs)?);
+            Some(match op {
+                BinaryOperator::Plus => l + r,
+                BinaryOperator::Minus => l - r,
+                // `±` has two values, and is expanded into its branches before evaluation.
+                BinaryOperator::PlusMinus => return None,
+                BinaryOperator::Mult => l * r,
+                BinaryOperator::Div => l / r,
+                BinaryOperator::Mod => l % r,
+                BinaryOperator::Exp => l.powf(r),
+            })
+        }
+        Expr::UnaryExpr(UnaryExpr { op, rhs }) => {
+            let n = constant_value(rhs)?;
+            match op {
+                UnaryOperator::SignPositive => Some(n),
+                UnaryOperator::SignNegative => Some(-n),
+                UnaryOperator::Factorial => factorial_of(n),
+            }
+        }
+        Expr::Parend(inner) | Expr::Bracketed(inner) => constant_value(inner),
+        Expr::Var(_) | Expr::Call(_) | Expr::Interval(_) => None,
+    }
+}
+
+pub(super) fn evaluate_call(expr: RcExpr) -> Option<RcExpr> {
+    let Call { func, args } = match expr.as_ref() {
+        Expr::Call(call) => call,
+        _ => return None,
+    };
+    if let ("sum", [index, lo, hi, body]) = (func.as_ref(), args.as_slice()) {
+        return expand_sum(index, lo, hi, body, expr.span);
+    }
+    let args = args
+        .iter()
+        .map(|arg| arg.get_const())
+        .collect::<Option<Vec<_>>>()?;
+    let result = match (func.as_ref(), args.as_slice()) {
+        ("min", [a, b]) => a.min(*b),
+        ("max", [a, b]) => a.max(*b),
+        ("clamp", [x, lo, hi]) if lo <= hi => x.max(*lo).min(*hi),
+        ("sqrt", [x]) => int_root(*x, 2.)?,
+        ("root", [n, x]) => int_root(*x, *n)?,
+        _ => return None,
+    };
+    Some(rc_expr!(Expr::Const(result), expr.span))
+}
+
+/// The most terms a sum is expanded to. Larger sums are left unevaluated.
+const MAX_SUM_TERMS: f64 = 10_000.;
+
+/// Expands a sum `sum(index, lo, hi, body)` with constant integer bounds into the addition of
+/// `body` with `index` substituted by each of `lo..=hi`, like `sum(i, 1, 3, i) -> 1 + 2 + 3`. An
+/// empty range sums to `0`.
+///
+/// The expansion is flattened so that like terms of the summands are combined.
+fn expand_sum(
+    index: &RcExpr,
+    lo: &RcExpr,
+    hi: &RcExpr,
+    body: &RcExpr,
+    span: Span,
+) -> Option<RcExpr> {
+    let index = match index.as_ref() {
+        Expr::Var(index) => *index,
+        _ => return None,
+    };
+    let (lo, hi) = (lo.get_const()?, hi.get_const()?);
+    if lo.fract() != 0. || hi.fract() != 0. || hi - lo >= MAX_SUM_TERMS {
+        return None;
+    }
+    if hi < lo {
+        return Some(rc_expr!(Expr::Const(0.), span));
+    }
+
+    let terms: Vec<_> = (0..=(hi - lo) as usize)
+        .map(|k| {
+            let value = lo + k as f64;
+            let term = SubstituteIndex { index, value }.transform(body);
+            rc_expr!(Expr::Parend(term), body.span)
+        })
+        .collect();
+    let sum = unflatten_binary_expr(&terms, BinaryOperator::Plus, UnflattenStrategy::Left);
+    Some(flatten_expr(sum, Rigor::Pragmatic).with_span(span))
+}
+
+/// Substitutes the index of a sum by a value in the sum's body.
+struct SubstituteIndex {
+    index: InternedStr,
+    value: f64,
+}
+
+impl<'a> ExpressionTransformer<'a> for SubstituteIndex {
+    fn transform_var(&self, var: &'a InternedStr, span: Span) -> RcExpr {
+        if *var == self.index {
+            rc_expr!(Expr::Const(self.value), span)
+        } else {
+            rc_expr!(Expr::Var(*var), span)
+        }
+    }
+
+    fn transform_call(&self, call: &'a Call, span: Span) -> RcExpr {
+        match (call.func.as_ref(), call.args.as_slice()) {
+            // A nested sum over the same index shadows it.
+            ("sum", [index, ..]) if index.as_ref() == &Expr::Var(self.index) => {
+                rc_expr!(Expr::Call(call.clone()), span)
+            }
+            _ => rc_expr!(
+                Expr::Call(Call {
+                    func: call.func,
+                    args: call.args.iter().map(|arg| self.transform(arg)).collect(),
+                }),
+                span
+            ),
+        }
+    }
+}
+
+/// Returns the integer `n`th root of `x`, if `n` is a positive integer and `x` is a perfect `n`th
+/// power of an integer.
+fn int_root(x: f64, n: f64) -> Option<f64> {
+    if n < 1. || n.fract() != 0. || n > i32::MAX as f64 {
+        return None;
+    }
+    // Negative numbers only have real roots of odd degree.
+    if x < 0. && n % 2. == 0. {
+        return None;
+    }
+    let root = x.abs().powf(n.recip()).round().copysign(x);
+    if root.powi(n as i32) == x {
+        Some(root)
+    } else {
+        None
+    }
+}
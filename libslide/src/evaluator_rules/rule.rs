@@ -1,8 +1,10 @@
-use super::pattern_match::{MatchRule, PatternMatch};
+use super::guard::{Guard, InvalidGuard};
+use super::pattern_match::{get_nary_operands, is_rest_pat, MatchRule, PatternMatch};
+use super::registry::RuleName;
 use crate::grammar::collectors::collect_pat_names;
 use crate::grammar::*;
 use crate::utils::{get_symmetric_expressions, hash, indent};
-use crate::{parse_expression_pattern, scan};
+use crate::{parse_expression_pattern, scan, Objective};
 
 use core::fmt;
 use std::collections::HashMap;
@@ -13,11 +15,22 @@ use std::error::Error;
 pub struct PatternMap {
     pub from: RcExprPat,
     pub to: RcExprPat,
+    /// A condition on the constants matched by `from` that must hold for the mapping to apply.
+    pub guard: Option<Guard>,
+    /// The named rule the mapping was built from, if any.
+    pub name: Option<RuleName>,
+    /// Whether the mapping may rewrite an expression to a costlier one. See
+    /// [`Rule::PatternMap`](Rule::PatternMap).
+    pub may_grow: bool,
 }
 
 impl fmt::Display for PatternMap {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} -> {}", self.from, self.to)
+        write!(f, "{} -> {}", self.from, self.to)?;
+        if let Some(guard) = &self.guard {
+            write!(f, " where {}", guard)?;
+        }
+        Ok(())
     }
 }
 
@@ -52,16 +65,82 @@ Specifically, source "{from}" is missing pattern(s) {pats} present in target "{t
 
 impl Error for UnresolvedMapping {}
 
+#[derive(Debug)]
+pub struct MisplacedRestPatterns {
+    map: PatternMap,
+    misplaced_pats: Vec<String>,
+}
+
+impl fmt::Display for MisplacedRestPatterns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let misplaced_pats = self
+            .misplaced_pats
+            .iter()
+            .map(|p| format!(r#""{}""#, p))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(
+            f,
+            r#"Could not resolve pattern map
+{}"{from} -> {to}"
+Specifically, rest pattern(s) {pats} in source "{from}" are not the only rest pattern operand of a `+` or `*` chain"#,
+            indent("\n", 4),
+            from = self.map.from,
+            to = self.map.to,
+            pats = misplaced_pats,
+        )
+    }
+}
+
+impl Error for MisplacedRestPatterns {}
+
+/// Collects the [rest patterns](is_rest_pat) of `pat` that are not the only rest pattern operand
+/// of a `+` or `*` chain, and so cannot be matched.
+fn collect_misplaced_rest_pats(pat: &RcExprPat, misplaced: &mut Vec<String>) {
+    if let Some((_, operands)) = get_nary_operands(pat) {
+        let (rests, others): (Vec<_>, Vec<_>) =
+            operands.iter().partition(|operand| is_rest_pat(operand));
+        if rests.len() > 1 {
+            misplaced.extend(rests.into_iter().map(|rest| rest.to_string()));
+        }
+        for operand in others {
+            collect_misplaced_rest_pats(operand, misplaced);
+        }
+        return;
+    }
+
+    match pat.as_ref() {
+        ExprPat::AnyPat(name) if is_rest_pat(pat) => misplaced.push(name.clone()),
+        ExprPat::BinaryExpr(BinaryExpr { lhs, rhs, .. }) => {
+            collect_misplaced_rest_pats(lhs, misplaced);
+            collect_misplaced_rest_pats(rhs, misplaced);
+        }
+        ExprPat::UnaryExpr(UnaryExpr { rhs, .. }) => collect_misplaced_rest_pats(rhs, misplaced),
+        ExprPat::Parend(inner) | ExprPat::Bracketed(inner) => {
+            collect_misplaced_rest_pats(inner, misplaced)
+        }
+        ExprPat::Const(_) | ExprPat::VarPat(_) | ExprPat::ConstPat(_) | ExprPat::AnyPat(_) => {}
+    }
+}
+
 impl PatternMap {
     /// Converts a string representation of a rule to a `PatternMap`.
     /// A rule's string form must be
     ///
     /// ```text
-    /// "<expr> -> <expr>"
+    /// "<expr> -> <expr> [where <guard>]"
     /// ```
     ///
-    /// Where `<expr>` is an expression pattern.
+    /// Where `<expr>` is an expression pattern and `<guard>` is an optional [`Guard`](Guard) on the
+    /// constant patterns of the source expression, like `#a != 0`.
     pub fn from_str(rule: &str) -> Self {
+        let mut rule_and_guard = rule.splitn(2, " where ");
+        let rule = rule_and_guard.next().unwrap();
+        let guard = rule_and_guard
+            .next()
+            .map(|guard| Guard::from_str(guard).unwrap_or_else(|err| panic!("{}", err)));
+
         let split = rule.split(" -> ");
         let mut split = split
             .map(|toks| scan(toks).tokens)
@@ -71,7 +150,18 @@ impl PatternMap {
         // Unofficially, rustc's expression evaluation order is L2R, but officially it is undefined.
         let from = split.next().unwrap();
         let to = split.next().unwrap();
-        Self { from, to }
+        Self {
+            from,
+            to,
+            guard,
+            name: None,
+            may_grow: false,
+        }
+    }
+
+    /// Sets whether the mapping [may grow](Self::may_grow) the expressions it rewrites.
+    pub fn growing(self, may_grow: bool) -> Self {
+        Self { may_grow, ..self }
     }
 
     /// Bootstraps a `PatternMap` rule with a one-pass application of a rule set, which may include
@@ -96,145 +186,294 @@ impl PatternMap {
         bootstrapped
     }
 
-    /// Checks a `PatternMap` is resolvable, returning an error if it is not.
-    pub fn validate(&self) -> Result<(), UnresolvedMapping> {
+    /// Checks a `PatternMap` is resolvable, its rest patterns can be matched, and its guard is on
+    /// constant patterns of its source, returning an error if it is not.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        let from_pats = collect_pat_names(&self.from);
         let unresolved_pats: Vec<_> = collect_pat_names(&self.to)
-            .difference(&collect_pat_names(&self.from))
+            .difference(&from_pats)
             .map(|&p| p.to_string())
             .collect();
 
-        if unresolved_pats.is_empty() {
-            return Ok(());
+        if !unresolved_pats.is_empty() {
+            return Err(UnresolvedMapping {
+                map: self.clone(),
+                unresolved_pats,
+            }
+            .into());
+        }
+
+        let mut misplaced_pats = Vec::new();
+        collect_misplaced_rest_pats(&self.from, &mut misplaced_pats);
+        if !misplaced_pats.is_empty() {
+            return Err(MisplacedRestPatterns {
+                map: self.clone(),
+                misplaced_pats,
+            }
+            .into());
+        }
+
+        if let Some(pat) = self
+            .guard
+            .iter()
+            .flat_map(Guard::pats)
+            .find(|pat| !from_pats.contains(pat))
+        {
+            return Err(InvalidGuard::UnboundPattern(pat.to_owned()).into());
         }
 
-        Err(UnresolvedMapping {
-            map: self.clone(),
-            unresolved_pats,
-        })
+        Ok(())
     }
 }
 
 /// An expression rewrite rule.
+#[derive(Clone)]
 pub enum Rule {
     /// A `PatternMap` rewrite rule attempts to match an expression via a pattern specified by
     /// [`PatternMap::from`](PatternMap::from). If a match is found, an instance of
     /// [`PatternMap::to`](PatternMap::to) with relevant substitutions from the initial match is
     /// instantiated, and the matched expression is replaced accordingly.
+    ///
+    /// A rewrite is only kept if it is no costlier, under the evaluation's
+    /// [objective](crate::Objective), than the best form of the expression found otherwise;
+    /// keeping costlier rewrites lets rules whose rhs is larger than their lhs grow an expression
+    /// without bound, or undo the work of other rules. Rules that intentionally grow expressions
+    /// as stepping stones to simpler forms, like
+    /// [`DistributeMultiplication`](RuleName::DistributeMultiplication), opt out of this with
+    /// [`PatternMap::may_grow`](PatternMap::may_grow).
     PatternMap(PatternMap),
     /// An `Evaluate` rewrite rule takes an expression and attempts to programatically apply a
     /// transformation to another expression. If no transformation can be undertaken by the rule,
-    /// `None` is returned.
-    Evaluate(fn(RcExpr) -> Option<RcExpr>),
+    /// `None` is returned. The rule may be named by the built-in rule it was built from.
+    Evaluate(fn(RcExpr) -> Option<RcExpr>, Option<RuleName>),
 }
 
 impl Rule {
     /// Creates an [evaluation rule](Rule::Evaluate) from a suitable function.
     pub fn from_fn(f: fn(RcExpr) -> Option<RcExpr>) -> Self {
-        Self::Evaluate(f)
+        Self::Evaluate(f, None)
     }
 
     /// Creates an [pattern rule](Rule::PatternMap) from a suitable string pattern.
     pub fn from_pat_str(s: &str) -> Self {
         Self::PatternMap(PatternMap::from_str(s))
     }
-}
 
-impl Transformer<RcExpr, RcExpr> for Rule {
-    /// Attempts to apply a rule on a target expression by
-    ///
-    /// 1. Applying the rule recursively on the target's subexpression to obtain a
-    ///    partially-transformed target expression.
-    ///
-    /// 2. Pattern matching the lhs of the rule with the partially-transformed target expression.
-    ///   - If pattern matching is unsuccessful, no application is done and the original expression
-    ///     is returned.
-    ///
-    /// 3. Expanding the rhs of the rule using the results of the pattern matching.
-    ///
-    /// Examples:
-    ///
-    /// ```ignore
-    /// "$x + 0 -> $x".try_apply("x + 0")  // Some(x)
-    /// "$x + 0 -> $x".try_apply("x + 1")  // None
-    /// "$x + 0 -> $x".try_apply("x")      // None
-    /// ```
-    fn transform(&self, target: RcExpr) -> RcExpr {
+    /// The name of the built-in rule this rule was built from, or [None](Option::None) for a
+    /// custom rule.
+    pub fn name(&self) -> Option<RuleName> {
+        match self {
+            Self::PatternMap(pm) => pm.name,
+            Self::Evaluate(_, name) => *name,
+        }
+    }
+
+    /// Names the rule by the built-in rule it was built from.
+    pub(super) fn named(self, name: Option<RuleName>) -> Self {
+        match self {
+            Self::PatternMap(pm) => Self::PatternMap(PatternMap { name, ..pm }),
+            Self::Evaluate(f, _) => Self::Evaluate(f, name),
+        }
+    }
+
+    /// Like [`transform`](Transformer::transform), but chooses between candidate forms of the
+    /// target, like its symmetric forms, by their cost under an [objective](Objective).
+    pub fn transform_for(&self, target: RcExpr, objective: Objective) -> RcExpr {
+        // Results are cached by the target expression and its span, so that a result is only reused
+        // for the same subexpression of the source.
         fn fill(cache: &mut HashMap<u64, RcExpr>, t: RcExpr, r: RcExpr) -> RcExpr {
-            cache.entry(hash(t.as_ref())).or_insert_with(|| r).clone()
+            cache
+                .entry(hash(&(t.as_ref(), t.span)))
+                .or_insert_with(|| r)
+                .clone()
         }
 
         fn transform_inner(
             rule: &Rule,
             target: RcExpr,
+            objective: Objective,
             cache: &mut HashMap<u64, RcExpr>,
         ) -> RcExpr {
+            // A target whose subexpressions are not transformed is returned as it is, so that
+            // callers can tell whether the rule changed an expression by its address.
+            let same = |a: &RcExpr, b: &RcExpr| a.addr() == b.addr();
             match target.as_ref() {
                 Expr::Const(_) => target,
                 Expr::Var(_) => target,
-                Expr::BinaryExpr(binary_expr) => rc_expr!(
-                    Expr::BinaryExpr(BinaryExpr {
-                        op: binary_expr.op,
-                        lhs: transform(rule, binary_expr.lhs.clone(), cache),
-                        rhs: transform(rule, binary_expr.rhs.clone(), cache),
-                    }),
-                    target.span
-                ),
-                Expr::UnaryExpr(unary_expr) => rc_expr!(
-                    Expr::UnaryExpr(UnaryExpr {
-                        op: unary_expr.op,
-                        rhs: transform(rule, unary_expr.rhs.clone(), cache),
-                    }),
-                    target.span
-                ),
+                Expr::BinaryExpr(binary_expr) => {
+                    let lhs = transform(rule, binary_expr.lhs.clone(), objective, cache);
+                    let rhs = transform(rule, binary_expr.rhs.clone(), objective, cache);
+                    if same(&lhs, &binary_expr.lhs) && same(&rhs, &binary_expr.rhs) {
+                        return target;
+                    }
+                    rc_expr!(
+                        Expr::BinaryExpr(BinaryExpr {
+                            op: binary_expr.op,
+                            lhs,
+                            rhs,
+                        }),
+                        target.span
+                    )
+                }
+                Expr::UnaryExpr(unary_expr) => {
+                    let rhs = transform(rule, unary_expr.rhs.clone(), objective, cache);
+                    if same(&rhs, &unary_expr.rhs) {
+                        return target;
+                    }
+                    rc_expr!(
+                        Expr::UnaryExpr(UnaryExpr {
+                            op: unary_expr.op,
+                            rhs,
+                        }),
+                        target.span
+                    )
+                }
                 Expr::Parend(expr) => {
-                    let inner = transform(rule, expr.clone(), cache);
+                    let inner = transform(rule, expr.clone(), objective, cache);
+                    if same(&inner, expr) {
+                        return target;
+                    }
                     rc_expr!(Expr::Parend(inner), target.span)
                 }
                 Expr::Bracketed(expr) => {
-                    let inner = transform(rule, expr.clone(), cache);
+                    let inner = transform(rule, expr.clone(), objective, cache);
+                    if same(&inner, expr) {
+                        return target;
+                    }
                     rc_expr!(Expr::Bracketed(inner), target.span)
                 }
+                Expr::Call(call) => {
+                    let args: Vec<_> = call
+                        .args
+                        .iter()
+                        .map(|arg| transform(rule, arg.clone(), objective, cache))
+                        .collect();
+                    if args.iter().zip(call.args.iter()).all(|(a, b)| same(a, b)) {
+                        return target;
+                    }
+                    rc_expr!(
+                        Expr::Call(Call {
+                            func: call.func,
+                            args,
+                        }),
+                        target.span
+                    )
+                }
+                Expr::Interval(interval) => {
+                    let lo = transform(rule, interval.lo.clone(), objective, cache);
+                    let hi = transform(rule, interval.hi.clone(), objective, cache);
+                    if same(&lo, &interval.lo) && same(&hi, &interval.hi) {
+                        return target;
+                    }
+                    rc_expr!(Expr::Interval(Interval { lo, hi }), target.span)
+                }
             }
         }
 
-        fn transform(rule: &Rule, target: RcExpr, cache: &mut HashMap<u64, RcExpr>) -> RcExpr {
-            if let Some(result) = cache.get(&hash(target.as_ref())) {
+        fn transform(
+            rule: &Rule,
+            target: RcExpr,
+            objective: Objective,
+            cache: &mut HashMap<u64, RcExpr>,
+        ) -> RcExpr {
+            if let Some(result) = cache.get(&hash(&(target.as_ref(), target.span))) {
                 return result.clone();
             }
 
-            let mut result = target.clone();
-            match rule {
-                Rule::PatternMap(PatternMap { from, to }) => {
-                    for target in get_symmetric_expressions(target.clone()) {
+            let result = match rule {
+                Rule::PatternMap(PatternMap {
+                    from,
+                    to,
+                    guard,
+                    may_grow,
+                    ..
+                }) => {
+                    // Keep transformations of the target's subexpressions even if they do not
+                    // reduce its cost, as with rules that expand expressions.
+                    let mut result = transform_inner(rule, target.clone(), objective, cache);
+                    // An n-ary pattern, like `_a... + 0`, matches the operands of a chain in any
+                    // order, so there is no need to try symmetric forms of the target.
+                    let targets = if get_nary_operands(from).is_some() {
+                        vec![target.clone()]
+                    } else {
+                        get_symmetric_expressions(target.clone())
+                    };
+                    for sym_target in targets {
                         // First, apply the rule recursively on the target's subexpressions.
-                        let partially_transformed = transform_inner(rule, target, cache);
-                        if partially_transformed.complexity() < result.complexity() {
+                        let partially_transformed =
+                            transform_inner(rule, sym_target, objective, cache);
+                        if objective.cost(&partially_transformed) < objective.cost(&result) {
                             result = partially_transformed.clone();
                         }
 
+                        let span = target.span;
                         if let Some(transformed) =
                             PatternMatch::match_rule(from.clone(), partially_transformed)
+                                .filter(|repls| guard.as_ref().map_or(true, |g| g.holds(repls)))
                                 // If the rule was matched on the expression, we have replacements for rule
                                 // patterns -> target subexpressions. Apply the rule by transforming the
-                                // rule's RHS with the replacements.
-                                .map(|repls| repls.transform(to.clone()))
+                                // rule's RHS with the replacements, at the span of the target so that
+                                // the result still points to its source.
+                                .map(|repls| repls.transform_at(to.clone(), span))
                         {
-                            result = transformed;
+                            if *may_grow || objective.cost(&transformed) <= objective.cost(&result)
+                            {
+                                result = transformed;
+                            }
                         }
                     }
+                    // A rewrite or symmetric form of the target may be the target itself, which
+                    // is not a transformation.
+                    if result.addr() != target.addr() && result == target {
+                        target.clone()
+                    } else {
+                        result
+                    }
                 }
-                Rule::Evaluate(f) => {
+                Rule::Evaluate(f, _) => {
                     // First, apply the rule recursively on the target's subexpressions.
-                    let partially_transformed = transform_inner(rule, target.clone(), cache);
-                    result = f(partially_transformed.clone()).unwrap_or(partially_transformed);
+                    let partially_transformed =
+                        transform_inner(rule, target.clone(), objective, cache);
+                    // Evaluation functions may rebuild an expression they cannot simplify, which
+                    // is not a transformation.
+                    match f(partially_transformed.clone()) {
+                        Some(evaluated) if evaluated != partially_transformed => evaluated,
+                        _ => partially_transformed,
+                    }
                 }
-            }
+            };
 
-            fill(cache, target, result)
+            // The result of a rewrite takes the span of the expression it rewrote.
+            let span = target.span;
+            fill(cache, target, result.with_span(span))
         }
 
         let mut cache: HashMap<u64, RcExpr> = HashMap::new();
-        transform(self, target, &mut cache)
+        transform(self, target, objective, &mut cache)
+    }
+}
+
+impl Transformer<RcExpr, RcExpr> for Rule {
+    /// Attempts to apply a rule on a target expression by
+    ///
+    /// 1. Applying the rule recursively on the target's subexpression to obtain a
+    ///    partially-transformed target expression.
+    ///
+    /// 2. Pattern matching the lhs of the rule with the partially-transformed target expression.
+    ///   - If pattern matching is unsuccessful, no application is done and the original expression
+    ///     is returned.
+    ///
+    /// 3. Expanding the rhs of the rule using the results of the pattern matching.
+    ///
+    /// Examples:
+    ///
+    /// ```ignore
+    /// "$x + 0 -> $x".try_apply("x + 0")  // Some(x)
+    /// "$x + 0 -> $x".try_apply("x + 1")  // None
+    /// "$x + 0 -> $x".try_apply("x")      // None
+    /// ```
+    fn transform(&self, target: RcExpr) -> RcExpr {
+        self.transform_for(target, Objective::OpCount)
     }
 }
 
@@ -276,7 +515,7 @@ impl Transformer<RcExprPat, RcExprPat> for Rule {
         };
 
         match self {
-            Rule::PatternMap(PatternMap { from, to }) => {
+            Rule::PatternMap(PatternMap { from, to, .. }) => {
                 PatternMatch::match_rule(from.clone(), partially_transformed.clone())
                     .map(|repls| repls.transform(to.clone()))
             }
@@ -299,7 +538,7 @@ impl fmt::Display for Rule {
             "{}",
             match self {
                 Self::PatternMap(pm) => pm.to_string(),
-                Self::Evaluate(fun) => fn_name(fun).to_string(),
+                Self::Evaluate(fun, _) => fn_name(fun).to_string(),
             }
         )
     }
@@ -309,7 +548,7 @@ impl fmt::Debug for Rule {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::PatternMap(pm) => write!(f, "{:?}", pm),
-            Self::Evaluate(fun) => write!(f, "{}", fn_name(fun)),
+            Self::Evaluate(fun, _) => write!(f, "{}", fn_name(fun)),
         }
     }
 }
@@ -317,6 +556,7 @@ impl fmt::Debug for Rule {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse_expr;
 
     #[test]
     fn validate_error() {
@@ -332,6 +572,111 @@ Specifically, source "_a + $b / #c * 3" is missing pattern(s) "#e", "_f" present
         );
     }
 
+    #[test]
+    fn validate_guard_error() {
+        let err = PatternMap::from_str("_a / _b -> _a * (1 / _b) where #b != 0")
+            .validate()
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            r##"Guard condition on "#b" must be on a constant pattern of the rule's source"##
+        );
+    }
+
+    #[test]
+    fn validate_rest_pattern_error() {
+        let err = PatternMap::from_str("_a... ^ 2 + _b... + _c... -> 0")
+            .validate()
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            r##"Could not resolve pattern map
+    "_a... ^ 2 + _b... + _c... -> 0"
+Specifically, rest pattern(s) "_b...", "_c...", "_a..." in source "_a... ^ 2 + _b... + _c..." are not the only rest pattern operand of a `+` or `*` chain"##
+        );
+    }
+
+    #[test]
+    fn nary_transform() {
+        let rule = Rule::from_pat_str("_a... + 0 -> _a...");
+        for (expr, expected) in &[
+            ("x + 0", "x"),
+            ("0 + x + y", "x + y"),
+            ("x + 0 + y + 0", "x + y"),
+            ("2 * (x + 0 + y)", "2 * (x + y)"),
+            ("x * 0", "x * 0"),
+        ] {
+            assert_eq!(rule.transform(parse_expr!(*expr)).to_string(), *expected);
+        }
+    }
+
+    #[test]
+    fn from_str_with_guard() {
+        let pm = PatternMap::from_str("_a / #b -> _a * (1 / #b) where #b != 0 and #b is integer");
+
+        assert_eq!(pm.from.to_string(), "_a / #b");
+        assert_eq!(pm.to.to_string(), "_a * (1 / #b)");
+        assert!(pm.validate().is_ok());
+        assert_eq!(
+            pm.to_string(),
+            "_a / #b -> _a * (1 / #b) where #b != 0 and #b is integer"
+        );
+    }
+
+    #[test]
+    fn from_str_without_guard() {
+        assert!(PatternMap::from_str("_a + 0 -> _a").guard.is_none());
+    }
+
+    mod guarded_transform {
+        use super::*;
+
+        macro_rules! guarded_transform_tests {
+            ($($name:ident: $rule:expr, $expr:expr => $result:expr)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let rule = Rule::from_pat_str($rule);
+                    let transformed = rule.transform(parse_expr!($expr));
+                    assert_eq!(transformed.to_string(), $result);
+                }
+            )*
+            }
+        }
+
+        guarded_transform_tests! {
+            nonzero_holds:              "_a / #b -> _a * #b where #b != 0", "x / 2" => "x * 2"
+            nonzero_prevents_rewrite:   "_a / #b -> _a * #b where #b != 0", "x / 0" => "x / 0"
+            nonzero_nested:             "_a / #b -> _a * #b where #b is nonzero", "1 + x / 0" => "1 + x / 0"
+            integer_holds:              "_a ^ #b -> _a where #b is integer", "x ^ 2" => "x"
+            integer_prevents_rewrite:   "_a ^ #b -> _a where #b is integer", "x ^ 0.5" => "x ^ 0.5"
+            comparison_holds:           "#a * _b -> _b where #a >= 1 and #a < 2", "1.5 * x" => "x"
+            comparison_prevents_rewrite: "#a * _b -> _b where #a >= 1 and #a < 2", "2 * x" => "2 * x"
+        }
+    }
+
+    #[test]
+    fn size_increasing_rewrite_not_kept() {
+        let pm = PatternMap::from_str("_a ^ #b -> _a * _a ^ (#b - 1)");
+        for (expr, result) in &[("x ^ 3", "x ^ 3"), ("(y ^ 2) ^ 3", "(y ^ 2) ^ 3")] {
+            let rule = Rule::PatternMap(pm.clone());
+            assert_eq!(rule.transform(parse_expr!(*expr)).to_string(), *result);
+        }
+    }
+
+    #[test]
+    fn size_increasing_rewrite_kept_if_rule_may_grow() {
+        let pm = PatternMap::from_str("_a ^ #b -> _a * _a ^ (#b - 1)").growing(true);
+        assert_eq!(
+            Rule::PatternMap(pm)
+                .transform(parse_expr!("x ^ 3"))
+                .to_string(),
+            "x * x ^ (3 - 1)"
+        );
+    }
+
     #[test]
     fn validate_ok() {
         assert!(PatternMap::from_str("_a + $b / #c -> _a + $b")
@@ -0,0 +1,115 @@
+//! Checks that the string rules of the default rule set are numerically sound; that is, that both
+//! sides of every rule evaluate to the same number for any assignment of numbers to the rule's
+//! patterns.
+
+use super::registry::{RuleName, RuleSet};
+use super::rule::{PatternMap, Rule};
+use crate::grammar::collectors::collect_pat_names;
+use crate::grammar::*;
+
+use proptest::prelude::*;
+use std::collections::HashMap;
+
+/// The number of values drawn for an assignment; no rule has more patterns than this.
+const MAX_PATS: usize = 4;
+
+type Assignment<'a> = HashMap<&'a str, f64>;
+
+/// Evaluates a pattern with each of its patterns replaced by its value in `assignment`. Returns
+/// `None` if the pattern has no finite real value, like when it divides by zero.
+fn eval(pat: &ExprPat, assignment: &Assignment) -> Option<f64> {
+    let value = match pat {
+        ExprPat::Const(num) => *num,
+        ExprPat::VarPat(pat) | ExprPat::ConstPat(pat) | ExprPat::AnyPat(pat) => {
+            *assignment.get(pat.as_str())?
+        }
+        ExprPat::BinaryExpr(BinaryExpr { op, lhs, rhs }) => {
+            let (lhs, rhs) = (eval(lhs, assignment)?, eval(rhs, assignment)?);
+            match op {
+                BinaryOperator::Plus => lhs + rhs,
+                BinaryOperator::Minus => lhs - rhs,
+                BinaryOperator::PlusMinus => return None,
+                BinaryOperator::Mult => lhs * rhs,
+                BinaryOperator::Div | BinaryOperator::Mod if rhs == 0. => return None,
+                BinaryOperator::Div => lhs / rhs,
+                BinaryOperator::Mod => lhs % rhs,
+                BinaryOperator::Exp => lhs.powf(rhs),
+            }
+        }
+        ExprPat::UnaryExpr(UnaryExpr { op, rhs }) => {
+            let rhs = eval(rhs, assignment)?;
+            match op {
+                UnaryOperator::SignPositive => rhs,
+                UnaryOperator::SignNegative => -rhs,
+                UnaryOperator::Factorial if rhs < 0. || rhs.fract() != 0. => return None,
+                UnaryOperator::Factorial => (2..=rhs as u64).map(|i| i as f64).product(),
+            }
+        }
+        ExprPat::Parend(inner) | ExprPat::Bracketed(inner) => eval(inner, assignment)?,
+    };
+    Some(value).filter(|value| value.is_finite())
+}
+
+/// Returns the string rules of the default rule set, with the names of the rules they were built
+/// from.
+fn default_pattern_maps() -> Vec<(RuleName, PatternMap)> {
+    RuleSet::default()
+        .build_named()
+        .unwrap()
+        .into_iter()
+        .filter_map(|(name, rule)| match rule {
+            Rule::PatternMap(pm) => Some((name.expect("default rules are named"), pm)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn show(assignment: &Assignment) -> String {
+    let mut bindings: Vec<_> = assignment
+        .iter()
+        .map(|(pat, value)| format!("{} = {}", pat, value))
+        .collect();
+    bindings.sort();
+    bindings.join(", ")
+}
+
+/// A small rational number, in lowest terms or not.
+fn rational() -> impl Strategy<Value = f64> {
+    (-6i32..=6, 1i32..=3).prop_map(|(num, den)| num as f64 / den as f64)
+}
+
+proptest! {
+    #[test]
+    fn default_string_rules_are_sound(values in prop::collection::vec(rational(), MAX_PATS)) {
+        for (name, pm) in default_pattern_maps() {
+            let mut pats: Vec<_> = collect_pat_names(&pm.from).into_iter().collect();
+            pats.sort_unstable();
+            prop_assert!(pats.len() <= MAX_PATS, "rule {:?} has too many patterns", name);
+            let assignment: Assignment = pats.into_iter().zip(values.iter().copied()).collect();
+
+            if let Some(guard) = &pm.guard {
+                if !guard.holds_for(|pat| assignment.get(pat).copied()) {
+                    continue;
+                }
+            }
+            let (lhs, rhs) = match (eval(&pm.from, &assignment), eval(&pm.to, &assignment)) {
+                (Some(lhs), Some(rhs)) => (lhs, rhs),
+                // The rule is not applicable to numbers the pattern is undefined for.
+                (None, _) => continue,
+                (Some(lhs), None) => {
+                    return Err(TestCaseError::fail(format!(
+                        "rule {:?} (`{}`) is unsound for {}: the source is {}, but the target \
+                         is undefined",
+                        name, pm, show(&assignment), lhs
+                    )))
+                }
+            };
+            let tolerance = 1e-9 * lhs.abs().max(rhs.abs()).max(1.);
+            prop_assert!(
+                (lhs - rhs).abs() <= tolerance,
+                "rule {:?} (`{}`) is unsound for {}: the source is {}, but the target is {}",
+                name, pm, show(&assignment), lhs, rhs
+            );
+        }
+    }
+}
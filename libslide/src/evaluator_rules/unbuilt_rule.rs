@@ -32,6 +32,26 @@ pub enum UnbuiltRule {
     ///
     /// Applied on the expression `"x + 0"` would yield `"x"`.
     ///
+    /// An any pattern suffixed by `...`, like `_a...`, is a rest pattern. A rest pattern that is an
+    /// operand of a `+` or `*` chain matches all operands of the chain not matched by the chain's
+    /// other operands, in any order. For example, the rule
+    ///
+    /// ```text
+    /// "_a... + 0 -> _a..."
+    /// ```
+    ///
+    /// Applied on the expression `"x + 0 + y"` would yield `"x + y"`.
+    ///
+    /// A string rule may end with a `where` clause of conditions on the constants its patterns
+    /// match, in which case the rule is only applied when the conditions hold. For example, the
+    /// rule
+    ///
+    /// ```text
+    /// "_a / #b -> _a * (1 / #b) where #b != 0"
+    /// ```
+    ///
+    /// is not applied on the expression `"x / 0"`.
+    ///
     /// Note that mapping rules are built as, matched with, and applied on expression parse trees
     /// rather than the string representations of expressions. This ensures rule application is
     /// always exact and deterministic.
@@ -4,6 +4,7 @@
 mod mem;
 pub mod collectors;
 mod expression_pattern;
+pub mod fold;
 mod statement;
 mod transformer;
 pub mod visit;
@@ -25,23 +26,40 @@ where
 {
 }
 
+/// An infix operator of a binary expression.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Debug)]
 pub enum BinaryOperator {
     // Discrimant values exist to describe a formal ordering, and are grouped by tens to express
     // precedence.
+    /// Addition, `a + b`.
     Plus = 1,
+    /// Subtraction, `a - b`.
     Minus = 2,
+    /// Addition or subtraction, `a ± b`, describing both results at once.
+    PlusMinus = 3,
+    /// Multiplication, `a * b`.
     Mult = 10,
+    /// Division, `a / b`.
     Div = 11,
+    /// Remainder, `a % b`.
     Mod = 12,
+    /// Exponentiation, `a ^ b`.
     Exp = 20,
 }
 
 impl BinaryOperator {
-    pub(crate) fn precedence(&self) -> u8 {
+    /// Returns the precedence level of the operator. Operators with higher precedence bind more
+    /// tightly; for example, `*` has a higher precedence than `+`.
+    pub fn precedence(&self) -> u8 {
         (*self as u8) / 10
     }
 
+    /// Returns whether a chain of the operator groups from the right, like `a ^ b ^ c` is
+    /// `a ^ (b ^ c)`. All other operators group from the left.
+    pub fn is_right_associative(&self) -> bool {
+        matches!(self, BinaryOperator::Exp)
+    }
+
     pub(crate) fn is_associative(&self) -> bool {
         use BinaryOperator::*;
         matches!(self, Plus | Mult | Exp)
@@ -56,6 +74,7 @@ impl TryFrom<&Token> for BinaryOperator {
         match token.ty {
             TokenType::Plus => Ok(Plus),
             TokenType::Minus => Ok(Minus),
+            TokenType::PlusMinus => Ok(PlusMinus),
             TokenType::Mult => Ok(Mult),
             TokenType::Div => Ok(Div),
             TokenType::Mod => Ok(Mod),
@@ -100,6 +119,7 @@ where
     E: RcExpression,
 {
     mkop! {
+        add: BinaryOperator::Plus
         sub: BinaryOperator::Minus
         mult: BinaryOperator::Mult
         div:  BinaryOperator::Div
@@ -131,12 +151,25 @@ where
     }
 }
 
+/// An operator of a unary expression.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Debug)]
 pub enum UnaryOperator {
+    /// The prefix plus sign, `+a`.
     SignPositive = 1,
+    /// The prefix minus sign, `-a`.
     SignNegative = 2,
+    /// The postfix factorial, `a!`.
+    Factorial = 3,
+}
+
+impl UnaryOperator {
+    /// Whether the operator is written after its operand, like the factorial in `5!`.
+    pub fn is_postfix(&self) -> bool {
+        matches!(self, Self::Factorial)
+    }
 }
 
+/// Converts a token to a prefix unary operator. Postfix operators are parsed separately.
 impl TryFrom<&Token> for UnaryOperator {
     type Error = ();
 
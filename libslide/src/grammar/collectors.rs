@@ -16,7 +16,7 @@ pub fn collect_var_names(expr: &RcExpr) -> HashSet<InternedStr> {
 struct VarNameCollector {
     vars: HashSet<InternedStr>,
 }
-impl<'a> StmtVisitor<'a> for VarNameCollector {
+impl<'a> ExprVisitor<'a> for VarNameCollector {
     fn visit_var(&mut self, var: &'a InternedStr, _span: Span) {
         self.vars.insert(*var);
     }
@@ -0,0 +1,235 @@
+//! Traits for folding slide expressions into new ones.
+
+use super::*;
+
+/// Folds an expression.
+pub fn descend_expr<F: ExprFolder>(folder: &mut F, expr: &RcExpr) -> RcExpr {
+    match expr.as_ref() {
+        Expr::Const(k) => folder.fold_const(expr, *k),
+        Expr::Var(v) => folder.fold_var(expr, *v),
+        Expr::BinaryExpr(b) => folder.fold_binary(expr, b),
+        Expr::UnaryExpr(u) => folder.fold_unary(expr, u),
+        Expr::Parend(p) => folder.fold_parend(expr, p),
+        Expr::Bracketed(b) => folder.fold_bracketed(expr, b),
+        Expr::Call(c) => folder.fold_call(expr, c),
+        Expr::Interval(i) => folder.fold_interval(expr, i),
+    }
+}
+
+/// Folds the operands of a binary expression, rebuilding it if any operand changed.
+pub fn descend_binary<F: ExprFolder>(
+    folder: &mut F,
+    expr: &RcExpr,
+    binary: &BinaryExpr<RcExpr>,
+) -> RcExpr {
+    let lhs = folder.fold_expr(&binary.lhs);
+    let rhs = folder.fold_expr(&binary.rhs);
+    if unchanged(&lhs, &binary.lhs) && unchanged(&rhs, &binary.rhs) {
+        return expr.clone();
+    }
+    rc_expr!(
+        Expr::BinaryExpr(BinaryExpr {
+            op: binary.op,
+            lhs,
+            rhs
+        }),
+        expr.span
+    )
+}
+
+/// Folds the operand of a unary expression, rebuilding it if the operand changed.
+pub fn descend_unary<F: ExprFolder>(
+    folder: &mut F,
+    expr: &RcExpr,
+    unary: &UnaryExpr<RcExpr>,
+) -> RcExpr {
+    let rhs = folder.fold_expr(&unary.rhs);
+    if unchanged(&rhs, &unary.rhs) {
+        return expr.clone();
+    }
+    rc_expr!(Expr::UnaryExpr(UnaryExpr { op: unary.op, rhs }), expr.span)
+}
+
+/// Folds the inner expression of a parenthesized expression, rebuilding it if the inner expression
+/// changed.
+pub fn descend_parend<F: ExprFolder>(folder: &mut F, expr: &RcExpr, inner: &RcExpr) -> RcExpr {
+    let folded = folder.fold_expr(inner);
+    if unchanged(&folded, inner) {
+        return expr.clone();
+    }
+    rc_expr!(Expr::Parend(folded), expr.span)
+}
+
+/// Folds the inner expression of a bracketed expression, rebuilding it if the inner expression
+/// changed.
+pub fn descend_bracketed<F: ExprFolder>(folder: &mut F, expr: &RcExpr, inner: &RcExpr) -> RcExpr {
+    let folded = folder.fold_expr(inner);
+    if unchanged(&folded, inner) {
+        return expr.clone();
+    }
+    rc_expr!(Expr::Bracketed(folded), expr.span)
+}
+
+/// Folds the arguments of a function call, rebuilding it if any argument changed.
+pub fn descend_call<F: ExprFolder>(folder: &mut F, expr: &RcExpr, call: &Call) -> RcExpr {
+    let args: Vec<_> = call.args.iter().map(|arg| folder.fold_expr(arg)).collect();
+    if args
+        .iter()
+        .zip(call.args.iter())
+        .all(|(a, b)| unchanged(a, b))
+    {
+        return expr.clone();
+    }
+    rc_expr!(
+        Expr::Call(Call {
+            func: call.func,
+            args
+        }),
+        expr.span
+    )
+}
+
+/// Folds the bounds of an interval, rebuilding it if either bound changed.
+pub fn descend_interval<F: ExprFolder>(
+    folder: &mut F,
+    expr: &RcExpr,
+    interval: &Interval,
+) -> RcExpr {
+    let lo = folder.fold_expr(&interval.lo);
+    let hi = folder.fold_expr(&interval.hi);
+    if unchanged(&lo, &interval.lo) && unchanged(&hi, &interval.hi) {
+        return expr.clone();
+    }
+    rc_expr!(Expr::Interval(Interval { lo, hi }), expr.span)
+}
+
+/// Returns `true` iff `folded` is `original`, at the same span.
+fn unchanged(folded: &RcExpr, original: &RcExpr) -> bool {
+    folded.addr() == original.addr() && folded.span == original.span
+}
+
+/// Describes an [expression](super::Expr) folder, which rebuilds an expression from the folds of
+/// its parts.
+///
+/// Each method receives the expression being folded along with its node. By default, leaves fold
+/// to themselves and other expressions fold their children; an expression whose children all fold
+/// to themselves is returned as-is rather than rebuilt, so a folder shares every subtree it does
+/// not change with the original expression.
+pub trait ExprFolder: Sized {
+    /// Folds an expression.
+    fn fold_expr(&mut self, expr: &RcExpr) -> RcExpr {
+        descend_expr(self, expr)
+    }
+
+    /// Folds a constant.
+    fn fold_const(&mut self, expr: &RcExpr, _konst: f64) -> RcExpr {
+        expr.clone()
+    }
+
+    /// Folds a variable.
+    fn fold_var(&mut self, expr: &RcExpr, _var: InternedStr) -> RcExpr {
+        expr.clone()
+    }
+
+    /// Folds a binary expression.
+    fn fold_binary(&mut self, expr: &RcExpr, binary: &BinaryExpr<RcExpr>) -> RcExpr {
+        descend_binary(self, expr, binary)
+    }
+
+    /// Folds a unary expression.
+    fn fold_unary(&mut self, expr: &RcExpr, unary: &UnaryExpr<RcExpr>) -> RcExpr {
+        descend_unary(self, expr, unary)
+    }
+
+    /// Folds a parenthesized expression.
+    fn fold_parend(&mut self, expr: &RcExpr, inner: &RcExpr) -> RcExpr {
+        descend_parend(self, expr, inner)
+    }
+
+    /// Folds a bracketed expression.
+    fn fold_bracketed(&mut self, expr: &RcExpr, inner: &RcExpr) -> RcExpr {
+        descend_bracketed(self, expr, inner)
+    }
+
+    /// Folds a function call.
+    fn fold_call(&mut self, expr: &RcExpr, call: &Call) -> RcExpr {
+        descend_call(self, expr, call)
+    }
+
+    /// Folds an interval.
+    fn fold_interval(&mut self, expr: &RcExpr, interval: &Interval) -> RcExpr {
+        descend_interval(self, expr, interval)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_expr;
+
+    struct Identity;
+    impl ExprFolder for Identity {}
+
+    /// Doubles constants equal to 2.
+    struct DoubleTwos;
+    impl ExprFolder for DoubleTwos {
+        fn fold_const(&mut self, expr: &RcExpr, konst: f64) -> RcExpr {
+            if (konst - 2.).abs() < f64::EPSILON {
+                rc_expr!(Expr::Const(4.), expr.span)
+            } else {
+                expr.clone()
+            }
+        }
+    }
+
+    fn children(expr: &RcExpr) -> Vec<RcExpr> {
+        match expr.as_ref() {
+            Expr::BinaryExpr(BinaryExpr { lhs, rhs, .. }) => vec![lhs.clone(), rhs.clone()],
+            Expr::UnaryExpr(UnaryExpr { rhs, .. }) => vec![rhs.clone()],
+            Expr::Parend(inner) | Expr::Bracketed(inner) => vec![inner.clone()],
+            Expr::Call(Call { args, .. }) => args.clone(),
+            Expr::Interval(Interval { lo, hi }) => vec![lo.clone(), hi.clone()],
+            Expr::Const(_) | Expr::Var(_) => vec![],
+        }
+    }
+
+    #[test]
+    fn identity_fold_shares_tree() {
+        for program in &[
+            "1",
+            "x",
+            "1 + x * 2",
+            "-[x]! ^ (3 - y)",
+            "max(x, (1 + 2) * y)",
+        ] {
+            let expr = parse_expr!(*program);
+            let folded = Identity.fold_expr(&expr);
+            assert_eq!(folded.addr(), expr.addr(), "{}", program);
+            assert_eq!(folded.span, expr.span, "{}", program);
+        }
+    }
+
+    #[test]
+    fn fold_shares_unchanged_subtrees() {
+        let expr = parse_expr!("(x + 1) * (y + 2)");
+        let folded = DoubleTwos.fold_expr(&expr);
+        assert_eq!(folded.to_string(), "(x + 1) * (y + 4)");
+        assert_ne!(folded.addr(), expr.addr());
+
+        let (original, folded) = (children(&expr), children(&folded));
+        assert_eq!(folded[0].addr(), original[0].addr());
+        assert_ne!(folded[1].addr(), original[1].addr());
+    }
+
+    #[test]
+    fn fold_call_shares_unchanged_args() {
+        let expr = parse_expr!("max(x, 2, y + 1)");
+        let folded = DoubleTwos.fold_expr(&expr);
+        assert_eq!(folded.to_string(), "max(x, 4, y + 1)");
+
+        let (original, folded) = (children(&expr), children(&folded));
+        assert_eq!(folded[0].addr(), original[0].addr());
+        assert_ne!(folded[1].addr(), original[1].addr());
+        assert_eq!(folded[2].addr(), original[2].addr());
+    }
+}
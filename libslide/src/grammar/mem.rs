@@ -4,12 +4,14 @@
 //! [Grammar]: super::Grammar
 
 use crate::emit::{Emit, EmitConfig};
-use crate::grammar::{Expr, ExprPat, Grammar};
+use crate::grammar::{BinaryExpr, BinaryOperator, Call, Expr, ExprPat, Grammar, Interval};
+use crate::grammar::{UnaryExpr, UnaryOperator};
 use crate::Span;
 
 use core::cmp::Ordering;
 use lasso::{Rodeo, Spur};
 use lazy_static::lazy_static;
+use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::{Arc, RwLock};
 
@@ -163,6 +165,10 @@ macro_rules! make_counted {
                 self.as_ref().emit_pretty(config)
             }
 
+            fn emit_pretty_wrapped(&self, config: EmitConfig, max_width: usize) -> String {
+                self.as_ref().emit_pretty_wrapped(config, max_width)
+            }
+
             fn emit_s_expression(&self, config: EmitConfig) -> String {
                 self.as_ref().emit_s_expression(config)
             }
@@ -170,6 +176,10 @@ macro_rules! make_counted {
             fn emit_latex(&self, config: EmitConfig) -> String {
                 self.as_ref().emit_latex(config)
             }
+
+            fn emit_json(&self, config: EmitConfig) -> String {
+                self.as_ref().emit_json_at(Some(self.span), config)
+            }
         }
 
         impl core::fmt::Display for $counted_struct {
@@ -211,6 +221,94 @@ make_counted! {
     rc_expr_pat, ExprPat, RcExprPat, EXPR_PAT_RODEO
 }
 
+impl RcExpr {
+    /// Returns the same underlying expression at a different span.
+    pub(crate) fn with_span(mut self, span: Span) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// Returns the address of the underlying expression, which identifies the structure of an
+    /// [interned](ExprInterner) expression among the expressions of its interner.
+    pub(crate) fn addr(&self) -> usize {
+        Arc::as_ptr(&self.expr) as usize
+    }
+}
+
+/// A hash-consing table of expressions, which shares one underlying expression between all
+/// expressions of the same structure it interns.
+///
+/// Nested expressions carry their own [span](crate::Span)s, so the nested expressions of an
+/// interned expression have the spans of the first expression of their structure that was
+/// interned. Consumers of spans should use the expressions of a program as they were parsed.
+#[derive(Default)]
+pub(crate) struct ExprInterner {
+    nodes: HashMap<NodeKey, Arc<Expr>>,
+}
+
+/// The structure of an expression whose nested expressions are interned, identifying nested
+/// expressions by the addresses of their underlying expressions.
+#[derive(PartialEq, Eq, Hash)]
+enum NodeKey {
+    Const(u64),
+    Var(InternedStr),
+    Binary(BinaryOperator, usize, usize),
+    Unary(UnaryOperator, usize),
+    Parend(usize),
+    Bracketed(usize),
+    Call(InternedStr, Vec<usize>),
+    Interval(usize, usize),
+}
+
+impl ExprInterner {
+    /// Interns `expr` and its nested expressions, returning an expression at the same span whose
+    /// underlying expression is shared with every expression of the same structure interned
+    /// before.
+    pub(crate) fn intern(&mut self, expr: &RcExpr) -> RcExpr {
+        let (key, node) = match expr.as_ref() {
+            Expr::Const(c) => (NodeKey::Const(c.to_bits()), Expr::Const(*c)),
+            Expr::Var(v) => (NodeKey::Var(*v), Expr::Var(*v)),
+            Expr::BinaryExpr(BinaryExpr { op, lhs, rhs }) => {
+                let (lhs, rhs) = (self.intern(lhs), self.intern(rhs));
+                let key = NodeKey::Binary(*op, lhs.addr(), rhs.addr());
+                (key, Expr::BinaryExpr(BinaryExpr { op: *op, lhs, rhs }))
+            }
+            Expr::UnaryExpr(UnaryExpr { op, rhs }) => {
+                let rhs = self.intern(rhs);
+                let key = NodeKey::Unary(*op, rhs.addr());
+                (key, Expr::UnaryExpr(UnaryExpr { op: *op, rhs }))
+            }
+            Expr::Parend(inner) => {
+                let inner = self.intern(inner);
+                (NodeKey::Parend(inner.addr()), Expr::Parend(inner))
+            }
+            Expr::Bracketed(inner) => {
+                let inner = self.intern(inner);
+                (NodeKey::Bracketed(inner.addr()), Expr::Bracketed(inner))
+            }
+            Expr::Call(Call { func, args }) => {
+                let args: Vec<_> = args.iter().map(|arg| self.intern(arg)).collect();
+                let key = NodeKey::Call(*func, args.iter().map(RcExpr::addr).collect());
+                (key, Expr::Call(Call { func: *func, args }))
+            }
+            Expr::Interval(Interval { lo, hi }) => {
+                let (lo, hi) = (self.intern(lo), self.intern(hi));
+                let key = NodeKey::Interval(lo.addr(), hi.addr());
+                (key, Expr::Interval(Interval { lo, hi }))
+            }
+        };
+        let expr_node = self
+            .nodes
+            .entry(key)
+            .or_insert_with(|| Arc::new(node))
+            .clone();
+        RcExpr {
+            expr: expr_node,
+            span: expr.span,
+        }
+    }
+}
+
 impl RcExpression for RcExpr {
     type Inner = Expr;
 
@@ -303,3 +401,66 @@ impl RcExpression for RcExprPat {
         self.span
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ExprInterner;
+    use crate::grammar::*;
+    use crate::parse_stmt;
+
+    /// Interns the right-hand sides of the assignments in `program` with one interner.
+    fn intern_rhs(program: &str) -> Vec<RcExpr> {
+        let mut interner = ExprInterner::default();
+        parse_stmt!(program)
+            .into_iter()
+            .map(|stmt| match stmt.kind {
+                StmtKind::Assignment(asgn) => interner.intern(&asgn.rhs),
+                StmtKind::Expr(_) => unreachable!(),
+            })
+            .collect()
+    }
+
+    fn binary(expr: &RcExpr) -> &BinaryExpr<RcExpr> {
+        match expr.as_ref() {
+            Expr::BinaryExpr(binary) => binary,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn shares_subexpressions_across_statements() {
+        let exprs = intern_rhs("a = (x + 1) * 2\nb = 3 * (x+1)\nc = (x + 1) * 2");
+        let (a, b, c) = (binary(&exprs[0]), binary(&exprs[1]), binary(&exprs[2]));
+        assert_eq!(a.lhs.addr(), b.rhs.addr());
+        assert_eq!(exprs[0].addr(), exprs[2].addr());
+        assert_ne!(exprs[0].addr(), exprs[1].addr());
+        // Interning keeps the spans of the interned expressions themselves.
+        assert_eq!(b.rhs.span, (24, 29).into());
+        assert_eq!(exprs[2].span, (34, 45).into());
+        assert_eq!(a.rhs.addr(), c.rhs.addr());
+    }
+
+    #[test]
+    fn shares_within_a_statement() {
+        let exprs = intern_rhs("a = max(y ^ 2, -(y ^ 2))");
+        let args = match exprs[0].as_ref() {
+            Expr::Call(Call { args, .. }) => args,
+            _ => unreachable!(),
+        };
+        match args[1].as_ref() {
+            Expr::UnaryExpr(UnaryExpr { rhs, .. }) => match rhs.as_ref() {
+                Expr::Parend(inner) => assert_eq!(inner.addr(), args[0].addr()),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn distinguishes_structures() {
+        let exprs = intern_rhs("a = x - 1\nb = 1 - x\nc = x - 1.5\nd = (x - 1)");
+        assert_ne!(exprs[0].addr(), exprs[1].addr());
+        assert_ne!(exprs[0].addr(), exprs[2].addr());
+        assert_ne!(exprs[0].addr(), exprs[3].addr());
+    }
+}
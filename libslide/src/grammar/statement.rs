@@ -1,18 +1,46 @@
 use super::*;
 use crate::Span;
 
+use std::sync::Arc;
+
 /// A list of statements in a slide program.
-#[derive(Clone, Debug)]
+///
+/// Statements are separated by newlines or `;`. A statement continues on the next line if its line
+/// ends with a binary operator, like `1 +`, or an unclosed delimiter, like `(1`, or if the next line
+/// begins with a binary operator, like `+ 2`.
+#[derive(Clone)]
 pub struct StmtList {
     /// The list of statements.
     list: Vec<Stmt>,
+    /// The program the statements were parsed from, which their spans point into, if known.
+    pub(crate) source: Option<Arc<str>>,
 }
 
 impl Grammar for StmtList {}
 
+// The source of the statements is not part of their debug form, which describes their structure.
+impl std::fmt::Debug for StmtList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StmtList")
+            .field("list", &self.list)
+            .finish()
+    }
+}
+
 impl StmtList {
     pub(crate) fn new(list: Vec<Stmt>) -> Self {
-        Self { list }
+        Self { list, source: None }
+    }
+
+    /// Returns the statements as parsed from the program `source`.
+    pub(crate) fn with_source(mut self, source: &str) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Returns the program the statements were parsed from, or an empty string if it is not known.
+    pub(crate) fn source(&self) -> &str {
+        self.source.as_deref().unwrap_or("")
     }
 
     pub(crate) fn iter(&self) -> std::slice::Iter<'_, Stmt> {
@@ -32,6 +60,12 @@ impl Iterator for StmtListIterator {
     }
 }
 
+impl std::iter::FromIterator<Stmt> for StmtList {
+    fn from_iter<I: IntoIterator<Item = Stmt>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
 impl IntoIterator for StmtList {
     type Item = Stmt;
     type IntoIter = StmtListIterator;
@@ -61,7 +95,13 @@ pub enum StmtKind {
     /// x = 1 + 1
     /// ```
     ///
-    /// binds the expression "1 + 1" to "x".
+    /// binds the expression "1 + 1" to "x". An assignment to a call with one parameter, like
+    ///
+    /// ```text
+    /// f(x) = x + 1
+    /// ```
+    ///
+    /// defines the function "f", which can be called by later statements.
     Assignment(Assignment),
 }
 impl From<RcExpr> for StmtKind {
@@ -175,18 +215,45 @@ pub enum Expr {
     Parend(RcExpr),
     /// An expression wrapped in brackets.
     Bracketed(RcExpr),
+    /// A call to a builtin or user-defined function.
+    Call(Call),
+    /// An interval between two bounds.
+    Interval(Interval),
 }
 
 impl Grammar for Expr {}
 
 impl Expr {
-    pub(crate) fn complexity(&self) -> u8 {
+    /// Returns the complexity of the expression, which is the number of nodes in its tree.
+    /// Constants and variables have a complexity of `1`, and every operation, grouping, and call
+    /// adds `1` to the complexity of its operands. For example, `(x + 1) * 2` has a complexity of
+    /// `6`.
+    ///
+    /// This is the measure evaluation minimizes under the default
+    /// [objective](crate::Objective::OpCount).
+    pub fn complexity(&self) -> usize {
         1 + match self {
             Self::Const(_) => 0,
             Self::Var(_) => 0,
             Self::BinaryExpr(BinaryExpr { lhs, rhs, .. }) => lhs.complexity() + rhs.complexity(),
             Self::UnaryExpr(UnaryExpr { rhs, .. }) => rhs.complexity(),
             Self::Parend(expr) | Self::Bracketed(expr) => expr.complexity(),
+            Self::Call(Call { args, .. }) => args.iter().map(|arg| arg.complexity()).sum(),
+            Self::Interval(Interval { lo, hi }) => lo.complexity() + hi.complexity(),
+        }
+    }
+
+    /// Returns the depth of the expression's tree. Constants and variables have a depth of `1`.
+    /// For example, `(x + 1) * 2` has a depth of `4`.
+    pub fn depth(&self) -> usize {
+        1 + match self {
+            Self::Const(_) => 0,
+            Self::Var(_) => 0,
+            Self::BinaryExpr(BinaryExpr { lhs, rhs, .. }) => lhs.depth().max(rhs.depth()),
+            Self::UnaryExpr(UnaryExpr { rhs, .. }) => rhs.depth(),
+            Self::Parend(expr) | Self::Bracketed(expr) => expr.depth(),
+            Self::Call(Call { args, .. }) => args.iter().map(|arg| arg.depth()).max().unwrap_or(0),
+            Self::Interval(Interval { lo, hi }) => lo.depth().max(hi.depth()),
         }
     }
 
@@ -210,6 +277,42 @@ impl Expr {
     pub fn is_var(&self) -> bool {
         matches!(self, Self::Var(_))
     }
+
+    /// Returns `true` iff a variable appears anywhere in the expression's tree, including in the
+    /// arguments of calls. For example, `(1 + x) * 2` contains a variable.
+    pub fn contains_variable(&self) -> bool {
+        match self {
+            Self::Const(_) => false,
+            Self::Var(_) => true,
+            Self::BinaryExpr(BinaryExpr { lhs, rhs, .. }) => {
+                lhs.contains_variable() || rhs.contains_variable()
+            }
+            Self::UnaryExpr(UnaryExpr { rhs, .. }) => rhs.contains_variable(),
+            Self::Parend(expr) | Self::Bracketed(expr) => expr.contains_variable(),
+            Self::Call(Call { args, .. }) => args.iter().any(|arg| arg.contains_variable()),
+            Self::Interval(Interval { lo, hi }) => lo.contains_variable() || hi.contains_variable(),
+        }
+    }
+
+    /// Returns `true` iff the expression's tree is built only of constants and operations on them,
+    /// like `(1 + 2) * 3!`, so that it has a numeric value. Unlike
+    /// [`is_const`](crate::RcExpression::is_const), which only checks whether the
+    /// expression is itself a constant, this walks the whole tree.
+    ///
+    /// Calls are not constant expressions, even if their arguments are, since they may be calls of
+    /// functions that are not defined. Intervals are not constant expressions either, since they
+    /// have no single numeric value.
+    pub fn is_constant_expr(&self) -> bool {
+        match self {
+            Self::Const(_) => true,
+            Self::Var(_) | Self::Call(_) | Self::Interval(_) => false,
+            Self::BinaryExpr(BinaryExpr { lhs, rhs, .. }) => {
+                lhs.is_constant_expr() && rhs.is_constant_expr()
+            }
+            Self::UnaryExpr(UnaryExpr { rhs, .. }) => rhs.is_constant_expr(),
+            Self::Parend(expr) | Self::Bracketed(expr) => expr.is_constant_expr(),
+        }
+    }
 }
 
 impl Eq for Expr {}
@@ -230,7 +333,9 @@ impl Ord for Expr {
             (Self::BinaryExpr(a), Self::BinaryExpr(b)) => a.cmp(b),
             (Self::Parend(a), Self::Parend(b)) => a.cmp(b),
             (Self::Bracketed(a), Self::Bracketed(b)) => a.cmp(b),
-            // Order: vars, consts, unary, binary, paren, brackets
+            (Self::Call(a), Self::Call(b)) => a.cmp(b),
+            (Self::Interval(a), Self::Interval(b)) => a.cmp(b),
+            // Order: vars, consts, unary, binary, paren, brackets, calls, intervals
             (Self::Const(_), Self::Var(_))
             | (Self::UnaryExpr(_), Self::Const(_))
             | (Self::UnaryExpr(_), Self::Var(_))
@@ -245,12 +350,21 @@ impl Ord for Expr {
             | (Self::Bracketed(_), Self::BinaryExpr(_))
             | (Self::Bracketed(_), Self::UnaryExpr(_))
             | (Self::Bracketed(_), Self::Const(_))
-            | (Self::Bracketed(_), Self::Var(_)) => Ordering::Greater,
+            | (Self::Bracketed(_), Self::Var(_))
+            | (Self::Call(_), Self::Bracketed(_))
+            | (Self::Call(_), Self::Parend(_))
+            | (Self::Call(_), Self::BinaryExpr(_))
+            | (Self::Call(_), Self::UnaryExpr(_))
+            | (Self::Call(_), Self::Const(_))
+            | (Self::Call(_), Self::Var(_))
+            | (Self::Interval(_), _) => Ordering::Greater,
             (Self::Var(_), _)
             | (Self::Const(_), _)
             | (Self::UnaryExpr(_), _)
             | (Self::BinaryExpr(_), _)
-            | (Self::Parend(_), _) => Ordering::Less,
+            | (Self::Parend(_), _)
+            | (Self::Bracketed(_), _)
+            | (Self::Call(_), _) => Ordering::Less,
         }
     }
 }
@@ -270,12 +384,125 @@ impl core::hash::Hash for Expr {
             UnaryExpr(e) => e.hash(state),
             e @ Parend(_) => e.to_string().hash(state),
             e @ Bracketed(_) => e.to_string().hash(state),
+            Call(c) => c.hash(state),
+            Interval(i) => i.hash(state),
         }
     }
 }
 
+/// A call to a builtin or user-defined function, like `max(a, b)` or `f(x)`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Call {
+    /// The name of the called function.
+    pub func: InternedStr,
+    /// The arguments passed to the function.
+    pub args: Vec<RcExpr>,
+}
+
+impl Call {
+    /// Names of the builtin functions that can be called in a slide program, and the number of
+    /// arguments each takes.
+    pub const BUILTINS: &'static [(&'static str, usize)] = &[
+        ("min", 2),
+        ("max", 2),
+        ("clamp", 3),
+        ("sqrt", 1),
+        ("root", 2),
+        ("sum", 4),
+    ];
+
+    /// Returns `true` iff `name` is the name of a builtin function.
+    pub fn is_builtin(name: &str) -> bool {
+        Self::builtin_arity(name).is_some()
+    }
+
+    /// Returns the number of arguments the builtin function `name` takes, or `None` if `name` is
+    /// not a builtin function.
+    pub fn builtin_arity(name: &str) -> Option<usize> {
+        Self::BUILTINS
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .map(|(_, arity)| *arity)
+    }
+}
+
+impl PartialOrd for Call {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Call {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.func
+            .get()
+            .cmp(&other.func.get())
+            .then_with(|| self.args.cmp(&other.args))
+    }
+}
+
+/// An interval of the numbers between two bounds, like `[a, b]`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Interval {
+    /// The lower bound of the interval.
+    pub lo: RcExpr,
+    /// The upper bound of the interval.
+    pub hi: RcExpr,
+}
+
+impl PartialOrd for Interval {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Interval {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.lo.cmp(&other.lo).then_with(|| self.hi.cmp(&other.hi))
+    }
+}
+
 impl From<f64> for Expr {
     fn from(f: f64) -> Self {
         Self::Const(f)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::parse_expr;
+
+    #[test]
+    fn contains_variable() {
+        for (expr, contains) in &[
+            ("1", false),
+            ("x", true),
+            ("(1 + 2) * [3 - 1]", false),
+            ("(1 + x) * 2", true),
+            ("-(2 ^ y)!", true),
+            ("max(1, 2)", false),
+            ("max(1, a)", true),
+        ] {
+            assert_eq!(
+                parse_expr!(*expr).contains_variable(),
+                *contains,
+                "{}",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn is_constant_expr() {
+        for (expr, constant) in &[
+            ("1", true),
+            ("x", false),
+            ("(1 + 2) * [3 - 1]", true),
+            ("-(2 ^ 3)! % 4 / 5", true),
+            ("(1 + x) * 2", false),
+            ("max(1, 2)", false),
+        ] {
+            assert_eq!(parse_expr!(*expr).is_constant_expr(), *constant, "{}", expr);
+        }
+    }
+}
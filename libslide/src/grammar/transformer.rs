@@ -17,6 +17,8 @@ pub trait ExpressionTransformer<'a> {
             Expr::UnaryExpr(u) => self.transform_unary(u, expr.span),
             Expr::Parend(p) => self.transform_parend(p, expr.span),
             Expr::Bracketed(b) => self.transform_bracketed(b, expr.span),
+            Expr::Call(c) => self.transform_call(c, expr.span),
+            Expr::Interval(i) => self.transform_interval(i, expr.span),
         }
     }
 
@@ -64,4 +66,24 @@ pub trait ExpressionTransformer<'a> {
     fn transform_bracketed(&self, expr: &'a RcExpr, span: Span) -> RcExpr {
         rc_expr!(Expr::Bracketed(self.transform(expr)), span)
     }
+
+    fn transform_call(&self, call: &'a Call, span: Span) -> RcExpr {
+        rc_expr!(
+            Expr::Call(Call {
+                func: call.func,
+                args: call.args.iter().map(|arg| self.transform(arg)).collect(),
+            }),
+            span
+        )
+    }
+
+    fn transform_interval(&self, interval: &'a Interval, span: Span) -> RcExpr {
+        rc_expr!(
+            Expr::Interval(Interval {
+                lo: self.transform(&interval.lo),
+                hi: self.transform(&interval.hi),
+            }),
+            span
+        )
+    }
 }
@@ -1,4 +1,7 @@
 //! Traits for visiting slide grammar trees.
+//!
+//! Statement and expression pattern visitors descend down trees with `descend_*` functions, and
+//! expression visitors walk down trees with `walk_*` functions.
 
 use super::*;
 use crate::Span;
@@ -39,6 +42,8 @@ pub fn descend_expr<'a, V: StmtVisitor<'a>>(visitor: &mut V, expr: &'a RcExpr) {
         Expr::UnaryExpr(u) => visitor.visit_unary(u, expr.span),
         Expr::Parend(p) => visitor.visit_parend(p, expr.span),
         Expr::Bracketed(b) => visitor.visit_bracketed(b, expr.span),
+        Expr::Call(c) => visitor.visit_call(c, expr.span),
+        Expr::Interval(i) => visitor.visit_interval(i, expr.span),
     }
 }
 
@@ -73,6 +78,23 @@ pub fn descend_bracketed<'a, V: StmtVisitor<'a>>(visitor: &mut V, expr: &'a RcEx
     visitor.visit_expr(expr);
 }
 
+/// Descends down a function call.
+pub fn descend_call<'a, V: StmtVisitor<'a>>(visitor: &mut V, call: &'a Call, _span: Span) {
+    for arg in call.args.iter() {
+        visitor.visit_expr(arg);
+    }
+}
+
+/// Descends down an interval.
+pub fn descend_interval<'a, V: StmtVisitor<'a>>(
+    visitor: &mut V,
+    interval: &'a Interval,
+    _span: Span,
+) {
+    visitor.visit_expr(&interval.lo);
+    visitor.visit_expr(&interval.hi);
+}
+
 /// Describes a [statement list](super::StmtList) visitor.
 pub trait StmtVisitor<'a>: Sized {
     /// Visits a statement list.
@@ -134,6 +156,16 @@ pub trait StmtVisitor<'a>: Sized {
     fn visit_bracketed(&mut self, expr: &'a RcExpr, span: Span) {
         descend_bracketed(self, expr, span);
     }
+
+    /// Visits a function call.
+    fn visit_call(&mut self, call: &'a Call, span: Span) {
+        descend_call(self, call, span);
+    }
+
+    /// Visits an interval.
+    fn visit_interval(&mut self, interval: &'a Interval, span: Span) {
+        descend_interval(self, interval, span);
+    }
 }
 
 /// Descends down an expression pattern.
@@ -232,3 +264,114 @@ pub trait ExprPatVisitor<'a>: Sized {
         descend_bracketed_expr_pat(self, expr, span);
     }
 }
+
+/// Walks down an expression.
+pub fn walk_expr<'a, V: ExprVisitor<'a>>(visitor: &mut V, expr: &'a RcExpr) {
+    match expr.as_ref() {
+        Expr::Const(k) => visitor.visit_const(k, expr.span),
+        Expr::Var(v) => visitor.visit_var(v, expr.span),
+        Expr::BinaryExpr(b) => visitor.visit_binary(b, expr.span),
+        Expr::UnaryExpr(u) => visitor.visit_unary(u, expr.span),
+        Expr::Parend(p) => visitor.visit_parend(p, expr.span),
+        Expr::Bracketed(b) => visitor.visit_bracketed(b, expr.span),
+        Expr::Call(c) => visitor.visit_call(c, expr.span),
+        Expr::Interval(i) => visitor.visit_interval(i, expr.span),
+    }
+}
+
+/// Walks down a binary expression.
+pub fn walk_binary<'a, V: ExprVisitor<'a>>(
+    visitor: &mut V,
+    expr: &'a BinaryExpr<RcExpr>,
+    _span: Span,
+) {
+    visitor.visit_expr(&expr.lhs);
+    visitor.visit_binary_op(expr.op);
+    visitor.visit_expr(&expr.rhs);
+}
+
+/// Walks down a unary expression.
+pub fn walk_unary<'a, V: ExprVisitor<'a>>(
+    visitor: &mut V,
+    expr: &'a UnaryExpr<RcExpr>,
+    _span: Span,
+) {
+    visitor.visit_unary_op(expr.op);
+    visitor.visit_expr(&expr.rhs);
+}
+
+/// Walks down a parenthesized expression.
+pub fn walk_parend<'a, V: ExprVisitor<'a>>(visitor: &mut V, expr: &'a RcExpr, _span: Span) {
+    visitor.visit_expr(expr);
+}
+
+/// Walks down a bracketed expression.
+pub fn walk_bracketed<'a, V: ExprVisitor<'a>>(visitor: &mut V, expr: &'a RcExpr, _span: Span) {
+    visitor.visit_expr(expr);
+}
+
+/// Walks down a function call.
+pub fn walk_call<'a, V: ExprVisitor<'a>>(visitor: &mut V, call: &'a Call, _span: Span) {
+    for arg in call.args.iter() {
+        visitor.visit_expr(arg);
+    }
+}
+
+/// Walks down an interval.
+pub fn walk_interval<'a, V: ExprVisitor<'a>>(visitor: &mut V, interval: &'a Interval, _span: Span) {
+    visitor.visit_expr(&interval.lo);
+    visitor.visit_expr(&interval.hi);
+}
+
+/// Describes an [expression](super::Expr) visitor.
+///
+/// Unlike a [`StmtVisitor`], an expression visitor only knows of expressions, and so can visit an
+/// expression without the statement it appears in.
+pub trait ExprVisitor<'a>: Sized {
+    /// Visits an expression.
+    fn visit_expr(&mut self, expr: &'a RcExpr) {
+        walk_expr(self, expr);
+    }
+
+    /// Visits a constant.
+    fn visit_const(&mut self, _konst: &'a f64, _span: Span) {}
+
+    /// Visits a variable.
+    fn visit_var(&mut self, _var: &'a InternedStr, _span: Span) {}
+
+    /// Visits a binary operator.
+    fn visit_binary_op(&mut self, _op: BinaryOperator) {}
+
+    /// Visits a binary expression.
+    fn visit_binary(&mut self, expr: &'a BinaryExpr<RcExpr>, span: Span) {
+        walk_binary(self, expr, span);
+    }
+
+    /// Visits a unary operator.
+    fn visit_unary_op(&mut self, _op: UnaryOperator) {}
+
+    /// Visits a unary expression.
+    fn visit_unary(&mut self, expr: &'a UnaryExpr<RcExpr>, span: Span) {
+        walk_unary(self, expr, span);
+    }
+
+    /// Visits a parenthesized expression.
+    fn visit_parend(&mut self, expr: &'a RcExpr, span: Span) {
+        walk_parend(self, expr, span);
+    }
+
+    /// Visits a bracketed expression.
+    fn visit_bracketed(&mut self, expr: &'a RcExpr, span: Span) {
+        walk_bracketed(self, expr, span);
+    }
+
+    /// Visits a function call.
+    fn visit_call(&mut self, call: &'a Call, span: Span) {
+        walk_call(self, call, span);
+    }
+
+    /// Visits an interval.
+    fn visit_interval(&mut self, interval: &'a Interval, span: Span) {
+        walk_interval(self, interval, span);
+    }
+}
@@ -132,6 +132,10 @@
 //! additional memory hit and provides several advantages, like skipping evaluation passes on identical
 //! expressions.
 //!
+//! Parsed programs keep a distinct node for each expression in the source, since each node carries
+//! the span it was parsed at. The evaluator interns the expressions of all statements of a program
+//! together, so that an expression repeated in several statements is evaluated only once.
+//!
 //! ## Glossary
 //!
 //! #### String rules
@@ -188,6 +192,11 @@
 //! | `$<name>` | A variable     |
 //! | `_<name>` | Any expression |
 //!
+//! In [string rules](#string-rules), an any pattern suffixed by `...`, like `_a...`, is a rest
+//! pattern. As an operand of a `+` or `*` chain, it matches the operands of the chain not matched
+//! by the chain's other operands, in any order. For example, `_a... + 0` matches `x + 0 + y`, with
+//! `_a...` matching `x + y`.
+//!
 //! A metavariable can match exactly one expression. This means that if `_a` matches `1 + 2`, all other
 //! references to `_a` must match `1 + 2` as well.
 //!
@@ -199,6 +208,30 @@
 //! | `#a + $a` | `10 + v` |
 //! | `_a / $a * _a` | `(9 + 1) / v * (9 + 1)` |
 //! | `_a + _b * $c * #d` | `(x ^ 2) + (5 / y) * w * 15` |
+//!
+//! ## Benchmarks
+//!
+//! libslide's benchmarks live in `libslide/benches` and use [criterion]. The `evaluate` benchmark
+//! measures the evaluation of small programs. The `pipeline` benchmark measures scanning, parsing,
+//! evaluation, and validation separately over larger programs: long additive chains, deep nesting,
+//! repeated subexpressions, polynomial divisions, and a document-sized program of many statements.
+//! The `gcd` and `gcd_poly` benchmarks measure math internals.
+//!
+//! All but the `evaluate` benchmark require the `benchmark-internals` feature, which exposes the
+//! internals they measure, like the validation phase of evaluation. From the `libslide`
+//! directory, run them with
+//!
+//! ```text
+//! cargo bench --features benchmark-internals
+//! ```
+//!
+//! or a single benchmark, like the pipeline, with
+//!
+//! ```text
+//! cargo bench --features benchmark-internals --bench pipeline
+//! ```
+//!
+//! [criterion]: https://docs.rs/criterion
 
 #![deny(warnings)]
 #![deny(missing_docs)]
@@ -209,10 +242,11 @@
 #[macro_use]
 mod grammar;
 pub use grammar::collectors;
+pub use grammar::fold;
 pub use grammar::visit;
 pub use grammar::{
-    Assignment, BinaryExpr, Expr, ExprPat, Grammar, InternedStr, RcExpr, RcExpression, Stmt,
-    StmtKind, StmtList, UnaryExpr,
+    Assignment, BinaryExpr, BinaryOperator, Expr, ExprPat, Grammar, InternedStr, RcExpr,
+    RcExpression, Stmt, StmtKind, StmtList, UnaryExpr, UnaryOperator,
 };
 
 mod common;
@@ -222,9 +256,11 @@ pub mod diagnostics;
 
 pub mod scanner;
 pub use scanner::scan;
+pub use scanner::tokenize;
 pub(crate) use scanner::ScanErrors;
 pub use scanner::ScanResult;
 pub use scanner::Token;
+pub use scanner::{SpannedTokenInfo, TokenClass};
 
 mod parser;
 pub use parser::parse_expression_pattern;
@@ -233,26 +269,41 @@ pub(crate) use parser::ParseErrors;
 pub use parser::ParseResult;
 
 mod linter;
+pub use linter::lint;
 pub use linter::lint_expr_pat;
 pub use linter::lint_stmt;
 pub(crate) use linter::LintConfig;
+pub use linter::LintMode;
 
 mod partial_evaluator;
 pub use partial_evaluator::build_rules;
 pub use partial_evaluator::evaluate;
 pub use partial_evaluator::evaluate_expr;
+pub use partial_evaluator::evaluate_expression;
+pub use partial_evaluator::evaluate_profiled;
+pub use partial_evaluator::evaluate_with_rule_set;
+pub use partial_evaluator::evaluate_with_rules;
+pub use partial_evaluator::expand_variables;
+pub use partial_evaluator::normalize_stmts;
+pub use partial_evaluator::substitute;
 pub use partial_evaluator::EvaluationResult;
 pub(crate) use partial_evaluator::PartialEvaluatorErrors;
+pub use partial_evaluator::RuleProfile;
+#[cfg(feature = "benchmark-internals")]
+pub use partial_evaluator::_validate;
 
 pub mod evaluator_rules;
+pub use evaluator_rules::match_pattern;
 
 mod math;
 pub use math::Poly; // Since poly! is exposed, expose Poly too.
 
 pub(crate) mod emit;
+pub use emit::check_latex;
 pub use emit::Emit;
 pub use emit::EmitConfig;
 pub use emit::EmitFormat;
+pub use emit::UnknownEmitOption;
 
 #[cfg(feature = "benchmark-internals")]
 pub use math::*;
@@ -20,6 +20,7 @@ use expr_pat::*;
 
 use crate::diagnostics::{Diagnostic, DiagnosticRecord, DiagnosticRegistry};
 use crate::grammar::{Grammar, RcExprPat, StmtList};
+use crate::{parse_expression_pattern, parse_statements, scan, ParseResult, ScanResult};
 
 /// Describes a slide program linter. A `Linter` is implemented on a slide [Grammar].
 ///
@@ -46,8 +47,12 @@ impl Default for LintConfig {
                 StmtLintRule::UnarySeriesLinter,
                 StmtLintRule::RedundantNestingLinter,
                 StmtLintRule::HomogenousAssignmentLinter,
+                StmtLintRule::AmbiguousContinuationLinter,
+            ],
+            expr_pat_linters: vec![
+                ExprPatLintRule::SimilarNamesLinter,
+                ExprPatLintRule::SingleUsePatternLinter,
             ],
-            expr_pat_linters: vec![ExprPatLintRule::SimilarNamesLinter],
         }
     }
 }
@@ -81,3 +86,74 @@ pub fn lint_expr_pat(expr_pat: &RcExprPat, source: &str) -> Vec<Diagnostic> {
     }
     diags
 }
+
+/// What a slide program is parsed as before it is [linted](lint).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LintMode {
+    /// The program is a [statement list](crate::grammar::StmtList), linted with
+    /// [`lint_stmt`](lint_stmt).
+    Stmt,
+    /// The program is an [expression pattern](crate::grammar::ExprPat), linted with
+    /// [`lint_expr_pat`](lint_expr_pat).
+    ExprPat,
+}
+
+/// Scans, parses, and lints a slide program, returning the diagnostics of all three stages in that
+/// order. Lints are run even if the program has scan or parse errors.
+pub fn lint(program: &str, mode: LintMode) -> Vec<Diagnostic> {
+    let ScanResult {
+        tokens,
+        mut diagnostics,
+    } = scan(program);
+    match mode {
+        LintMode::Stmt => {
+            let ParseResult {
+                program: stmt_list,
+                diagnostics: parse_diagnostics,
+            } = parse_statements(tokens, program);
+            diagnostics.extend(parse_diagnostics);
+            diagnostics.extend(lint_stmt(&stmt_list, program));
+        }
+        LintMode::ExprPat => {
+            let ParseResult {
+                program: expr_pat,
+                diagnostics: parse_diagnostics,
+            } = parse_expression_pattern(tokens);
+            diagnostics.extend(parse_diagnostics);
+            diagnostics.extend(lint_expr_pat(&expr_pat, program));
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lint, LintMode};
+
+    fn codes(program: &str, mode: LintMode) -> Vec<&'static str> {
+        lint(program, mode).into_iter().map(|d| d.code).collect()
+    }
+
+    #[test]
+    fn lint_stmt_mode() {
+        assert_eq!(codes("a = --1", LintMode::Stmt), vec!["L0002"]);
+        assert!(codes("a = 1", LintMode::Stmt).is_empty());
+    }
+
+    #[test]
+    fn lint_expr_pat_mode() {
+        assert_eq!(codes("$a + $a", LintMode::ExprPat), Vec::<&str>::new());
+        assert_eq!(codes("$a + #a + $a + #a", LintMode::ExprPat), vec!["L0003"]);
+        assert_eq!(
+            codes("$a + #a", LintMode::ExprPat),
+            vec!["L0003", "L0006", "L0006"]
+        );
+    }
+
+    #[test]
+    fn lint_with_parse_errors() {
+        let codes = codes("--1 + ", LintMode::Stmt);
+        assert_eq!(codes[0], "P0002");
+        assert!(codes.contains(&"L0002"));
+    }
+}
@@ -3,6 +3,9 @@
 mod similar_names;
 use similar_names::*;
 
+mod single_use;
+use single_use::*;
+
 use super::{DiagnosticRecord, LintRule};
 use crate::diagnostics::Diagnostic;
 use crate::grammar::RcExprPat;
@@ -33,4 +36,5 @@ macro_rules! define_expr_pat_lints {
 
 define_expr_pat_lints! {
     SimilarNamesLinter,
+    SingleUsePatternLinter,
 }
@@ -0,0 +1,91 @@
+explain_lint! {
+    ///The single use lint detects patterns used only once in an expression pattern.
+    ///
+    ///A pattern used exactly once is often a typo of another pattern. For example, in the
+    ///following expression pattern
+    ///
+    ///```text
+    ///$a + _a - $a
+    ///```
+    ///
+    ///"_a" is likely meant to be "$a". When similarly named patterns (patterns with the same name,
+    ///ignoring their kind and case) are present in the expression, they are listed alongside the
+    ///warning.
+    ///
+    ///Patterns that are meant to be used once can be ignored, or this lint can be allowed.
+    L0006: SingleUsePatternLinter
+}
+
+use crate::common::Span;
+use crate::diagnostics::Diagnostic;
+use crate::grammar::collectors::collect_pat_names;
+use crate::grammar::visit::ExprPatVisitor;
+use crate::grammar::*;
+use crate::linter::LintRule;
+
+use std::collections::BTreeMap;
+
+#[derive(Default)]
+pub struct SingleUsePatternLinter<'a> {
+    /// Pattern names, including their prefix, and the spans they are used at.
+    uses: BTreeMap<&'a str, Vec<Span>>,
+}
+
+impl<'a> SingleUsePatternLinter<'a> {
+    fn check_uses(self, expr_pat: &RcExprPat) -> Vec<Diagnostic> {
+        let names = collect_pat_names(expr_pat);
+        let mut single_uses: Vec<_> = self
+            .uses
+            .iter()
+            .filter(|(_, spans)| spans.len() == 1)
+            .map(|(pat, spans)| (*pat, spans[0]))
+            .collect();
+        single_uses.sort_by_key(|(_, span)| *span);
+
+        single_uses
+            .into_iter()
+            .map(|(pat, span)| {
+                let mut similar: Vec<_> = names
+                    .iter()
+                    .filter(|name| **name != pat && name[1..].eq_ignore_ascii_case(&pat[1..]))
+                    .map(|name| format!("`{}`", name))
+                    .collect();
+                similar.sort();
+
+                let diag = Diagnostic::span_warn(
+                    span,
+                    format!("Pattern \"{}\" is used only once", pat),
+                    Self::CODE,
+                    "used only here".to_owned(),
+                );
+                if similar.is_empty() {
+                    diag
+                } else {
+                    diag.with_note(format!("similarly named patterns: {}", similar.join(", ")))
+                }
+            })
+            .collect()
+    }
+}
+
+impl<'a> visit::ExprPatVisitor<'a> for SingleUsePatternLinter<'a> {
+    fn visit_var_pat(&mut self, var_pat: &'a str, span: Span) {
+        self.uses.entry(var_pat).or_default().push(span);
+    }
+
+    fn visit_const_pat(&mut self, const_pat: &'a str, span: Span) {
+        self.uses.entry(const_pat).or_default().push(span);
+    }
+
+    fn visit_any_pat(&mut self, any_pat: &'a str, span: Span) {
+        self.uses.entry(any_pat).or_default().push(span);
+    }
+}
+
+impl<'a> LintRule<'a, RcExprPat> for SingleUsePatternLinter<'a> {
+    fn lint(expr_pat: &RcExprPat, _source: &'a str) -> Vec<Diagnostic> {
+        let mut linter = Self::default();
+        linter.visit_expr_pat(expr_pat);
+        linter.check_uses(expr_pat)
+    }
+}
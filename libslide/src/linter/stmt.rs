@@ -1,8 +1,10 @@
 //! Lints for a statement in a slide program.
 
+mod ambiguous_continuation;
 mod homogenous_assignment;
 mod redundant_nesting;
 mod unary_series;
+use ambiguous_continuation::*;
 use homogenous_assignment::*;
 use redundant_nesting::*;
 use unary_series::*;
@@ -39,4 +41,5 @@ define_stmt_lints! {
     UnarySeriesLinter,
     RedundantNestingLinter,
     HomogenousAssignmentLinter,
+    AmbiguousContinuationLinter,
 }
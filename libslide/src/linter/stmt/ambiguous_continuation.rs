@@ -0,0 +1,116 @@
+explain_lint! {
+    ///The ambiguous continuation lint detects lines that begin with a `+` or `-` operator and
+    ///continue the statement of the previous line.
+    ///
+    ///For example, in the following program
+    ///
+    ///```text
+    ///a = 1
+    ///- 2
+    ///```
+    ///
+    ///the second line may be intended as a separate statement `-2`, but slide parses it as a
+    ///continuation of the first line, assigning `a = 1 - 2`.
+    ///
+    ///A line that ends with an operator, like `a = 1 -`, or that is inside an unclosed delimiter
+    ///always continues on the next line, so it is suggested that multi-line expressions are
+    ///written that way. If the lines are separate statements, end the first line with `;`:
+    ///
+    ///```text
+    ///a = 1;
+    ///- 2
+    ///```
+    L0007: AmbiguousContinuationLinter
+}
+
+use crate::linter::LintRule;
+
+use crate::common::Span;
+use crate::diagnostics::{Autofix, Diagnostic, Edit};
+use crate::grammar::*;
+use visit::StmtVisitor;
+
+pub struct AmbiguousContinuationLinter<'a> {
+    source: &'a str,
+    /// The number of delimiters the visited expression is nested in. Lines inside a delimiter
+    /// always continue the expression.
+    nesting: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> AmbiguousContinuationLinter<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            nesting: 0,
+            diagnostics: vec![],
+        }
+    }
+
+    /// Warns if the operator of `expr`, a `+` or `-`, begins a line.
+    fn check_continuation(&mut self, expr: &BinaryExpr<RcExpr>) {
+        let lo = expr.lhs.span.hi;
+        if lo > expr.rhs.span.lo {
+            return;
+        }
+        let between = Span::from((lo, expr.rhs.span.lo)).over(self.source);
+        let op_offset = match between.find(&['+', '-'][..]) {
+            Some(offset) => offset,
+            None => return,
+        };
+        if !between[..op_offset].contains('\n') {
+            return;
+        }
+
+        let op = expr.op.to_string();
+        let span = Span::from((lo, lo + op_offset + 1));
+        self.diagnostics.push(
+            Diagnostic::span_warn(
+                span,
+                "Ambiguous line continuation",
+                Self::CODE,
+                format!("`{}` continues the previous line", op),
+            )
+            .with_help("if the lines are separate statements, end the first line with `;`")
+            .with_autofix(Autofix::maybe(
+                "join the lines",
+                Edit::Replace(format!(" {}", op)),
+            )),
+        );
+    }
+}
+
+impl<'a> StmtVisitor<'a> for AmbiguousContinuationLinter<'a> {
+    fn visit_binary(&mut self, expr: &'a BinaryExpr<RcExpr>, span: Span) {
+        if self.nesting == 0 && matches!(expr.op, BinaryOperator::Plus | BinaryOperator::Minus) {
+            self.check_continuation(expr);
+        }
+        visit::descend_binary(self, expr, span);
+    }
+
+    fn visit_parend(&mut self, expr: &'a RcExpr, span: Span) {
+        self.nesting += 1;
+        visit::descend_parend(self, expr, span);
+        self.nesting -= 1;
+    }
+
+    fn visit_bracketed(&mut self, expr: &'a RcExpr, span: Span) {
+        self.nesting += 1;
+        visit::descend_bracketed(self, expr, span);
+        self.nesting -= 1;
+    }
+
+    fn visit_call(&mut self, call: &'a Call, span: Span) {
+        self.nesting += 1;
+        visit::descend_call(self, call, span);
+        self.nesting -= 1;
+    }
+}
+
+impl<'a> LintRule<'a, StmtList> for AmbiguousContinuationLinter<'a> {
+    fn lint(stmt_list: &StmtList, source: &'a str) -> Vec<Diagnostic> {
+        let mut linter = Self::new(source);
+        linter.visit_stmt_list(stmt_list);
+        linter.diagnostics
+    }
+}
@@ -38,10 +38,19 @@ impl<'a> UnarySeriesLinter<'a> {
 
 impl<'a> StmtVisitor<'a> for UnarySeriesLinter<'a> {
     fn visit_unary(&mut self, expr: &'a UnaryExpr<RcExpr>, start_span: Span) {
+        // Only chains of signs are reducible; `5!!` is not `5!`.
+        if expr.op.is_postfix() {
+            self.visit_expr(&expr.rhs);
+            return;
+        }
+
         let mut is_neg = expr.op == UnaryOperator::SignNegative;
         let mut nested = &expr.rhs;
         let mut count = 1;
         while let Expr::UnaryExpr(UnaryExpr { op, rhs }) = nested.as_ref() {
+            if op.is_postfix() {
+                break;
+            }
             if op == &UnaryOperator::SignNegative {
                 is_neg = !is_neg;
             }
@@ -70,7 +70,18 @@ impl std::fmt::Display for Dec2FracError {
 /// assert_eq!(dec2frac(3.14159265358979323, 10), None)
 /// assert_eq!(dec2frac(-3.142857142857142857, 100_000), Some((-22, 7)))
 /// ```
-pub fn dec2frac(mut num: f64, max_iter: u64) -> Result<Fraction, Dec2FracError> {
+pub fn dec2frac(num: f64, max_iter: u64) -> Result<Fraction, Dec2FracError> {
+    dec2frac_within(num, max_iter, f64::EPSILON)
+}
+
+/// Like [dec2frac], but accepts a fraction whose value is within `precision` of the decimal part
+/// of `num`. The decimal part of a large number is less precise than [f64::EPSILON], since it was
+/// taken from a number with fewer bits for its decimal places.
+pub fn dec2frac_within(
+    mut num: f64,
+    max_iter: u64,
+    precision: f64,
+) -> Result<Fraction, Dec2FracError> {
     let coeff = if num < 0. { -1 } else { 1 };
     let num = num.abs();
 
@@ -99,7 +110,7 @@ pub fn dec2frac(mut num: f64, max_iter: u64) -> Result<Fraction, Dec2FracError>
                 //            ^-- if == decimal, we're done
                 //   ^^^^^^       ^^^^^^^-- otherwise, the decimal is in one of these two ranges.
                 //                          update lo/hi to search in the appropriate range.
-                if (med_dec - decimal).abs() <= std::f64::EPSILON {
+                if (med_dec - decimal).abs() <= precision {
                     return Ok(med);
                 } else if decimal < med_dec {
                     hi = med;
@@ -120,6 +131,35 @@ pub fn dec2frac(mut num: f64, max_iter: u64) -> Result<Fraction, Dec2FracError>
     Ok((coeff * combined_numerator, denominator))
 }
 
+/// Computes the [continued fraction](https://en.wikipedia.org/wiki/Continued_fraction) expansion
+/// of a fraction, with the terms `[a0; a1, a2, ...]` such that
+///
+/// ```text
+/// num / den = a0 + 1 / (a1 + 1 / (a2 + ...))
+/// ```
+///
+/// The expansion is computed with the Euclidean algorithm, so its first term is the floor of the
+/// fraction, and its later terms are positive.
+///
+/// ## Examples
+///
+/// ```ignore
+/// assert_eq!(continued_fraction((43, 19)), vec![2, 3, 1, 4]);
+/// assert_eq!(continued_fraction((-1, 2)), vec![-1, 2]);
+/// ```
+pub fn continued_fraction((mut num, den): Fraction) -> Vec<i64> {
+    let mut den = den as i64;
+    let mut terms = Vec::new();
+    while den != 0 {
+        let term = num.div_euclid(den);
+        terms.push(term);
+        let rem = num.rem_euclid(den);
+        num = den;
+        den = rem;
+    }
+    terms
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,4 +194,21 @@ mod tests {
             assert_eq!(dec2frac(*dec, 1_000_000), *frac, "{} != {:?}", dec, frac);
         }
     }
+
+    #[test]
+    fn test_continued_fraction() {
+        let cases: [((i64, u64), &[i64]); 8] = [
+            ((0, 1), &[0]),
+            ((3, 1), &[3]),
+            ((1, 2), &[0, 2]),
+            ((43, 19), &[2, 3, 1, 4]),
+            ((415, 93), &[4, 2, 6, 7]),
+            ((649, 200), &[3, 4, 12, 4]),
+            ((22, 7), &[3, 7]),
+            ((-43, 19), &[-3, 1, 2, 1, 4]),
+        ];
+        for (frac, expansion) in cases.iter() {
+            assert_eq!(continued_fraction(*frac), *expansion, "{:?}", frac);
+        }
+    }
 }
@@ -51,8 +51,15 @@ pub fn gcd_poly_zz_heu(f: Poly, g: Poly) -> Result<(Poly, Poly, Poly), &'static
     let mut _cfg2;
     let mut r;
     for _ in 0..GCD_HEU_MAX_ITER {
-        f_xi = f.eval(xi as isize);
-        g_xi = g.eval(xi as isize);
+        // The evaluations grow quickly in `xi`; give up once they are too large to represent.
+        f_xi = f
+            .eval(xi)
+            .filter(|v| *v != isize::MIN)
+            .ok_or(HEU_OVERFLOW)?;
+        g_xi = g
+            .eval(xi)
+            .filter(|v| *v != isize::MIN)
+            .ok_or(HEU_OVERFLOW)?;
 
         if f_xi != 0 && g_xi != 0 {
             heu = gcd(f_xi.abs() as usize, g_xi.abs() as usize) as isize;
@@ -125,12 +132,18 @@ pub fn gcd_poly_zz_heu(f: Poly, g: Poly) -> Result<(Poly, Poly, Poly), &'static
                 }
             }
         }
-        xi = 73794 * xi * ((xi as f64).sqrt().sqrt() as isize)
+        let xi_root = (xi as f64).sqrt().sqrt() as isize;
+        xi = xi
+            .checked_mul(73794)
+            .and_then(|xi| xi.checked_mul(xi_root))
+            .ok_or(HEU_OVERFLOW)?;
     }
 
     Err("gcd_poly_zz_heu failed")
 }
 
+const HEU_OVERFLOW: &str = "gcd_poly_zz_heu overflowed";
+
 #[cfg(feature = "benchmark-internals")]
 /// Tries to calculate the GCD of two integer polynomials using a heuristic.
 pub fn _gcd_poly_zz_heu<T, U>(f: T, g: U) -> Result<(Poly, Poly, Poly), &'static str>
@@ -146,7 +159,7 @@ fn poly_coeffs_gcd(p: &Poly) -> usize {
     if p.is_zero() {
         return 0;
     }
-    let mut cont: usize = p.vec[0] as usize;
+    let mut cont: usize = p.vec[0].abs() as usize;
     for i in &p.vec {
         cont = gcd(cont, i.abs() as usize);
         if cont == 1 {
@@ -227,4 +240,15 @@ mod test {
             Ok((poly![1, -8, -9], poly![1, -1], poly![1]))
         )
     }
+
+    #[test]
+    fn test_gcd_poly_zz_heu_overflow() {
+        let mut x23_plus_1 = vec![0; 24];
+        x23_plus_1[0] = 1;
+        x23_plus_1[23] = 1;
+        assert_eq!(
+            gcd_poly_zz_heu(Poly::new(x23_plus_1), poly![1, 1]),
+            Err("gcd_poly_zz_heu overflowed")
+        )
+    }
 }
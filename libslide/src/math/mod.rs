@@ -12,6 +12,7 @@
 //! [partial_evaluator]: crate::partial_evaluator
 
 mod fraction;
+pub use fraction::*;
 
 mod gcd;
 pub use gcd::*;
@@ -1,9 +1,12 @@
 #![allow(clippy::should_implement_trait)]
 
-use crate::grammar::{BinaryExpr, BinaryOperator, Expr, RcExpr, RcExpression};
+use crate::grammar::{
+    BinaryExpr, BinaryOperator, Expr, RcExpr, RcExpression, UnaryExpr, UnaryOperator,
+};
 use crate::math::gcd;
 use crate::partial_evaluator::flatten::flatten_expr;
 use crate::utils::{get_flattened_binary_args, unflatten_binary_expr, UnflattenStrategy};
+use crate::Rigor;
 
 use core::cmp::max;
 use std::collections::{HashMap, HashSet};
@@ -164,14 +167,16 @@ impl Poly {
         self
     }
 
-    /// Divides each term in the polynomial by a scalar.
+    /// Divides each term in the polynomial by a scalar, which should divide every term.
     /// If the scalar divisor is 0, an error is returned.
-    pub fn div_scalar(self, c: isize) -> Result<Self, &'static str> {
+    pub fn div_scalar(mut self, c: isize) -> Result<Self, &'static str> {
         if c == 0 {
-            Err("Cannot divide a polynomial by 0")
-        } else {
-            Ok(self.mul_scalar(1 / c))
+            return Err("Cannot divide a polynomial by 0");
+        }
+        for term in self.vec.iter_mut() {
+            *term /= c;
         }
+        Ok(self)
     }
 
     /// Subtracts `other` from `self`, yielding a new polynomial.
@@ -245,7 +250,7 @@ impl Poly {
 
             let d_rem_old = d_rem;
             d_rem = rem_poly.deg();
-            if d_rem < d_other {
+            if rem_poly.is_zero() || d_rem < d_other {
                 break;
             } else if d_rem >= d_rem_old {
                 return Err("Unexpected state: remainder degreee not lower after division");
@@ -272,20 +277,20 @@ impl Poly {
         *self.vec.last().unwrap_or(&0)
     }
 
-    /// Evaluates the polynomial at a value `x`.
+    /// Evaluates the polynomial at a value `x`, or returns `None` if the value overflows.
     ///
     /// # Examples:
     ///
     /// ```ignore
     /// // (x^2 - 4)(1) -> -3
-    /// assert_eq!(poly![-4, 0, 1].eval(1), -3);
+    /// assert_eq!(poly![-4, 0, 1].eval(1), Some(-3));
     /// ```
     #[inline]
-    pub fn eval(&self, x: isize) -> isize {
-        self.vec.iter().rev().fold(0, |mut res, &n| {
-            res *= x;
-            res + n
-        })
+    pub fn eval(&self, x: isize) -> Option<isize> {
+        self.vec
+            .iter()
+            .rev()
+            .try_fold(0isize, |res, &n| res.checked_mul(x)?.checked_add(n))
     }
 
     /// Transforms an expression into a polynomial relative to some term.
@@ -314,7 +319,7 @@ impl Poly {
         // First, let's try to flatten the expression which will automatically combine terms for
         // us. If the expr is an addition or subtraction, this will also normalize it to an
         // addition.
-        let expr = flatten_expr(expr);
+        let expr = flatten_expr(expr, Rigor::Pragmatic);
         // Next, let's unroll the addition into its individual polynomial parts.
         // TODO: we should really rename this, it overlaps with `flatten_expr`.
         // TODO: we can more efficient by getting the unrolled args during flattening, skipping
@@ -329,8 +334,16 @@ impl Poly {
         }
         let mut konst_f64 = 0.;
         for poly_part in poly_parts.iter() {
+            // Subtracted terms are flattened to negations, like `x - 2x -> x + -(2x)`.
+            let (poly_part, sign) = match poly_part.as_ref() {
+                Expr::UnaryExpr(UnaryExpr {
+                    op: UnaryOperator::SignNegative,
+                    rhs,
+                }) => (rhs, -1),
+                _ => (poly_part, 1),
+            };
             match poly_part.as_ref() {
-                Expr::Const(c) => konst_f64 += c,
+                Expr::Const(c) => konst_f64 += sign as f64 * c,
                 Expr::BinaryExpr(BinaryExpr {
                     op: BinaryOperator::Mult,
                     lhs,
@@ -351,7 +364,7 @@ impl Poly {
                     // Get the raw term and exponent.
                     let (term, pow) = term_and_pow_from_expr(term.clone())?;
 
-                    degree_coeffs.insert(pow, coeff);
+                    *degree_coeffs.entry(pow).or_insert(0) += sign * coeff;
                     uniq_terms.insert(term);
                 }
 
@@ -359,7 +372,7 @@ impl Poly {
                 _ => {
                     let (term, pow) = term_and_pow_from_expr(poly_part.clone())?;
                     // We couldn't unroll the coefficient in the above match, so make it one here.
-                    degree_coeffs.insert(pow, 1);
+                    *degree_coeffs.entry(pow).or_insert(0) += sign;
                     uniq_terms.insert(term);
                 }
             }
@@ -411,27 +424,28 @@ impl Poly {
     pub fn to_expr(&self, relative_to: RcExpr, span: crate::Span) -> RcExpr {
         let mut terms = Vec::with_capacity(self.vec.len());
         for (pow, coeff) in self.vec.iter().enumerate() {
-            let term = match coeff {
-                0 => {
-                    continue;
-                }
+            let term = match pow {
+                0 => rc_expr!(Expr::Const(*coeff as f64), span),
                 1 => relative_to.clone(),
                 _ => rc_expr!(
-                    Expr::BinaryExpr(BinaryExpr::mult(
-                        rc_expr!(Expr::Const(*coeff as f64), span),
-                        relative_to.clone()
+                    Expr::BinaryExpr(BinaryExpr::exp(
+                        relative_to.clone(),
+                        rc_expr!(Expr::Const(pow as f64), span),
                     )),
                     span
                 ),
             };
 
-            terms.push(match pow {
-                0 => rc_expr!(Expr::Const(*coeff as f64), span),
+            terms.push(match coeff {
+                0 => {
+                    continue;
+                }
+                _ if pow == 0 => term,
                 1 => term,
                 _ => rc_expr!(
-                    Expr::BinaryExpr(BinaryExpr::exp(
-                        term,
-                        rc_expr!(Expr::Const(pow as f64), span),
+                    Expr::BinaryExpr(BinaryExpr::mult(
+                        rc_expr!(Expr::Const(*coeff as f64), span),
+                        term
                     )),
                     span
                 ),
@@ -552,6 +566,36 @@ mod test {
         )
     }
 
+    #[test]
+    fn div_with_remainder() {
+        // (x^2 - 2x) / (x + 1) -> ((x - 3), 3)
+        assert_eq!(
+            poly![0, -2, 1].div(poly![1, 1]).unwrap(),
+            (poly![-3, 1], poly![3])
+        )
+    }
+
+    #[test]
+    fn div_by_constant() {
+        assert_eq!(poly![2, 4].div(poly![2]).unwrap(), (poly![1, 2], poly![]))
+    }
+
+    #[test]
+    fn div_scalar() {
+        assert_eq!(poly![2, -4, 6].div_scalar(2).unwrap(), poly![1, -2, 3]);
+    }
+
+    #[test]
+    fn eval() {
+        assert_eq!(poly![-4, 0, 1].eval(1), Some(-3));
+        assert_eq!(poly![].eval(5), Some(0));
+    }
+
+    #[test]
+    fn eval_overflow() {
+        assert_eq!(poly![1, 0, 1].eval(isize::MAX), None);
+    }
+
     #[test]
     fn is_one() {
         let cases = [
@@ -598,8 +642,8 @@ mod test {
         with_coeff: "2x + x^3 + 10x^2 + 5x^4" => Some((poly![0, 2, 10, 1, 5], Some("x".to_string())))
         complex_term: "2(x + y ^ z) + 5(x + y ^ z)^3" => Some((poly![0, 2, 0, 5], Some("x + y ^ z".to_string())))
         multi_term: "10 + x + y^2" => None
-        // TODO: this doesn't work yet, `flatten` and/or Poly::from_expr need to be smarter about unaries
-        // add_and_sub: "10 + x - 2x^2 + 3x^4 - 4x^8" => Some((poly![1, 0, 0, 0, 1, 0, 1, 1, 10], Some("x".to_string())))
+        add_and_sub: "10 + x - 2x^2 + 3x^4 - 4x^8" => Some((poly![10, 1, -2, 0, 3, 0, 0, 0, -4], Some("x".to_string())))
+        sub_term: "x^2 - 2x" => Some((poly![0, -2, 1], Some("x".to_string())))
     }
 
     poly_from_expr_tests! {
@@ -625,6 +669,6 @@ mod test {
         to_empty_all_zeros: poly![0, 0, 0, 0], "x" => "0"
         zero_coefficient: poly![10, 0], "x" => "10"
         one_coefficient: poly![5, 1, 1, 0, 1], "x" => "5 + x + x ^ 2 + x ^ 4"
-        larger_coefficient: poly![1, 2, 3, 4, 5], "x" => "1 + 2 * x + (3 * x) ^ 2 + (4 * x) ^ 3 + (5 * x) ^ 4"
+        larger_coefficient: poly![1, 2, 3, 4, 5], "x" => "1 + 2 * x + 3 * x ^ 2 + 4 * x ^ 3 + 5 * x ^ 4"
     }
 }
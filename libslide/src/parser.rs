@@ -34,16 +34,16 @@ pub struct ParseResult<T> {
 macro_rules! binary_expr_parser {
     ($self:ident $($name:ident: lhs=$lhs_term:ident, rhs=$rhs_term:ident, op=[$($matching_op:tt)+])*) => {
         $(
-        fn $name(&mut $self) -> Self::Expr {
+        fn $name(&mut $self, after: Option<&Token>) -> Self::Expr {
             use BinaryOperator::*;
 
-            let mut lhs = $self.$lhs_term();
+            let mut lhs = $self.$lhs_term(after);
             while let Ok(op) = BinaryOperator::try_from($self.peek())
             {
                 match op {
                     $($matching_op)+ => {
-                        $self.next();
-                        let rhs = $self.$rhs_term();
+                        let op_tok = $self.next();
+                        let rhs = $self.$rhs_term(Some(&op_tok));
                         let span = lhs.span().to(rhs.span());
                         lhs = Self::Expr::binary(
                             BinaryExpr { op, lhs, rhs, },
@@ -59,6 +59,9 @@ macro_rules! binary_expr_parser {
     };
 }
 
+/// The maximum number of subexpressions an expression can be nested in.
+const MAX_DEPTH: usize = 256;
+
 /// Returns a diagnostic for an unclosed delimiter.
 fn unclosed_delimiter(opener: Token, expected_closer: TT, found_closer: Token) -> Diagnostic {
     let mut found_str = found_closer.to_string();
@@ -79,6 +82,11 @@ where
 
     // fn new(input: Vec<Token>) -> Self;
     fn input(&mut self) -> &mut PeekIter<Token>;
+    /// The number of parentheses, brackets, and call argument lists the parser is in.
+    fn nesting(&mut self) -> &mut usize;
+    /// The number of subexpressions the parser is [nested](Self::deeper) in, which bounds the
+    /// depth of the parser's recursion.
+    fn depth(&mut self) -> &mut usize;
     fn parse(&mut self) -> T;
     fn parse_float(&mut self, f: f64, span: Span) -> Self::Expr;
     fn parse_variable(&mut self, name: String, span: Span) -> Self::Expr;
@@ -86,7 +94,7 @@ where
     fn parse_const_pattern(&mut self, name: String, span: Span) -> Self::Expr;
     fn parse_any_pattern(&mut self, name: String, span: Span) -> Self::Expr;
     fn parse_open_paren(&mut self, open: Token) -> Self::Expr {
-        let inner = self.expr();
+        let inner = self.nested_expr();
         let closing_tok = self.next();
         let sp = open.span.to(closing_tok.span);
         if !matches!(closing_tok.ty, TT::CloseParen) {
@@ -95,7 +103,7 @@ where
         Self::Expr::paren(inner, sp)
     }
     fn parse_open_bracket(&mut self, open: Token) -> Self::Expr {
-        let inner = self.expr();
+        let inner = self.nested_expr();
         let closing_tok = self.next();
         let sp = open.span.to(closing_tok.span);
         if !matches!(closing_tok.ty, TT::CloseBracket) {
@@ -105,6 +113,8 @@ where
     }
     fn push_diag(&mut self, diagnostic: Diagnostic);
 
+    /// Returns whether the next token begins another statement, i.e. whether the current
+    /// statement is broken before it.
     fn has_stmt_break(&mut self) -> bool;
 
     #[inline]
@@ -114,7 +124,63 @@ where
 
     #[inline]
     fn expr(&mut self) -> Self::Expr {
-        self.add_sub_term()
+        self.add_sub_term(None)
+    }
+
+    /// Parses an expression nested in a delimiter, like a parenthesis. Statements cannot be
+    /// broken inside a delimiter, so a nested expression may span several lines.
+    fn nested_expr(&mut self) -> Self::Expr {
+        *self.nesting() += 1;
+        let inner = self.deeper(Self::expr);
+        *self.nesting() -= 1;
+        inner
+    }
+
+    /// Parses a subexpression with `parse` one level deeper than the expression it is part of.
+    /// If the subexpression would be nested more than [`MAX_DEPTH`](MAX_DEPTH) levels deep, the
+    /// rest of it is skipped and diagnosed instead, so that deeply nested programs cannot overflow
+    /// the stack.
+    fn deeper(&mut self, parse: impl FnOnce(&mut Self) -> Self::Expr) -> Self::Expr {
+        if *self.depth() >= MAX_DEPTH {
+            return self.skip_too_deep();
+        }
+        *self.depth() += 1;
+        let expr = parse(self);
+        *self.depth() -= 1;
+        expr
+    }
+
+    /// Skips the rest of a subexpression nested too deeply, up to the closing delimiter, comma, or
+    /// statement break ending it, and returns an empty expression in its place.
+    fn skip_too_deep(&mut self) -> Self::Expr {
+        let first = self.peek().span;
+        let mut span = Span::from((first.lo, first.lo));
+        let mut open_delimiters = 0;
+        let mut skipped_any = false;
+        loop {
+            let ends_stmt = open_delimiters == 0 && skipped_any && self.has_stmt_break();
+            match self.peek().ty {
+                TT::OpenParen | TT::OpenBracket => open_delimiters += 1,
+                TT::CloseParen | TT::CloseBracket if open_delimiters > 0 => open_delimiters -= 1,
+                TT::Eof => break,
+                TT::CloseParen | TT::CloseBracket | TT::Comma | TT::Semicolon
+                    if open_delimiters == 0 =>
+                {
+                    break
+                }
+                _ if ends_stmt => break,
+                _ => {}
+            }
+            span = span.to(self.next().span);
+            skipped_any = true;
+        }
+        self.push_diag(NestingTooDeep!(first, MAX_DEPTH));
+        Self::Expr::empty(span)
+    }
+
+    /// Parses the exponent of an exponentiation, which is nested one level deeper than the base.
+    fn exponent_term(&mut self, after: Option<&Token>) -> Self::Expr {
+        self.deeper(|parser| parser.exp_term(after))
     }
 
     #[inline]
@@ -134,17 +200,25 @@ where
     binary_expr_parser!(
         self
 
-        // Level 1: +, -
-        add_sub_term:        lhs = mul_divide_mod_term, rhs = mul_divide_mod_term, op = [Plus | Minus]
+        // Level 1: +, -, ±
+        add_sub_term:        lhs = mul_divide_mod_term, rhs = mul_divide_mod_term, op = [Plus | Minus | PlusMinus]
 
         // Level 2: *, /, %
         mul_divide_mod_term: lhs = exp_term,            rhs = exp_term,            op = [Mult | Div | Mod]
 
         // Level 3: ^                                   right-associativity of ^
-        exp_term:            lhs = num_term,            rhs = exp_term,            op = [Exp]
+        exp_term:            lhs = num_term,            rhs = exponent_term,       op = [Exp]
     );
 
-    fn num_term(&mut self) -> Self::Expr {
+    /// Parses a number, variable, or other primary term. `after` is the operator the term is an
+    /// operand of, if any.
+    fn num_term(&mut self, after: Option<&Token>) -> Self::Expr {
+        if let Some(op) = after {
+            if let Some(empty) = self.missing_operand(op) {
+                return empty;
+            }
+        }
+
         let tok = self.next();
         let tok_span = tok.span;
         if matches!(tok.ty, TT::Eof) {
@@ -153,11 +227,11 @@ where
         }
 
         let node = if let Ok(op) = UnaryOperator::try_from(&tok) {
-            let rhs = self.exp_term();
+            let rhs = self.deeper(|parser| parser.exp_term(Some(&tok)));
             let span = tok.span.to(rhs.span());
             Self::Expr::unary(UnaryExpr { op, rhs }, span)
         } else {
-            match tok.ty {
+            let node = match tok.ty {
                 TT::Float(f) => self.parse_float(f, tok.span),
                 TT::Variable(name) => self.parse_variable(name, tok.span),
                 TT::VariablePattern(name) => self.parse_var_pattern(name, tok.span),
@@ -173,12 +247,16 @@ where
                     });
                     Self::Expr::empty(tok.span)
                 }
-            }
+            };
+            self.postfix_term(node)
         };
 
+        let next_starts_assignment = self.starts_assignment_at(1);
         let insert_synthetic_mult = match self.peek().ty {
             // <node>(<other>) => <node> * (<other>)
             TT::OpenParen | TT::OpenBracket => true,
+            // <num><var> = ... is <num> followed by another statement, not <num> * <var>.
+            TT::Variable(_) if next_starts_assignment => false,
             // <num><var> => <num> * <var>
             TT::Variable(_) | TT::VariablePattern(_) | TT::ConstPattern(_) | TT::AnyPattern(_)
                 if node.is_const() =>
@@ -197,6 +275,48 @@ where
         node
     }
 
+    /// Diagnoses a missing operand of the operator `op`, returning an empty operand in its place.
+    /// An operand is missing when `op` is followed by the end of the program, another statement,
+    /// a closing delimiter, comma, or semicolon, or a binary operator that cannot begin an
+    /// operand. The following token is not consumed, so that parsing can continue from it.
+    fn missing_operand(&mut self, op: &Token) -> Option<Self::Expr> {
+        let next = self.peek().clone();
+        let found = match next.ty {
+            TT::Eof => "end of file".to_owned(),
+            TT::Variable(_) if self.has_stmt_break() && self.starts_assignment_at(1) => {
+                "another statement".to_owned()
+            }
+            TT::CloseParen | TT::CloseBracket | TT::Comma | TT::Semicolon => format!("`{}`", next),
+            _ if BinaryOperator::try_from(&next).is_ok()
+                && UnaryOperator::try_from(&next).is_err() =>
+            {
+                format!("`{}`", next)
+            }
+            _ => return None,
+        };
+        self.push_diag(ExpectedExpr!(after op, at op.span; found found, at next.span));
+        Some(Self::Expr::empty(Span::from((op.span.hi, op.span.hi))))
+    }
+
+    /// Applies any postfix operators following `node`, like the factorial in `5!`.
+    fn postfix_term(&mut self, mut node: Self::Expr) -> Self::Expr {
+        while self.peek().ty == TT::Bang {
+            let span = node.span().to(self.next().span);
+            let op = UnaryOperator::Factorial;
+            node = Self::Expr::unary(UnaryExpr { op, rhs: node }, span);
+        }
+        node
+    }
+
+    /// Returns whether the `n`th next token is an assignment operator, i.e. whether the token
+    /// before it is the left-hand side of an assignment.
+    fn starts_assignment_at(&mut self, n: usize) -> bool {
+        matches!(
+            self.input().peek_nth(n).map(|t| &t.ty),
+            Some(TT::Equal) | Some(TT::AssignDefine)
+        )
+    }
+
     /// Creates diagnostics for extra tokens following a primary item.
     /// All remaining tokens will be consumed in the construction of the diagnostic.
     ///
@@ -231,6 +351,9 @@ mod tests {
         subtraction:                            "2 - 2"
         subtraction_nested:                     "1 - 2 - 3"
         subtraction_add_nested:                 "1 - 2 + 3"
+        plus_minus:                             "1 ± 2"
+        plus_minus_ascii:                       "1 +/- 2 => 1 ± 2"
+        plus_minus_nested:                      "1 ± 2 - 3"
         multiplication:                         "2 * 2"
         multiplication_nested:                  "1 * 2 * 3"
         division:                               "2 / 2"
@@ -250,6 +373,8 @@ mod tests {
         precedence_minus_div:                   "1 - 2 / 3"
         precedence_div_minus:                   "1 / 2 - 3"
         precedence_minus_mod:                   "1 - 2 % 3"
+        precedence_plus_minus_times:            "1 ± 2 * 3"
+        precedence_times_plus_minus:            "1 * 2 ± 3"
         precedence_mod_minus:                   "1 % 2 - 3"
         precedence_expo_plus:                   "1 + 2 ^ 3"
         precedence_plus_exp:                    "1 ^ 2 + 3"
@@ -293,5 +418,65 @@ mod tests {
         implicit_mult_unary_bracket:            "-1[2] => -1 * [2]"
         implicit_mult_unary_nested_var:         "-2x => -2 * x"
         implicit_mult_exp:                      "2x^5 => 2 * x ^ 5"
+        factorial:                              "5!"
+        factorial_nested:                       "3!!"
+        factorial_var:                          "x!"
+        factorial_paren:                        "(1 + 2)!"
+        precedence_factorial_exp:               "2 ^ 3!"
+        precedence_factorial_unary:             "-3!"
+        implicit_mult_factorial_paren:          "3!(2) => 3! * (2)"
+    }
+
+    mod nesting_too_deep {
+        use super::super::MAX_DEPTH;
+        use crate::parser::{parse_expression_pattern, parse_statements};
+        use crate::scanner::scan;
+
+        /// Parses `program` as statements and as an expression pattern, returning the codes of
+        /// the diagnostics of each.
+        fn diag_codes(program: &str) -> [Vec<&'static str>; 2] {
+            let stmt_diags = parse_statements(scan(program).tokens, program).diagnostics;
+            let pat_diags = parse_expression_pattern(scan(program).tokens).diagnostics;
+            [stmt_diags, pat_diags].map(|diags| diags.into_iter().map(|d| d.code).collect())
+        }
+
+        #[test]
+        fn deepest_nesting_allowed() {
+            let program = format!("{}1{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+            assert!(diag_codes(&program).iter().all(Vec::is_empty));
+        }
+
+        #[test]
+        fn deep_delimiters() {
+            for (open, close) in &[("(", ")"), ("[", "]")] {
+                let program = format!("{}1{} + 2", open.repeat(10_000), close.repeat(10_000));
+                assert_eq!(diag_codes(&program), [vec!["P0007"], vec!["P0007"]]);
+            }
+        }
+
+        #[test]
+        fn deep_unary_operators() {
+            let program = format!("{}1", "-".repeat(10_000));
+            assert_eq!(diag_codes(&program), [vec!["P0007"], vec!["P0007"]]);
+        }
+
+        #[test]
+        fn deep_exponents() {
+            let program = format!("{}1", "2 ^ ".repeat(10_000));
+            assert_eq!(diag_codes(&program), [vec!["P0007"], vec!["P0007"]]);
+        }
+
+        #[test]
+        fn deep_calls() {
+            let program = format!("{}1{}", "sqrt(".repeat(10_000), ")".repeat(10_000));
+            assert_eq!(diag_codes(&program)[0], vec!["P0007"]);
+        }
+
+        #[test]
+        fn skips_to_end_of_statement() {
+            let program = format!("a = {}1\nb = 2", "-".repeat(10_000));
+            let parsed = parse_statements(scan(program.as_str()).tokens, &program);
+            assert_eq!(parsed.program.to_string().lines().last(), Some("b = 2"));
+        }
     }
 }
@@ -43,17 +43,11 @@ define_errors! {
     ///`3 + 4` are not connected to the primary expression statement `1 + 2`, and slide does not
     ///know how this is intended to be evaluated.
     ///
-    ///In the future, statement that are separated by a newline will not emit this error. The
-    ///following are examples of programs that currently emit this error, but in the future should not:
+    ///Statements are separated by newlines or `;`. If `3 + 4` is intended to be another
+    ///statement, place it on a new line or separate it with `;`:
     ///
     ///```text
-    ///a = 1
-    ///b = 2 - in the future, parsed as two assignment statements
-    ///```
-    ///
-    ///```text
-    ///1 + 2
-    ///3 + 4 - in the future, parsed as two expression statements
+    ///1 + 2; 3 + 4
     ///```
     P0001: ExtraTokens {
         ($span:expr) => {{
@@ -85,11 +79,15 @@ define_errors! {
     ///
     ///```text
     ///1 /
-    ///   ^- offending token: end of file
+    ///  ^- operator missing an operand
     ///```
     ///
     ///In all cases such programs are malformed and should be refactored to include complete
     ///expressions.
+    ///
+    ///When the missing expression is the operand of an operator, as in the first and last examples,
+    ///the error points at the operator. Parsing continues as if the operand were present, so other
+    ///errors in the program are still reported.
     P0002: ExpectedExpr {
         ($span:expr, $found:expr) => {
             Diagnostic::span_err(
@@ -98,7 +96,19 @@ define_errors! {
                 ExpectedExpr::CODE,
                 "expected an expression".to_string(),
             )
-        }
+        };
+        (after $op:expr, at $op_span:expr; found $found:expr, at $found_span:expr) => {{
+            use crate::diagnostics::*;
+
+            Diagnostic::span_err(
+                $op_span,
+                format!("Expected an operand after `{}`", $op),
+                ExpectedExpr::CODE,
+                "operator missing an operand".to_string(),
+            )
+            .with_spanned_note($found_span, format!("found {}", $found))
+            .with_autofix(Autofix::maybe("consider deleting this operator", Edit::Delete))
+        }}
     }
 
     ///All opening delimiters with closing pairs must have that closing delimiter as a
@@ -221,4 +231,71 @@ define_errors! {
             .with_autofix(Autofix::maybe("consider deleting this token", Edit::Delete))
         }}
     }
+
+    ///Expressions in a slide program can be nested at most 256 levels deep. Every parenthesized
+    ///or bracketed expression, call argument list, operand of a unary operator, and exponent
+    ///nests its contents one level deeper than the expression it appears in.
+    ///
+    ///For example, each `-` below nests its operand one level deeper, so the `1` is 300 levels
+    ///deep:
+    ///
+    ///```text
+    ///------...------1
+    ///^^^^^^^^^^^^^^^^ 300 `-` operators
+    ///```
+    ///
+    ///The part of an expression nested too deeply is not parsed. Consider simplifying the
+    ///expression, for example by removing redundant parentheses or signs.
+    P0007: NestingTooDeep {
+        ($span:expr, $max_depth:expr) => {{
+            use crate::diagnostics::*;
+
+            Diagnostic::span_err(
+                $span,
+                "Expression nested too deeply",
+                NestingTooDeep::CODE,
+                format!("nested more than {} levels deep", $max_depth),
+            )
+        }}
+    }
+
+    ///This error fires on calls of functions with the wrong number of arguments.
+    ///
+    ///For example, in the program
+    ///
+    ///```text
+    ///max(1, 2, 3)
+    ///^^^^^^^^^^^^- expected 2 arguments, found 3
+    ///```
+    ///
+    ///`max` takes the larger of two arguments, but is called with three. Nest calls to compare
+    ///more than two values, as in `max(1, max(2, 3))`.
+    ///
+    ///The builtin functions take
+    ///
+    ///- `min(a, b)` and `max(a, b)`: two arguments
+    ///- `clamp(x, lo, hi)`: three arguments
+    ///- `sqrt(x)`: one argument
+    ///- `root(n, x)`: two arguments
+    ///- `sum(i, lo, hi, body)`: four arguments
+    ///
+    ///User-defined functions, like `f(x) = x + 1`, take one argument.
+    P0008: WrongArgumentCount {
+        ($span:expr, $func:expr, $expected:expr, $found:expr) => {{
+            use crate::diagnostics::*;
+
+            let plural = |n: usize| if n == 1 { "" } else { "s" };
+            Diagnostic::span_err(
+                $span,
+                format!("Wrong number of arguments to `{}`", $func),
+                WrongArgumentCount::CODE,
+                format!(
+                    "expected {} argument{}, found {}",
+                    $expected,
+                    plural($expected),
+                    $found
+                ),
+            )
+        }}
+    }
 }
@@ -18,6 +18,8 @@ pub fn parse(input: Vec<Token>) -> ParseResult<RcExprPat> {
 
 pub struct ExpressionPatternParser {
     _input: PeekIter<Token>,
+    _nesting: usize,
+    _depth: usize,
     diagnostics: Vec<Diagnostic>,
 }
 
@@ -25,6 +27,8 @@ impl ExpressionPatternParser {
     fn new(input: Vec<Token>) -> Self {
         Self {
             _input: PeekIter::new(input.into_iter()),
+            _nesting: 0,
+            _depth: 0,
             diagnostics: vec![],
         }
     }
@@ -37,6 +41,14 @@ impl Parser<RcExprPat> for ExpressionPatternParser {
         &mut self._input
     }
 
+    fn nesting(&mut self) -> &mut usize {
+        &mut self._nesting
+    }
+
+    fn depth(&mut self) -> &mut usize {
+        &mut self._depth
+    }
+
     fn push_diag(&mut self, diagnostic: Diagnostic) {
         self.diagnostics.push(diagnostic);
     }
@@ -1,10 +1,12 @@
-use super::{errors::*, ParseResult, Parser};
+use super::{errors::*, unclosed_delimiter, ParseResult, Parser};
 use crate::common::Span;
 use crate::diagnostics::Diagnostic;
 use crate::grammar::*;
 use crate::scanner::types::{Token, TokenType};
 use crate::utils::{PeekIter, StringUtils};
 
+use std::collections::HashSet;
+
 /// Parses a tokenized slide program, emitting the result and any diagnostics.
 pub fn parse(input: Vec<Token>, program: &str) -> ParseResult<StmtList> {
     let mut parser = ExpressionParser::new(input, program);
@@ -18,20 +20,29 @@ pub fn parse(input: Vec<Token>, program: &str) -> ParseResult<StmtList> {
 
 pub struct ExpressionParser<'a> {
     _input: PeekIter<Token>,
+    _nesting: usize,
+    _depth: usize,
     program: &'a str,
     diagnostics: Vec<Diagnostic>,
+    /// Names of the functions defined so far in the program, like `f` in `f(x) = x ^ 2`.
+    functions: HashSet<String>,
 }
 
 impl<'a> ExpressionParser<'a> {
     fn new(input: Vec<Token>, program: &'a str) -> Self {
         Self {
             _input: PeekIter::new(input.into_iter()),
+            _nesting: 0,
+            _depth: 0,
             program,
             diagnostics: vec![],
+            functions: HashSet::new(),
         }
     }
 
-    fn parse_stmt(&mut self) -> Stmt {
+    /// Parses a statement. A chained assignment like `a = b = 1` is parsed as one assignment
+    /// statement per assigned variable, each with the same right-hand side.
+    fn parse_stmt(&mut self) -> Vec<Stmt> {
         #[allow(clippy::naive_bytecount)] // naiveness is fine, we're not counting a lot of bytes
         let vw = self
             .peek_content()
@@ -41,38 +52,111 @@ impl<'a> ExpressionParser<'a> {
             .count()
             .saturating_sub(1); // don' count the newline always present between statements
 
-        let mut next_2 = self.input().peek_map_n(2, |tok| (tok.ty.clone(), tok.span));
-        let kind = match (next_2.pop_front(), next_2.pop_front()) {
-            (
-                Some((TokenType::Variable(name), name_span)),
-                Some((asgn_ty @ TokenType::Equal, asgn_span)),
-            )
-            | (
-                Some((TokenType::Variable(name), name_span)),
-                Some((asgn_ty @ TokenType::AssignDefine, asgn_span)),
-            ) => {
-                let Span { lo, .. } = self.input().next().unwrap().span;
+        // Assignments are right-associative, so first collect all assigned variables.
+        let mut assigned = Vec::new();
+        while let Some((lhs, lhs_len, asgn_op)) = self.peek_assignment_lhs() {
+            // Consume the left-hand side and the assignment operator.
+            for _ in 0..=lhs_len {
                 self.input().next();
+            }
+            assigned.push((lhs, asgn_op));
+        }
 
-                let asgn_op = if matches!(asgn_ty, TokenType::Equal) {
-                    AssignmentOp::Equal(asgn_span)
-                } else {
-                    AssignmentOp::AssignDefine(asgn_span)
-                };
-                let rhs = self.expr();
-                let span = (lo..rhs.span.hi).into();
-                StmtKind::Assignment(Assignment {
-                    // TODO: handle more than variables on the LHS
-                    lhs: rc_expr!(Expr::Var(intern_str!(name)), name_span),
+        let rhs = self.expr();
+        if assigned.is_empty() {
+            return vec![Stmt::new(StmtKind::Expr(rhs), vw)];
+        }
+
+        // A function can be called only after its definition, so its name is known once the
+        // right-hand side is parsed. This also keeps a function from calling itself.
+        for (lhs, _) in &assigned {
+            if let Expr::Call(Call { func, .. }) = lhs.as_ref() {
+                self.functions.insert(func.to_string());
+            }
+        }
+
+        assigned
+            .into_iter()
+            .enumerate()
+            .map(|(i, (lhs, asgn_op))| {
+                let span = (lhs.span.lo..rhs.span.hi).into();
+                let kind = StmtKind::Assignment(Assignment {
+                    // TODO: handle more than variables and function definitions on the LHS
+                    lhs,
                     asgn_op,
-                    rhs,
+                    rhs: rhs.clone(),
                     span,
-                })
-            }
+                });
+                // Only the first assignment in a chain is preceded by any vertical whitespace.
+                Stmt::new(kind, if i == 0 { vw } else { 0 })
+            })
+            .collect()
+    }
 
-            _ => StmtKind::Expr(self.expr()),
+    /// If the next tokens are the left-hand side of an assignment, like `a =`, or of a function
+    /// definition, like `f(x) =`, returns the left-hand side, the number of tokens in it, and the
+    /// assignment operator.
+    ///
+    /// A function definition has exactly one parameter, and cannot redefine a builtin function.
+    fn peek_assignment_lhs(&mut self) -> Option<(RcExpr, usize, AssignmentOp)> {
+        let next = self.input().peek_map_n(5, |tok| (tok.ty.clone(), tok.span));
+        let asgn_op = |i: usize| match next.get(i) {
+            Some((TokenType::Equal, span)) => Some(AssignmentOp::Equal(*span)),
+            Some((TokenType::AssignDefine, span)) => Some(AssignmentOp::AssignDefine(*span)),
+            _ => None,
         };
-        Stmt::new(kind, vw)
+        let (name, name_span) = match next.front() {
+            Some((TokenType::Variable(name), span)) => (name, *span),
+            _ => return None,
+        };
+
+        if let Some(asgn_op) = asgn_op(1) {
+            let lhs = rc_expr!(Expr::Var(intern_str!(name)), name_span);
+            return Some((lhs, 1, asgn_op));
+        }
+        match (next.get(1), next.get(2), next.get(3), asgn_op(4)) {
+            (
+                Some((TokenType::OpenParen, _)),
+                Some((TokenType::Variable(param), param_span)),
+                Some((TokenType::CloseParen, close_span)),
+                Some(asgn_op),
+            ) if !Call::is_builtin(name) => {
+                let call = Call {
+                    func: intern_str!(name),
+                    args: vec![rc_expr!(Expr::Var(intern_str!(param)), *param_span)],
+                };
+                let lhs = rc_expr!(Expr::Call(call), name_span.to(*close_span));
+                Some((lhs, 4, asgn_op))
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses a call to the builtin or user-defined function `func`, whose name is at `func_span`.
+    /// The next token must be the opening parenthesis of the call.
+    fn parse_call(&mut self, func: String, func_span: Span) -> RcExpr {
+        let open = self.next();
+        let mut args = vec![];
+        if self.peek().ty != TokenType::CloseParen {
+            args.push(self.nested_expr());
+            while self.peek().ty == TokenType::Comma {
+                self.next();
+                args.push(self.nested_expr());
+            }
+        }
+
+        let closing_tok = self.next();
+        let span = func_span.to(closing_tok.span);
+        if !matches!(closing_tok.ty, TokenType::CloseParen) {
+            self.push_diag(unclosed_delimiter(open, TokenType::CloseParen, closing_tok));
+        }
+        // User-defined functions have exactly one parameter.
+        let arity = Call::builtin_arity(&func).unwrap_or(1);
+        if args.len() != arity {
+            self.push_diag(WrongArgumentCount!(span, func, arity, args.len()));
+        }
+        let func = intern_str!(func);
+        rc_expr!(Expr::Call(Call { func, args }), span)
     }
 
     fn parse_pattern(&mut self, name: String, span: Span) -> RcExpr {
@@ -80,6 +164,13 @@ impl<'a> ExpressionParser<'a> {
         rc_expr!(Expr::Var(intern_str!(name)), span)
     }
 
+    /// Skips any `;` statement separators, including empty statements like `;;`.
+    fn skip_separators(&mut self) {
+        while self.peek().ty == TokenType::Semicolon {
+            self.next();
+        }
+    }
+
     /// Returns the full content of the current (peeked) token.
     fn peek_content(&mut self) -> &str {
         self.peek().full_span.clone().over(self.program)
@@ -93,26 +184,36 @@ impl<'a> Parser<StmtList> for ExpressionParser<'a> {
         &mut self._input
     }
 
+    fn nesting(&mut self) -> &mut usize {
+        &mut self._nesting
+    }
+
+    fn depth(&mut self) -> &mut usize {
+        &mut self._depth
+    }
+
     fn push_diag(&mut self, diagnostic: Diagnostic) {
         self.diagnostics.push(diagnostic);
     }
 
     fn parse(&mut self) -> StmtList {
         let mut stmts = Vec::new();
+        self.skip_separators();
         while !self.done() {
-            stmts.push(self.parse_stmt());
+            stmts.extend(self.parse_stmt());
 
             if !self.done() && !self.has_stmt_break() {
                 self.extra_tokens_diag(|diag, first_tok_span| {
                     diag.with_spanned_help(
                         first_tok_span,
-                        "if you meant to specify another statement, add a newline before this token"
+                        "if you meant to specify another statement, add a newline or `;` before this token"
                     )
                 });
                 break;
             }
+            self.skip_separators();
         }
-        StmtList::new(stmts)
+        StmtList::new(stmts).with_source(self.program)
     }
 
     fn parse_float(&mut self, f: f64, span: Span) -> Self::Expr {
@@ -120,9 +221,39 @@ impl<'a> Parser<StmtList> for ExpressionParser<'a> {
     }
 
     fn parse_variable(&mut self, name: String, span: Span) -> Self::Expr {
+        let is_function = Call::is_builtin(&name) || self.functions.contains(&name);
+        if is_function && self.peek().ty == TokenType::OpenParen {
+            return self.parse_call(name, span);
+        }
         rc_expr!(Expr::Var(intern_str!(name)), span)
     }
 
+    /// Parses a bracketed expression like `[a + b]`, or an interval like `[a, b]` if the brackets
+    /// hold two comma-separated bounds.
+    fn parse_open_bracket(&mut self, open: Token) -> Self::Expr {
+        let lo = self.nested_expr();
+        let interval = if self.peek().ty == TokenType::Comma {
+            self.next();
+            Some(self.nested_expr())
+        } else {
+            None
+        };
+
+        let closing_tok = self.next();
+        let span = open.span.to(closing_tok.span);
+        if !matches!(closing_tok.ty, TokenType::CloseBracket) {
+            self.push_diag(unclosed_delimiter(
+                open,
+                TokenType::CloseBracket,
+                closing_tok,
+            ));
+        }
+        match interval {
+            Some(hi) => rc_expr!(Expr::Interval(Interval { lo, hi }), span),
+            None => rc_expr!(Expr::Bracketed(lo), span),
+        }
+    }
+
     fn parse_var_pattern(&mut self, name: String, span: Span) -> Self::Expr {
         self.parse_pattern(name, span)
     }
@@ -135,9 +266,11 @@ impl<'a> Parser<StmtList> for ExpressionParser<'a> {
         self.parse_pattern(name, span)
     }
 
-    /// Do we have another statement (on a newline)?
+    /// Do we have another statement (after a `;` or on a newline)? A newline does not break a
+    /// statement nested in a delimiter, like `(1\n+ 2)`.
     fn has_stmt_break(&mut self) -> bool {
-        self.peek_content().contains('\n')
+        self.peek().ty == TokenType::Semicolon
+            || (self._nesting == 0 && self.peek_content().contains('\n'))
     }
 }
 
@@ -151,5 +284,141 @@ mod tests {
         variable_in_op_right:    "1 + a"
         assignment_op:           "a = 5"
         assignment_op_expr:      "a = 5 + 2 ^ 3"
+        chained_assignment_2:    "a = b = 5 => a = 5\nb = 5"
+        chained_assignment_3:    "a = b := c = 5 + d => a = 5 + d\nb := 5 + d\nc = 5 + d"
+        call:                    "max(1, a)"
+        call_nested:             "min(max(a, 1), b + 2)"
+        call_in_op:              "2 * clamp(x, 0, 1)"
+        call_no_args:            "max()"
+        call_non_builtin:        "f(1) => f * (1)"
+        implicit_mult_asgn_rhs:  "a = 2b + 3 => a = 2 * b + 3"
+        no_implicit_mult_asgn:   "a = 2 b = 3 => a = 2"
+        semicolon_separated:     "a = 1; b + 2 => a = 1\nb + 2"
+        semicolon_repeated:      ";a = 1;; b;\n; => a = 1\nb"
+        nested_newline:          "(1\n+ 2) => (1 + 2)"
+        nested_newline_no_stmt:  "(2\nx) => (2 * x)"
+        call_newline:            "max(1,\n2) => max(1, 2)"
+        crlf_separated:          "a = 1\r\nb + 2\r\n => a = 1\nb + 2"
+        nbsp_separated:          "a\u{a0}=\u{a0}1 => a = 1"
+        interval:                "[1, 2]"
+        interval_exprs:          "[x - 1, 2 * x]"
+        interval_in_op:          "2 * [a, b] + 1"
+        interval_newline:        "[1,\n2] => [1, 2]"
+        bracket_not_interval:    "[1 + 2]"
+        bracket_var:             "[x]"
+    }
+
+    #[test]
+    fn interval_distinct_from_bracket() {
+        use crate::grammar::*;
+        use crate::parse_expr;
+
+        assert!(matches!(parse_expr!("[1, 2]").as_ref(), Expr::Interval(_)));
+        assert!(matches!(
+            parse_expr!("[1 + 2]").as_ref(),
+            Expr::Bracketed(_)
+        ));
+    }
+
+    #[test]
+    fn wrong_argument_count() {
+        use crate::parser::parse_statements;
+        use crate::scanner::scan;
+
+        for (program, arg_counts) in &[
+            ("max(1, 2)", vec![]),
+            ("max()", vec!["expected 2 arguments, found 0"]),
+            ("max(1, 2, 3)", vec!["expected 2 arguments, found 3"]),
+            ("sqrt(1, 2)", vec!["expected 1 argument, found 2"]),
+            ("f(x) = x\nf(1, 2)", vec!["expected 1 argument, found 2"]),
+            ("min(max(1), 2)", vec!["expected 2 arguments, found 1"]),
+        ] {
+            let diagnostics = parse_statements(scan(*program).tokens, program).diagnostics;
+            let messages: Vec<_> = diagnostics
+                .iter()
+                .inspect(|d| assert_eq!(d.code, "P0008"))
+                .map(|d| d.msg.as_deref().unwrap())
+                .collect();
+            assert_eq!(&messages, arg_counts, "{}", program);
+        }
+    }
+
+    mod missing_operand {
+        use crate::diagnostics::{Diagnostic, Edit};
+        use crate::parser::parse_statements;
+        use crate::scanner::scan;
+
+        /// Parses `program`, returning its emitted statements and diagnostics.
+        fn parse(program: &str) -> (String, Vec<Diagnostic>) {
+            let parsed = parse_statements(scan(program).tokens, program);
+            (parsed.program.to_string(), parsed.diagnostics)
+        }
+
+        /// Checks that `diag` reports a missing operand of the operator at `op_span`, and notes the
+        /// token at `found_span`.
+        fn check_missing_operand(
+            diag: &Diagnostic,
+            op: &str,
+            op_span: (usize, usize),
+            found_span: (usize, usize),
+        ) {
+            assert_eq!(diag.code, "P0002");
+            assert_eq!(diag.title, format!("Expected an operand after `{}`", op));
+            assert_eq!((diag.span.lo, diag.span.hi), op_span);
+            let note = &diag.associated_diagnostics[0];
+            assert_eq!((note.span.lo, note.span.hi), found_span);
+            assert!(matches!(
+                diag.autofix.as_ref().map(|a| &a.fix),
+                Some(Edit::Delete)
+            ));
+        }
+
+        #[test]
+        fn trailing_operator_at_eof() {
+            let (stmts, diags) = parse("1 +");
+            assert_eq!(stmts, "1 + ");
+            assert_eq!(diags.len(), 1);
+            check_missing_operand(&diags[0], "+", (2, 3), (3, 4));
+        }
+
+        #[test]
+        fn trailing_operator_before_stmt_break() {
+            let (stmts, diags) = parse("a = 1 *\nb = 2 /\nc = 3");
+            assert_eq!(stmts, "a = 1 * \nb = 2 / \nc = 3");
+            assert_eq!(diags.len(), 2);
+            check_missing_operand(&diags[0], "*", (6, 7), (8, 9));
+            check_missing_operand(&diags[1], "/", (14, 15), (16, 17));
+        }
+
+        #[test]
+        fn operator_continues_on_next_line() {
+            let (stmts, diags) = parse("1 +\n2");
+            assert_eq!(stmts, "1 + 2");
+            assert!(diags.is_empty());
+        }
+
+        #[test]
+        fn doubled_operators() {
+            let (stmts, diags) = parse("1 + * 2 - 3");
+            assert_eq!(stmts, "1 +  * 2 - 3");
+            assert_eq!(diags.len(), 1);
+            check_missing_operand(&diags[0], "+", (2, 3), (4, 5));
+        }
+
+        #[test]
+        fn trailing_unary_operator() {
+            let (stmts, diags) = parse("1 + -");
+            assert_eq!(diags.len(), 1);
+            assert_eq!(stmts, "1 + -");
+            check_missing_operand(&diags[0], "-", (4, 5), (5, 6));
+        }
+
+        #[test]
+        fn operator_before_closing_delimiter() {
+            let (stmts, diags) = parse("(1 +) * 2");
+            assert_eq!(stmts, "(1 + ) * 2");
+            assert_eq!(diags.len(), 1);
+            check_missing_operand(&diags[0], "+", (3, 4), (4, 5));
+        }
     }
 }
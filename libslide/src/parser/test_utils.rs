@@ -109,6 +109,11 @@ pub fn verify_expr_spans(expr: &RcExpr, input: &str) {
             verify_expr_spans(rhs, input);
         }
         Expr::Parend(inner) | Expr::Bracketed(inner) => verify_expr_spans(inner, input),
+        Expr::Call(Call { args, .. }) => args.iter().for_each(|arg| verify_expr_spans(arg, input)),
+        Expr::Interval(Interval { lo, hi }) => {
+            verify_expr_spans(lo, input);
+            verify_expr_spans(hi, input);
+        }
         _ => (),
     }
 }
@@ -4,19 +4,29 @@
 mod errors;
 mod compare;
 pub mod flatten;
+mod function_expand;
+mod memo;
+mod plus_minus_expand;
+mod profile;
 mod validate;
 mod variable_expand;
 
 pub use errors::PartialEvaluatorErrors;
 use flatten::flatten_expr;
+use function_expand::FunctionDefinitions;
+use memo::{EvaluationMemo, MemoizedEvaluation};
+use plus_minus_expand::expand_plus_minus;
+pub use profile::RuleProfile;
 use validate::validate;
+pub use variable_expand::{expand_variables, substitute};
 
 use crate::diagnostics::Diagnostic;
-use crate::evaluator_rules::{BuildRuleErrors, Rule, RuleSet};
+use crate::evaluator_rules::{BuildRuleErrors, Rule, RuleName, RuleSet};
 use crate::grammar::*;
 use crate::utils::{hash, normalize};
-use crate::ProgramContext;
+use crate::{Objective, ProgramContext, Rigor, SquareForm};
 
+use std::cell::{Cell, RefCell};
 use std::collections::HashSet;
 use std::error::Error;
 
@@ -26,65 +36,302 @@ pub struct EvaluationResult {
     pub simplified: StmtList,
     /// Diagnostic
     pub diagnostics: Vec<Diagnostic>,
+    /// Named rules that transformed an expression during evaluation, in the order they were
+    /// applied. A rule is listed at most once per pass of the rules over an expression.
+    pub fired_rules: Vec<RuleName>,
+    /// The number of passes of the rules over expressions of the program until each expression
+    /// reached a fixed point, summed over all statements. This includes the final pass over each
+    /// expression, which finds that it cannot be simplified further.
+    pub iterations: usize,
 }
 
 /// Evaluates a list of statements to as simplified a form as possible for each.
 /// The evaluation may be partial, as some values (like variables) may be unknown.
+///
+/// A statement with `±` operators is evaluated as one statement for each choice of `+` or `-` for
+/// its operators, so `1 ± 2` evaluates to the two statements `3` and `-1`.
 pub fn evaluate(
     stmt_list: StmtList,
     ctxt: &ProgramContext,
 ) -> Result<EvaluationResult, Box<dyn Error>> {
     let eval_rules = build_rules(ctxt)?;
-    let simplify = |expr: RcExpr| evaluate_expr(expr, &eval_rules, &ctxt);
+    Ok(evaluate_with_rules(stmt_list, &eval_rules, ctxt))
+}
+
+/// Like [`evaluate`](evaluate), but evaluates a list of statements with a set of rules that has
+/// already been [built](build_rules). This is useful when many programs are evaluated under the
+/// same context, and the rules need not be rebuilt for every evaluation.
+pub fn evaluate_with_rules(
+    stmt_list: StmtList,
+    rules: &[Rule],
+    ctxt: &ProgramContext,
+) -> EvaluationResult {
+    evaluate_stmts(stmt_list, rules, ctxt, &mut |_| {})
+}
+
+/// Like [`evaluate`](evaluate), but evaluates a list of statements with the rules of a
+/// [`RuleSet`](RuleSet) rather than those selected by the context. The context's rule denylist and
+/// square form are not applied to the rule set.
+pub fn evaluate_with_rule_set(
+    stmt_list: StmtList,
+    rule_set: &RuleSet,
+    ctxt: &ProgramContext,
+) -> Result<EvaluationResult, Box<dyn Error>> {
+    let eval_rules = rule_set.build()?;
+    Ok(evaluate_with_rules(stmt_list, &eval_rules, ctxt))
+}
+
+/// Like [`evaluate`](evaluate), but also profiles how many times each rule transformed an
+/// expression during evaluation.
+pub fn evaluate_profiled(
+    stmt_list: StmtList,
+    ctxt: &ProgramContext,
+) -> Result<(EvaluationResult, RuleProfile), Box<dyn Error>> {
+    let named_rules = rule_set(ctxt).build_named()?;
+    let mut profile = RuleProfile::new(&named_rules);
+    let rules: Vec<_> = named_rules.into_iter().map(|(_, rule)| rule).collect();
+    let result = evaluate_stmts(stmt_list, &rules, ctxt, &mut |i| profile.record_hit(i));
+    Ok((result, profile))
+}
+
+/// Evaluates a list of statements, calling `on_hit` with the index of a rule whenever that rule
+/// transforms an expression.
+fn evaluate_stmts(
+    stmt_list: StmtList,
+    rules: &[Rule],
+    ctxt: &ProgramContext,
+    on_hit: &mut dyn FnMut(usize),
+) -> EvaluationResult {
+    let source = stmt_list.source.clone();
+    let (stmt_list, mut diagnostics) = expand_plus_minus(stmt_list);
+    let original = stmt_list.clone();
+    let on_hit = RefCell::new(on_hit);
+    let fired_rules = RefCell::new(Vec::new());
+    let iterations = Cell::new(0);
+    // Identical expressions in different statements evaluate identically, so each is evaluated
+    // once and its evaluation replayed for the others.
+    let memo = RefCell::new(EvaluationMemo::default());
+    let simplify = |expr: RcExpr| {
+        if let Some(memoized) = memo.borrow_mut().get(&expr) {
+            let MemoizedEvaluation {
+                simplified,
+                passes,
+                fired_rules: fired,
+                hits,
+                ..
+            } = memoized;
+            hits.into_iter().for_each(&mut *on_hit.borrow_mut());
+            fired_rules.borrow_mut().extend(fired);
+            iterations.set(iterations.get() + passes);
+            return simplified;
+        }
+
+        let fired_before = fired_rules.borrow().len();
+        let mut hits = Vec::new();
+        let (simplified, passes) = evaluate_expr_inner(
+            expr.clone(),
+            rules,
+            ctxt,
+            &mut |i| {
+                hits.push(i);
+                (on_hit.borrow_mut())(i)
+            },
+            &mut fired_rules.borrow_mut(),
+        );
+        iterations.set(iterations.get() + passes);
+        let fired = fired_rules.borrow()[fired_before..].to_vec();
+        memo.borrow_mut()
+            .insert(expr, simplified.clone(), passes, fired, hits);
+        simplified
+    };
+    // Calls of functions defined by earlier statements are expanded before simplification.
+    let mut functions = FunctionDefinitions::default();
     let evaluated = stmt_list
         .into_iter()
-        .map(|stmt| stmt.update_with(simplify, |asgn| asgn.redefine_with(simplify)))
+        .map(|stmt| {
+            let expand = |expr| simplify(functions.expand(expr));
+            let stmt = stmt.update_with(expand, |asgn| asgn.redefine_with(expand));
+            if let StmtKind::Assignment(asgn) = &stmt.kind {
+                functions.define(asgn);
+            }
+            stmt
+        })
         .collect::<Vec<_>>();
 
-    let simplified = StmtList::new(evaluated);
-    let diagnostics = validate(&simplified, "", ctxt, &eval_rules); // TODO: propogate program text
-    Ok(EvaluationResult {
+    let mut simplified = StmtList::new(evaluated);
+    simplified.source = source;
+    diagnostics.extend(validate(
+        &simplified,
+        &original,
+        simplified.source(),
+        ctxt,
+        rules,
+    ));
+    EvaluationResult {
         simplified,
         diagnostics,
-    })
+        fired_rules: fired_rules.into_inner(),
+        iterations: iterations.get(),
+    }
+}
+
+/// Normalizes a list of statements as the first pass of [evaluation](evaluate) does, without
+/// applying any simplification rules. Statements with `±` operators are expanded into their
+/// branches, expressions are [flattened](flatten) if the context always flattens them, and the
+/// operands of their operations are put in a canonical order.
+pub fn normalize_stmts(stmt_list: StmtList, ctxt: &ProgramContext) -> StmtList {
+    let normalize_expr = |expr| {
+        if ctxt.always_flatten {
+            normalize(flatten_expr(expr, ctxt.rigor))
+        } else {
+            normalize(expr)
+        }
+    };
+    let normalized = expand_plus_minus(stmt_list)
+        .0
+        .into_iter()
+        .map(|stmt| stmt.update_with(normalize_expr, |asgn| asgn.redefine_with(normalize_expr)))
+        .collect();
+    StmtList::new(normalized)
+}
+
+/// Evaluates a single expression to as simplified a form as possible under a context, without
+/// wrapping it in a program. The expression is simplified by the same rules and passes as each
+/// expression of a program [evaluated](evaluate) under the context, and is validated like one;
+/// if validation reports any diagnostics, they are returned instead.
+///
+/// To evaluate many expressions under the same context, [build the rules](build_rules) once and
+/// use [`evaluate_expr`](evaluate_expr) instead.
+///
+/// ```
+/// use libslide::{evaluate_expression, parse_statements, scan, ProgramContext, StmtKind};
+///
+/// let parse = |program| {
+///     let stmt = parse_statements(scan(program).tokens, program).program.into_iter().next();
+///     match stmt.unwrap().kind {
+///         StmtKind::Expr(expr) => expr,
+///         _ => unreachable!(),
+///     }
+/// };
+///
+/// let ctxt = ProgramContext::default();
+/// let simplified = evaluate_expression(parse("1 + 2 + x"), &ctxt).unwrap();
+/// assert_eq!(simplified.to_string(), "x + 3");
+///
+/// let diagnostics = evaluate_expression(parse("1 / 0"), &ctxt).unwrap_err();
+/// assert_eq!(diagnostics[0].code, "V0002");
+/// ```
+pub fn evaluate_expression(expr: RcExpr, ctxt: &ProgramContext) -> Result<RcExpr, Vec<Diagnostic>> {
+    let rules = build_rules(ctxt).expect("the rules of a context always build");
+    let original = StmtList::new(vec![Stmt::new(expr.clone().into(), 0)]);
+    let simplified = evaluate_expr(expr, &rules, ctxt);
+    let stmt_list = StmtList::new(vec![Stmt::new(simplified.clone().into(), 0)]);
+    let diagnostics = validate(&stmt_list, &original, "", ctxt, &rules);
+    if diagnostics.is_empty() {
+        Ok(simplified)
+    } else {
+        Err(diagnostics)
+    }
 }
 
 /// Evaluates an expression to as simplified a form as possible.
 /// The evaluation may be partial, as some values (like variables) may be unknown.
 /// The returned expression is normalized.
 pub fn evaluate_expr(expr: RcExpr, rules: &[Rule], ctxt: &ProgramContext) -> RcExpr {
+    evaluate_expr_inner(expr, rules, ctxt, &mut |_| {}, &mut Vec::new()).0
+}
+
+/// Evaluates an expression, calling `on_hit` with the index of a rule and adding the rule's name to
+/// `fired_rules` whenever that rule transforms the expression. Also returns the number of passes of
+/// the rules over the expression.
+fn evaluate_expr_inner(
+    expr: RcExpr,
+    rules: &[Rule],
+    ctxt: &ProgramContext,
+    on_hit: &mut dyn FnMut(usize),
+    fired_rules: &mut Vec<RuleName>,
+) -> (RcExpr, usize) {
     let mut simplified_expr = expr;
     // Try simplifying the expression with a rule set until the same expression is seen again,
     // meaning we can't simplify any further or are stuck in a cycle.
     let mut expr_hash = hash(&simplified_expr);
     let mut seen: HashSet<u64> = HashSet::new();
     if ctxt.always_flatten {
-        simplified_expr = flatten_expr(simplified_expr);
+        simplified_expr = flatten_expr(simplified_expr, ctxt.rigor);
     }
+    // The form of the expression preferred by the objective among those seen after each pass.
+    let mut best_expr = simplified_expr.clone();
     while seen.insert(expr_hash) {
-        for rule in rules {
-            simplified_expr = rule.transform(simplified_expr);
+        let fired_in_pass = fired_rules.len();
+        for (i, rule) in rules.iter().enumerate() {
+            // Rules return an expression they do not change as it is, so a rule transformed the
+            // expression iff it returned a different one.
+            let before = simplified_expr.addr();
+            simplified_expr = rule.transform_for(simplified_expr, ctxt.objective);
+            if simplified_expr.addr() != before {
+                on_hit(i);
+                match rule.name() {
+                    Some(name) if !fired_rules[fired_in_pass..].contains(&name) => {
+                        fired_rules.push(name)
+                    }
+                    _ => {}
+                }
+            }
         }
         expr_hash = hash(&simplified_expr);
+        // Later forms win ties, since they have been simplified further. Only the primary cost
+        // of the objective is compared, since rules may deliberately increase complexity, as in
+        // expansions.
+        if ctxt.objective.cost(&simplified_expr).0 <= ctxt.objective.cost(&best_expr).0 {
+            best_expr = simplified_expr.clone();
+        }
     }
 
-    normalize(simplified_expr)
+    // Each pass saw a new form of the expression.
+    (normalize(best_expr), seen.len())
+}
+
+#[cfg(feature = "benchmark-internals")]
+/// Validates an evaluated list of statements, as the last phase of [`evaluate`](evaluate) does.
+pub fn _validate(stmt_list: &StmtList, rules: &[Rule], ctxt: &ProgramContext) -> Vec<Diagnostic> {
+    validate(stmt_list, stmt_list, stmt_list.source(), ctxt, rules)
 }
 
 /// Given an evaluator context, builds a set of evaluator rules to be used in partial evaluation.
 pub fn build_rules(ctxt: &ProgramContext) -> Result<Vec<Rule>, BuildRuleErrors> {
-    let mut rule_set = RuleSet::default();
+    rule_set(ctxt).build()
+}
+
+/// Retrieves the set of unbuilt rules to be used under an evaluator context.
+fn rule_set(ctxt: &ProgramContext) -> RuleSet {
+    let mut rule_set = RuleSet::default().preset(ctxt.rule_set);
+    if ctxt.square_form == SquareForm::Multiply {
+        rule_set.remove(RuleName::FoldSquare);
+        rule_set.insert(RuleName::ExpandSquare);
+    }
+    if ctxt.rigor == Rigor::Rigorous {
+        rule_set.remove(RuleName::CancelDivision);
+        rule_set.remove(RuleName::CancelPolynomials);
+    }
+    match ctxt.objective {
+        Objective::PreferFactored => rule_set.insert(RuleName::FactorOut),
+        Objective::PreferExpanded => rule_set.insert(RuleName::DistributeMultiplication),
+        Objective::OpCount | Objective::Depth => {}
+    }
     for rule in &ctxt.rule_denylist {
-        rule_set.remove(rule)
+        rule_set.remove(*rule)
     }
-    rule_set.build()
+    rule_set
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{evaluate, EvaluationResult};
+    use super::{
+        evaluate, evaluate_expression, evaluate_profiled, normalize_stmts, EvaluationResult,
+    };
     use crate::evaluator_rules::RuleName;
-    use crate::{parse_stmt, ProgramContext};
+    use crate::grammar::*;
+    use crate::{parse_stmt, Emit, EmitConfig, Objective, ProgramContext, Rigor, Span, SquareForm};
 
     macro_rules! partial_evaluator_tests {
         ($($name:ident: $program:expr => $result:expr)*) => {
@@ -110,12 +357,23 @@ mod tests {
         sub:                            "1 - 2"     => "-1"
         sub_nested_left:                "1 - 2 - a" => "-1 - a"
 
+        plus_minus:                     "1 ± 2"     => "3\n-1"
+        plus_minus_ascii:               "1 +/- 2"   => "3\n-1"
+        plus_minus_var:                 "x ± 1"     => "x + 1\nx + -1"
+        plus_minus_quadratic:           "(3 ± (3^2 - 4 * 2)^0.5) / 2" => "2\n1"
+
         mult:                           "2 * 3"     => "6"
         mult_nested_left:               "2 * 3 * a" => "a * 6"
 
         div:                            "6 / 2"     => "3"
         div_nested_left:                "6 / 2 / a" => "3 / a"
         div_associated:                 "6 / 2 / 3" => "1"
+        fold_constants:                 "(1 + 2) * (3 - 1)" => "6"
+        fold_constants_partially:       "x * (1 + 2)"       => "x * 3"
+        fold_constants_unary:           "-(2 + 1)! + x"     => "x + -6"
+
+        div_polys_exact:                "(x^2 - 4) / (x + 2)"  => "x + -2"
+        div_polys_remainder:            "(x^2 - 2x) / (x + 1)" => "x + -3 + 3 / (x + 1)"
 
         modulo:                         "6 % 4"     => "2"
         modulo_nested_left:             "6 % 4 % a" => "2 % a"
@@ -168,18 +426,467 @@ mod tests {
         unwrap_brackets_var:              "[a]"       => "a"
         unwrap_brackets_nested:           "[a] + [1]" => "a + 1"
 
-        flattened_addition:             "1 + 2 - b + 3 - b" => "6 - b - b"
+        min:                            "min(2, 5)"          => "2"
+        min_nested:                     "min(1 + 4, 2 * 3)"  => "5"
+        min_mixed:                      "min(1 + 1, a)"      => "min(2, a)"
+        max:                            "max(2, 5)"          => "5"
+        max_negative:                   "max(-2, -5)"        => "-2"
+        max_mixed:                      "max(a, 2 * 3)"      => "max(a, 6)"
+        clamp_low:                      "clamp(-1, 0, 1)"    => "0"
+        clamp_high:                     "clamp(5, 0, 1)"     => "1"
+        clamp_within:                   "clamp(0.5, 0, 1)"   => "0.5"
+        clamp_mixed:                    "clamp(x, 0, 1 + 1)" => "clamp(x, 0, 2)"
+        clamp_empty_range:              "clamp(0.5, 1, 0)"   => "clamp(0.5, 1, 0)"
+        call_in_expr:                   "max(1, 2) + a"      => "a + 2"
+        call_wrong_arity:               "max(1, 2, 3)"       => "max(1, 2, 3)"
+
+        sqrt:                           "sqrt(9)"            => "3"
+        sqrt_nested:                    "sqrt(4 * 4)"        => "4"
+        sqrt_non_exact:                 "sqrt(2)"            => "sqrt(2)"
+        sqrt_negative:                  "sqrt(-4)"           => "sqrt(-4)"
+        sqrt_var:                       "sqrt(a)"            => "sqrt(a)"
+        root_cube:                      "root(3, 27)"        => "3"
+        root_fourth:                    "root(4, 625)"       => "5"
+        root_odd_negative:              "root(3, -8)"        => "-2"
+        root_first:                     "root(1, 7)"         => "7"
+        root_square_agrees_with_sqrt:   "root(2, 9) - sqrt(9)" => "0"
+        root_non_exact:                 "root(3, 10)"        => "root(3, 10)"
+        root_even_negative:             "root(4, -16)"       => "root(4, -16)"
+        root_non_integer_degree:        "root(1.5, 8)"       => "root(1.5, 8)"
+        root_zero_degree:               "root(0, 8)"         => "root(0, 8)"
+        root_var:                       "root(3, a)"         => "root(3, a)"
+        root_var_degree:                "root(n, 8)"         => "root(n, 8)"
+        sum:                            "sum(i, 1, 3, i)"    => "6"
+        sum_body:                       "sum(i, 1, 4, i^2 + 1)" => "34"
+        sum_computed_bound:             "sum(i, 1, 1 + 2, 2 * i)" => "12"
+        sum_empty:                      "sum(i, 3, 1, i)"    => "0"
+        sum_free_var:                   "sum(i, 1, 2, a + i)" => "3 + a * 2"
+        sum_nested:                     "sum(i, 1, 2, sum(j, 1, i, j))" => "4"
+        sum_symbolic_bound:             "sum(i, 1, n, i)"    => "sum(i, 1, n, i)"
+        sum_non_integer_bound:          "sum(i, 1, 2.5, i)"  => "sum(i, 1, 2.5, i)"
+        factorial:                      "5!"                 => "120"
+        factorial_zero:                 "0!"                 => "1"
+        factorial_nested:               "3!!"                => "720"
+        factorial_expr:                 "(1 + 2)! + 1"       => "7"
+        factorial_precedence:           "2 ^ 3! - 3!^2"      => "28"
+        factorial_var:                  "a!"                 => "a!"
+        // Rounded once from the exact factorial; multiplying in floats rounds to a different number.
+        factorial_rounded:              "30!"                => "265252859812191070000000000000000"
+        factorial_overflow:             "171!"               => "171!"
+
+        flattened_addition:             "1 + 2 - b + 3 - b" => "6 - b * 2"
 
         issue_92: "a + 1 - 1" => "a"
+
+        chained_assignment_2:           "a = b = 1 + 2"     => "a = 3\nb = 3"
+        chained_assignment_3:           "a = b = c = 1 + 2" => "a = 3\nb = 3\nc = 3"
+        chained_assignment_symbolic:    "a = b = c + 0"     => "a = c\nb = c"
     }
 
     #[test]
     fn remove_rule() {
         let parsed = parse_stmt!("1 - 2 + 3 * 4");
         let ctxt = ProgramContext::default()
-            .with_denylist([RuleName::Add].to_vec())
+            .with_denylist([RuleName::Add, RuleName::FoldConstants].to_vec())
             .always_flatten(false);
         let EvaluationResult { simplified, .. } = evaluate(parsed, &ctxt).unwrap();
         assert_eq!(simplified.to_string(), "-1 + 12".to_string());
     }
+
+    #[test]
+    fn fold_constants_without_operation_rules() {
+        let parsed = parse_stmt!("x * ((1 + 2) * (3 - 1) + 2 ^ 2)");
+        let ctxt = ProgramContext::default()
+            .with_denylist(
+                [
+                    RuleName::Add,
+                    RuleName::Subtract,
+                    RuleName::Multiply,
+                    RuleName::Exponentiate,
+                ]
+                .to_vec(),
+            )
+            .always_flatten(false);
+        let EvaluationResult { simplified, .. } = evaluate(parsed, &ctxt).unwrap();
+        assert_eq!(simplified.to_string(), "x * 10".to_string());
+    }
+
+    #[test]
+    fn normalize_before_evaluation() {
+        for (program, always_flatten, normalized, evaluated) in &[
+            ("1 + 2 + x", false, "x + 1 + 2", "x + 3"),
+            ("1 + 2 + x", true, "x + 3", "x + 3"),
+            (
+                "(x^2 - 1) / (x - 1)",
+                true,
+                "(-1 + x ^ 2) * 1 / (x + -1)",
+                "x + 1",
+            ),
+        ] {
+            let ctxt = ProgramContext::default().always_flatten(*always_flatten);
+            let normalized_program = normalize_stmts(parse_stmt!(*program), &ctxt);
+            let EvaluationResult { simplified, .. } =
+                evaluate(parse_stmt!(*program), &ctxt).unwrap();
+            assert_eq!(normalized_program.to_string(), normalized.to_string());
+            assert_eq!(simplified.to_string(), evaluated.to_string());
+        }
+    }
+
+    #[test]
+    fn evaluate_expression_like_statements() {
+        let program = "1 + 2 + x\n(x + 1) * (x + 1)\n2 ^ 3 / y\nmax(1, 2) + 1";
+        for ctxt in &[
+            ProgramContext::default(),
+            ProgramContext::default().always_flatten(false),
+            ProgramContext::default().rigor(Rigor::Rigorous),
+        ] {
+            let EvaluationResult { simplified, .. } = evaluate(parse_stmt!(program), ctxt).unwrap();
+            for (stmt, simplified) in parse_stmt!(program).into_iter().zip(simplified) {
+                match (stmt.kind, simplified.kind) {
+                    (StmtKind::Expr(expr), StmtKind::Expr(simplified)) => assert_eq!(
+                        evaluate_expression(expr, ctxt).unwrap().to_string(),
+                        simplified.to_string()
+                    ),
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn evaluate_expression_diagnostics() {
+        let ctxt = ProgramContext::default();
+        for (program, code) in &[("1 / 0", "V0002"), ("(-1)!", "V0003")] {
+            let expr = match parse_stmt!(*program).into_iter().next().unwrap().kind {
+                StmtKind::Expr(expr) => expr,
+                _ => unreachable!(),
+            };
+            let diagnostics = evaluate_expression(expr, &ctxt).unwrap_err();
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].code, *code);
+        }
+    }
+
+    #[test]
+    fn square_form() {
+        for (program, square_form, expected) in &[
+            ("x * x", SquareForm::Power, "x ^ 2"),
+            ("x ^ 2", SquareForm::Power, "x ^ 2"),
+            ("x * x", SquareForm::Multiply, "x * x"),
+            ("x ^ 2", SquareForm::Multiply, "x * x"),
+            ("x ^ 2 + 1", SquareForm::Multiply, "1 + x * x"),
+            (
+                "(a + 1) ^ 2 + 1",
+                SquareForm::Multiply,
+                "1 + (a + 1) * (a + 1)",
+            ),
+        ] {
+            let parsed = parse_stmt!(*program);
+            let ctxt = ProgramContext::default().square_form(*square_form);
+            let EvaluationResult { simplified, .. } = evaluate(parsed, &ctxt).unwrap();
+            assert_eq!(simplified.to_string(), *expected);
+        }
+    }
+
+    /// Returns the expression of the only statement of a statement list.
+    fn only_expr(stmt_list: &StmtList) -> RcExpr {
+        match &stmt_list.iter().next().unwrap().kind {
+            StmtKind::Expr(expr) => expr.clone(),
+            StmtKind::Assignment(asgn) => asgn.rhs.clone(),
+        }
+    }
+
+    #[test]
+    fn simplified_root_span() {
+        for program in &[
+            "x + 0",
+            "1 * x",
+            "(x)",
+            "+x",
+            "-(-x)",
+            "3 + x - 3",
+            "1 + (2 + 3)",
+            "(1 + 2) * x",
+            "2 * x + 3 * x",
+            "a * (1 / b)",
+            "(x + 1) / (x + 1)",
+            "max(1, 2)",
+            "y = x * x",
+        ] {
+            for always_flatten in &[true, false] {
+                let parsed = parse_stmt!(*program);
+                let original_span = only_expr(&parsed).span;
+                let ctxt = ProgramContext::default().always_flatten(*always_flatten);
+                let EvaluationResult { simplified, .. } = evaluate(parsed, &ctxt).unwrap();
+                assert_eq!(only_expr(&simplified).span, original_span, "{}", program);
+            }
+        }
+    }
+
+    #[test]
+    fn folded_constant_span() {
+        for always_flatten in &[true, false] {
+            let program = "1 + x + 2";
+            let ctxt = ProgramContext::default().always_flatten(*always_flatten);
+            let EvaluationResult { simplified, .. } =
+                evaluate(parse_stmt!(program), &ctxt).unwrap();
+            let simplified = only_expr(&simplified);
+            assert_eq!(simplified.to_string(), "x + 3");
+
+            let konst = match simplified.as_ref() {
+                Expr::BinaryExpr(BinaryExpr { rhs, .. }) => rhs.clone(),
+                _ => unreachable!(),
+            };
+            assert_eq!(konst.span, Span::new(0, 9));
+            assert_eq!(konst.span.over(program), "1 + x + 2");
+        }
+    }
+
+    #[test]
+    fn objective() {
+        for (program, objective, expected) in &[
+            ("a * (b + c)", Objective::OpCount, "a * (b + c)"),
+            ("a * (b + c)", Objective::PreferExpanded, "a * b + a * c"),
+            ("a * b + a * c", Objective::OpCount, "a * b + a * c"),
+            ("a * b + a * c", Objective::PreferFactored, "a * (b + c)"),
+            ("a * (b + c)", Objective::Depth, "a * (b + c)"),
+        ] {
+            let parsed = parse_stmt!(*program);
+            let ctxt = ProgramContext::default().objective(*objective);
+            let EvaluationResult { simplified, .. } = evaluate(parsed, &ctxt).unwrap();
+            assert_eq!(simplified.to_string(), *expected, "{:?}", objective);
+        }
+    }
+
+    #[test]
+    fn rigor() {
+        for (program, rigor, always_flatten, expected) in &[
+            ("x / x", Rigor::Pragmatic, true, "1"),
+            ("x / x", Rigor::Pragmatic, false, "1"),
+            ("x / x", Rigor::Rigorous, true, "x / x"),
+            ("x / x", Rigor::Rigorous, false, "x / x"),
+            ("2 * x / x + y", Rigor::Pragmatic, true, "y + 2"),
+            ("2 * x / x + y", Rigor::Rigorous, true, "y + 2 * x / x"),
+            ("2 * (x / x) + y", Rigor::Pragmatic, false, "y + 2"),
+            ("2 * (x / x) + y", Rigor::Rigorous, false, "y + 2 * x / x"),
+            ("(x^2 - 4) / (x + 2)", Rigor::Pragmatic, true, "x + -2"),
+            (
+                "(x^2 - 4) / (x + 2)",
+                Rigor::Rigorous,
+                true,
+                "(-4 + x ^ 2) / (x + 2)",
+            ),
+            (
+                "(x^2 - 2x) / (x + 1)",
+                Rigor::Rigorous,
+                true,
+                "x + -3 + 3 / (x + 1)",
+            ),
+        ] {
+            let parsed = parse_stmt!(*program);
+            let ctxt = ProgramContext::default()
+                .rigor(*rigor)
+                .always_flatten(*always_flatten);
+            let EvaluationResult { simplified, .. } = evaluate(parsed, &ctxt).unwrap();
+            assert_eq!(simplified.to_string(), *expected, "{} {:?}", program, rigor);
+        }
+    }
+
+    #[test]
+    fn profile_rules() {
+        // Constant folding in `1 + 2 + x + 0` subsumes the additive identity, so the identity is
+        // exercised by a separate statement.
+        let parsed = parse_stmt!("1 + 2 + x + 0\ny + 0");
+        let ctxt = ProgramContext::default().always_flatten(false);
+        let (EvaluationResult { simplified, .. }, profile) =
+            evaluate_profiled(parsed, &ctxt).unwrap();
+        assert_eq!(simplified.to_string(), "x + 3\ny".to_string());
+
+        let hits = profile.hits();
+        let hits_of = |rule: &str| hits.iter().find(|(r, _)| *r == rule).unwrap().1;
+        assert!(hits_of("Add") > 0);
+        assert!(hits_of("AdditiveIdentity") > 0);
+        assert_eq!(hits_of("Multiply"), 0);
+
+        // Hits are sorted in descending order.
+        assert!(hits.windows(2).all(|w| w[0].1 >= w[1].1));
+    }
+
+    #[test]
+    fn fired_rules() {
+        let parsed = parse_stmt!("1 * x + 0");
+        // Normalization would simplify the program before any rule is applied.
+        let ctxt = ProgramContext::default().always_flatten(false);
+        let EvaluationResult {
+            simplified,
+            fired_rules,
+            ..
+        } = evaluate(parsed, &ctxt).unwrap();
+        assert_eq!(simplified.to_string(), "x");
+        // Constant folding moves the `1` in `1 * x` to the end of the product, where the
+        // multiplicative identity can remove it.
+        assert_eq!(
+            fired_rules,
+            vec![
+                RuleName::Multiply,
+                RuleName::MultiplicateIdentity,
+                RuleName::AdditiveIdentity
+            ]
+        );
+    }
+
+    #[test]
+    fn iterations() {
+        let parsed = parse_stmt!("1 + 2 + 3");
+        let EvaluationResult {
+            simplified,
+            iterations,
+            ..
+        } = evaluate(parsed, &ProgramContext::default()).unwrap();
+        assert_eq!(simplified.to_string(), "6");
+        // The first pass folds the sum, and the second finds no further simplification.
+        assert_eq!(iterations, 2);
+    }
+
+    #[test]
+    fn identical_expressions_in_statements() {
+        let evaluate_last = |program| {
+            let ctxt = ProgramContext::default().always_flatten(false);
+            let result = evaluate(parse_stmt!(program), &ctxt).unwrap();
+            let last = match result.simplified.into_iter().last().unwrap().kind {
+                StmtKind::Assignment(asgn) => asgn.rhs,
+                StmtKind::Expr(_) => unreachable!(),
+            };
+            (last, result.fired_rules, result.iterations)
+        };
+        // The first statements are of the same length, so that the last statements are at the
+        // same place in both programs, but only the first program repeats an expression.
+        let (repeated, repeated_fired, repeated_iterations) =
+            evaluate_last("a = 1 + x * 2 + 3\nb = 1+x*2+3");
+        let (distinct, _, _) = evaluate_last("a = 9 + y / 8 - 7\nb = 1+x*2+3");
+        let (_, once_fired, once_iterations) = evaluate_last("b = 1+x*2+3");
+
+        // The evaluation of the repeated expression is reused, at the spans of the repetition.
+        assert_eq!(repeated.to_string(), "4 + x * 2");
+        assert_eq!(
+            repeated.emit_json(EmitConfig::default()),
+            distinct.emit_json(EmitConfig::default())
+        );
+        assert_eq!(repeated_fired, [once_fired.clone(), once_fired].concat());
+        assert_eq!(repeated_iterations, 2 * once_iterations);
+    }
+
+    #[test]
+    fn no_fired_rules() {
+        let parsed = parse_stmt!("x");
+        let EvaluationResult { fired_rules, .. } =
+            evaluate(parsed, &ProgramContext::default()).unwrap();
+        assert!(fired_rules.is_empty());
+    }
+
+    #[test]
+    fn non_finite_value_points_to_source() {
+        for (program, span, spanned) in &[
+            ("1/0", Span::new(1, 2), "/"),
+            ("a := x + 1 / 0", Span::new(11, 12), "/"),
+            ("a := x + 2 ^ 10000", Span::new(9, 18), "2 ^ 10000"),
+        ] {
+            for ctxt in [
+                ProgramContext::default(),
+                ProgramContext::default().always_flatten(false),
+            ]
+            .iter()
+            {
+                let parsed = parse_stmt!(*program);
+                let EvaluationResult { diagnostics, .. } = evaluate(parsed, ctxt).unwrap();
+
+                assert_eq!(diagnostics.len(), 1, "{}", program);
+                assert_eq!(diagnostics[0].code, "V0002");
+                assert_eq!(diagnostics[0].span, *span, "{}", program);
+                assert_eq!(diagnostics[0].span.over(program), *spanned);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_factorials() {
+        let program = "(-1)! + 2.5! + 3!";
+        let parsed = parse_stmt!(program);
+        let EvaluationResult {
+            simplified,
+            diagnostics,
+            ..
+        } = evaluate(parsed, &ProgramContext::default()).unwrap();
+
+        assert_eq!(simplified.to_string(), "6 + (-1)! + 2.5!");
+        let codes: Vec<_> = diagnostics.iter().map(|d| d.code).collect();
+        assert_eq!(codes, vec!["V0003", "V0003"]);
+    }
+
+    #[test]
+    fn plus_minus_definitions() {
+        for (program, expected, codes) in &[
+            ("a := 1 ± 2", "a := 3\na := -1", vec![]),
+            (
+                "a := 1 ± 2\na := 3",
+                "a := 3\na := -1\na := 3",
+                vec!["V0001"],
+            ),
+        ] {
+            let parsed = parse_stmt!(*program);
+            let EvaluationResult {
+                simplified,
+                diagnostics,
+                ..
+            } = evaluate(parsed, &ProgramContext::default()).unwrap();
+
+            assert_eq!(simplified.to_string(), *expected);
+            let diagnostics: Vec<_> = diagnostics.iter().map(|d| d.code).collect();
+            assert_eq!(&diagnostics, codes, "{}", program);
+        }
+    }
+
+    #[test]
+    fn too_many_plus_minus_operators() {
+        let program = ["1"; 10].join(" ± ");
+        let parsed = parse_stmt!(&*program);
+        let EvaluationResult {
+            simplified,
+            diagnostics,
+            ..
+        } = evaluate(parsed, &ProgramContext::default()).unwrap();
+
+        assert_eq!(simplified.to_string(), program);
+        let codes: Vec<_> = diagnostics.iter().map(|d| d.code).collect();
+        assert_eq!(codes, vec!["V0005"]);
+    }
+
+    #[test]
+    fn strict_integers() {
+        let strict = ProgramContext::default().strict_integers(true);
+        for (program, ctxt, expected, spans) in &[
+            ("a := x + 1 / 2", &strict, "a := x + 0.5", vec!["1 / 2"]),
+            ("a := x + 4 / 2", &strict, "a := x + 2", vec![]),
+            ("x / 2", &strict, "x * 0.5", vec![]),
+            ("x / (1 + 1)", &strict, "x * 0.5", vec![]),
+            ("a := 1 / 2", &ProgramContext::default(), "a := 0.5", vec![]),
+            ("2.5 * 3", &strict, "7.5", vec!["2.5 * 3"]),
+            ("1 / 0", &strict, "inf", vec![]),
+        ] {
+            let parsed = parse_stmt!(*program);
+            let EvaluationResult {
+                simplified,
+                diagnostics,
+                ..
+            } = evaluate(parsed, ctxt).unwrap();
+
+            assert_eq!(simplified.to_string(), *expected);
+            let non_integers: Vec<_> = diagnostics
+                .iter()
+                .filter(|d| d.code == "V0004")
+                .map(|d| d.span.over(program))
+                .collect();
+            assert_eq!(&non_integers, spans, "{}", program);
+        }
+    }
 }
@@ -77,6 +77,124 @@ define_errors! {
         }
     }
 
+    ///This error is fired on expressions that evaluate to a non-finite value, like infinity or
+    ///"NaN". For example, the program
+    ///
+    ///```text
+    ///a := 1 / 0
+    ///```
+    ///
+    ///defines "a" as the result of a division by zero, which is undefined.
+    ///
+    ///Non-finite values are almost always the result of a division by zero, but may also be
+    ///caused by operations whose results are too large to represent.
+    V0002: NonFiniteValue {
+        ($span:expr, $value:expr) => {
+            Diagnostic::span_err(
+                $span,
+                "Expression evaluates to a non-finite value",
+                "V0002",
+                format!(r#"this evaluates to "{}""#, $value),
+            )
+            .with_note("this is usually caused by a division by zero")
+        }
+    }
+
+    ///This error is fired on factorials of numbers that are not non-negative integers. For
+    ///example, the programs
+    ///
+    ///```text
+    ///(-1)!
+    ///2.5!
+    ///```
+    ///
+    ///take the factorial of a negative number and of a fractional number, neither of which is
+    ///defined.
+    V0003: InvalidFactorial {
+        ($span:expr, $value:expr) => {
+            Diagnostic::span_err(
+                $span,
+                "Factorial of a number that is not a non-negative integer",
+                "V0003",
+                format!(r#"this is the factorial of "{}""#, $value),
+            )
+            .with_note("factorials are only defined on non-negative integers")
+        }
+    }
+
+    ///This error is fired on expressions that evaluate to a number that is not an integer when
+    ///evaluating with strict integers, as with the "--strict-integers" option. For example, the
+    ///program
+    ///
+    ///```text
+    ///a := 1 / 2
+    ///```
+    ///
+    ///defines "a" as "0.5", which is not an integer. "4 / 2" evaluates to "2", and so is valid.
+    ///
+    ///Numbers that are not integers in the program itself, like "2.5", are also errors, since
+    ///they remain in the evaluated program. Numbers introduced by rewriting expressions with
+    ///variables are not errors, so "x / 2" is valid even though it evaluates to "x * 0.5".
+    V0004: NonIntegerValue {
+        ($span:expr, $value:expr) => {
+            Diagnostic::span_err(
+                $span,
+                "Expression evaluates to a number that is not an integer",
+                "V0004",
+                format!(r#"this evaluates to "{}""#, $value),
+            )
+            .with_note("only integers are allowed when evaluating with strict integers")
+        }
+    }
+
+    ///This error is fired on statements with too many "±" operators to be expanded into their
+    ///branches. A statement with "n" "±" operators has "2^n" branches, so a statement like
+    ///
+    ///```text
+    ///1 ± 1 ± 1 ± 1 ± 1 ± 1 ± 1 ± 1 ± 1
+    ///```
+    ///
+    ///with more than 8 operators is not expanded, and its "±" operators are left unevaluated.
+    V0005: TooManyPlusMinusOperators {
+        ($span:expr, $operators:expr, $max:expr) => {
+            Diagnostic::span_err(
+                $span,
+                "Too many `±` operators to expand",
+                "V0005",
+                format!("this statement has {} `±` operators", $operators),
+            )
+            .with_note(format!(
+                "statements with more than {} `±` operators are not expanded into their branches",
+                $max
+            ))
+        }
+    }
+
+    ///This error is fired on factorials of integers too large for the factorial to be represented.
+    ///Numbers are 64-bit floating-point numbers, the largest of which is about "1.8e308", so
+    ///
+    ///```text
+    ///171!
+    ///```
+    ///
+    ///cannot be evaluated, and is left as it is. "170!" is the largest factorial that is evaluated.
+    ///Like other large numbers, large factorials are rounded to a representable number, so they
+    ///may not be exact.
+    V0006: FactorialTooLarge {
+        ($span:expr, $value:expr) => {
+            Diagnostic::span_err(
+                $span,
+                "Factorial too large to represent",
+                "V0006",
+                format!(r#"this is the factorial of "{}""#, $value),
+            )
+            .with_note(format!(
+                r#"the largest factorial that can be represented is "{}!""#,
+                MAX_FACTORIAL_OPERAND
+            ))
+        }
+    }
+
     // TODO(#263): unify this with other lints.
     ///This warning is fired on variable definitions that may be incompatible. For example, given
     ///the program
@@ -111,7 +229,7 @@ define_errors! {
             Diagnostic::span_warn(
                 $a_def.span,
                 format!(r#"Definitions of "{}" may be incompatible"#, $var),
-                "L0002",
+                "L0005",
                 format!(r#"this definition evaluates to "{}""#, $a_def),
             )
             .with_spanned_warn(
@@ -130,4 +248,35 @@ define_errors! {
             )
         }}
     }
+
+    // TODO(#263): unify this with other lints.
+    ///This warning is fired on variable definitions that re-define a variable to a value it is
+    ///already defined as. For example, given the program
+    ///
+    ///```text
+    ///a := 1 + 1
+    ///a := 2
+    ///```
+    ///
+    ///both definitions of "a" evaluate to "2", so the second definition is redundant and can be
+    ///deleted.
+    ///
+    ///Only definitions that are always equal are redundant. Definitions that are never equal are
+    ///incompatible (see V0001), and definitions that may be equal are maybe-incompatible (see
+    ///L0005).
+    L0008: RedundantDefinition {
+        ($var:expr, $a_def:expr, $b_def:expr) => {
+            Diagnostic::span_warn(
+                $b_def.span,
+                format!(r#"Redundant re-definition of "{}""#, $var),
+                "L0008",
+                format!(r#"this definition evaluates to "{}""#, $b_def),
+            )
+            .with_spanned_note(
+                $a_def.span,
+                format!(r#""{}" is already defined as "{}" here"#, $var, $a_def.rhs),
+            )
+            .with_autofix(Autofix::for_sure("delete this definition", Edit::Delete))
+        }
+    }
 }
@@ -19,8 +19,9 @@
 
 use crate::grammar::*;
 use crate::utils::{unflatten_binary_expr, UnflattenStrategy};
+use crate::{Rigor, Span};
 
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
 /// Attempts to flatten an expression, folding constant expressions and like terms.
 ///
@@ -34,22 +35,34 @@ use std::collections::{BTreeMap, VecDeque};
 /// Expressions flattened to a binary operation have the following conditions:
 ///
 /// - Additions and subtractions become additions
-pub fn flatten_expr(expr: RcExpr) -> RcExpr {
-    match expr.as_ref() {
+///
+/// Under [rigorous](Rigor::Rigorous) evaluation, a term divided by itself is not cancelled, since
+/// the division is undefined when the term is zero.
+///
+/// The flattened expression takes the span of the original expression, and folded constants take
+/// the span covering all constants folded into them.
+pub fn flatten_expr(expr: RcExpr, rigor: Rigor) -> RcExpr {
+    let span = expr.span;
+    let flattened = match expr.as_ref() {
         // #a -> #a, $a -> $a
         // We can't do better than this.
         Expr::Const(_) | Expr::Var(_) => expr,
 
         // (_a) -> _a, [_a] -> _a
         // We can't do better than this.
-        Expr::Parend(inner) | Expr::Bracketed(inner) => flatten_expr(inner.clone()),
+        Expr::Parend(inner) | Expr::Bracketed(inner) => flatten_expr(inner.clone(), rigor),
 
         // _a + _b -> _c
         // _a - _b -> _c
         Expr::BinaryExpr(BinaryExpr { op, lhs, rhs })
             if op == &BinaryOperator::Plus || op == &BinaryOperator::Minus =>
         {
-            flatten_add_or_sub(lhs.clone(), rhs.clone(), op == &BinaryOperator::Minus)
+            flatten_add_or_sub(
+                lhs.clone(),
+                rhs.clone(),
+                op == &BinaryOperator::Minus,
+                rigor,
+            )
         }
 
         // _a * _b -> _c
@@ -57,13 +70,13 @@ pub fn flatten_expr(expr: RcExpr) -> RcExpr {
         Expr::BinaryExpr(BinaryExpr { op, lhs, rhs })
             if op == &BinaryOperator::Mult || op == &BinaryOperator::Div =>
         {
-            flatten_mul_or_div(lhs.clone(), rhs.clone(), op == &BinaryOperator::Div)
+            flatten_mul_or_div(lhs.clone(), rhs.clone(), op == &BinaryOperator::Div, rigor)
         }
 
         // TODO: handle everything else better
         Expr::BinaryExpr(BinaryExpr { op, lhs, rhs }) => {
-            let lhs = flatten_expr(lhs.clone());
-            let rhs = flatten_expr(rhs.clone());
+            let lhs = flatten_expr(lhs.clone(), rigor);
+            let rhs = flatten_expr(rhs.clone(), rigor);
             rc_expr!(
                 Expr::BinaryExpr(BinaryExpr { op: *op, lhs, rhs }),
                 expr.span
@@ -71,10 +84,41 @@ pub fn flatten_expr(expr: RcExpr) -> RcExpr {
         }
 
         Expr::UnaryExpr(UnaryExpr { op, rhs }) => {
-            let rhs = flatten_expr(rhs.clone());
-            rc_expr!(Expr::UnaryExpr(UnaryExpr { op: *op, rhs }), expr.span)
+            let rhs = flatten_expr(rhs.clone(), rigor);
+            match (op, rhs.as_ref()) {
+                // +_a -> _a
+                (UnaryOperator::SignPositive, _) => rhs,
+                // -#a -> #(-a)
+                (UnaryOperator::SignNegative, Expr::Const(konst)) => {
+                    rc_expr!(Expr::Const(-konst), expr.span)
+                }
+                // -(-_a) -> _a
+                (
+                    UnaryOperator::SignNegative,
+                    Expr::UnaryExpr(UnaryExpr {
+                        op: UnaryOperator::SignNegative,
+                        rhs: inner,
+                    }),
+                ) => inner.clone(),
+                _ => rc_expr!(Expr::UnaryExpr(UnaryExpr { op: *op, rhs }), expr.span),
+            }
         }
-    }
+
+        Expr::Call(Call { func, args }) => {
+            let args = args
+                .iter()
+                .map(|arg| flatten_expr(arg.clone(), rigor))
+                .collect();
+            rc_expr!(Expr::Call(Call { func: *func, args }), expr.span)
+        }
+
+        Expr::Interval(Interval { lo, hi }) => {
+            let lo = flatten_expr(lo.clone(), rigor);
+            let hi = flatten_expr(hi.clone(), rigor);
+            rc_expr!(Expr::Interval(Interval { lo, hi }), expr.span)
+        }
+    };
+    flattened.with_span(span)
 }
 
 /// Flattens an addition or subtraction, folding constants and like terms as far as possible.
@@ -83,13 +127,15 @@ pub fn flatten_expr(expr: RcExpr) -> RcExpr {
 /// ```text
 /// 1 + 2x - 3 + x -> -2 + 3x
 /// ```
-fn flatten_add_or_sub(o_lhs: RcExpr, o_rhs: RcExpr, is_subtract: bool) -> RcExpr {
-    let o_span = o_lhs.span.to(o_rhs.span);
-    let lhs = flatten_expr(o_lhs);
-    let rhs = flatten_expr(o_rhs);
+fn flatten_add_or_sub(o_lhs: RcExpr, o_rhs: RcExpr, is_subtract: bool, rigor: Rigor) -> RcExpr {
+    let o_span = o_lhs.span.union(o_rhs.span);
+    let lhs = flatten_expr(o_lhs, rigor);
+    let rhs = flatten_expr(o_rhs, rigor);
 
     // Leading coefficients to fold constants into.
     let mut coeff = 0.;
+    // Span of the constants folded into the coefficient.
+    let mut coeff_span: Option<Span> = None;
     // Terms -> coefficients present in the expression.
     let mut terms = BTreeMap::<RcExpr, f64>::new();
 
@@ -113,6 +159,7 @@ fn flatten_add_or_sub(o_lhs: RcExpr, o_rhs: RcExpr, is_subtract: bool) -> RcExpr
                 } else {
                     coeff += konst;
                 }
+                coeff_span = Some(coeff_span.map_or(arg.span, |span| span.union(arg.span)));
             }
             // `flatten` will always normalize add/sub expressions to add, so we only have to
             // handle that.
@@ -128,6 +175,20 @@ fn flatten_add_or_sub(o_lhs: RcExpr, o_rhs: RcExpr, is_subtract: bool) -> RcExpr
                     args_before_sub += 2;
                 }
             }
+            Expr::UnaryExpr(UnaryExpr {
+                op: UnaryOperator::SignNegative,
+                rhs,
+            }) => {
+                if sub_side {
+                    // 1 - -2 -> 1 + 2; everything left is on the sub side, so the operand is the
+                    // only arg on the add side.
+                    args.push_front(rhs.clone());
+                    args_before_sub = 1;
+                } else {
+                    // 1 + -2 -> 1 - 2
+                    args.push_back(rhs.clone());
+                }
+            }
             _ => {
                 // Otherwise the arg is something we cannot further decompose in an add/sub context
                 // (e.g. a variable or an exponentiation), so add it as a term.
@@ -144,7 +205,8 @@ fn flatten_add_or_sub(o_lhs: RcExpr, o_rhs: RcExpr, is_subtract: bool) -> RcExpr
 
     let mut new_args: Vec<RcExpr> = Vec::with_capacity(1 + terms.len());
     if coeff != 0. {
-        new_args.push(rc_expr!(Expr::Const(coeff), o_span));
+        let coeff_span = coeff_span.unwrap_or(o_span);
+        new_args.push(rc_expr!(Expr::Const(coeff), coeff_span));
     }
     for (term, coeff) in terms {
         if coeff == 0. {
@@ -157,6 +219,11 @@ fn flatten_add_or_sub(o_lhs: RcExpr, o_rhs: RcExpr, is_subtract: bool) -> RcExpr
             // coeff == -1
             let neg = UnaryExpr::negate(term.clone());
             new_args.push(rc_expr!(Expr::UnaryExpr(neg), o_span));
+        } else if coeff < 0. {
+            // -2x is emitted as -(2x), so that it can be folded into a subtraction.
+            let mult = BinaryExpr::mult(rc_expr!(Expr::Const(-coeff), o_span), term.clone());
+            let neg = UnaryExpr::negate(rc_expr!(Expr::BinaryExpr(mult), o_span));
+            new_args.push(rc_expr!(Expr::UnaryExpr(neg), o_span));
         } else {
             let mult = BinaryExpr::mult(rc_expr!(Expr::Const(coeff), o_span), term.clone());
             new_args.push(rc_expr!(Expr::BinaryExpr(mult), o_span));
@@ -371,14 +438,18 @@ fn flatten_add_or_sub(o_lhs: RcExpr, o_rhs: RcExpr, is_subtract: bool) -> RcExpr
 /// ```
 ///
 /// And now, all that needs to be done is to construct the flattened expression `2/5 * x^2 / y^-2`.
-fn flatten_mul_or_div(o_lhs: RcExpr, o_rhs: RcExpr, is_div: bool) -> RcExpr {
-    let o_span = o_lhs.span.to(o_rhs.span);
-    let lhs = flatten_expr(o_lhs);
-    let rhs = flatten_expr(o_rhs);
+fn flatten_mul_or_div(o_lhs: RcExpr, o_rhs: RcExpr, is_div: bool, rigor: Rigor) -> RcExpr {
+    let o_span = o_lhs.span.union(o_rhs.span);
+    let lhs = flatten_expr(o_lhs, rigor);
+    let rhs = flatten_expr(o_rhs, rigor);
 
     let mut coeff = 1.;
+    // Span of the constants folded into the coefficient.
+    let mut coeff_span: Option<Span> = None;
     // Term -> # of times it is multiplied. Negative values are equivalent to division.
     let mut terms = BTreeMap::<RcExpr, f64>::new();
+    // Terms seen on the division side.
+    let mut divisors = BTreeSet::<RcExpr>::new();
 
     let mut args = VecDeque::with_capacity(2);
     args.push_back(lhs);
@@ -400,6 +471,7 @@ fn flatten_mul_or_div(o_lhs: RcExpr, o_rhs: RcExpr, is_div: bool) -> RcExpr {
                 } else {
                     coeff *= konst;
                 }
+                coeff_span = Some(coeff_span.map_or(arg.span, |span| span.union(arg.span)));
             }
             Expr::BinaryExpr(BinaryExpr { op, lhs, rhs })
                 if op == &BinaryOperator::Mult || op == &BinaryOperator::Div =>
@@ -438,6 +510,7 @@ fn flatten_mul_or_div(o_lhs: RcExpr, o_rhs: RcExpr, is_div: bool) -> RcExpr {
                 let entry = terms.entry(arg.clone()).or_insert(0.);
                 if div_side {
                     *entry -= 1.;
+                    divisors.insert(arg.clone());
                 } else {
                     *entry += 1.;
                 }
@@ -448,9 +521,15 @@ fn flatten_mul_or_div(o_lhs: RcExpr, o_rhs: RcExpr, is_div: bool) -> RcExpr {
     let mut new_args: Vec<RcExpr> = Vec::with_capacity(1 + terms.len());
     if (coeff - 1.).abs() >= std::f64::EPSILON {
         // coeff != 1
-        new_args.push(rc_expr!(Expr::Const(coeff), o_span));
+        let coeff_span = coeff_span.unwrap_or(o_span);
+        new_args.push(rc_expr!(Expr::Const(coeff), coeff_span));
     }
     for (term, coeff) in terms {
+        if rigor == Rigor::Rigorous && coeff >= 0. && divisors.contains(&term) {
+            // x^2 / x is undefined when x is zero, so rigorously it is x * (x / x) rather than x.
+            let division = BinaryExpr::div(term.clone(), term.clone());
+            new_args.push(rc_expr!(Expr::BinaryExpr(division), o_span));
+        }
         if coeff == 0. {
             // The happiest path :)
             continue;
@@ -491,7 +570,7 @@ mod tests {
     use super::flatten_expr;
     use crate::parse_expr;
     use crate::utils::normalize;
-    use crate::Emit;
+    use crate::{Emit, Rigor};
 
     static CASES: &[&str] = &[
         "1 + 2 + 3 -> 6",
@@ -505,8 +584,13 @@ mod tests {
         "1 - 2 + 3 -> 2",
         "a - a + 1 -> 1",
         "a + 1 - 1 -> a",
-        // TODO: (- (- c)) -> c
-        "a - (b - c) -> (+ (+ a (- b)) (- (- c)))",
+        "a - (b - c) -> (+ (+ a c) (- b))",
+        "a - -b -> (+ a b)",
+        "-a + -b - -c -> (+ (+ c (- a)) (- b))",
+        "a - -(-b) -> (+ a (- b))",
+        "- - a -> a",
+        "-(2) + 1 -> -1",
+        "+a -> a",
         "10 * 2x / 5 / 2 / 4x -> (* 0.5 (^ x 2))",
         "x * 2 / y / (5 / (x / y)) -> (* (* 0.4 (^ x 2)) (^ y -2))",
         "x * x -> (^ x 2)",
@@ -525,7 +609,32 @@ mod tests {
             let expr = parse_expr!(lhs);
             let expected_flattened = split.next().unwrap();
 
-            let flattened = normalize(flatten_expr(expr)).emit_s_expression(Default::default());
+            let flattened = normalize(flatten_expr(expr, Rigor::Pragmatic))
+                .emit_s_expression(Default::default());
+
+            assert_eq!(flattened, expected_flattened);
+        }
+    }
+
+    static RIGOROUS_CASES: &[&str] = &[
+        "x / x -> (/ x x)",
+        "2 * x / x -> (* 2 (/ x x))",
+        "x / x * x -> (* x (/ x x))",
+        "x / x / x -> (/ 1 x)",
+        "1 + x / x -> (+ 1 (/ x x))",
+        "x * x -> (^ x 2)",
+    ];
+
+    #[test]
+    fn flatten_rigorous_cases() {
+        for case in RIGOROUS_CASES {
+            let mut split = case.split(" -> ");
+            let lhs = split.next().unwrap();
+            let expr = parse_expr!(lhs);
+            let expected_flattened = split.next().unwrap();
+
+            let flattened = normalize(flatten_expr(expr, Rigor::Rigorous))
+                .emit_s_expression(Default::default());
 
             assert_eq!(flattened, expected_flattened);
         }
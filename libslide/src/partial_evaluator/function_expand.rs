@@ -0,0 +1,159 @@
+//! This module expands calls to user-defined functions with their definitions.
+
+use crate::grammar::*;
+use crate::Span;
+
+use std::collections::HashMap;
+
+/// Functions defined by the statements of a slide program, like `f(x) = x ^ 2 + 1`.
+///
+/// Functions are defined in program order, so a call is expanded with the last definition of its
+/// function before the call.
+#[derive(Default)]
+pub(super) struct FunctionDefinitions {
+    defs: HashMap<InternedStr, FunctionDefinition>,
+}
+
+/// The parameter and body of a function with one parameter.
+struct FunctionDefinition {
+    param: InternedStr,
+    body: RcExpr,
+}
+
+impl FunctionDefinitions {
+    /// Defines the function assigned by `asgn`, if it assigns a function. A definition replaces any
+    /// earlier definition of the same function.
+    pub(super) fn define(&mut self, asgn: &Assignment) {
+        if let Expr::Call(Call { func, args }) = asgn.lhs.as_ref() {
+            if let [param] = args.as_slice() {
+                if let Some(param) = param.get_var() {
+                    let body = asgn.rhs.clone();
+                    self.defs.insert(*func, FunctionDefinition { param, body });
+                }
+            }
+        }
+    }
+
+    /// Expands calls of defined functions in `expr` by substituting the arguments of each call
+    /// for the parameter in its function's body. For example, with `f(x) = x ^ 2 + 1` defined,
+    /// `f(a + 1)` expands to `((a + 1) ^ 2 + 1)`.
+    ///
+    /// Definitions are expanded when they are made, so bodies are not expanded again.
+    pub(super) fn expand(&self, expr: RcExpr) -> RcExpr {
+        if self.defs.is_empty() {
+            return expr;
+        }
+        FunctionExpander { defs: &self.defs }.transform(&expr)
+    }
+}
+
+struct FunctionExpander<'a> {
+    defs: &'a HashMap<InternedStr, FunctionDefinition>,
+}
+
+impl<'a> ExpressionTransformer<'a> for FunctionExpander<'_> {
+    fn transform_call(&self, call: &'a Call, span: Span) -> RcExpr {
+        let args: Vec<_> = call.args.iter().map(|arg| self.transform(arg)).collect();
+        match (self.defs.get(&call.func), args.as_slice()) {
+            (Some(def), [arg]) => {
+                let body = SubstituteParam {
+                    param: def.param,
+                    arg,
+                }
+                .transform(&def.body);
+                group(body, span)
+            }
+            _ => rc_expr!(
+                Expr::Call(Call {
+                    func: call.func,
+                    args,
+                }),
+                span
+            ),
+        }
+    }
+}
+
+/// Substitutes the parameter of a function by an argument in the function's body.
+struct SubstituteParam<'a> {
+    param: InternedStr,
+    arg: &'a RcExpr,
+}
+
+impl<'a> ExpressionTransformer<'a> for SubstituteParam<'_> {
+    fn transform_var(&self, var: &'a InternedStr, span: Span) -> RcExpr {
+        if *var == self.param {
+            group(self.arg.clone(), self.arg.span)
+        } else {
+            rc_expr!(Expr::Var(*var), span)
+        }
+    }
+}
+
+/// Wraps `expr` in parentheses at `span`, unless it is already a single term.
+fn group(expr: RcExpr, span: Span) -> RcExpr {
+    match expr.as_ref() {
+        Expr::Const(_)
+        | Expr::Var(_)
+        | Expr::Parend(_)
+        | Expr::Bracketed(_)
+        | Expr::Call(_)
+        | Expr::Interval(_) => expr.with_span(span),
+        Expr::BinaryExpr(_) | Expr::UnaryExpr(_) => rc_expr!(Expr::Parend(expr), span),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FunctionDefinitions;
+    use crate::grammar::*;
+    use crate::parse_stmt;
+
+    /// Expands the last statement of `program`, an expression, with the functions defined by the
+    /// statements before it.
+    fn expand(program: &str) -> String {
+        let stmts: Vec<_> = parse_stmt!(program).into_iter().collect();
+        let (last, defs) = stmts.split_last().unwrap();
+        let mut functions = FunctionDefinitions::default();
+        for def in defs {
+            if let StmtKind::Assignment(asgn) = &def.kind {
+                functions.define(asgn);
+            }
+        }
+        match &last.kind {
+            StmtKind::Expr(expr) => functions.expand(expr.clone()).to_string(),
+            StmtKind::Assignment(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn expand_function_call() {
+        assert_eq!(
+            expand("f(x) = x ^ 2 + 1\n2 * f(a + 1)"),
+            "2 * ((a + 1) ^ 2 + 1)"
+        );
+    }
+
+    #[test]
+    fn expand_nested_function_calls() {
+        assert_eq!(expand("f(x) = x * 2\nf(f(y))"), "((y * 2) * 2)");
+    }
+
+    #[test]
+    fn expand_with_last_definition() {
+        assert_eq!(expand("f(x) = x + 1\nf(y) = y - 1\nf(1)"), "(1 - 1)");
+    }
+
+    #[test]
+    fn variables_are_not_functions() {
+        assert_eq!(expand("f = 2\nf(x) = x\nf + f(1)"), "f + 1");
+    }
+
+    #[test]
+    fn calls_of_other_arity_are_not_expanded() {
+        assert_eq!(
+            expand("f(x) = x + 1\nf(1, 2) + max(1, 2)"),
+            "f(1, 2) + max(1, 2)"
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! This module memoizes the evaluations of the expressions of a program, so that identical
+//! expressions in different statements are only evaluated once.
+
+use crate::evaluator_rules::RuleName;
+use crate::grammar::*;
+use crate::{Span, DUMMY_SP};
+
+use std::collections::HashMap;
+
+/// The evaluations of the expressions of a program, keyed by the addresses of their
+/// [interned](ExprInterner) expressions, which the interner keeps alive.
+#[derive(Default)]
+pub(super) struct EvaluationMemo {
+    interner: ExprInterner,
+    evaluations: HashMap<usize, MemoizedEvaluation>,
+}
+
+/// The evaluation of an expression, and everything observed while evaluating it.
+pub(super) struct MemoizedEvaluation {
+    /// The evaluated expression, with the spans it was parsed with.
+    expr: RcExpr,
+    /// The simplified expression.
+    pub simplified: RcExpr,
+    /// The number of passes of the rules over the expression.
+    pub passes: usize,
+    /// The rules that fired during the evaluation, as recorded in
+    /// [`EvaluationResult::fired_rules`](super::EvaluationResult::fired_rules).
+    pub fired_rules: Vec<RuleName>,
+    /// The indices of the rules that transformed the expression, in the order they did.
+    pub hits: Vec<usize>,
+}
+
+impl EvaluationMemo {
+    /// Returns the evaluation of an expression identical to `expr`, if one was memoized. The
+    /// spans of the simplified expression are mapped to the spans of `expr`.
+    pub(super) fn get(&mut self, expr: &RcExpr) -> Option<MemoizedEvaluation> {
+        let interned = self.interner.intern(expr);
+        let memo = self.evaluations.get(&interned.addr())?;
+        Some(MemoizedEvaluation {
+            expr: expr.clone(),
+            simplified: respan(&memo.simplified, &memo.expr, expr),
+            passes: memo.passes,
+            fired_rules: memo.fired_rules.clone(),
+            hits: memo.hits.clone(),
+        })
+    }
+
+    /// Memoizes the evaluation of `expr`.
+    pub(super) fn insert(
+        &mut self,
+        expr: RcExpr,
+        simplified: RcExpr,
+        passes: usize,
+        fired_rules: Vec<RuleName>,
+        hits: Vec<usize>,
+    ) {
+        let interned = self.interner.intern(&expr);
+        self.evaluations.insert(
+            interned.addr(),
+            MemoizedEvaluation {
+                expr,
+                simplified,
+                passes,
+                fired_rules,
+                hits,
+            },
+        );
+    }
+}
+
+/// Maps the spans of `simplified`, the simplification of `from`, to the spans of `to`, an
+/// expression identical to `from` elsewhere in a program. The bounds of spans are mapped from the
+/// corresponding bounds of `from` and `to`; bounds not in `from` are shifted by the distance
+/// between the expressions.
+fn respan(simplified: &RcExpr, from: &RcExpr, to: &RcExpr) -> RcExpr {
+    let mut mapping = SpanMapping {
+        los: HashMap::new(),
+        his: HashMap::new(),
+        shift: to.span.lo as isize - from.span.lo as isize,
+    };
+    mapping.collect(from, to);
+    mapping.apply(simplified)
+}
+
+struct SpanMapping {
+    los: HashMap<usize, usize>,
+    his: HashMap<usize, usize>,
+    shift: isize,
+}
+
+impl SpanMapping {
+    /// Records the mapping of the spans of `from` to those of `to`, which has the same structure.
+    fn collect(&mut self, from: &RcExpr, to: &RcExpr) {
+        self.los.entry(from.span.lo).or_insert(to.span.lo);
+        self.his.entry(from.span.hi).or_insert(to.span.hi);
+        match (from.as_ref(), to.as_ref()) {
+            (Expr::BinaryExpr(from), Expr::BinaryExpr(to)) => {
+                self.collect(&from.lhs, &to.lhs);
+                self.collect(&from.rhs, &to.rhs);
+            }
+            (Expr::UnaryExpr(from), Expr::UnaryExpr(to)) => self.collect(&from.rhs, &to.rhs),
+            (Expr::Parend(from), Expr::Parend(to))
+            | (Expr::Bracketed(from), Expr::Bracketed(to)) => self.collect(from, to),
+            (Expr::Interval(from), Expr::Interval(to)) => {
+                self.collect(&from.lo, &to.lo);
+                self.collect(&from.hi, &to.hi);
+            }
+            (Expr::Call(from), Expr::Call(to)) => {
+                for (from, to) in from.args.iter().zip(to.args.iter()) {
+                    self.collect(from, to);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn map(&self, span: Span) -> Span {
+        if span == DUMMY_SP {
+            return span;
+        }
+        let shift = |offset: usize| (offset as isize + self.shift).max(0) as usize;
+        let lo = self.los.get(&span.lo).or_else(|| self.his.get(&span.lo));
+        let hi = self.his.get(&span.hi).or_else(|| self.los.get(&span.hi));
+        Span::new(
+            lo.copied().unwrap_or_else(|| shift(span.lo)),
+            hi.copied().unwrap_or_else(|| shift(span.hi)),
+        )
+    }
+
+    fn apply(&self, expr: &RcExpr) -> RcExpr {
+        let span = self.map(expr.span);
+        let mapped = match expr.as_ref() {
+            Expr::Const(_) | Expr::Var(_) => return expr.clone().with_span(span),
+            Expr::BinaryExpr(BinaryExpr { op, lhs, rhs }) => Expr::BinaryExpr(BinaryExpr {
+                op: *op,
+                lhs: self.apply(lhs),
+                rhs: self.apply(rhs),
+            }),
+            Expr::UnaryExpr(UnaryExpr { op, rhs }) => Expr::UnaryExpr(UnaryExpr {
+                op: *op,
+                rhs: self.apply(rhs),
+            }),
+            Expr::Parend(inner) => Expr::Parend(self.apply(inner)),
+            Expr::Bracketed(inner) => Expr::Bracketed(self.apply(inner)),
+            Expr::Call(Call { func, args }) => Expr::Call(Call {
+                func: *func,
+                args: args.iter().map(|arg| self.apply(arg)).collect(),
+            }),
+            Expr::Interval(Interval { lo, hi }) => Expr::Interval(Interval {
+                lo: self.apply(lo),
+                hi: self.apply(hi),
+            }),
+        };
+        rc_expr!(mapped, span)
+    }
+}
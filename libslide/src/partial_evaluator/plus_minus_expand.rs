@@ -0,0 +1,135 @@
+//! This module expands statements with `±` operators into a statement for each of their branches.
+
+use crate::diagnostics::Diagnostic;
+use crate::grammar::fold::{descend_binary, ExprFolder};
+use crate::grammar::*;
+
+/// Max number of `±` operators in a statement that is expanded.
+///
+/// A statement with `n` operators has `2^n` branches, so statements with more operators than this
+/// are left as they are rather than expanded into a tremendous number of statements.
+const MAX_PLUS_MINUS_OPERATORS: usize = 8;
+
+/// Expands each statement of a program with `±` operators into one statement for each choice of
+/// `+` or `-` for its operators, like `1 ± 2` into `1 + 2` and `1 - 2`. The branches of a statement
+/// replace it in order, starting with the branch that chooses `+` for every operator; an operator
+/// earlier in the statement alternates slower than those after it.
+///
+/// Only expressions and the right hand sides of assignments are expanded, so a definition with a
+/// `±` operator defines its variable once for each branch. The branches of a definition keep its
+/// span.
+///
+/// Statements with more than [`MAX_PLUS_MINUS_OPERATORS`](MAX_PLUS_MINUS_OPERATORS) operators are
+/// not expanded; a diagnostic is returned for each of them.
+pub(super) fn expand_plus_minus(stmt_list: StmtList) -> (StmtList, Vec<Diagnostic>) {
+    let mut diagnostics = Vec::new();
+    let expanded = stmt_list
+        .into_iter()
+        .flat_map(|stmt| expand_stmt(stmt, &mut diagnostics))
+        .collect();
+    (StmtList::new(expanded), diagnostics)
+}
+
+/// Expands a statement into its branches, or returns it as it is if it has no `±` operators or too
+/// many of them.
+fn expand_stmt(stmt: Stmt, diagnostics: &mut Vec<Diagnostic>) -> Vec<Stmt> {
+    // The first branch, which chooses `+` for every operator, also counts the operators.
+    let (first, operators) = choose_signs(&stmt, 0, 0);
+    if operators == 0 {
+        return vec![stmt];
+    }
+    if operators > MAX_PLUS_MINUS_OPERATORS {
+        diagnostics.push(TooManyPlusMinusOperators!(
+            *stmt.span(),
+            operators,
+            MAX_PLUS_MINUS_OPERATORS
+        ));
+        return vec![stmt];
+    }
+    let mut branches = vec![Stmt::new(first, stmt.vw())];
+    for signs in 1..1 << operators {
+        // Branches after the first follow it without vertical whitespace.
+        let (branch, _) = choose_signs(&stmt, signs, operators);
+        branches.push(Stmt::new(branch, 0));
+    }
+    branches
+}
+
+/// Returns the branch of a statement choosing the signs `signs` for its `operators` `±` operators,
+/// and the number of operators in the statement. See [`SignChooser`](SignChooser).
+fn choose_signs(stmt: &Stmt, signs: usize, operators: usize) -> (StmtKind, usize) {
+    let mut chooser = SignChooser {
+        signs,
+        operators,
+        seen: 0,
+    };
+    let kind = match &stmt.kind {
+        StmtKind::Expr(expr) => chooser.fold_expr(expr).into(),
+        StmtKind::Assignment(asgn) => asgn
+            .clone()
+            .redefine_with(|rhs| chooser.fold_expr(&rhs))
+            .into(),
+    };
+    (kind, chooser.seen)
+}
+
+/// Replaces each `±` operator of an expression with `+` or `-`. The operators are numbered in the
+/// order they appear in the expression, and the `i`th of `operators` operators is replaced with `-`
+/// iff the `i`th most significant of the `operators` low bits of `signs` is set.
+struct SignChooser {
+    signs: usize,
+    operators: usize,
+    /// The number of `±` operators seen so far.
+    seen: usize,
+}
+
+impl ExprFolder for SignChooser {
+    fn fold_binary(&mut self, expr: &RcExpr, binary: &BinaryExpr<RcExpr>) -> RcExpr {
+        if binary.op != BinaryOperator::PlusMinus {
+            return descend_binary(self, expr, binary);
+        }
+        let lhs = self.fold_expr(&binary.lhs);
+        let index = self.seen;
+        self.seen += 1;
+        let rhs = self.fold_expr(&binary.rhs);
+        let minus = self.signs != 0 && self.signs & (1 << (self.operators - 1 - index)) != 0;
+        let op = if minus {
+            BinaryOperator::Minus
+        } else {
+            BinaryOperator::Plus
+        };
+        rc_expr!(Expr::BinaryExpr(BinaryExpr { op, lhs, rhs }), expr.span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expand_plus_minus;
+    use crate::parse_stmt;
+
+    #[test]
+    fn expand() {
+        for (program, expanded) in &[
+            ("1 ± 2", "1 + 2\n1 - 2"),
+            ("1 +/- 2", "1 + 2\n1 - 2"),
+            ("a ± b ± c", "a + b + c\na + b - c\na - b + c\na - b - c"),
+            ("(1 ± x) * 2", "(1 + x) * 2\n(1 - x) * 2"),
+            ("a = 1 ± 2\n\nb", "a = 1 + 2\na = 1 - 2\n\nb"),
+            ("1 + 2", "1 + 2"),
+        ] {
+            let (stmt_list, diagnostics) = expand_plus_minus(parse_stmt!(*program));
+            assert_eq!(stmt_list.to_string(), *expanded, "{}", program);
+            assert!(diagnostics.is_empty(), "{}", program);
+        }
+    }
+
+    #[test]
+    fn too_many_operators() {
+        let program = ["1"; 10].join(" ± ");
+        let (stmt_list, diagnostics) = expand_plus_minus(parse_stmt!(&*program));
+        assert_eq!(stmt_list.to_string(), program);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "V0005");
+        assert_eq!(diagnostics[0].span, (0, program.len()).into());
+    }
+}
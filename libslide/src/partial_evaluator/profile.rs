@@ -0,0 +1,75 @@
+//! Profiling of the rules applied during partial evaluation.
+
+use crate::evaluator_rules::{Rule, RuleName};
+
+use core::fmt;
+
+/// Describes how many times each rule in a set of rules transformed an expression during
+/// evaluation.
+///
+/// Named rules are profiled by their [name](RuleName), so all rules built from the same named rule
+/// share one count. Custom rules are profiled by their definition.
+pub struct RuleProfile {
+    /// Profiled rules, in the order they are applied, and the number of times each rule changed
+    /// an expression it was applied to.
+    hits: Vec<(String, usize)>,
+    /// Maps the index of a built rule to its entry in `hits`.
+    slots: Vec<usize>,
+}
+
+impl RuleProfile {
+    /// Creates an empty profile for a set of built rules.
+    pub(super) fn new(rules: &[(Option<RuleName>, Rule)]) -> Self {
+        let mut hits: Vec<(String, usize)> = Vec::new();
+        let slots = rules
+            .iter()
+            .map(|(name, rule)| {
+                let label = match name {
+                    Some(name) => format!("{:?}", name),
+                    None => rule.to_string(),
+                };
+                match hits.iter().position(|(l, _)| *l == label) {
+                    Some(slot) => slot,
+                    None => {
+                        hits.push((label, 0));
+                        hits.len() - 1
+                    }
+                }
+            })
+            .collect();
+        Self { hits, slots }
+    }
+
+    /// Records a transformation of an expression by the `i`th rule of the profiled rule set.
+    pub(super) fn record_hit(&mut self, i: usize) {
+        self.hits[self.slots[i]].1 += 1;
+    }
+
+    /// Returns each profiled rule with the number of times it transformed an expression, sorted
+    /// by descending hit count. Rules with the same number of hits are ordered by their order of
+    /// application.
+    pub fn hits(&self) -> Vec<(&str, usize)> {
+        let mut hits: Vec<_> = self
+            .hits
+            .iter()
+            .map(|(rule, count)| (rule.as_str(), *count))
+            .collect();
+        hits.sort_by(|(_, a), (_, b)| b.cmp(a));
+        hits
+    }
+}
+
+impl fmt::Display for RuleProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hits = self.hits();
+        let width = hits
+            .iter()
+            .map(|(_, count)| count.to_string().len())
+            .max()
+            .unwrap_or(0);
+        for (rule, count) in hits {
+            writeln!(f, "{:>width$} {}", count, rule, width = width)?;
+        }
+        Ok(())
+    }
+}
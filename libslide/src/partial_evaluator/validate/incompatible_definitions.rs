@@ -1,18 +1,19 @@
-//! Detects incompatible variable definitions in a slide program.
+//! Detects incompatible and redundant variable definitions in a slide program.
 //!
 //! See the [`IncompatibleDefinitions`](super::super::errors::IncompatibleDefinitions)
-//! error for more details.
+//! error and [`RedundantDefinition`](super::super::errors::RedundantDefinition) lint for more
+//! details.
 
 use super::Validator;
 
-use crate::diagnostics::Diagnostic;
+use crate::diagnostics::{Autofix, Diagnostic, Edit};
 use crate::evaluator_rules::Rule;
 use crate::grammar::collectors::collect_var_asgns;
 use crate::grammar::*;
 use crate::partial_evaluator::compare::{cmp_eq, EqRelation};
 use crate::ProgramContext;
 
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 /// Max number of definition pairs we generate diagnostics for.
 ///
@@ -35,6 +36,11 @@ fn all_ordered_definition_pairs(
         let mut pairs = Vec::with_capacity((asgns.len() * asgns.len() - 1) / 2);
         for i in 0..asgns.len() {
             for j in i + 1..asgns.len() {
+                // Definitions with the same span are branches of one definition with `±`
+                // operators, which are expected to differ.
+                if asgns[i].span == asgns[j].span {
+                    continue;
+                }
                 pairs.push((name, asgns[i], asgns[j]));
             }
         }
@@ -61,10 +67,20 @@ fn validate(
     let mut diagnostics = Vec::new();
     let var_asgns = collect_var_asgns(&program);
     let definition_pairs = all_ordered_definition_pairs(var_asgns);
+    // Spans of definitions already reported as redundant. Pairs are ordered by their first
+    // definition, so a redundant definition is reported once, against the earliest definition it
+    // repeats.
+    let mut redundant_defs = HashSet::new();
     for (name, def_a, def_b) in definition_pairs.into_iter() {
         diagnostics.push(
             match cmp_eq(&def_a.rhs, &def_b.rhs, evaluator_rules, context) {
-                EqRelation::AlwaysEquivalent => continue,
+                EqRelation::AlwaysEquivalent if !context.lint => continue,
+                EqRelation::AlwaysEquivalent => {
+                    if !redundant_defs.insert(def_b.span) {
+                        continue;
+                    }
+                    RedundantDefinition!(name, def_a, def_b)
+                }
                 EqRelation::NeverEquivalent => IncompatibleDefinitions!(name, def_a, def_b),
                 EqRelation::DependsOn(_) if !context.lint => continue,
                 EqRelation::DependsOn(dep_vars) => {
@@ -84,6 +100,7 @@ pub(super) struct IncompatibleDefinitionsValidator;
 impl<'a> Validator<'a> for IncompatibleDefinitionsValidator {
     fn validate(
         stmt_list: &StmtList,
+        _original: &StmtList,
         _source: &'a str,
         context: &ProgramContext,
         evaluator_rules: &[Rule],
@@ -0,0 +1,50 @@
+//! Detects factorials of negative or non-integer numbers, and of numbers too large for their
+//! factorial to be represented, in a slide program.
+//!
+//! See the [`InvalidFactorial`](super::super::errors::InvalidFactorial) and
+//! [`FactorialTooLarge`](super::super::errors::FactorialTooLarge) errors for more details.
+
+use super::Validator;
+
+use crate::diagnostics::Diagnostic;
+use crate::evaluator_rules::{Rule, MAX_FACTORIAL_OPERAND};
+use crate::grammar::visit::{descend_unary, StmtVisitor};
+use crate::grammar::*;
+use crate::{ProgramContext, Span};
+
+#[derive(Default)]
+struct InvalidFactorialCollector {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> StmtVisitor<'a> for InvalidFactorialCollector {
+    fn visit_unary(&mut self, expr: &'a UnaryExpr<RcExpr>, span: Span) {
+        if expr.op == UnaryOperator::Factorial {
+            match expr.rhs.get_const() {
+                Some(n) if n.is_finite() && (n < 0. || n.fract() != 0.) => {
+                    self.diagnostics.push(InvalidFactorial!(span, n))
+                }
+                Some(n) if n.is_finite() && n > MAX_FACTORIAL_OPERAND => {
+                    self.diagnostics.push(FactorialTooLarge!(span, n))
+                }
+                _ => {}
+            }
+        }
+        descend_unary(self, expr, span);
+    }
+}
+
+pub(super) struct InvalidFactorialsValidator;
+impl<'a> Validator<'a> for InvalidFactorialsValidator {
+    fn validate(
+        stmt_list: &StmtList,
+        _original: &StmtList,
+        _source: &'a str,
+        _context: &ProgramContext,
+        _evaluator_rules: &[Rule],
+    ) -> Vec<Diagnostic> {
+        let mut collector = InvalidFactorialCollector::default();
+        collector.visit_stmt_list(stmt_list);
+        collector.diagnostics
+    }
+}
@@ -2,7 +2,13 @@
 //! post-evaluator linters.
 
 mod incompatible_definitions;
+mod invalid_factorials;
+mod non_finite_values;
+mod non_integer_values;
 use incompatible_definitions::*;
+use invalid_factorials::*;
+use non_finite_values::*;
+use non_integer_values::*;
 
 use super::ProgramContext;
 
@@ -10,9 +16,12 @@ use crate::diagnostics::Diagnostic;
 use crate::evaluator_rules::Rule;
 use crate::grammar::StmtList;
 
+/// Validates an evaluated list of statements. The statements were evaluated from `original`, and
+/// the spans of both point into `source`.
 trait Validator<'a> {
     fn validate(
         stmt_list: &StmtList,
+        original: &StmtList,
         source: &'a str,
         context: &ProgramContext,
         evaluator_rules: &[Rule],
@@ -29,26 +38,28 @@ macro_rules! register_validators {
             fn validate<'a>(
                 &self,
                 stmt_list: &StmtList,
+                original: &StmtList,
                 source: &'a str,
                 context: &ProgramContext,
                 evaluator_rules: &[Rule],
             ) -> Vec<Diagnostic> {
                 match self {
                     $(Self::$validator => $validator::validate(
-                            stmt_list, source, context, evaluator_rules)),*
+                            stmt_list, original, source, context, evaluator_rules)),*
                 }
             }
         }
 
         pub(super) fn validate<'a>(
             stmt_list: &StmtList,
+            original: &StmtList,
             source: &'a str,
             context: &ProgramContext,
             evaluator_rules: &[Rule],
         ) -> Vec<Diagnostic> {
             [$(PEValidator::$validator),*]
                 .iter()
-                .flat_map(|v| v.validate(stmt_list, source, context, evaluator_rules))
+                .flat_map(|v| v.validate(stmt_list, original, source, context, evaluator_rules))
                 .collect()
         }
     }
@@ -56,4 +67,7 @@ macro_rules! register_validators {
 
 register_validators! {
     IncompatibleDefinitionsValidator,
+    NonFiniteValuesValidator,
+    NonIntegerValuesValidator,
+    InvalidFactorialsValidator,
 }
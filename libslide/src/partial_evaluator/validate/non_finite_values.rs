@@ -0,0 +1,75 @@
+//! Detects expressions that evaluate to non-finite values in a slide program.
+//!
+//! See the [`NonFiniteValue`](super::super::errors::NonFiniteValue) error for more details.
+
+use super::Validator;
+
+use crate::diagnostics::Diagnostic;
+use crate::evaluator_rules::Rule;
+use crate::grammar::visit::{descend_binary, StmtVisitor};
+use crate::grammar::*;
+use crate::{ProgramContext, Span};
+
+use std::collections::HashMap;
+
+/// Collects the spans of the operators of divisions in a program, by the spans of the divisions.
+struct DivisionOperatorCollector<'a> {
+    source: &'a str,
+    operators: HashMap<Span, Span>,
+}
+
+impl<'a> StmtVisitor<'a> for DivisionOperatorCollector<'_> {
+    fn visit_binary(&mut self, expr: &'a BinaryExpr<RcExpr>, span: Span) {
+        let (lhs, rhs) = (expr.lhs.span, expr.rhs.span);
+        if expr.op == BinaryOperator::Div && lhs.hi <= rhs.lo {
+            // The operator lies between the operands, with any whitespace around it.
+            let operator = Span::new(lhs.hi, rhs.lo).trim(self.source);
+            self.operators.insert(span, operator);
+        }
+        descend_binary(self, expr, span);
+    }
+}
+
+struct NonFiniteValueCollector {
+    /// Spans of division operators in the original program, by the spans of their divisions.
+    division_operators: HashMap<Span, Span>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> StmtVisitor<'a> for NonFiniteValueCollector {
+    fn visit_const(&mut self, konst: &'a f64, span: Span) {
+        if !konst.is_finite() {
+            // A value is evaluated at the span of the expression it was evaluated from, so a
+            // division by zero is reported at its division operator.
+            let span = self.division_operators.get(&span).copied().unwrap_or(span);
+            self.diagnostics.push(NonFiniteValue!(span, konst));
+        }
+    }
+}
+
+pub(super) struct NonFiniteValuesValidator;
+impl<'a> Validator<'a> for NonFiniteValuesValidator {
+    fn validate(
+        stmt_list: &StmtList,
+        original: &StmtList,
+        source: &'a str,
+        _context: &ProgramContext,
+        _evaluator_rules: &[Rule],
+    ) -> Vec<Diagnostic> {
+        let mut divisions = DivisionOperatorCollector {
+            source,
+            operators: HashMap::new(),
+        };
+        // Without the source, the whitespace around division operators is not known.
+        if !source.is_empty() {
+            divisions.visit_stmt_list(original);
+        }
+
+        let mut collector = NonFiniteValueCollector {
+            division_operators: divisions.operators,
+            diagnostics: Vec::new(),
+        };
+        collector.visit_stmt_list(stmt_list);
+        collector.diagnostics
+    }
+}
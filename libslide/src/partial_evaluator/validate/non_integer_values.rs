@@ -0,0 +1,87 @@
+//! Detects expressions that evaluate to numbers that are not integers in a slide program evaluated
+//! with [strict integers](crate::ProgramContext::strict_integers).
+//!
+//! See the [`NonIntegerValue`](super::super::errors::NonIntegerValue) error for more details.
+
+use super::Validator;
+
+use crate::diagnostics::Diagnostic;
+use crate::evaluator_rules::Rule;
+use crate::grammar::visit::{descend_expr, StmtVisitor};
+use crate::grammar::*;
+use crate::partial_evaluator::evaluate_expr;
+use crate::{ProgramContext, Span};
+
+use std::collections::HashMap;
+
+/// Whether `konst` is a number that is not an integer. Non-finite values are reported by the
+/// `NonFiniteValue` error instead.
+fn is_non_integer(konst: f64) -> bool {
+    konst.is_finite() && konst.fract() != 0.
+}
+
+/// Collects the non-integer constants of an evaluated program.
+#[derive(Default)]
+struct NonIntegerValueCollector {
+    values: Vec<(Span, f64)>,
+}
+
+impl<'a> StmtVisitor<'a> for NonIntegerValueCollector {
+    fn visit_const(&mut self, konst: &'a f64, span: Span) {
+        if is_non_integer(*konst) {
+            self.values.push((span, *konst));
+        }
+    }
+}
+
+/// Collects the expressions of a program built only of constants, like `1 / 2`, by their spans.
+#[derive(Default)]
+struct ConstantExprCollector<'a> {
+    exprs: HashMap<Span, &'a RcExpr>,
+}
+
+impl<'a> StmtVisitor<'a> for ConstantExprCollector<'a> {
+    fn visit_expr(&mut self, expr: &'a RcExpr) {
+        if expr.is_constant_expr() {
+            self.exprs.insert(expr.span, expr);
+        }
+        descend_expr(self, expr);
+    }
+}
+
+pub(super) struct NonIntegerValuesValidator;
+impl<'a> Validator<'a> for NonIntegerValuesValidator {
+    fn validate(
+        stmt_list: &StmtList,
+        original: &StmtList,
+        _source: &'a str,
+        context: &ProgramContext,
+        evaluator_rules: &[Rule],
+    ) -> Vec<Diagnostic> {
+        if !context.strict_integers {
+            return vec![];
+        }
+        let mut values = NonIntegerValueCollector::default();
+        values.visit_stmt_list(stmt_list);
+        if values.values.is_empty() {
+            return vec![];
+        }
+
+        // Evaluation may introduce non-integers of its own, like the `0.5` of rewriting `x / 2` to
+        // `x * 0.5`. Only constants folded from an expression of the program that is built only of
+        // constants, and that is itself not an integer, are reported.
+        let mut constant_exprs = ConstantExprCollector::default();
+        constant_exprs.visit_stmt_list(original);
+        values
+            .values
+            .into_iter()
+            .filter(|(span, _)| match constant_exprs.exprs.get(span) {
+                Some(&expr) => evaluate_expr(expr.clone(), evaluator_rules, context)
+                    .get_const()
+                    .map_or(false, is_non_integer),
+                None => false,
+            })
+            .map(|(span, konst)| NonIntegerValue!(span, konst))
+            .collect()
+    }
+}
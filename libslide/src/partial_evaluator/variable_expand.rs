@@ -2,11 +2,110 @@
 
 #![allow(unused)] // TODO: remove
 
+use crate::grammar::fold::ExprFolder;
 use crate::grammar::*;
 use crate::Span;
 
 use std::collections::HashMap;
 
+/// Expands variables in an expression with their definitions in a list of assignments.
+///
+/// Each variable is expanded at most once, and definitions are not themselves expanded. If a
+/// variable is defined multiple times, the last definition is used. For example,
+/// `"a + b"` expanded with `["a = 1", "b = a", "a = 2"]` would expand to `"2 + a"`.
+pub fn expand_variables<'a>(
+    expr: RcExpr,
+    definitions: impl IntoIterator<Item = &'a Assignment>,
+) -> RcExpr {
+    definitions
+        .into_iter()
+        .fold(LazyVariableExpander::new(expr), |expander, asgn| {
+            expander.expand(asgn)
+        })
+        .finish()
+}
+
+/// Substitutes the variable an assignment defines with the assignment's definition in each
+/// statement of a program, returning the substituted program, or [None](Option::None) if the
+/// variable does not appear in the program. Only the right hand sides of assignments in the
+/// program are substituted.
+///
+/// Each substituted definition is spanned at the variable it replaces, so that diagnostics of the
+/// substituted program point into the program's source. For example, `"a = x + 1; x ^ 2"`
+/// substituted with `"x = 3"` would be `"a = 3 + 1; 3 ^ 2"`.
+pub fn substitute(stmt_list: &StmtList, asgn: &Assignment) -> Option<StmtList> {
+    let var = asgn.lhs.get_var()?;
+    let mut substituter = Substituter {
+        var,
+        def: &asgn.rhs,
+        substituted: false,
+    };
+    let substituted = stmt_list
+        .iter()
+        .map(|stmt| {
+            let mut stmt = stmt.clone();
+            stmt.kind = match stmt.kind {
+                StmtKind::Expr(expr) => substituter.fold_expr(&expr).into(),
+                StmtKind::Assignment(asgn) => {
+                    asgn.redefine_with(|rhs| substituter.fold_expr(&rhs)).into()
+                }
+            };
+            stmt
+        })
+        .collect();
+    if substituter.substituted {
+        Some(substituted)
+    } else {
+        None
+    }
+}
+
+/// Substitutes a variable with its definition, spanned at the substituted variable.
+struct Substituter<'a> {
+    var: InternedStr,
+    def: &'a RcExpr,
+    /// Whether the variable was substituted anywhere.
+    substituted: bool,
+}
+
+impl ExprFolder for Substituter<'_> {
+    fn fold_var(&mut self, expr: &RcExpr, var: InternedStr) -> RcExpr {
+        if var == self.var {
+            self.substituted = true;
+            span_at(self.def, expr.span)
+        } else {
+            expr.clone()
+        }
+    }
+}
+
+/// Returns an expression with each of its nodes spanned at `span`.
+fn span_at(expr: &RcExpr, span: Span) -> RcExpr {
+    let spanned = match expr.as_ref() {
+        Expr::Const(_) | Expr::Var(_) => return expr.clone().with_span(span),
+        Expr::BinaryExpr(BinaryExpr { op, lhs, rhs }) => Expr::BinaryExpr(BinaryExpr {
+            op: *op,
+            lhs: span_at(lhs, span),
+            rhs: span_at(rhs, span),
+        }),
+        Expr::UnaryExpr(UnaryExpr { op, rhs }) => Expr::UnaryExpr(UnaryExpr {
+            op: *op,
+            rhs: span_at(rhs, span),
+        }),
+        Expr::Parend(inner) => Expr::Parend(span_at(inner, span)),
+        Expr::Bracketed(inner) => Expr::Bracketed(span_at(inner, span)),
+        Expr::Call(Call { func, args }) => Expr::Call(Call {
+            func: *func,
+            args: args.iter().map(|arg| span_at(arg, span)).collect(),
+        }),
+        Expr::Interval(Interval { lo, hi }) => Expr::Interval(Interval {
+            lo: span_at(lo, span),
+            hi: span_at(hi, span),
+        }),
+    };
+    rc_expr!(spanned, span)
+}
+
 trait VariableExpander<'a> {
     /// Creates a new VariableExpander from an expression to expand.
     fn new(expr: RcExpr) -> Self;
@@ -116,8 +215,11 @@ impl<'a> VariableExpander<'a> for LazyVariableExpander<'a> {
 
 #[cfg(test)]
 mod test {
-    use super::{EagerVariableExpander, LazyVariableExpander, VariableExpander};
-    use crate::{parse_asgn, parse_expr};
+    use super::{
+        expand_variables, substitute, EagerVariableExpander, LazyVariableExpander, VariableExpander,
+    };
+    use crate::grammar::*;
+    use crate::{parse_asgn, parse_expr, parse_stmt, Span};
 
     #[test]
     fn eager_variable_expander() {
@@ -146,4 +248,48 @@ mod test {
 
         assert_eq!(expanded.to_string(), "b / 5 + c + b / 5 + 1 / 2");
     }
+
+    #[test]
+    fn expand_variables_with_definitions() {
+        let expr = parse_expr!("a + b");
+        let definitions = vec![
+            parse_asgn!("a = 1"),
+            parse_asgn!("b = a"),
+            parse_asgn!("a = 2"),
+        ];
+
+        let expanded = expand_variables(expr, &definitions);
+
+        assert_eq!(expanded.to_string(), "2 + a");
+    }
+
+    #[test]
+    fn substitute_program() {
+        let program = parse_stmt!("a = x + 1\nx ^ 2 + y");
+        let x = parse_asgn!("x = (y + 1)");
+
+        let substituted = substitute(&program, &x).unwrap();
+
+        assert_eq!(substituted.to_string(), "a = (y + 1) + 1\n(y + 1) ^ 2 + y");
+        let lhs = |expr: &RcExpr| match expr.as_ref() {
+            Expr::BinaryExpr(BinaryExpr { lhs, .. }) => lhs.clone(),
+            _ => unreachable!(),
+        };
+        let spans: Vec<_> = substituted
+            .into_iter()
+            .map(|stmt| match stmt.kind {
+                StmtKind::Assignment(asgn) => lhs(&asgn.rhs).span,
+                StmtKind::Expr(expr) => lhs(&lhs(&expr)).span,
+            })
+            .collect();
+        assert_eq!(spans, vec![Span::new(4, 5), Span::new(10, 11)]);
+    }
+
+    #[test]
+    fn substitute_absent_variable() {
+        let program = parse_stmt!("a = x + 1");
+        let z = parse_asgn!("z = 1");
+
+        assert!(substitute(&program, &z).is_none());
+    }
 }
@@ -9,8 +9,11 @@ pub mod types;
 use types::TokenType as TT;
 pub use types::*;
 
+mod tokenize;
+pub use tokenize::{tokenize, SpannedTokenInfo, TokenClass};
+
 use crate::common::Span;
-use crate::diagnostics::Diagnostic;
+use crate::diagnostics::{Autofix, Diagnostic, Edit};
 use strtod::strtod;
 
 /// Describes the result of tokenizing a slide program.
@@ -21,6 +24,18 @@ pub struct ScanResult {
     pub diagnostics: Vec<Diagnostic>,
 }
 
+impl ScanResult {
+    /// Whether the scanner recovered from all invalid input in the program, by skipping or
+    /// replacing it. The tokens of a recovered program can be parsed without reporting the invalid
+    /// input again, even if there are diagnostics.
+    pub fn is_recovered(&self) -> bool {
+        !self
+            .tokens
+            .iter()
+            .any(|tok| matches!(tok.ty, TokenType::Invalid(..)))
+    }
+}
+
 /// Scans and tokenizes a string-like slide program.
 pub fn scan<'a, T: Into<&'a str>>(input: T) -> ScanResult {
     let mut scanner = Scanner::new(input.into());
@@ -34,6 +49,9 @@ pub fn scan<'a, T: Into<&'a str>>(input: T) -> ScanResult {
 struct Scanner {
     pos: usize,
     input: Vec<char>,
+    /// The byte offset of each character of the input, followed by the length of the input in
+    /// bytes. [Span]s count bytes, whereas the scanner's position counts characters.
+    byte_offsets: Vec<usize>,
     leading_trivia_start: usize,
     pub output: Vec<Token>,
     pub diagnostics: Vec<Diagnostic>,
@@ -42,9 +60,15 @@ struct Scanner {
 impl Scanner {
     /// Instantiate a new scanner.
     pub fn new(input: &str) -> Scanner {
+        let byte_offsets = input
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(input.len()))
+            .collect();
         Scanner {
             pos: 0,
             input: input.chars().collect(),
+            byte_offsets,
             leading_trivia_start: 0,
             output: Vec::new(),
             diagnostics: Vec::new(),
@@ -70,7 +94,8 @@ impl Scanner {
 
     fn expect(&mut self, what: &str) {
         for c in what.chars() {
-            debug_assert!(*self.next().unwrap() == c);
+            let next = self.next();
+            debug_assert_eq!(next, Some(&c));
         }
     }
 
@@ -79,8 +104,19 @@ impl Scanner {
         self.diagnostics.push(diagnostic);
     }
 
+    /// Converts a span of character positions in the input to a span of byte offsets.
+    fn byte_span<S: Into<Span>>(&self, span: S) -> Span {
+        let Span { lo, hi } = span.into();
+        let byte_offset = |pos: usize| match self.byte_offsets.get(pos) {
+            Some(&offset) => offset,
+            // Only the end-of-file token reaches past the input.
+            None => self.byte_offsets[self.input.len()] + pos - self.input.len(),
+        };
+        Span::new(byte_offset(lo), byte_offset(hi))
+    }
+
     fn push_tok<S: Into<Span>>(&mut self, ty: TokenType, span: S) {
-        let span = span.into();
+        let span = self.byte_span(span);
         let full_span = (self.leading_trivia_start, span.hi);
         self.output.push(Token::new(ty, span, full_span));
 
@@ -119,18 +155,42 @@ impl Scanner {
     }
 
     /// Matches a symbol with a token and creates it.
+    ///
+    /// An invalid character is replaced by the operator it is commonly confused for, if any, or
+    /// else skipped, so that the rest of the program is still scanned. Reserved symbols, like `!=`,
+    /// are scanned as invalid tokens.
     fn scan_symbol(&mut self) {
         use TokenType::*;
-        let mut did_you_mean = None;
+        let mut autofix = None;
+        let mut replacement = None;
+        let mut skip = false;
         let start = self.pos;
         let mut span = None;
         let ty = match self.next().unwrap() {
-            '+' => Plus,
+            '+' => {
+                if self.peek_n(2) == "/-" {
+                    self.expect("/-");
+                    PlusMinus
+                } else {
+                    Plus
+                }
+            }
+            '±' => PlusMinus,
             '-' => Minus,
             '*' => Mult,
             '/' => Div,
             '%' => Mod,
             '^' => Exp,
+            '!' => {
+                // `!=` is reserved for an inequality operator; don't scan it as a factorial
+                // followed by an equals sign.
+                if self.peek() == Some(&'=') {
+                    self.next();
+                    Invalid("!=".to_owned())
+                } else {
+                    Bang
+                }
+            }
             '=' => Equal,
             ':' => {
                 if self.peek() == Some(&'=') {
@@ -140,7 +200,7 @@ impl Scanner {
                     span = Some(start..start + 1);
                     self.collect_while(|c| c.is_whitespace());
                     if self.peek() == Some(&'=') {
-                        did_you_mean = Some((":=", start..self.pos + 1));
+                        autofix = Some(Autofix::maybe("did you mean", Edit::Replace(":=".into())));
                     }
                     Invalid(":".to_owned())
                 }
@@ -149,22 +209,43 @@ impl Scanner {
             ')' => CloseParen,
             '[' => OpenBracket,
             ']' => CloseBracket,
-            c => Invalid(c.to_string()),
+            ',' => Comma,
+            ';' => Semicolon,
+            c => {
+                replacement = ascii_operator_for(*c);
+                skip = replacement.is_none();
+                autofix = Some(match &replacement {
+                    Some(op) => Autofix::for_sure("did you mean", Edit::Replace(op.to_string())),
+                    None => Autofix::maybe("consider deleting this token", Edit::Delete),
+                });
+                Invalid(c.to_string())
+            }
         };
         let span = span.unwrap_or(start..self.pos);
 
         if matches!(ty, Invalid(..)) {
-            self.push_diag(InvalidToken!(span.clone(), did_you_mean));
+            self.push_diag(InvalidToken!(self.byte_span(span.clone()), autofix));
+        }
+        match replacement {
+            Some(op) => self.push_tok(op, span),
+            None if skip => {}
+            None => self.push_tok(ty, span),
         }
-        self.push_tok(ty, span);
     }
 
     /// Scans through the content of a number to create a token of that value.
     fn scan_num(&mut self) {
         let start = self.pos;
 
-        let num = if self.peek_n(2) == "0x" {
-            self.scan_hex() as f64
+        // A `0x` not followed by a hex digit is a `0` followed by a variable, as in `0x + 1`.
+        let is_hex = self.peek_n(2) == "0x"
+            && self
+                .peek_n(3)
+                .chars()
+                .nth(2)
+                .map_or(false, |c| c.is_ascii_hexdigit());
+        let num = if is_hex {
+            self.scan_hex()
         } else {
             self.scan_float()
         };
@@ -172,10 +253,13 @@ impl Scanner {
         self.push_tok(TT::Float(num), (start, self.pos));
     }
 
-    fn scan_hex(&mut self) -> u64 {
+    fn scan_hex(&mut self) -> f64 {
         self.expect("0x");
         let hex = self.collect_while(char::is_ascii_hexdigit);
-        u64::from_str_radix(&hex, 16).unwrap()
+        // Accumulate the value as a float, so that literals too large for an integer don't
+        // overflow.
+        hex.chars()
+            .fold(0., |n, digit| n * 16. + digit.to_digit(16).unwrap() as f64)
     }
 
     fn scan_float(&mut self) -> f64 {
@@ -229,11 +313,28 @@ impl Scanner {
         // Push the pattern prefix, which we already verified exists.
         pat.push(*self.next().unwrap());
         pat.push_str(&self.scan_var_str());
+        // A `...`-suffixed any pattern, like `_a...`, matches the remaining operands of a `+` or `*`
+        // chain.
+        if self.peek_n(3) == "..." {
+            self.expect("...");
+            pat.push_str("...");
+        }
 
         self.push_tok(TT::AnyPattern(pat), (start, self.pos));
     }
 }
 
+/// Returns the ASCII operator a non-ASCII character is commonly confused for, if any; for example,
+/// `*` for `×`.
+fn ascii_operator_for(c: char) -> Option<TokenType> {
+    match c {
+        '×' | '∗' | '⋅' | '·' => Some(TT::Mult),
+        '÷' | '∕' => Some(TT::Div),
+        '−' | '–' => Some(TT::Minus),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     /// Tests the Scanner's output against a humanized string representation of the expected tokens.
@@ -272,23 +373,36 @@ mod tests {
             integer: "2", "2"
             float: "3.2", "3.2"
             hex: "0x0fF", "255", false
+            hex_prefix_only: "0x", "0 x"
+            hex_prefix_then_var: "0xyz", "0 xyz"
+            hex_too_large_for_integer: "0x1FFFFFFFFFFFFFFFF", "36893488147419103000", false
             plus: "+", "+"
             minus: "-", "-"
+            plus_minus: "±", "±"
+            plus_minus_ascii: "+/-", "±", false
+            plus_then_div: "+ /-", "+ / -"
             mult: "*", "*"
             div: "/", "/"
             modulo: "%", "%"
             exp: "^", "^"
+            bang: "!", "!"
             equal: "=", "="
             open_paren: "(", "("
             close_paren: ")", ")"
             open_bracket: "[", "["
             close_bracket: "]", "]"
+            comma: ",", ","
+            semicolon: ";", ";"
             variable_pattern: "$a", "$a"
             const_pattern: "#a", "#a"
             any_pattern: "_a", "_a"
+            rest_pattern: "_a...", "_a..."
+            rest_pattern_in_op: "_a... + 0", "_a... + 0"
 
             empty_string: "", ""
             skip_whitespace: "  =  ", "="
+            skip_unicode_whitespace: "1\u{a0}+\u{2003}x", "1 + x"
+            skip_crlf: "a\r\n+ 1\r\n", "a + 1"
 
             multiple_integers: "1 2 3", "1 2 3"
             multiple_floats: "1.2 2.3 3.4", "1.2 2.3 3.4"
@@ -303,13 +417,97 @@ mod tests {
 
     mod scan_invalid {
         scanner_tests! {
-            invalid_numbers: "1.2.3", "1.2 . 3"
-            invalid_tokens: "@", "@"
-            invalid_tokens_mixed_with_valid: "=@/", "= @ /"
+            invalid_numbers: "1.2.3", "1.2 3"
+            invalid_tokens: "@", ""
+            invalid_tokens_mixed_with_valid: "=@/", "= /"
+            separated_invalid_tokens: "1 @ 2 & 3", "1 2 3"
             invalid_expressions: "1 + * 2", "1 + * 2"
+            reserved_not_equal: "a != b", "a != b"
+        }
+
+        #[test]
+        fn diagnoses_each_invalid_token() {
+            let result = crate::scanner::scan("1 @ 2 & 3");
+            let spans: Vec<_> = result.diagnostics.iter().map(|d| d.span).collect();
+            assert_eq!(spans, vec![(2, 3).into(), (6, 7).into()]);
+            assert!(result.is_recovered());
+        }
+
+        #[test]
+        fn replaces_confused_operators() {
+            let result = crate::scanner::scan("2 × 3 − 1");
+            let tokens: Vec<_> = result.tokens.iter().map(|tok| tok.to_string()).collect();
+            assert_eq!(tokens, vec!["2", "*", "3", "-", "1", "end of file"]);
+            assert_eq!(result.diagnostics.len(), 2);
+            assert!(result.is_recovered());
+        }
+
+        #[test]
+        fn reserved_tokens_are_not_recovered() {
+            assert!(!crate::scanner::scan("a != b").is_recovered());
+        }
+    }
+
+    mod invalid_token_autofix {
+        macro_rules! autofix_tests {
+            ($($name:ident: $program:expr => $confidence:ident $fix:expr)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    use crate::diagnostics::{AutofixConfidence, Edit};
+                    use crate::scanner::scan;
+
+                    let diagnostics = scan($program).diagnostics;
+                    assert_eq!(diagnostics.len(), 1);
+                    let autofix = diagnostics[0].autofix.as_ref().expect("no autofix");
+                    assert!(matches!(autofix.confidence, AutofixConfidence::$confidence));
+                    let fix = match &autofix.fix {
+                        Edit::Replace(replacement) => Some(replacement.as_str()),
+                        Edit::Delete => None,
+                    };
+                    assert_eq!(fix, $fix);
+                }
+            )*
+            }
+        }
+
+        autofix_tests! {
+            delete_invalid_token:   "1 @ 2"     => Maybe None
+            delete_smart_quote:     "\u{201C}a"  => Maybe None
+            times:                  "2 × 3"     => ForSure Some("*")
+            middle_dot:             "2 · 3"     => ForSure Some("*")
+            division:               "6 ÷ 3"     => ForSure Some("/")
+            minus:                  "6 − 3"     => ForSure Some("-")
+            define_assign:          "a : = 1"   => Maybe Some(":=")
+        }
+
+        #[test]
+        fn no_autofix_for_reserved_not_equal() {
+            let diagnostics = crate::scanner::scan("a != b").diagnostics;
+            assert_eq!(diagnostics.len(), 1);
+            assert!(diagnostics[0].autofix.is_none());
         }
     }
 
+    #[test]
+    fn skipped_tokens_are_leading_trivia() {
+        let program = "1 @ + 2";
+        let tokens = crate::scan(program).tokens;
+        assert_eq!(tokens[1].full_span.over(program), " @ +");
+    }
+
+    #[test]
+    fn spans_count_bytes() {
+        let program = "π\u{a0}× 2";
+        let tokens = crate::scan(program).tokens;
+        let spans: Vec<_> = tokens
+            .iter()
+            .map(|tok| (tok.span.lo, tok.span.hi))
+            .collect();
+        assert_eq!(spans, vec![(0, 2), (4, 6), (7, 8), (8, 9)]);
+        assert_eq!(tokens[1].full_span.over(program), "\u{a0}×");
+    }
+
     #[test]
     fn leading_trivia() {
         let program = r#"1 + 2  +    3 -  
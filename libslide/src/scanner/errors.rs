@@ -46,7 +46,7 @@ define_errors! {
     ///to use notation that is intuitive and obvious. Of course, reasonable people can disagree on
     ///what this means.
     S0001: InvalidToken {
-        ($span:expr, $did_you_mean:expr) => {{
+        ($span:expr, $autofix:expr) => {{
             use crate::diagnostics::*;
 
             let mut diag = Diagnostic::span_err(
@@ -56,8 +56,8 @@ define_errors! {
                 None,
             )
             .with_note("token must be mathematically significant");
-            if let Some((did_you_mean, _)) = $did_you_mean {
-                diag = diag.with_autofix(Autofix::maybe("did you mean", Edit::Replace(did_you_mean.to_string())))
+            if let Some(autofix) = $autofix {
+                diag = diag.with_autofix(autofix)
             }
             diag
         }}
@@ -15,6 +15,9 @@ pub enum TokenType {
     /// - symbol
     Minus,
 
+    /// ± symbol, also written +/-
+    PlusMinus,
+
     /// * symbol
     Mult,
 
@@ -27,6 +30,9 @@ pub enum TokenType {
     /// ^ symbol
     Exp,
 
+    /// ! symbol
+    Bang,
+
     /// = symbol
     Equal,
 
@@ -45,6 +51,12 @@ pub enum TokenType {
     /// ] symbol
     CloseBracket,
 
+    /// , symbol
+    Comma,
+
+    /// ; symbol
+    Semicolon,
+
     /// A variable name.
     Variable(String),
 
@@ -86,16 +98,20 @@ impl fmt::Display for TokenType {
                 Float(num) => num.to_string(),
                 Plus => "+".into(),
                 Minus => "-".into(),
+                PlusMinus => "±".into(),
                 Mult => "*".into(),
                 Div => "/".into(),
                 Mod => "%".into(),
                 Exp => "^".into(),
+                Bang => "!".into(),
                 Equal => "=".into(),
                 AssignDefine => ":=".into(),
                 OpenParen => "(".into(),
                 CloseParen => ")".into(),
                 OpenBracket => "[".into(),
                 CloseBracket => "]".into(),
+                Comma => ",".into(),
+                Semicolon => ";".into(),
                 Variable(s) => s.to_string(),
                 VariablePattern(s) => s.to_string(),
                 ConstPattern(s) => s.to_string(),
@@ -161,6 +177,7 @@ mod tests {
             float: Float(1.3), "1.3"
             plus: Plus, "+"
             minus: Minus, "-"
+            plus_minus: PlusMinus, "±"
             mult: Mult, "*"
             div: Div, "/"
             modulo: Mod, "%"
@@ -170,6 +187,7 @@ mod tests {
             close_paren: CloseParen, ")"
             open_bracket: OpenBracket, "["
             close_bracket: CloseBracket, "]"
+            comma: Comma, ","
             variable: Variable("ab".into()), "ab"
             invalid: Invalid("@&@".into()), "@&@"
         }
@@ -1,3 +1,4 @@
+use crate::grammar::fold::{self, ExprFolder};
 use crate::grammar::*;
 
 use std::collections::VecDeque;
@@ -54,9 +55,7 @@ macro_rules! insert_back {
 pub fn get_flattened_binary_args(expr: RcExpr, parent_op: BinaryOperator) -> Vec<RcExpr> {
     match expr.as_ref() {
         Expr::BinaryExpr(
-            child
-            @
-            BinaryExpr {
+            child @ BinaryExpr {
                 op: BinaryOperator::Plus,
                 ..
             },
@@ -71,9 +70,7 @@ pub fn get_flattened_binary_args(expr: RcExpr, parent_op: BinaryOperator) -> Vec
         }
 
         Expr::BinaryExpr(
-            child
-            @
-            BinaryExpr {
+            child @ BinaryExpr {
                 op: BinaryOperator::Mult,
                 ..
             },
@@ -88,9 +85,7 @@ pub fn get_flattened_binary_args(expr: RcExpr, parent_op: BinaryOperator) -> Vec
         }
 
         Expr::BinaryExpr(
-            child
-            @
-            BinaryExpr {
+            child @ BinaryExpr {
                 op: BinaryOperator::Minus,
                 ..
             },
@@ -145,7 +140,16 @@ fn negate(expr: RcExpr) -> RcExpr {
         // TODO: We could expand factorable expressions further:
         //       -(_a + _b) = -_a + -_b
         //       -(_a - _b) = -_a - -_b
-        Expr::BinaryExpr(_) => rc_expr!(
+        // f(_a, ...) => -f(_a, ...)
+        // [_a, _b] => -[_a, _b]
+        // _a! => -_a!
+        Expr::BinaryExpr(_)
+        | Expr::Call(_)
+        | Expr::Interval(_)
+        | Expr::UnaryExpr(UnaryExpr {
+            op: UnaryOperator::Factorial,
+            ..
+        }) => rc_expr!(
             Expr::UnaryExpr(UnaryExpr {
                 op: UnaryOperator::SignNegative,
                 rhs: expr,
@@ -170,7 +174,7 @@ where
         let mut args = args.iter();
         let mut lhs = args.next().unwrap().clone();
         for rhs in args {
-            let span = lhs.span().to(rhs.span());
+            let span = lhs.span().union(rhs.span());
             lhs = E::binary(
                 BinaryExpr {
                     op,
@@ -187,7 +191,7 @@ where
         let mut args = args.iter().rev();
         let mut rhs = args.next().unwrap().clone();
         for lhs in args {
-            let span = lhs.span().to(rhs.span());
+            let span = lhs.span().union(rhs.span());
             rhs = E::binary(
                 BinaryExpr {
                     op,
@@ -206,38 +210,19 @@ where
     }
 }
 
+/// Normalizes an expression by sorting the flattened arguments of each of its binary expressions.
 pub fn normalize(expr: RcExpr) -> RcExpr {
-    match expr.as_ref() {
-        Expr::BinaryExpr(BinaryExpr { op, lhs, rhs }) => {
-            let partially_normalized = Expr::BinaryExpr(BinaryExpr {
-                op: *op,
-                lhs: normalize(lhs.clone()),
-                rhs: normalize(rhs.clone()),
-            });
-            let mut flattened_args =
-                get_flattened_binary_args(rc_expr!(partially_normalized, expr.span), *op);
-            flattened_args.sort();
-            unflatten_binary_expr(&flattened_args, *op, UnflattenStrategy::Left)
-        }
-        Expr::UnaryExpr(UnaryExpr { op, rhs }) => rc_expr!(
-            Expr::UnaryExpr(UnaryExpr {
-                op: *op,
-                rhs: normalize(rhs.clone()),
-            }),
-            expr.span
-        ),
-        Expr::Parend(expr) => {
-            let inner = normalize(expr.clone());
-            let span = inner.span;
-            rc_expr!(Expr::Parend(inner), span)
-        }
-        Expr::Bracketed(expr) => {
-            let inner = normalize(expr.clone());
-            let span = inner.span;
-            rc_expr!(Expr::Bracketed(inner), span)
-        }
+    Normalizer.fold_expr(&expr)
+}
 
-        _ => expr,
+struct Normalizer;
+impl ExprFolder for Normalizer {
+    fn fold_binary(&mut self, expr: &RcExpr, binary: &BinaryExpr<RcExpr>) -> RcExpr {
+        let partially_normalized = fold::descend_binary(self, expr, binary);
+        let mut flattened_args = get_flattened_binary_args(partially_normalized, binary.op);
+        flattened_args.sort();
+        unflatten_binary_expr(&flattened_args, binary.op, UnflattenStrategy::Left)
+            .with_span(expr.span)
     }
 }
 
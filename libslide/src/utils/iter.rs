@@ -81,12 +81,18 @@ where
     /// Returns a reference to the next value in the iterator, without consuming it, or `None` if
     /// the iteration is complete.
     pub fn peek(&mut self) -> Option<&T> {
-        if self.lookahead.is_empty() {
-            // Hopefully the branch gets optimized out. Not sure if we can reduce it.
+        self.peek_nth(0)
+    }
+
+    /// Returns a reference to the `n`th next value in the iterator (where the 0th value is the one
+    /// [`peek`](Self::peek) would return), without consuming any values, or `None` if the iteration
+    /// completes before that value.
+    pub fn peek_nth(&mut self, n: usize) -> Option<&T> {
+        while self.lookahead.len() <= n {
             let next = self.iter.next();
             self.lookahead.push_back(next);
         }
-        self.lookahead[0].as_ref()
+        self.lookahead[n].as_ref()
     }
 
     /// Returns a deque of up to `n` peeked items mapped over a function `f`.
@@ -126,3 +132,43 @@ where
             .unwrap_or_else(|| self.iter.next())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::PeekIter;
+
+    #[test]
+    fn peek_nth() {
+        let mut iter = PeekIter::new(vec![1, 2, 3].into_iter());
+
+        assert_eq!(iter.peek_nth(2), Some(&3));
+        assert_eq!(iter.peek_nth(0), Some(&1));
+        assert_eq!(iter.peek_nth(1), Some(&2));
+        assert_eq!(iter.peek_nth(3), None);
+        assert_eq!(iter.peek(), Some(&1));
+
+        // Peeked values are not consumed.
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.peek_nth(1), Some(&3));
+        assert_eq!(iter.collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn peek_nth_after_push_front() {
+        let mut iter = PeekIter::new(vec![2, 3].into_iter());
+        iter.push_front(1);
+
+        assert_eq!(iter.peek_nth(0), Some(&1));
+        assert_eq!(iter.peek_nth(2), Some(&3));
+        assert_eq!(iter.collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn peek_nth_past_end() {
+        let mut iter = PeekIter::new(vec![1].into_iter());
+
+        assert_eq!(iter.peek_nth(5), None);
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), None);
+    }
+}
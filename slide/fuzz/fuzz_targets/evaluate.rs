@@ -0,0 +1,39 @@
+//! Evaluating any program that scans and parses without diagnostics must terminate without
+//! panicking.
+//!
+//! Termination is checked by libFuzzer's `-timeout`. Rules are matched against an addition or
+//! multiplication chain with each of its operands moved to the front, recursively, so evaluation
+//! time grows exponentially in the length of a chain, and programs like `(x^9 + 1) / (x + 1)`
+//! that produce long chains are reported as timeouts. Pass `-fork=N -ignore_timeouts=1` to keep fuzzing for panics past them.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use libslide::evaluator_rules::Rule;
+use libslide::{build_rules, evaluate_with_rules, parse_statements, scan, ProgramContext};
+
+use lazy_static::lazy_static;
+
+/// The largest program evaluated, in bytes. The time evaluation takes grows quickly in the size of
+/// an expression, so larger programs mostly report slow evaluations rather than new failures.
+const MAX_PROGRAM_LEN: usize = 256;
+
+lazy_static! {
+    static ref CONTEXT: ProgramContext = ProgramContext::default();
+    static ref RULES: Vec<Rule> = build_rules(&CONTEXT).unwrap();
+}
+
+fuzz_target!(|program: String| {
+    if program.len() > MAX_PROGRAM_LEN {
+        return;
+    }
+    let scanned = scan(program.as_str());
+    if !scanned.diagnostics.is_empty() {
+        return;
+    }
+    let parsed = parse_statements(scanned.tokens, &program);
+    if !parsed.diagnostics.is_empty() {
+        return;
+    }
+    evaluate_with_rules(parsed.program, &RULES, &CONTEXT);
+});
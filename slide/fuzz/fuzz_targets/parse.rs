@@ -0,0 +1,40 @@
+//! Parsing any program must not panic, and every diagnostic produced while scanning and parsing
+//! it must lie within the program.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use libslide::diagnostics::Diagnostic;
+use libslide::{parse_statements, scan, Span};
+
+fuzz_target!(|program: String| {
+    let scanned = scan(program.as_str());
+    let parsed = parse_statements(scanned.tokens, &program);
+    for diagnostic in scanned.diagnostics.iter().chain(parsed.diagnostics.iter()) {
+        check_spans(diagnostic, &program);
+    }
+});
+
+fn check_spans(diagnostic: &Diagnostic, program: &str) {
+    let spans = std::iter::once(diagnostic.span)
+        .chain(diagnostic.associated_diagnostics.iter().map(|d| d.span));
+    for span in spans {
+        assert!(
+            in_program(span, program),
+            "{} spans {:?}, outside of a program of {} bytes",
+            diagnostic.code,
+            span,
+            program.len()
+        );
+    }
+}
+
+/// Returns whether `span` lies within `program`. The end of file is a position one past the end
+/// of the program, so diagnostics reporting a missing token may span it.
+fn in_program(span: Span, program: &str) -> bool {
+    let on_boundary = |offset| offset > program.len() || program.is_char_boundary(offset);
+    span.lo <= span.hi
+        && span.hi <= program.len() + 1
+        && on_boundary(span.lo)
+        && on_boundary(span.hi)
+}
@@ -0,0 +1,11 @@
+//! Scanning any program must not panic.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use libslide::scan;
+
+fuzz_target!(|data: &[u8]| {
+    let program = String::from_utf8_lossy(data);
+    scan(program.as_ref());
+});
@@ -0,0 +1,151 @@
+//! Configuration files of slide options, like a `slide.toml` discovered from the working
+//! directory.
+
+use libslide::diagnostics::Diagnostic;
+use libslide::{
+    invalid_config_diagnostic, EmitConfig, EmitFormat, LineCol, ProgramContextBuilder, SourceIndex,
+    Span,
+};
+
+use serde::Deserialize;
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+use toml::Spanned;
+
+/// Name of the configuration file discovered from the working directory.
+const CONFIG_FILE_NAME: &str = "slide.toml";
+
+/// A configuration file of slide options.
+#[cfg_attr(feature = "wasm", derive(serde::Serialize, Deserialize))]
+#[derive(Clone)]
+pub struct ConfigFile {
+    /// Path of the file.
+    pub path: String,
+    /// Contents of the file.
+    pub source: String,
+}
+
+/// Options of a configuration file. Each option has the name and values of a command line flag,
+/// with dashes in the name replaced by underscores. Options of the
+/// [evaluation context](libslide::ProgramContext) are in a `context` table.
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ConfigOptions {
+    pub output_form: Option<Spanned<String>>,
+    pub emit_config: Option<Vec<Spanned<String>>>,
+    pub max_output_width: Option<Spanned<usize>>,
+    pub lint: Option<bool>,
+    pub allow: Option<Vec<Spanned<String>>>,
+    pub color: Option<Spanned<String>>,
+    pub context: Option<ProgramContextBuilder>,
+}
+
+impl ConfigFile {
+    /// Finds a `slide.toml` in `dir` or the nearest of its ancestors that has one.
+    pub fn discover(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Reads the configuration file at `path`.
+    pub fn read(path: &str) -> std::io::Result<Self> {
+        Ok(Self {
+            path: path.to_owned(),
+            source: std::fs::read_to_string(path)?,
+        })
+    }
+
+    /// Reads the options of the configuration file, or returns a diagnostic describing the first
+    /// invalid option.
+    pub(crate) fn options(&self) -> Result<ConfigOptions, Diagnostic> {
+        let options: ConfigOptions = toml::from_str(&self.source).map_err(|err| {
+            let offset = match err.line_col() {
+                // toml counts lines like `str::lines`, and columns in bytes.
+                Some((line, column)) => {
+                    SourceIndex::new(&self.source).offset(LineCol::new(line, column))
+                }
+                None => 0,
+            };
+            // The position of the error is already shown by the diagnostic.
+            let reason = err.to_string();
+            let reason = match reason.rfind(" at line ") {
+                Some(i) => reason[..i].to_owned(),
+                None => reason,
+            };
+            invalid_config_diagnostic(Span::from((offset, offset + 1)), reason)
+        })?;
+
+        if let Some(output_form) = &options.output_form {
+            if let Err(err) = EmitFormat::try_from(output_form.get_ref().as_str()) {
+                return Err(self.invalid_option(output_form, err, "output_form"));
+            }
+        }
+        for opt in options.emit_config.iter().flatten() {
+            if let Err(err) = EmitConfig::try_from(opt.get_ref().as_str()) {
+                return Err(self.invalid_option(opt, err, "emit_config"));
+            }
+        }
+        if let Some(width) = &options.max_output_width {
+            if *width.get_ref() == 0 {
+                let reason = format!("`{}` is not a positive integer", width.get_ref());
+                return Err(self.invalid_option(width, reason, "max_output_width"));
+            }
+        }
+        for code in options.allow.iter().flatten() {
+            if Diagnostic::explanation(code.get_ref()).is_none() {
+                let reason = format!("`{}` is not a diagnostic code", code.get_ref());
+                return Err(self.invalid_option(code, reason, "allow"));
+            }
+        }
+        if let Some(color) = &options.color {
+            if !matches!(color.get_ref().as_str(), "always" | "never" | "auto") {
+                let reason = format!(
+                    "`{}` is not one of `always`, `never`, or `auto`",
+                    color.get_ref()
+                );
+                return Err(self.invalid_option(color, reason, "color"));
+            }
+        }
+        if let Some(context) = &options.context {
+            if let Err(err) = context.clone().build() {
+                let libslide::ConfigError::RequiredRuleDenied { option, .. } = &err;
+                return Err(err.to_diagnostic(self.key_span(option)));
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Creates a diagnostic for an option `key` whose value is invalid for `reason`.
+    fn invalid_option<T>(
+        &self,
+        value: &Spanned<T>,
+        reason: impl ToString,
+        key: &str,
+    ) -> Diagnostic {
+        invalid_config_diagnostic(
+            Span::from(value.span()),
+            format!("{} for key `{}`", reason.to_string(), key),
+        )
+    }
+
+    /// Returns the span of the first definition of the option `key` in the file, or an empty span
+    /// at the start of the file if there is none.
+    fn key_span(&self, key: &str) -> Span {
+        let source = &self.source;
+        source
+            .match_indices(key)
+            .map(|(start, _)| start)
+            .find(|&start| {
+                let before = source[..start].chars().next_back();
+                let after = source[start + key.len()..].trim_start_matches(&[' ', '\t'][..]);
+                matches!(
+                    before,
+                    None | Some('\n') | Some(' ') | Some('\t') | Some('{') | Some(',')
+                ) && after.starts_with('=')
+            })
+            .map(|start| Span::from((start, start + key.len())))
+            .unwrap_or_else(|| Span::from((0, 0)))
+    }
+}
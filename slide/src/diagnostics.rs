@@ -1,24 +1,40 @@
 //! User-facing slide diagnostics.
 //!
 //! The diagnostics module translates [libslide diagnostics](libslide::diagnostics) into a form
-//! pleasant for standard output.
+//! pleasant for standard output, or into JSON for consumption by other tools.
 
 use libslide::diagnostics::{AssociatedDiagnostic, Autofix, Diagnostic, DiagnosticKind, Edit};
+use libslide::{LineCol, LineColSpan, SourceIndex, Span};
 
 use annotate_snippets::{
-    display_list::{DisplayList, FormatOptions},
+    display_list::{DisplayLine, DisplayList, DisplayRawLine, FormatOptions},
     snippet::{Annotation, AnnotationType, Slice, Snippet, SourceAnnotation},
 };
+use serde_json::{json, Value};
+
+/// Origin shown in diagnostics of programs that do not come from a file.
+const NO_FILE_ORIGIN: &str = "<stdin>";
 
 pub fn sanitize_source_for_diagnostics(source: &str) -> String {
-    let source = source.to_owned();
-    source + " " // we might emit an EOF diagnostic, so add extra space.
+    // A `\r` that does not end a line would be displayed as a carriage return, so replace it with
+    // a space of the same width.
+    let mut sanitized: String = source
+        .char_indices()
+        .map(|(i, c)| match c {
+            '\r' if !source[i + 1..].starts_with('\n') => ' ',
+            c => c,
+        })
+        .collect();
+    sanitized.push(' '); // we might emit an EOF diagnostic, so add extra space.
+    sanitized
 }
 
+/// Emits diagnostics of `source` for display. `index` must index `source`.
 pub fn emit_slide_diagnostics(
     file: Option<&str>,
     source: &str,
-    diagnostics: &[Diagnostic],
+    index: &SourceIndex,
+    diagnostics: &[&Diagnostic],
     color: bool,
 ) -> String {
     if diagnostics.is_empty() {
@@ -41,7 +57,7 @@ pub fn emit_slide_diagnostics(
         let autofix = diagnostic
             .autofix
             .as_ref()
-            .map(|af| convert_autofix(&af, diagnostic.span.into()))
+            .map(|af| convert_autofix(&af, char_range(source, diagnostic.span)))
             .unwrap_or_else(SourceAnnotationShim::dummy);
         let main_label = diagnostic.msg.clone().unwrap_or_default();
 
@@ -57,7 +73,7 @@ pub fn emit_slide_diagnostics(
         annotations.push(SourceAnnotation {
             label: first_label,
             annotation_type: first_annotation_type,
-            range: diagnostic.span.into(),
+            range: char_range(source, diagnostic.span),
         });
         if has_autofix && !merge_autofix {
             annotations.push(autofix.deshim());
@@ -68,7 +84,7 @@ pub fn emit_slide_diagnostics(
             annotations.push(SourceAnnotation {
                 label: &associated_diagnostic.msg,
                 annotation_type: convert_diagnostic_kind(&associated_diagnostic.kind),
-                range: associated_diagnostic.span.into(),
+                range: char_range(source, associated_diagnostic.span),
             });
         }
 
@@ -88,7 +104,7 @@ pub fn emit_slide_diagnostics(
             slices: vec![Slice {
                 source: &source,
                 line_start: 1,
-                origin: file,
+                origin: Some(file.unwrap_or(NO_FILE_ORIGIN)),
                 fold: true,
                 annotations,
             }],
@@ -97,12 +113,113 @@ pub fn emit_slide_diagnostics(
                 ..Default::default()
             },
         };
+        let mut display_list = DisplayList::from(snippet);
+        // annotate-snippets positions the header by the characters of an annotation; position it
+        // by the bytes of the diagnostic's span instead.
+        let ((line, col), _) = diagnostic.line_col(index);
+        for display_line in display_list.body.iter_mut() {
+            if let DisplayLine::Raw(DisplayRawLine::Origin { pos, .. }) = display_line {
+                *pos = Some((line + 1, col + 1));
+            }
+        }
+
         let suffix = if i != last_i { "\n" } else { "" };
-        emitted_diagnostics.push_str(&format!("{}\n{}", DisplayList::from(snippet), suffix));
+        emitted_diagnostics.push_str(&format!("{}\n{}", display_list, suffix));
     }
     emitted_diagnostics
 }
 
+/// Serializes diagnostics to JSON values, one per diagnostic, of the form
+///
+/// ```json
+/// {
+///   "code": "P0001",
+///   "kind": "error",
+///   "title": "Unexpected extra tokens",
+///   "message": "not connected to a primary statement",
+///   "span": { "start": { "line": 1, "column": 7 }, "end": { "line": 1, "column": 8 } },
+///   "associated": [
+///     { "kind": "help", "message": "if you meant to specify another statement, ...", "span": ... }
+///   ],
+///   "autofix": { "message": "consider deleting these tokens", "replacement": null }
+/// }
+/// ```
+///
+/// Lines and columns are 1-indexed, columns count bytes, and a span's end is exclusive. `message`
+/// and `autofix` may be `null`, as may the `span` of associated diagnostics that do not cover a
+/// specific span. An autofix `replacement` of `null` means its span should be deleted. `index` must
+/// index the source of the diagnostics.
+pub fn serialize_slide_diagnostics(index: &SourceIndex, diagnostics: &[&Diagnostic]) -> Vec<Value> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let spanned = diagnostic
+                .associated_diagnostics
+                .iter()
+                .map(|associated| serialize_associated_diagnostic(index, associated, true));
+            let unspanned = diagnostic
+                .unspanned_associated_diagnostics
+                .iter()
+                .map(|associated| serialize_associated_diagnostic(index, associated, false));
+            let autofix = diagnostic.autofix.as_ref().map(|autofix| {
+                let replacement = match &autofix.fix {
+                    Edit::Replace(replacement) => Some(replacement),
+                    Edit::Delete => None,
+                };
+                json!({ "message": autofix.msg, "replacement": replacement })
+            });
+
+            json!({
+                "code": diagnostic.code,
+                "kind": serialize_diagnostic_kind(&diagnostic.kind),
+                "title": diagnostic.title,
+                "message": diagnostic.msg,
+                "span": serialize_span(index, diagnostic.span),
+                "associated": spanned.chain(unspanned).collect::<Vec<_>>(),
+                "autofix": autofix,
+            })
+        })
+        .collect()
+}
+
+fn serialize_associated_diagnostic(
+    index: &SourceIndex,
+    diagnostic: &AssociatedDiagnostic,
+    spanned: bool,
+) -> Value {
+    json!({
+        "kind": serialize_diagnostic_kind(&diagnostic.kind),
+        "message": diagnostic.msg,
+        "span": if spanned { serialize_span(index, diagnostic.span) } else { Value::Null },
+    })
+}
+
+fn serialize_diagnostic_kind(diagnostic_kind: &DiagnosticKind) -> &'static str {
+    match diagnostic_kind {
+        DiagnosticKind::Error => "error",
+        DiagnosticKind::Warning => "warning",
+        DiagnosticKind::Note => "note",
+        DiagnosticKind::Help => "help",
+    }
+}
+
+fn serialize_span(index: &SourceIndex, span: Span) -> Value {
+    let LineColSpan { start, end } = index.span(span);
+    let position = |LineCol { line, col }| json!({ "line": line + 1, "column": col + 1 });
+    json!({ "start": position(start), "end": position(end) })
+}
+
+/// Converts a byte [Span] in `source` to the range of characters it covers, which is how
+/// annotate-snippets ranges annotations. Offsets past the end of `source` are counted as
+/// single-byte characters.
+fn char_range(source: &str, span: Span) -> (usize, usize) {
+    let char_offset = |offset: usize| match source.get(..offset) {
+        Some(prefix) => prefix.chars().count(),
+        None => source.chars().count() + offset.saturating_sub(source.len()),
+    };
+    (char_offset(span.lo), char_offset(span.hi))
+}
+
 /// Converts a slide DiagnosticKind to an AnnotationType.
 fn convert_diagnostic_kind(diagnostic_kind: &DiagnosticKind) -> AnnotationType {
     match diagnostic_kind {
@@ -179,3 +296,51 @@ impl SourceAnnotationShim {
         self.range == Self::DUMMY_RANGE
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{emit_slide_diagnostics, sanitize_source_for_diagnostics};
+    use libslide::{parse_statements, scan, SourceIndex};
+
+    /// Emits the diagnostics of parsing `program`.
+    fn emit_parse_diagnostics(file: Option<&str>, program: &str) -> String {
+        let parsed = parse_statements(scan(program).tokens, program);
+        let source = sanitize_source_for_diagnostics(program);
+        emit_slide_diagnostics(
+            file,
+            &source,
+            &SourceIndex::new(&source),
+            &parsed.diagnostics.iter().collect::<Vec<_>>(),
+            false,
+        )
+    }
+
+    #[test]
+    fn header_position() {
+        let emitted = emit_parse_diagnostics(None, "a = 1\nb = 2 +");
+        assert!(emitted.contains("--> <stdin>:2:7"), "{}", emitted);
+    }
+
+    #[test]
+    fn header_position_multibyte() {
+        // `π` is two bytes.
+        let emitted = emit_parse_diagnostics(Some("fi.slide"), "π = 1\nππ = 2 +");
+        assert!(emitted.contains("--> fi.slide:2:10"), "{}", emitted);
+    }
+
+    #[test]
+    fn header_position_crlf() {
+        let emitted = emit_parse_diagnostics(None, "π = 1\r\nππ = 2 +");
+        assert!(emitted.contains("--> <stdin>:2:10"), "{}", emitted);
+    }
+
+    #[test]
+    fn underline_multibyte() {
+        let emitted = emit_parse_diagnostics(None, "a\u{a0}= 1 +");
+        assert!(
+            emitted.contains("1 | a\u{a0}= 1 + \n  |       ^"),
+            "{}",
+            emitted
+        );
+    }
+}
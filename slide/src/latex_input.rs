@@ -0,0 +1,113 @@
+//! Translation of LaTeX math mode input into slide programs.
+
+/// Translates a LaTeX math mode snippet, like `$1 + \frac{2}{3}$`, into a slide program.
+///
+/// Math delimiters (`$`, `$$`, `\[ \]`) wrapping the snippet are stripped, and the following
+/// commands are translated:
+///
+/// - `\frac{a}{b}` to `(a)/(b)`
+/// - `\times` and `\cdot` to `*`
+/// - `\pm` to `±`
+///
+/// Anything else is left as-is, for the slide scanner to accept or reject.
+pub fn translate_latex(input: &str) -> String {
+    translate_commands(strip_math_delimiters(input))
+}
+
+fn strip_math_delimiters(input: &str) -> &str {
+    let input = input.trim();
+    [("$$", "$$"), ("$", "$"), ("\\[", "\\]")]
+        .iter()
+        .find_map(|(open, close)| {
+            input
+                .strip_prefix(open)
+                .and_then(|inner| inner.strip_suffix(close))
+        })
+        .unwrap_or(input)
+}
+
+fn translate_commands(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('\\') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        let name_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let (name, after) = rest.split_at(name_len);
+        match name {
+            "times" | "cdot" => {
+                out.push('*');
+                rest = after;
+            }
+            "pm" => {
+                out.push('±');
+                rest = after;
+            }
+            "frac" => match brace_group(after)
+                .and_then(|(num, after)| brace_group(after).map(|(den, after)| (num, den, after)))
+            {
+                Some((num, den, after)) => {
+                    out.push_str(&format!(
+                        "({})/({})",
+                        translate_commands(num),
+                        translate_commands(den)
+                    ));
+                    rest = after;
+                }
+                None => out.push('\\'),
+            },
+            _ => out.push('\\'),
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Splits a brace-delimited group, like `{a}` in `{a}{b}`, from the start of `input`, ignoring
+/// leading whitespace. Returns the contents of the group and the input after it.
+fn brace_group(input: &str) -> Option<(&str, &str)> {
+    let input = input.trim_start().strip_prefix('{')?;
+    let mut depth = 0;
+    for (i, c) in input.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some((&input[..i], &input[i + 1..])),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::translate_latex;
+
+    macro_rules! translate_latex_tests {
+        ($($name:ident: $latex:expr => $slide:expr)*) => {
+        $(
+            #[test]
+            fn $name() {
+                assert_eq!(translate_latex($latex), $slide);
+            }
+        )*
+        }
+    }
+
+    translate_latex_tests! {
+        frac:               "\\frac{1}{2} + 3"              => "(1)/(2) + 3"
+        frac_nested:        "\\frac{\\frac{a}{2}}{b + 1}"   => "((a)/(2))/(b + 1)"
+        frac_spaced:        "\\frac {1} {2}"                => "(1)/(2)"
+        frac_malformed:     "\\frac{1}"                     => "\\frac{1}"
+        cdot:               "2 \\cdot 3"                    => "2 * 3"
+        times:              "2\\times3"                     => "2*3"
+        pm:                 "1 \\pm 2"                      => "1 ± 2"
+        unknown_command:    "\\sqrt{2}"                     => "\\sqrt{2}"
+        dollars:            "$1 + \\frac{2}{3}$"            => "1 + (2)/(3)"
+        double_dollars:     " $$1 + 2$$ "                   => "1 + 2"
+        brackets:           "\\[1 + 2\\]"                   => "1 + 2"
+        no_delimiters:      "1 + 2"                         => "1 + 2"
+    }
+}
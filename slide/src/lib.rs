@@ -7,14 +7,31 @@
 #[cfg(test)]
 mod test;
 
+mod config;
+pub use config::ConfigFile;
+
 mod diagnostics;
-use diagnostics::{emit_slide_diagnostics, sanitize_source_for_diagnostics};
+use diagnostics::{
+    emit_slide_diagnostics, sanitize_source_for_diagnostics, serialize_slide_diagnostics,
+};
+
+mod latex_input;
+use latex_input::translate_latex;
+
+mod session;
+pub use session::SlideSession;
 
 use libslide::diagnostics::{Diagnostic, DiagnosticKind};
+use libslide::evaluator_rules::RuleSetPreset;
 use libslide::{
-    evaluate, lint_expr_pat, lint_stmt, parse_expression_pattern, parse_statements, scan, Emit,
-    EmitConfig, EmitFormat, EvaluationResult, ParseResult, ProgramContext, ScanResult, Token,
+    evaluate, evaluate_profiled, lint_expr_pat, lint_stmt, match_pattern, normalize_stmts,
+    parse_expression_pattern, parse_statements, scan, substitute, Assignment, Emit, EmitConfig,
+    EmitFormat, EvaluationResult, ParseResult, ProgramContext, RcExpr, ScanResult, SourceIndex,
+    Stmt, StmtKind, StmtList, Token,
 };
+use std::cell::RefCell;
+use std::ffi::OsString;
+use toml::Spanned;
 
 #[cfg(feature = "wasm")]
 use serde::{Deserialize, Serialize};
@@ -35,21 +52,105 @@ pub struct Opts {
     pub emit_format: String,
     /// Configuration options for slide emit.
     pub emit_config: Vec<String>,
+    /// When is [Some](Option::Some) width, pretty emit breaks lines longer than that many
+    /// characters.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub max_output_width: Option<usize>,
     /// When true, lint warnings for the program will be emitted, if any.
     pub lint: bool,
+    /// When true, evaluations to numbers that are not integers are errors.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub strict_integers: bool,
+    /// When is [Some](Option::Some) preset, the program is simplified with only the rules of that
+    /// preset.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub rule_set: Option<RuleSetPreset>,
     /// When true, slide will stop after parsing a program.
     pub parse_only: bool,
+    /// When true, slide will stop after normalizing a program, before simplification rules are
+    /// applied to it.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub emit_normalized: bool,
     /// When true, slide will expect the program to be an expression pattern.
     pub expr_pat: bool,
     /// When is [Some](Option::Some) diagnostic code, will explain that code.
     pub explain_diagnostic: Option<String>,
+    /// When true, slide will report how many times each simplification rule transformed the
+    /// program.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub profile_rules: bool,
+    /// When true, slide will report how many passes of the simplification rules it took to
+    /// evaluate the program.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub show_iterations: bool,
     /// When true, slide emit will be colored.
     pub color: bool,
+    /// When true, each line of the program is evaluated as its own program, and slide emits a CSV
+    /// of the lines and their results.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub csv: bool,
+    /// When true, the program is LaTeX math mode code, and is translated to slide before it is
+    /// scanned.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub latex_input: bool,
+    /// When true, diagnostics are emitted as a JSON array rather than in a human-readable form.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub json_diagnostics: bool,
+    /// Codes of diagnostics that should not be emitted. Errors are always emitted.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub allowed_diagnostics: Vec<String>,
+    /// When is [Some](Option::Some) expression and slide expects the program to be an expression
+    /// pattern, the pattern is matched against the expression, and the expression bound to each
+    /// pattern is emitted.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub against: Option<String>,
+    /// Substitutions of variables, like `x=3`, applied in order to the simplified program, which is
+    /// then simplified again.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub substitutions: Vec<String>,
+    /// When true and there are substitutions, only the program simplified after the substitutions
+    /// is emitted.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub quiet: bool,
+    /// When is [Some](Option::Some) command, paged output is paged with that command rather than
+    /// `$PAGER`.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub pager: Option<String>,
+    /// When true, output is never paged.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub no_pager: bool,
+    /// When is [Some](Option::Some) file, options not given on the command line are read from that
+    /// file, and the program is evaluated with the [context](libslide::ProgramContext) options in
+    /// it.
+    #[cfg_attr(feature = "wasm", serde(default))]
+    pub config: Option<ConfigFile>,
+}
+
+impl Opts {
+    /// Returns the command paged output should be paged with, or [None](Option::None) if output
+    /// should not be paged. `env_pager` is the value of `$PAGER`, if it is set.
+    pub fn pager_command(&self, env_pager: Option<OsString>) -> Option<OsString> {
+        if self.no_pager {
+            return None;
+        }
+        let default = || OsString::from(if cfg!(windows) { "more.com" } else { "less" });
+        Some(match &self.pager {
+            Some(pager) => OsString::from(pager),
+            None => env_pager.unwrap_or_else(default),
+        })
+    }
 }
 
 /// Parses [Opts](self::Opts) from the command line or given a parser that acts on the clap
 /// [App](clap::App).
-pub fn get_opts<P>(parser: P, color: bool) -> Result<Opts, clap::Error>
+///
+/// `supports_color` is whether the environment slide is run in supports colored output; it
+/// decides whether slide emit is colored when `--color` is `auto`.
+///
+/// Unless `--no-config` is given, options are also read from the file given by `--config`, or else
+/// from the `slide.toml` in the working directory or its nearest ancestor that has one. Options
+/// given on the command line override those of the file.
+pub fn get_opts<P>(parser: P, supports_color: bool) -> Result<Opts, clap::Error>
 where
     P: for<'a> FnOnce(clap::App<'a, '_>) -> Result<clap::ArgMatches<'a>, clap::Error>,
 {
@@ -74,12 +175,13 @@ where
                     \ts-expression: Prefixed s-expression, like \"(+ 1 2)\".\n\
                     \tlatex:        LaTeX math mode code, like \"$\\left\\(1 + 2\\right\\)$\".\n\
                     \tdebug:        Opaque internal representation. Note: this format is not stable.\n\
+                    \tjson:         JSON syntax tree, with the kind and span of each node.\n\
                     ",
                 )
                 .hide_possible_values(true)
                 .default_value("pretty")
                 .takes_value(true)
-                .possible_values(&["pretty", "s-expression", "latex", "debug"]),
+                .possible_values(&["pretty", "s-expression", "latex", "debug", "json"]),
         )
         .arg(
             // TODO: validate that -olatex is present.
@@ -93,51 +195,285 @@ where
                     \tdiv           (latex):        Emit \"\\div\" for divisions.\n\
                     \timplicit-mult (pretty|latex): Use implicit multiplication where possible.\n\
                     \thex           (pretty):       Emit integers as hexadecimal.\n\
+                    \tsuperscript   (pretty):       Emit integer exponents as superscripts.\n\
+                    \tdefine-assign (all):          Emit all assignments with \":=\".\n\
+                    \tcompact       (pretty):       Emit binary operators without spaces, like \"1+2\".\n\
+                    \ttight-exp     (pretty):       Emit \"^\" without spaces, like \"1 + 2^3\".\n\
+                    \tcontinued-fraction (pretty):  Emit constant results as continued fractions, like \"[2; 3, 1, 4]\".\n\
                     ",
                 )
                 .hide_possible_values(true)
                 .takes_value(true)
-                .possible_values(&["frac", "times", "div", "implicit-mult", "hex"])
+                .possible_values(&[
+                    "frac",
+                    "times",
+                    "div",
+                    "implicit-mult",
+                    "hex",
+                    "superscript",
+                    "define-assign",
+                    "compact",
+                    "tight-exp",
+                    "continued-fraction",
+                ])
                 .multiple(true),
         )
+        .arg(
+            clap::Arg::with_name("max-output-width")
+                .long("--max-output-width")
+                .value_name("width")
+                .help("Break pretty emit longer than this many characters before additions and subtractions.")
+                .takes_value(true)
+                .validator(|width| match width.parse::<usize>() {
+                    Ok(width) if width > 0 => Ok(()),
+                    _ => Err(format!("`{}` is not a positive integer", width)),
+                }),
+        )
         .arg(
             clap::Arg::with_name("lint")
                 .long("--lint")
                 .help("Emit lint warnings for the program, if any."),
         )
+        .arg(
+            clap::Arg::with_name("strict-integers")
+                .long("--strict-integers")
+                .help("Report an error on evaluations to numbers that are not integers, like `1 / 2`."),
+        )
+        .arg(
+            clap::Arg::with_name("rule-set")
+                .long("--rule-set")
+                .value_name("preset")
+                .help("Simplify the program with this preset of rules. `basic` only folds constants and applies identities, like `a * 1 = a`, though flattening still combines like terms; `full` applies all rules.")
+                .takes_value(true)
+                .possible_values(&["basic", "full"]),
+        )
+        .arg(
+            clap::Arg::with_name("config")
+                .long("--config")
+                .value_name("file")
+                .help("Read options from this TOML file rather than a slide.toml in the working directory.")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("no-config")
+                .long("--no-config")
+                .help("Do not read options from a slide.toml in the working directory.")
+                .conflicts_with("config"),
+        )
+        .arg(
+            clap::Arg::with_name("allow")
+                .long("--allow")
+                .value_name("diagnostic")
+                .help("Do not emit diagnostics with this code. Errors are always emitted.")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .validator(|code| match Diagnostic::explanation(&code) {
+                    Some(_) => Ok(()),
+                    None => Err(format!("`{}` is not a diagnostic code", code)),
+                }),
+        )
         .arg(
             clap::Arg::with_name("parse-only")
                 .long("--parse-only")
                 .help("Stop after parsing and dump the AST"),
         )
+        .arg(
+            clap::Arg::with_name("emit-normalized")
+                .long("--emit-normalized")
+                .help("Stop after normalizing the program, before simplification rules are applied, and emit it.")
+                .conflicts_with_all(&["parse-only", "expr-pat"]),
+        )
         .arg(
             clap::Arg::with_name("expr-pat")
                 .long("--expr-pat")
                 .help("Parse the program as an expression pattern. Implies --parse-only."),
         )
+        .arg(
+            clap::Arg::with_name("against")
+                .long("--against")
+                .value_name("expr")
+                .help("Match the expression pattern against an expression, emitting what each pattern binds to. Exits with 1 if the pattern does not match.")
+                .takes_value(true)
+                .requires("expr-pat"),
+        )
+        .arg(
+            clap::Arg::with_name("sub")
+                .long("--sub")
+                .value_name("var=expr")
+                .help("After simplifying the program, substitute the variable with the expression and simplify it again, emitting both results. Substitutions are applied in order.")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .conflicts_with_all(&["parse-only", "expr-pat", "emit-normalized"]),
+        )
+        .arg(
+            clap::Arg::with_name("quiet")
+                .long("--quiet")
+                .help("With --sub, only emit the program simplified after the substitutions.")
+                .requires("sub"),
+        )
         .arg(
             clap::Arg::with_name("explain")
                 .long("--explain")
                 .value_name("diagnostic")
                 .help("Provide a detailed explanation for a diagnostic code.")
                 .takes_value(true)
+        )
+        .arg(
+            clap::Arg::with_name("profile-rules")
+                .long("--profile-rules")
+                .help("Report how many times each simplification rule transformed the program."),
+        )
+        .arg(
+            clap::Arg::with_name("show-iterations")
+                .long("--show-iterations")
+                .help("Report how many passes of the simplification rules it took to evaluate the program."),
+        )
+        .arg(
+            clap::Arg::with_name("csv")
+                .long("--csv")
+                .help("Evaluate each line of the program separately, emitting a CSV of the lines and their results."),
+        )
+        .arg(
+            clap::Arg::with_name("stdin-format")
+                .long("--stdin-format")
+                .next_line_help(true)
+                .help(
+                    "Format of the program. Possible values:\n\
+                    \tslide: A slide program, like \"1 + 2 / 3\".\n\
+                    \tlatex: LaTeX math mode code, like \"$1 + \\frac{2}{3}$\". Math delimiters are\n\
+                    \t       stripped, and \\frac, \\times, and \\cdot are translated to slide.\n\
+                    ",
+                )
+                .hide_possible_values(true)
+                .default_value("slide")
+                .takes_value(true)
+                .possible_values(&["slide", "latex"]),
+        )
+        .arg(
+            clap::Arg::with_name("diagnostic-format")
+                .long("--diagnostic-format")
+                .value_name("format")
+                .help("How to emit diagnostics. \"json\" emits a JSON array of diagnostics.")
+                .default_value("human")
+                .takes_value(true)
+                .possible_values(&["human", "json"]),
+        )
+        .arg(
+            clap::Arg::with_name("color")
+                .long("--color")
+                .value_name("when")
+                .help("Whether to color emit. \"auto\" colors emit when stderr is a color terminal.")
+                .default_value("auto")
+                .takes_value(true)
+                .possible_values(&["always", "never", "auto"]),
+        )
+        .arg(
+            clap::Arg::with_name("pager")
+                .long("--pager")
+                .value_name("cmd")
+                .help("Command to page long output, like diagnostic explanations. Defaults to $PAGER.")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("no-pager")
+                .long("--no-pager")
+                .help("Print long output directly rather than paging it.")
+                .conflicts_with("pager"),
         );
     let matches = parser(matches)?;
 
+    let config_path = if matches.is_present("no-config") {
+        None
+    } else if let Some(path) = matches.value_of("config") {
+        Some(path.to_owned())
+    } else {
+        std::env::current_dir()
+            .ok()
+            .and_then(|dir| ConfigFile::discover(&dir))
+            .map(|path| path.display().to_string())
+    };
+    let config = match config_path {
+        Some(path) => Some(ConfigFile::read(&path).map_err(|err| {
+            clap::Error::with_description(
+                &format!("Failed to read config file `{}`: {}", path, err),
+                clap::ErrorKind::Io,
+            )
+        })?),
+        None => None,
+    };
+    // Options of an invalid config file are not applied; running slide reports why it is invalid.
+    let options = config
+        .as_ref()
+        .and_then(|config| config.options().ok())
+        .unwrap_or_default();
+    let from_cli = |arg| matches.occurrences_of(arg) > 0;
+    let color = match options.color {
+        Some(color) if !from_cli("color") => color.into_inner(),
+        _ => matches.value_of("color").unwrap().to_owned(),
+    };
+
     let expr_pat = matches.is_present("expr-pat");
     Ok(Opts {
         program: matches.value_of("program").unwrap().into(),
         // TODO: we should consolidate emit_format and output-form before any stable release.
-        emit_format: matches.value_of("output-form").unwrap().into(),
-        emit_config: matches
-            .values_of("emit-config")
-            .map(|opts| opts.map(str::to_owned).collect())
-            .unwrap_or_default(),
-        lint: matches.is_present("lint"),
+        emit_format: match options.output_form {
+            Some(form) if !from_cli("output-form") => form.into_inner(),
+            _ => matches.value_of("output-form").unwrap().into(),
+        },
+        emit_config: match options.emit_config {
+            Some(opts) if !from_cli("emit-config") => {
+                opts.into_iter().map(Spanned::into_inner).collect()
+            }
+            _ => matches
+                .values_of("emit-config")
+                .map(|opts| opts.map(str::to_owned).collect())
+                .unwrap_or_default(),
+        },
+        max_output_width: matches
+            .value_of("max-output-width")
+            .map(|width| width.parse().unwrap())
+            .or(options.max_output_width.map(Spanned::into_inner)),
+        lint: matches.is_present("lint") || options.lint.unwrap_or(false),
+        strict_integers: matches.is_present("strict-integers"),
+        rule_set: match matches.value_of("rule-set") {
+            Some("basic") => Some(RuleSetPreset::Basic),
+            Some(_) => Some(RuleSetPreset::Full),
+            None => None,
+        },
         parse_only: matches.is_present("parse-only") || expr_pat,
+        emit_normalized: matches.is_present("emit-normalized"),
         explain_diagnostic: matches.value_of("explain").map(str::to_owned),
+        profile_rules: matches.is_present("profile-rules"),
+        show_iterations: matches.is_present("show-iterations"),
         expr_pat,
-        color,
+        color: match color.as_str() {
+            "always" => true,
+            "never" => false,
+            _ => supports_color,
+        },
+        csv: matches.is_present("csv"),
+        latex_input: matches.value_of("stdin-format") == Some("latex"),
+        json_diagnostics: matches.value_of("diagnostic-format") == Some("json"),
+        allowed_diagnostics: match options.allow {
+            Some(codes) if !from_cli("allow") => {
+                codes.into_iter().map(Spanned::into_inner).collect()
+            }
+            _ => matches
+                .values_of("allow")
+                .map(|codes| codes.map(str::to_owned).collect())
+                .unwrap_or_default(),
+        },
+        against: matches.value_of("against").map(str::to_owned),
+        substitutions: matches
+            .values_of("sub")
+            .map(|subs| subs.map(str::to_owned).collect())
+            .unwrap_or_default(),
+        quiet: matches.is_present("quiet"),
+        pager: matches.value_of("pager").map(str::to_owned),
+        no_pager: matches.is_present("no-pager"),
+        config,
     })
 }
 
@@ -164,9 +500,18 @@ struct SlideResultBuilder<'a> {
     org_program: &'a str,
     /// Program source code sanitized for diagnostic emission.
     sanitized_program: String,
+    /// Line index of the sanitized program, used to position diagnostics.
+    source_index: SourceIndex,
     emit_format: EmitFormat,
     emit_config: EmitConfig,
+    /// Width beyond which pretty emit is broken into multiple lines, if any.
+    max_output_width: Option<usize>,
     color: bool,
+    /// Serialized diagnostics, when diagnostics are emitted as JSON. They are written to stderr
+    /// when the result is built.
+    json_diagnostics: Option<Vec<serde_json::Value>>,
+    /// Codes of non-error diagnostics that are dropped rather than emitted.
+    allowed_diagnostics: &'a [String],
     stdout: String,
     stderr: String,
     page: bool,
@@ -179,14 +524,22 @@ impl<'a> SlideResultBuilder<'a> {
         emit_format: impl Into<EmitFormat>,
         emit_config: impl Into<EmitConfig>,
         color: bool,
+        json_diagnostics: bool,
+        allowed_diagnostics: &'a [String],
     ) -> Self {
+        let sanitized_program = sanitize_source_for_diagnostics(program);
+        let source_index = SourceIndex::new(&sanitized_program);
         Self {
             file,
             org_program: program,
-            sanitized_program: sanitize_source_for_diagnostics(program),
+            sanitized_program,
+            source_index,
             emit_format: emit_format.into(),
             emit_config: emit_config.into(),
+            max_output_width: None,
             color,
+            json_diagnostics: if json_diagnostics { Some(vec![]) } else { None },
+            allowed_diagnostics,
             page: false,
             stdout: String::new(),
             stderr: String::new(),
@@ -194,17 +547,42 @@ impl<'a> SlideResultBuilder<'a> {
     }
 
     fn emit(&mut self, obj: &dyn Emit) {
-        self.stdout
-            .push_str(&obj.emit(self.emit_format, self.emit_config));
+        let emit = match (self.emit_format, self.max_output_width) {
+            (EmitFormat::Pretty, Some(width)) => obj.emit_pretty_wrapped(self.emit_config, width),
+            (emit_format, _) => obj.emit(emit_format, self.emit_config),
+        };
+        self.stdout.push_str(&emit);
     }
 
     fn err(&mut self, diagnostics: &[Diagnostic]) {
-        self.stderr.push_str(&emit_slide_diagnostics(
+        let allowed_diagnostics = self.allowed_diagnostics;
+        let diagnostics: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| {
+                d.kind == DiagnosticKind::Error
+                    || !allowed_diagnostics.iter().any(|code| code == d.code)
+            })
+            .collect();
+
+        if let Some(json_diagnostics) = &mut self.json_diagnostics {
+            json_diagnostics.extend(serialize_slide_diagnostics(
+                &self.source_index,
+                &diagnostics,
+            ));
+            return;
+        }
+        let emitted = emit_slide_diagnostics(
             self.file,
             &self.sanitized_program,
-            diagnostics,
+            &self.source_index,
+            &diagnostics,
             self.color,
-        ));
+        );
+        // Separate diagnostics of different passes, like scanning and parsing, by a blank line.
+        if !self.stderr.is_empty() && !emitted.is_empty() {
+            self.stderr.push('\n');
+        }
+        self.stderr.push_str(&emitted);
     }
 
     fn page(&mut self, page: bool) {
@@ -212,33 +590,73 @@ impl<'a> SlideResultBuilder<'a> {
     }
 
     fn ok(self) -> SlideResult {
-        SlideResult {
-            code: 0,
-            stdout: self.stdout,
-            stderr: self.stderr,
-            page: self.page,
-        }
+        self.build(0)
     }
 
     fn failed(self) -> SlideResult {
+        self.build(1)
+    }
+
+    fn build(self, code: i32) -> SlideResult {
+        let stderr = match self.json_diagnostics {
+            Some(json_diagnostics) if !json_diagnostics.is_empty() => {
+                let json = serde_json::to_string_pretty(&json_diagnostics).unwrap();
+                if self.stderr.is_empty() {
+                    json
+                } else {
+                    format!("{}\n{}", json, self.stderr)
+                }
+            }
+            _ => self.stderr,
+        };
         SlideResult {
-            code: 1,
+            code,
             stdout: self.stdout,
-            stderr: self.stderr,
+            stderr,
             page: self.page,
         }
     }
 }
 
 /// Runs slide end-to-end.
-pub fn run_slide(opts: Opts) -> SlideResult {
+pub fn run_slide(mut opts: Opts) -> SlideResult {
+    if opts.csv && opts.explain_diagnostic.is_none() {
+        return run_slide_csv(opts);
+    }
+    if opts.latex_input && opts.explain_diagnostic.is_none() {
+        opts.program = translate_latex(&opts.program);
+    }
+    let against = match &opts.against {
+        Some(against) if opts.expr_pat && opts.explain_diagnostic.is_none() => {
+            match parse_against(&opts, against) {
+                Ok(against) => Some(against),
+                Err(result) => return result,
+            }
+        }
+        _ => None,
+    };
+    let substitutions = match opts.explain_diagnostic {
+        None => match parse_substitutions(&opts) {
+            Ok(substitutions) => substitutions,
+            Err(result) => return result,
+        },
+        Some(_) => Vec::new(),
+    };
+
+    // The context is loaded before emit options are moved out of `opts`, but an invalid config
+    // file is only reported after diagnostic explanations, which do not need it.
+    let context = load_context(&opts);
+
     let mut result = SlideResultBuilder::new(
         None, // file: currently programs can only be read from stdin
         &opts.program,
         opts.emit_format,
         opts.emit_config,
         opts.color,
+        opts.json_diagnostics,
+        &opts.allowed_diagnostics,
     );
+    result.max_output_width = opts.max_output_width;
 
     if let Some(diag_code) = opts.explain_diagnostic {
         let codes = Diagnostic::all_codes_with_explanations();
@@ -257,16 +675,39 @@ pub fn run_slide(opts: Opts) -> SlideResult {
         };
     }
 
-    let ScanResult {
-        tokens,
-        diagnostics,
-    } = scan(&*opts.program);
-    result.err(&diagnostics);
-    if !diagnostics.is_empty() {
+    let context = match context {
+        Ok(context) => context,
+        Err(result) => return result,
+    };
+
+    let scanned = scan(&*opts.program);
+    result.err(&scanned.diagnostics);
+    // Programs the scanner recovered from are still parsed and linted, so that all of their
+    // problems are reported at once.
+    if !scanned.is_recovered() {
         return result.failed();
     }
+    let scan_failed = scanned
+        .diagnostics
+        .iter()
+        .any(|d| d.kind == DiagnosticKind::Error);
 
-    let evaluator = ProgramEvaluator::new(result, tokens, opts.lint, opts.parse_only);
+    let evaluation_opts = EvaluationOpts {
+        parse_only: opts.parse_only,
+        emit_normalized: opts.emit_normalized,
+        profile_rules: opts.profile_rules,
+        show_iterations: opts.show_iterations,
+        quiet: opts.quiet,
+    };
+    let evaluator = ProgramEvaluator::new(
+        result,
+        scanned.tokens,
+        scan_failed,
+        context,
+        evaluation_opts,
+        against,
+        substitutions,
+    );
 
     if opts.expr_pat {
         evaluator.eval_expr_pat()
@@ -275,26 +716,232 @@ pub fn run_slide(opts: Opts) -> SlideResult {
     }
 }
 
+/// Loads the [context](ProgramContext) a program is evaluated with from the options of the config
+/// file, if any. `--lint` enables lints, `--strict-integers` strict integers, and `--rule-set`
+/// selects the preset of rules regardless of the file. If the config file is invalid, the failed
+/// result of slide reporting why is returned instead.
+fn load_context(opts: &Opts) -> Result<ProgramContext, SlideResult> {
+    let builder = match &opts.config {
+        Some(config) => match config.options() {
+            Ok(options) => options.context.unwrap_or_default(),
+            Err(diagnostic) => {
+                let mut result = SlideResultBuilder::new(
+                    Some(&config.path),
+                    &config.source,
+                    EmitFormat::Pretty,
+                    EmitConfig::default(),
+                    opts.color,
+                    opts.json_diagnostics,
+                    &[],
+                );
+                result.err(&[diagnostic]);
+                return Err(result.failed());
+            }
+        },
+        None => ProgramContext::builder(),
+    };
+    let builder = if opts.lint {
+        builder.lint(true)
+    } else {
+        builder
+    };
+    let builder = if opts.strict_integers {
+        builder.strict_integers(true)
+    } else {
+        builder
+    };
+    let builder = match opts.rule_set {
+        Some(preset) => builder.rule_set(preset),
+        None => builder,
+    };
+    Ok(builder
+        .build()
+        .expect("context options are validated when the config file is read"))
+}
+
+/// Parses the expression an expression pattern is matched against. If the expression cannot be
+/// parsed, or is not a single expression, the failed result of slide is returned instead.
+fn parse_against(opts: &Opts, against: &str) -> Result<RcExpr, SlideResult> {
+    parse_option_stmt(
+        opts,
+        against,
+        |stmt| match stmt.kind {
+            StmtKind::Expr(expr) => Some(expr),
+            _ => None,
+        },
+        "a single expression to match against",
+    )
+}
+
+/// Parses the substitutions of variables applied to a simplified program, in order. If a
+/// substitution cannot be parsed, or is not a single assignment to a variable, the failed result of
+/// slide is returned instead.
+fn parse_substitutions(opts: &Opts) -> Result<Vec<Assignment>, SlideResult> {
+    opts.substitutions
+        .iter()
+        .map(|substitution| {
+            parse_option_stmt(
+                opts,
+                substitution,
+                |stmt| match stmt.kind {
+                    StmtKind::Assignment(asgn) if asgn.lhs.is_var() => Some(asgn),
+                    _ => None,
+                },
+                "a substitution of a variable, like `x=1`",
+            )
+        })
+        .collect()
+}
+
+/// Parses a program given as the value of an option, which should be a single statement accepted
+/// by `accept`. If the program cannot be parsed, or is not such a statement, the failed result of
+/// slide is returned instead, reporting that the program is not what is `expected`.
+fn parse_option_stmt<T>(
+    opts: &Opts,
+    program: &str,
+    accept: impl FnOnce(Stmt) -> Option<T>,
+    expected: &str,
+) -> Result<T, SlideResult> {
+    let mut result = SlideResultBuilder::new(
+        None,
+        program,
+        opts.emit_format.clone(),
+        opts.emit_config.clone(),
+        opts.color,
+        opts.json_diagnostics,
+        &opts.allowed_diagnostics,
+    );
+
+    let ScanResult {
+        tokens,
+        diagnostics,
+    } = scan(program);
+    result.err(&diagnostics);
+    if !diagnostics.is_empty() {
+        return Err(result.failed());
+    }
+
+    let ParseResult {
+        program: stmts,
+        diagnostics,
+    } = parse_statements(tokens, program);
+    result.err(&diagnostics);
+    if !diagnostics.is_empty() {
+        return Err(result.failed());
+    }
+
+    let mut stmts = stmts.into_iter();
+    match (stmts.next().and_then(accept), stmts.next()) {
+        (Some(stmt), None) => Ok(stmt),
+        _ => {
+            result
+                .stderr
+                .push_str(&format!("`{}` is not {}", program, expected));
+            Err(result.failed())
+        }
+    }
+}
+
+/// Runs each non-empty line of a program as its own slide program, emitting a CSV with a row of
+/// the line and its result per line. Diagnostics of all lines are emitted in order.
+fn run_slide_csv(opts: Opts) -> SlideResult {
+    // Report an invalid config file once, rather than for each line.
+    if let Err(result) = load_context(&opts) {
+        return result;
+    }
+    let mut csv = vec![csv_row(&["input", "output"])];
+    let mut stderr = String::new();
+    let mut code = 0;
+    let lines = opts
+        .program
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty());
+    for line in lines {
+        let result = run_slide(Opts {
+            program: line.to_owned(),
+            emit_format: opts.emit_format.clone(),
+            emit_config: opts.emit_config.clone(),
+            allowed_diagnostics: opts.allowed_diagnostics.clone(),
+            against: opts.against.clone(),
+            substitutions: opts.substitutions.clone(),
+            pager: opts.pager.clone(),
+            config: opts.config.clone(),
+            explain_diagnostic: None,
+            csv: false,
+            ..opts
+        });
+        csv.push(csv_row(&[line, &result.stdout]));
+        stderr.push_str(&result.stderr);
+        code = code.max(result.code);
+    }
+
+    SlideResult {
+        code,
+        stdout: csv.join("\n"),
+        stderr,
+        page: false,
+    }
+}
+
+/// Formats a CSV row of fields, quoting fields that contain separators, quotes, or newlines.
+fn csv_row(fields: &[&str]) -> String {
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            if field.contains(&[',', '"', '\n', '\r'][..]) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect();
+    fields.join(",")
+}
+
+/// Options of [Opts](self::Opts) deciding how a [ProgramEvaluator](self::ProgramEvaluator)
+/// evaluates a program.
+struct EvaluationOpts {
+    parse_only: bool,
+    emit_normalized: bool,
+    profile_rules: bool,
+    show_iterations: bool,
+    quiet: bool,
+}
+
 /// Evaluates a slide program either as a regular program or an expression pattern.
 struct ProgramEvaluator<'a> {
     result: SlideResultBuilder<'a>,
     tokens: Vec<Token>,
-    lint: bool,
-    parse_only: bool,
+    /// Whether scanning the program reported errors. The program is still parsed and linted, but
+    /// not evaluated.
+    scan_failed: bool,
+    context: ProgramContext,
+    opts: EvaluationOpts,
+    /// The expression an expression pattern is matched against, if any.
+    against: Option<RcExpr>,
+    /// Substitutions of variables applied in order to the simplified program.
+    substitutions: Vec<Assignment>,
 }
 
 impl<'a> ProgramEvaluator<'a> {
     fn new(
         result: SlideResultBuilder<'a>,
         tokens: Vec<Token>,
-        lint: bool,
-        parse_only: bool,
+        scan_failed: bool,
+        context: ProgramContext,
+        opts: EvaluationOpts,
+        against: Option<RcExpr>,
+        substitutions: Vec<Assignment>,
     ) -> Self {
         Self {
             result,
             tokens,
-            lint,
-            parse_only,
+            scan_failed,
+            context,
+            opts,
+            against,
+            substitutions,
         }
     }
 
@@ -303,41 +950,110 @@ impl<'a> ProgramEvaluator<'a> {
         let ParseResult {
             program,
             diagnostics,
-        } = parse_statements(self.tokens, &self.result.org_program);
+        } = parse_statements(std::mem::take(&mut self.tokens), &self.result.org_program);
 
         self.result.err(&diagnostics);
         if !diagnostics.is_empty() {
             return self.result.failed();
         }
 
-        let program_context = ProgramContext::default().lint(self.lint);
-        if self.lint {
+        if self.context.lints() {
             self.result
                 .err(&lint_stmt(&program, self.result.org_program));
         }
+        if self.scan_failed {
+            return self.result.failed();
+        }
 
-        if self.parse_only {
+        if self.opts.parse_only {
             self.result.emit(&program);
 
+            self.result.ok()
+        } else if self.opts.emit_normalized {
+            self.result.emit(&normalize_stmts(program, &self.context));
+
             self.result.ok()
         } else {
-            let EvaluationResult {
-                simplified,
-                diagnostics,
-            } = evaluate(program, &program_context).unwrap();
+            let (
+                EvaluationResult {
+                    simplified,
+                    diagnostics,
+                    fired_rules,
+                    iterations,
+                },
+                profile,
+            ) = if self.opts.profile_rules {
+                let (result, profile) = evaluate_profiled(program, &self.context).unwrap();
+                (result, Some(profile))
+            } else {
+                (evaluate(program, &self.context).unwrap(), None)
+            };
             let fatal = diagnostics.iter().any(|d| d.kind == DiagnosticKind::Error);
 
             self.result.err(&diagnostics);
+            let mut failed = !diagnostics.is_empty();
             if !fatal {
-                self.result.emit(&simplified);
+                let emit_simplified = self.substitutions.is_empty() || !self.opts.quiet;
+                if emit_simplified {
+                    self.result.emit(&simplified);
+                    // The debug form also describes how the program was simplified.
+                    if let EmitFormat::Debug = self.result.emit_format {
+                        let fired_rules = format!("\nfired_rules: {:?}", fired_rules);
+                        self.result.stdout.push_str(&fired_rules);
+                    }
+                }
+                if !self.substitutions.is_empty() {
+                    failed |= self.eval_substitutions(&simplified, emit_simplified);
+                }
+            }
+            if let Some(profile) = profile {
+                self.result.stderr.push_str(&profile.to_string());
+            }
+            if self.opts.show_iterations {
+                let iterations = format!("iterations: {}\n", iterations);
+                self.result.stderr.push_str(&iterations);
             }
 
-            if diagnostics.is_empty() {
-                self.result.ok()
-            } else {
+            if failed {
                 self.result.failed()
+            } else {
+                self.result.ok()
+            }
+        }
+    }
+
+    /// Applies the substitutions to a simplified program in order and simplifies the program
+    /// again, emitting it on a new line if the simplified program was emitted. Substitutions of
+    /// variables that do not appear in the program are warned about. Returns whether simplifying
+    /// the substituted program reported diagnostics.
+    fn eval_substitutions(&mut self, simplified: &StmtList, after_simplified: bool) -> bool {
+        let mut substituted = simplified.clone();
+        for asgn in &self.substitutions {
+            match substitute(&substituted, asgn) {
+                Some(program) => substituted = program,
+                None => {
+                    let warning = format!(
+                        "warning: `{}` does not appear in the simplified program, so it is not substituted\n",
+                        asgn.lhs
+                    );
+                    self.result.stderr.push_str(&warning);
+                }
+            }
+        }
+
+        let EvaluationResult {
+            simplified,
+            diagnostics,
+            ..
+        } = evaluate(substituted, &self.context).unwrap();
+        self.result.err(&diagnostics);
+        if !diagnostics.iter().any(|d| d.kind == DiagnosticKind::Error) {
+            if after_simplified {
+                self.result.stdout.push('\n');
             }
+            self.result.emit(&simplified);
         }
+        !diagnostics.is_empty()
     }
 
     /// Handles evaluation of a slide expression pattern.
@@ -351,12 +1067,40 @@ impl<'a> ProgramEvaluator<'a> {
             return self.result.failed();
         }
 
-        if self.lint {
+        if self.context.lints() {
             self.result
                 .err(&lint_expr_pat(&program, self.result.org_program));
         }
+        if self.scan_failed {
+            return self.result.failed();
+        }
+
+        if let Some(against) = self.against {
+            return match match_pattern(program, against) {
+                Some(bindings) => {
+                    let (format, config) = (self.result.emit_format, self.result.emit_config);
+                    let bindings: Vec<_> = bindings
+                        .into_iter()
+                        .map(|(pat, expr)| match format {
+                            // Pattern names start with characters special to LaTeX, so they are
+                            // escaped and emitted in the same math as what they bind to.
+                            EmitFormat::Latex => {
+                                format!("$\\{} \\to {}$", pat, expr.emit_latex(config))
+                            }
+                            _ => format!("{} -> {}", pat, expr.emit(format, config)),
+                        })
+                        .collect();
+                    self.result.stdout.push_str(&bindings.join("\n"));
+                    self.result.ok()
+                }
+                None => {
+                    self.result.stdout.push_str("no match");
+                    self.result.failed()
+                }
+            };
+        }
 
-        if self.parse_only {
+        if self.opts.parse_only {
             self.result.emit(&program);
         } else {
             panic!("Expression patterns can only be parsed.");
@@ -366,12 +1110,287 @@ impl<'a> ProgramEvaluator<'a> {
     }
 }
 
+/// Note emitted when slide encounters an internal slide error (ISE; it's like an ICE, but for
+/// slide).
+pub const ISE_NOTE: &str = "\nnote: you found an internal slide error (ISE; it's like an ICE, but for slide)!\n\
+                            \nnote: we would appreciate a bug report: https://github.com/yslide/slide\n";
+
+/// Output of a slide execution through an entry point that may receive malformed input, like the
+/// [wasm entry point](self::run_slide_wasm).
+#[cfg_attr(feature = "wasm", derive(Serialize, Deserialize))]
+pub struct GuardedSlideResult {
+    /// Output of the slide execution.
+    #[cfg_attr(feature = "wasm", serde(flatten))]
+    pub result: SlideResult,
+    /// Why slide could not be run to completion, if it could not; for example, if the given
+    /// options were malformed or slide encountered an internal error.
+    pub error: Option<String>,
+}
+
+thread_local! {
+    /// Message of the internal slide error encountered by the current guarded run, if any.
+    static ISE: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Starts recording internal slide errors of a guarded run.
+/// The first call installs a panic hook that records the panic message as the run's internal
+/// slide error, before deferring to the previously-installed panic hook.
+fn record_ise() {
+    static INSTALL_HOOK: std::sync::Once = std::sync::Once::new();
+    INSTALL_HOOK.call_once(|| {
+        let prev_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let payload = info.payload();
+            let msg = match payload.downcast_ref::<&str>() {
+                Some(msg) => msg.to_string(),
+                None => match payload.downcast_ref::<String>() {
+                    Some(msg) => msg.clone(),
+                    None => "unknown error".to_owned(),
+                },
+            };
+            ISE.with(|ise| *ise.borrow_mut() = Some(msg));
+            prev_hook(info);
+        }));
+    });
+    ISE.with(|ise| *ise.borrow_mut() = None);
+}
+
+/// Takes the internal slide error recorded during the last guarded run, if there was one.
+fn take_ise() -> Option<GuardedSlideResult> {
+    let msg = ISE.with(|ise| ise.borrow_mut().take())?;
+    Some(GuardedSlideResult {
+        result: SlideResult {
+            code: 2,
+            stderr: ISE_NOTE.to_owned(),
+            ..SlideResult::default()
+        },
+        error: Some(format!("internal slide error: {}", msg)),
+    })
+}
+
+/// Runs slide end-to-end given options that may have failed to be constructed, catching any
+/// internal slide errors rather than panicking.
+///
+/// Internal errors are caught on targets that unwind on panic. Targets built with `panic=abort`,
+/// like wasm, still abort on an internal error, but the error is recorded before the abort; see
+/// [run_slide_wasm](self::run_slide_wasm).
+pub fn run_slide_guarded(opts: Result<Opts, String>) -> GuardedSlideResult {
+    guard(opts, run_slide)
+}
+
+/// Runs `run` with `opts`, reporting malformed options and panics in `run` as a
+/// [GuardedSlideResult](self::GuardedSlideResult).
+fn guard<F>(opts: Result<Opts, String>, run: F) -> GuardedSlideResult
+where
+    F: FnOnce(Opts) -> SlideResult + std::panic::UnwindSafe,
+{
+    record_ise();
+    let opts = match opts {
+        Ok(opts) => opts,
+        Err(why) => {
+            return GuardedSlideResult {
+                result: SlideResult {
+                    code: 1,
+                    stderr: format!("Invalid slide options: {}", why),
+                    ..SlideResult::default()
+                },
+                error: Some(why),
+            }
+        }
+    };
+
+    match std::panic::catch_unwind(move || run(opts)) {
+        Ok(result) => GuardedSlideResult {
+            result,
+            error: None,
+        },
+        Err(_) => take_ise().expect("panics are recorded by the ISE hook"),
+    }
+}
+
 /// Runs slide through a wasm entry point.
 /// `opts` must be a JS object with the same fields as [Opts](self::Opts).
-/// Returns a JS object with the same fields as [SlideResult](self::SlideResult).
+/// Returns a JS object with the same fields as [GuardedSlideResult](self::GuardedSlideResult);
+/// if `opts` is malformed, the `error` field is set.
+///
+/// wasm builds with `panic=abort`, so an internal slide error makes this function throw rather
+/// than return. The error is recorded before then, and can be retrieved with
+/// [take_ise_wasm](self::take_ise_wasm).
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
 pub fn run_slide_wasm(opts: JsValue) -> JsValue {
-    let opts: Opts = opts.into_serde().unwrap();
-    JsValue::from_serde(&run_slide(opts)).unwrap()
+    let opts: Result<Opts, String> = opts.into_serde().map_err(|e| e.to_string());
+    JsValue::from_serde(&run_slide_guarded(opts)).unwrap()
+}
+
+/// Retrieves the internal slide error that made the last call to
+/// [run_slide_wasm](self::run_slide_wasm) throw.
+/// Returns a JS object with the same fields as [GuardedSlideResult](self::GuardedSlideResult), or
+/// `null` if the last call did not encounter an internal error.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn take_ise_wasm() -> JsValue {
+    JsValue::from_serde(&take_ise()).unwrap()
+}
+
+/// Tokenizes a slide program through a wasm entry point, for highlighting the program.
+/// Returns a JS array of objects with the same fields as
+/// [SpannedTokenInfo](libslide::SpannedTokenInfo), covering the whole program.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn scan_wasm(program: &str) -> JsValue {
+    JsValue::from_serde(&libslide::tokenize(program)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_opts, guard, record_ise, run_slide, run_slide_guarded, take_ise, GuardedSlideResult,
+        Opts, ISE_NOTE,
+    };
+
+    /// Gets the options slide is run with from command-line `args`, which don't include the
+    /// binary name or program.
+    fn opts(args: &[&str], program: &str, supports_color: bool) -> Opts {
+        let args = [&["slide"], args, &[program]].concat();
+        get_opts(|app| app.get_matches_from_safe(args), supports_color).unwrap()
+    }
+
+    mod run_slide_guarded {
+        use super::*;
+
+        #[test]
+        fn ok() {
+            let GuardedSlideResult { result, error } =
+                run_slide_guarded(Ok(opts(&[], "1 + 2", false)));
+            assert_eq!(result.code, 0);
+            assert_eq!(result.stdout, "3");
+            assert_eq!(error, None);
+        }
+
+        #[test]
+        fn invalid_opts() {
+            let GuardedSlideResult { result, error } =
+                run_slide_guarded(Err("missing field `program`".to_owned()));
+            assert_eq!(result.code, 1);
+            assert_eq!(result.stdout, "");
+            assert_eq!(
+                result.stderr,
+                "Invalid slide options: missing field `program`"
+            );
+            assert_eq!(error, Some("missing field `program`".to_owned()));
+        }
+
+        #[test]
+        fn ise() {
+            let GuardedSlideResult { result, error } =
+                guard(Ok(opts(&[], "1 + 2", false)), |_| panic!("internal error"));
+            assert_eq!(result.code, 2);
+            assert_eq!(result.stderr, ISE_NOTE);
+            assert_eq!(
+                error,
+                Some("internal slide error: internal error".to_owned())
+            );
+            assert!(take_ise().is_none());
+        }
+
+        #[test]
+        fn ise_recorded_without_unwinding() {
+            // Targets with `panic=abort` never return from a panicking run, so the ISE must be
+            // recorded by the time the panic starts.
+            record_ise();
+            let _ = std::panic::catch_unwind(|| panic!("internal error {}", 1));
+
+            let GuardedSlideResult { result, error } = take_ise().unwrap();
+            assert_eq!(result.code, 2);
+            assert_eq!(result.stderr, ISE_NOTE);
+            assert_eq!(
+                error,
+                Some("internal slide error: internal error 1".to_owned())
+            );
+            assert!(take_ise().is_none());
+        }
+
+        #[test]
+        fn no_ise() {
+            run_slide_guarded(Ok(opts(&[], "1 + 2", false)));
+            assert!(take_ise().is_none());
+        }
+    }
+
+    mod color {
+        use super::*;
+
+        fn is_colored(color: &str, supports_color: bool) -> bool {
+            let opts = opts(&["--color", color], "1 +", supports_color);
+            run_slide(opts).stderr.contains('\u{1b}')
+        }
+
+        #[test]
+        fn always() {
+            assert!(is_colored("always", false));
+            assert!(is_colored("always", true));
+        }
+
+        #[test]
+        fn never() {
+            assert!(!is_colored("never", false));
+            assert!(!is_colored("never", true));
+        }
+
+        #[test]
+        fn auto() {
+            assert!(!is_colored("auto", false));
+            assert!(is_colored("auto", true));
+        }
+
+        #[test]
+        fn defaults_to_auto() {
+            assert!(!opts(&[], "1 +", false).color);
+            assert!(opts(&[], "1 +", true).color);
+        }
+    }
+
+    mod pager {
+        use super::*;
+        use std::ffi::OsString;
+
+        fn pager_command(args: &[&str]) -> Option<OsString> {
+            opts(args, "1 + 2", false).pager_command(Some(OsString::from("more")))
+        }
+
+        #[test]
+        fn defaults_to_env_pager() {
+            assert_eq!(pager_command(&[]), Some(OsString::from("more")));
+        }
+
+        #[test]
+        fn falls_back_without_env_pager() {
+            assert!(opts(&[], "1 + 2", false).pager_command(None).is_some());
+        }
+
+        #[test]
+        fn pager() {
+            assert_eq!(
+                pager_command(&["--pager=most"]),
+                Some(OsString::from("most"))
+            );
+        }
+
+        #[test]
+        fn no_pager() {
+            assert_eq!(pager_command(&["--no-pager"]), None);
+        }
+    }
+
+    #[test]
+    fn discovers_config_in_ancestor() {
+        use super::ConfigFile;
+
+        let root = std::env::temp_dir().join("slide-config-test-discover");
+        let nested = root.join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join("slide.toml"), "lint = true").unwrap();
+        assert_eq!(ConfigFile::discover(&nested), Some(root.join("slide.toml")));
+    }
 }
@@ -1,4 +1,4 @@
-use slide::{get_opts, run_slide, SlideResult};
+use slide::{get_opts, run_slide, SlideResult, ISE_NOTE};
 use std::env;
 use std::ffi::OsString;
 use std::io::Write;
@@ -9,9 +9,10 @@ fn main_impl() -> Result<(), Box<dyn std::error::Error>> {
     let mut ch_stdout = BufferedStandardStream::stdout(ColorChoice::Auto);
     let mut ch_stderr = BufferedStandardStream::stderr(ColorChoice::Auto);
     let is_tty = atty::is(atty::Stream::Stderr);
-    let use_color = is_tty && ch_stderr.supports_color();
+    let supports_color = is_tty && ch_stderr.supports_color();
 
-    let opts = get_opts(|args| Ok(args.get_matches()), use_color).unwrap();
+    let opts = get_opts(|args| Ok(args.get_matches()), supports_color).unwrap_or_else(|e| e.exit());
+    let pager = opts.pager_command(env::var_os("PAGER"));
     let SlideResult {
         code,
         stdout,
@@ -24,24 +25,24 @@ fn main_impl() -> Result<(), Box<dyn std::error::Error>> {
         ch_stderr.flush()?;
     }
     if !stdout.is_empty() {
-        print_stdout(&stdout, &mut ch_stdout, page)?;
+        print_stdout(&stdout, &mut ch_stdout, pager.filter(|_| page))?;
     }
 
     std::process::exit(code)
 }
 
+/// Prints stdout with `pager`, or directly if there is no pager.
+///
 /// Basically just copied from rust/src/librustc_driver/lib.rs#show_content_with_pager
 fn print_stdout(
     stdout: &str,
     mut ch_stdout: &mut BufferedStandardStream,
-    page: bool,
+    pager: Option<OsString>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut fallback_to_println = false;
+    let page = pager.is_some();
 
-    if page {
-        let pager_name = env::var_os("PAGER")
-            .unwrap_or_else(|| OsString::from(if cfg!(windows) { "more.com" } else { "less" }));
-
+    if let Some(pager_name) = pager {
         match Command::new(pager_name).stdin(Stdio::piped()).spawn() {
             Ok(mut pager) => {
                 if let Some(pipe) = pager.stdin.as_mut() {
@@ -73,10 +74,7 @@ fn main() {
     let out = std::panic::catch_unwind(main_impl);
 
     if let Err(..) = out {
-        eprint!(
-            "\nnote: you found an internal slide error (ISE; it's like an ICE, but for slide)!\n"
-        );
-        eprint!("\nnote: we would appreciate a bug report: https://github.com/yslide/slide\n");
+        eprint!("{}", ISE_NOTE);
         std::process::exit(2);
     }
 }
@@ -0,0 +1,328 @@
+//! Module `session` describes a persistent slide session, in which many programs can be evaluated
+//! with a shared context and environment of definitions.
+
+use crate::{SlideResult, SlideResultBuilder};
+
+use core::convert::TryFrom;
+use libslide::diagnostics::DiagnosticKind;
+use libslide::evaluator_rules::{Rule, RuleSetPreset};
+use libslide::{
+    build_rules, evaluate_with_rules, expand_variables, lint_stmt, parse_statements, scan,
+    Assignment, EmitConfig, EmitFormat, EvaluationResult, ParseResult, ProgramContext, StmtKind,
+    StmtList,
+};
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// A slide session, evaluating programs one after another.
+///
+/// Unlike [`run_slide`](crate::run_slide), a session builds its evaluation rules exactly once, and
+/// remembers the definitions made by every program it evaluates. Later programs in the session
+/// have variables defined by earlier programs expanded to their (simplified) definitions, so
+///
+/// ```text
+/// > a = 1 + 2
+/// a = 3
+/// > a * 2
+/// 6
+/// ```
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct SlideSession {
+    emit_format: EmitFormat,
+    emit_config: EmitConfig,
+    lint: bool,
+    color: bool,
+    json_diagnostics: bool,
+    context: ProgramContext,
+    /// Evaluation rules, built once from the session's [context](Self::context).
+    rules: Vec<Rule>,
+    /// Definitions made by programs evaluated in the session, in the order they were made.
+    bindings: Vec<Assignment>,
+}
+
+impl Default for SlideSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl SlideSession {
+    /// Creates a fresh session with default options and no definitions.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        let context = ProgramContext::default();
+        Self {
+            emit_format: EmitFormat::Pretty,
+            emit_config: EmitConfig::default(),
+            lint: false,
+            color: false,
+            json_diagnostics: false,
+            // The default rule set always builds.
+            rules: build_rules(&context).unwrap(),
+            context,
+            bindings: vec![],
+        }
+    }
+
+    /// Forgets all definitions made in the session. Session options are preserved.
+    pub fn reset(&mut self) {
+        self.bindings.clear();
+    }
+}
+
+impl SlideSession {
+    /// Sets a session option, returning an error if the option or its value is unknown.
+    ///
+    /// Options have the same names as the fields of [`Opts`](crate::Opts):
+    ///
+    /// | option | value |
+    /// | -- | -- |
+    /// | `emit_format` | one of `pretty`, `s-expression`, `latex`, `debug`, `json` |
+    /// | `emit_config` | a comma-separated list of emit configuration options, like `frac,times` |
+    /// | `lint` | `true` or `false` |
+    /// | `strict_integers` | `true` or `false` |
+    /// | `rule_set` | `basic` or `full` |
+    /// | `color` | `true` or `false` |
+    /// | `json_diagnostics` | `true` or `false` |
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let as_bool = || match value {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(format!("`{}` expects a boolean, found `{}`", name, value)),
+        };
+        match name {
+            "emit_format" => {
+                self.emit_format = EmitFormat::try_from(value)
+                    .map_err(|_| format!("`{}` is not an emit format", value))?;
+            }
+            "emit_config" => {
+                self.emit_config = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|opt| !opt.is_empty())
+                    .map(|opt| {
+                        EmitConfig::try_from(opt)
+                            .map_err(|_| format!("`{}` is not an emit configuration option", opt))
+                    })
+                    .collect::<Result<EmitConfig, _>>()?;
+            }
+            "lint" => {
+                self.lint = as_bool()?;
+                self.context = std::mem::take(&mut self.context).lint(self.lint);
+            }
+            "strict_integers" => {
+                let strict_integers = as_bool()?;
+                self.context = std::mem::take(&mut self.context).strict_integers(strict_integers);
+            }
+            "rule_set" => {
+                let preset = match value {
+                    "basic" => RuleSetPreset::Basic,
+                    "full" => RuleSetPreset::Full,
+                    _ => return Err(format!("`{}` is not a rule set preset", value)),
+                };
+                self.context = std::mem::take(&mut self.context).rule_set(preset);
+                // A preset only selects among the built-in rules, which always build.
+                self.rules = build_rules(&self.context).unwrap();
+            }
+            "color" => self.color = as_bool()?,
+            "json_diagnostics" => self.json_diagnostics = as_bool()?,
+            _ => return Err(format!("`{}` is not a session option", name)),
+        }
+        Ok(())
+    }
+
+    /// Evaluates a slide program in the session. Variables defined by programs previously
+    /// evaluated in the session are expanded in the program, and definitions made by the program
+    /// are remembered for later evaluations.
+    pub fn eval(&mut self, program: &str) -> SlideResult {
+        let mut result = SlideResultBuilder::new(
+            None,
+            program,
+            self.emit_format,
+            self.emit_config,
+            self.color,
+            self.json_diagnostics,
+            &[],
+        );
+
+        let scanned = scan(program);
+        result.err(&scanned.diagnostics);
+        if !scanned.is_recovered() {
+            return result.failed();
+        }
+        let scan_failed = scanned
+            .diagnostics
+            .iter()
+            .any(|d| d.kind == DiagnosticKind::Error);
+
+        let ParseResult {
+            program: stmts,
+            diagnostics,
+        } = parse_statements(scanned.tokens, program);
+        result.err(&diagnostics);
+        if !diagnostics.is_empty() {
+            return result.failed();
+        }
+
+        if self.lint {
+            result.err(&lint_stmt(&stmts, program));
+        }
+        if scan_failed {
+            return result.failed();
+        }
+
+        let bindings = &self.bindings;
+        let expand = |expr| expand_variables(expr, bindings);
+        let stmts: StmtList = stmts
+            .into_iter()
+            .map(|stmt| stmt.update_with(expand, |asgn| asgn.redefine_with(expand)))
+            .collect();
+
+        let EvaluationResult {
+            simplified,
+            diagnostics,
+            ..
+        } = evaluate_with_rules(stmts, &self.rules, &self.context);
+        let fatal = diagnostics.iter().any(|d| d.kind == DiagnosticKind::Error);
+
+        result.err(&diagnostics);
+        if !fatal {
+            result.emit(&simplified);
+            self.bindings
+                .extend(simplified.into_iter().filter_map(|stmt| match stmt.kind {
+                    StmtKind::Assignment(asgn) if asgn.lhs.is_var() => Some(asgn),
+                    _ => None,
+                }));
+        }
+
+        if diagnostics.is_empty() {
+            result.ok()
+        } else {
+            result.failed()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl SlideSession {
+    /// Sets a session option; see [`set_option`](Self::set_option).
+    /// `value` may be a JS string, boolean, or array of strings.
+    #[wasm_bindgen(js_name = set_option)]
+    pub fn set_option_wasm(&mut self, name: &str, value: JsValue) -> Result<(), JsValue> {
+        let value = if let Some(b) = value.as_bool() {
+            b.to_string()
+        } else if let Some(s) = value.as_string() {
+            s
+        } else {
+            value
+                .into_serde::<Vec<String>>()
+                .map_err(|e| JsValue::from_str(&e.to_string()))?
+                .join(",")
+        };
+        self.set_option(name, &value)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Evaluates a slide program in the session; see [`eval`](Self::eval).
+    /// Returns a JS object with the same fields as [SlideResult](crate::SlideResult).
+    #[wasm_bindgen(js_name = eval)]
+    pub fn eval_wasm(&mut self, program: &str) -> JsValue {
+        JsValue::from_serde(&self.eval(program)).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SlideSession;
+
+    fn eval(session: &mut SlideSession, program: &str) -> (i32, String) {
+        let result = session.eval(program);
+        (result.code, result.stdout)
+    }
+
+    #[test]
+    fn uses_previous_definitions() {
+        let mut session = SlideSession::new();
+
+        assert_eq!(eval(&mut session, "a = 1 + 2"), (0, "a = 3".to_owned()));
+        assert_eq!(eval(&mut session, "a * 2"), (0, "6".to_owned()));
+        assert_eq!(eval(&mut session, "b = a + c"), (0, "b = c + 3".to_owned()));
+        assert_eq!(eval(&mut session, "b - c"), (0, "3".to_owned()));
+    }
+
+    #[test]
+    fn chained_definitions() {
+        let mut session = SlideSession::new();
+
+        assert_eq!(
+            eval(&mut session, "a = b = c"),
+            (0, "a = c\nb = c".to_owned())
+        );
+        assert_eq!(eval(&mut session, "a - b"), (0, "0".to_owned()));
+    }
+
+    #[test]
+    fn later_definitions_shadow() {
+        let mut session = SlideSession::new();
+
+        eval(&mut session, "a = 1");
+        eval(&mut session, "a = 10");
+        assert_eq!(eval(&mut session, "a + 1"), (0, "11".to_owned()));
+    }
+
+    #[test]
+    fn reset() {
+        let mut session = SlideSession::new();
+
+        eval(&mut session, "a = 1");
+        session.reset();
+        assert_eq!(eval(&mut session, "a + 1"), (0, "a + 1".to_owned()));
+    }
+
+    #[test]
+    fn set_option() {
+        let mut session = SlideSession::new();
+
+        assert!(session.set_option("emit_format", "s-expression").is_ok());
+        assert_eq!(eval(&mut session, "a + 1 + 2"), (0, "(+ a 3)".to_owned()));
+
+        assert!(session.set_option("emit_format", "latex").is_ok());
+        assert!(session.set_option("emit_config", "frac, times").is_ok());
+        assert!(session.set_option("lint", "true").is_ok());
+
+        assert!(session.set_option("emit_format", "binary").is_err());
+        assert!(session.set_option("emit_config", "frac,bad").is_err());
+        assert!(session.set_option("lint", "yes").is_err());
+        assert!(session.set_option("bad_option", "").is_err());
+    }
+
+    #[test]
+    fn strict_integers() {
+        let mut session = SlideSession::new();
+
+        assert_eq!(eval(&mut session, "1 / 2"), (0, "0.5".to_owned()));
+        assert!(session.set_option("strict_integers", "true").is_ok());
+        assert_eq!(eval(&mut session, "4 / 2"), (0, "2".to_owned()));
+        assert_eq!(eval(&mut session, "1 / 2"), (1, "".to_owned()));
+    }
+
+    #[test]
+    fn rule_set() {
+        let mut session = SlideSession::new();
+
+        assert_eq!(
+            eval(&mut session, "(x^2 - 1) / (x + 1)"),
+            (0, "x + -1".to_owned())
+        );
+        assert!(session.set_option("rule_set", "basic").is_ok());
+        assert_eq!(
+            eval(&mut session, "(x^2 - 1) / (x + 1)"),
+            (0, "(-1 + x ^ 2) * 1 / (x + 1)".to_owned())
+        );
+        assert_eq!(eval(&mut session, "2 * 3 + x * 1"), (0, "x + 6".to_owned()));
+        assert!(session.set_option("rule_set", "minimal").is_err());
+    }
+}
@@ -1,6 +1,8 @@
 use lazy_static::lazy_static;
 use libtest_mimic::LinePrinter;
+use serde_json::Value;
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::RwLock;
 
 pub type ErrorMsgRef = usize;
@@ -54,3 +56,128 @@ macro_rules! real_path {
         format!("slide/{}", $path)
     };
 }
+
+/// Marks the start of an inline diagnostic annotation in the input of a system test.
+const ANNOTATION_MARKER: &str = "//~";
+
+/// A diagnostic expected by an inline annotation in the input of a system test, like
+/// `1 + @ 2  //~ ERROR S0001: invalid token`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiagnosticAnnotation {
+    /// The 1-indexed line of the input the diagnostic is expected to start on.
+    pub line: usize,
+    /// The lowercase kind of the diagnostic, like "error".
+    pub kind: String,
+    /// The code of the diagnostic, like "S0001".
+    pub code: String,
+    /// Text expected in the title or message of the diagnostic, ignoring case.
+    pub message: Option<String>,
+}
+
+impl DiagnosticAnnotation {
+    /// Whether a diagnostic emitted in the JSON diagnostic format is the one annotated.
+    fn matches(&self, diagnostic: &Value) -> bool {
+        let contains_message = |message: &str| {
+            let message = message.to_lowercase();
+            ["title", "message"].iter().any(|field| {
+                diagnostic[field]
+                    .as_str()
+                    .map_or(false, |text| text.to_lowercase().contains(&message))
+            })
+        };
+        diagnostic["kind"] == self.kind.as_str()
+            && diagnostic["code"] == self.code.as_str()
+            && diagnostic["span"]["start"]["line"] == self.line as u64
+            && self.message.as_deref().map_or(true, contains_message)
+    }
+}
+
+impl fmt::Display for DiagnosticAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} on line {}",
+            self.kind.to_uppercase(),
+            self.code,
+            self.line
+        )?;
+        match &self.message {
+            Some(message) => write!(f, ": {}", message),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Extracts the inline diagnostic annotations of a test input, returning the input without them.
+///
+/// An annotation `//~ KIND CODE: message` expects a diagnostic of that kind and code starting on
+/// the line of the annotation, whose title or message contains `message`. The message is optional.
+/// Each `^` after the marker, as in `//~^`, moves the expected line one line up.
+pub fn parse_diagnostic_annotations(
+    input: &str,
+) -> Result<(String, Vec<DiagnosticAnnotation>), String> {
+    let mut program = Vec::new();
+    let mut annotations = Vec::new();
+    for (i, line) in input.split('\n').enumerate() {
+        let at = match line.find(ANNOTATION_MARKER) {
+            Some(at) => at,
+            None => {
+                program.push(line);
+                continue;
+            }
+        };
+        program.push(line[..at].trim_end());
+
+        let annotation = &line[at + ANNOTATION_MARKER.len()..];
+        let up = annotation.chars().take_while(|&c| c == '^').count();
+        let annotation = &annotation[up..];
+        let (header, message) = match annotation.find(':') {
+            Some(colon) => (&annotation[..colon], Some(annotation[colon + 1..].trim())),
+            None => (annotation, None),
+        };
+        let mut header = header.split_whitespace();
+        let (kind, code) = match (header.next(), header.next(), header.next()) {
+            (Some(kind), Some(code), None) if up <= i => (kind, code),
+            _ => return Err(format!("Malformed annotation on line {}: {}", i + 1, line)),
+        };
+        annotations.push(DiagnosticAnnotation {
+            line: i + 1 - up,
+            kind: kind.to_lowercase(),
+            code: code.to_owned(),
+            message: message.filter(|m| !m.is_empty()).map(str::to_owned),
+        });
+    }
+    Ok((program.join("\n"), annotations))
+}
+
+/// Matches diagnostics emitted in the JSON diagnostic format against the annotations expecting
+/// them, in any order. Returns a description of every diagnostic without an annotation and every
+/// annotation without a diagnostic.
+pub fn match_diagnostic_annotations(
+    annotations: &[DiagnosticAnnotation],
+    diagnostics: &[Value],
+) -> Vec<String> {
+    let mut unmatched: Vec<_> = annotations.iter().collect();
+    let mut mismatches = Vec::new();
+    for diagnostic in diagnostics {
+        match unmatched.iter().position(|a| a.matches(diagnostic)) {
+            Some(i) => {
+                unmatched.remove(i);
+            }
+            None => mismatches.push(format!(
+                "Unexpected {} {} on line {}: {}",
+                diagnostic["kind"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_uppercase(),
+                diagnostic["code"].as_str().unwrap_or_default(),
+                diagnostic["span"]["start"]["line"],
+                diagnostic["title"].as_str().unwrap_or_default(),
+            )),
+        }
+    }
+    for annotation in unmatched {
+        mismatches.push(format!("Expected {}", annotation));
+    }
+    mismatches
+}
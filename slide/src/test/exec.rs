@@ -1,4 +1,5 @@
 use libtest_mimic::Outcome;
+use std::path::Path;
 
 pub type SlideOutput = (
     /*stdout*/ String,
@@ -6,7 +7,15 @@ pub type SlideOutput = (
     /*exit code*/ String,
 );
 
-pub fn run_slide(args: &str, input: &str) -> Result<SlideOutput, Outcome> {
+/// Runs slide with the args and input of the test at `test_path`. If there is a config file named
+/// after the test next to it, like `add.toml` for `add.slide`, slide reads options from that file.
+pub fn run_slide(args: &str, input: &str, test_path: &Path) -> Result<SlideOutput, Outcome> {
+    let config_path = test_path.with_extension("toml");
+    let config_args = if config_path.is_file() {
+        vec!["--config".to_owned(), config_path.display().to_string()]
+    } else {
+        vec![]
+    };
     let sanitized_args = vec!["slide"]
         .into_iter()
         .chain(
@@ -14,6 +23,7 @@ pub fn run_slide(args: &str, input: &str) -> Result<SlideOutput, Outcome> {
                 .filter(|l| !l.is_empty())
                 .flat_map(|arg| arg.split(' ')),
         )
+        .chain(config_args.iter().map(String::as_str))
         .chain(vec!["--", input].into_iter());
 
     let opts = match slide::get_opts(|args| args.get_matches_from_safe(sanitized_args), false) {
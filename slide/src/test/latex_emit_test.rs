@@ -15,20 +15,32 @@ pub struct LaTeXEmitTest {
 }
 
 impl LaTeXEmitTest {
-    /// Executes a LaTeX emit test, ensuring the code of a slide program's LaTeX emit renders as
-    /// expected from a golden image file.
+    /// Executes a LaTeX emit test, ensuring the code of a slide program's LaTeX emit is
+    /// well-formed and, unless LaTeX rendering is disabled, renders as expected from a golden image
+    /// file.
     /// If run in bless mode, the golden image is updated to be the actual image.
     pub fn drive_test(self, test_name: String, test_path: PathBuf) -> Outcome {
-        let (stdout, _stderr, _exitcode) = match run_slide(&self.args, &self.input) {
+        let (stdout, _stderr, _exitcode) = match run_slide(&self.args, &self.input, &test_path) {
             Ok(res) => res,
             Err(outcome) => return outcome,
         };
-        let stdout = stdout.trim();
+        // Owned, so that the failure report, which outlives this function, can print it.
+        let stdout = stdout.trim().to_owned();
+
+        if let Err(err) = libslide::check_latex(&stdout) {
+            return print_fail! {
+                Failure: "LaTeX emit is malformed: {}", err;
+                Text:    "{}", stdout;
+            };
+        }
+        if !*TEST_LATEX_EMIT {
+            return Outcome::Passed;
+        }
 
         let math_mode_inner = if stdout.starts_with('$') {
             &stdout[1..(stdout.len() - 1)]
         } else {
-            &stdout
+            &stdout[..]
         };
         let latex_img_url = format!(
             "https://latex.codecogs.com/png.latex?\\dpi{{400}}{}",
@@ -101,7 +113,7 @@ impl From<&SlideEmitTest> for LaTeXEmitTest {
     fn from(test: &SlideEmitTest) -> Self {
         Self {
             args: test.args.clone(),
-            input: test.input.clone(),
+            input: test.program.clone(),
         }
     }
 }
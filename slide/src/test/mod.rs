@@ -1,9 +1,11 @@
 use difference::{Changeset, Difference};
 use libtest_mimic::{run_tests, Arguments, LineFormat, Outcome, Test};
+use std::env;
 use std::error::Error;
 use std::ffi::OsStr;
 use std::fs;
 use std::path::{Path, PathBuf};
+use structopt::StructOpt;
 
 #[macro_use]
 mod common;
@@ -14,16 +16,40 @@ mod slide_emit_test;
 use common::*;
 use exec::*;
 use latex_emit_test::LaTeXEmitTest;
-use slide_emit_test::SlideEmitTest;
+use slide_emit_test::{bless_self_test, SlideEmitTest};
 
 #[allow(unused)]
 fn main() -> Result<(), Box<dyn Error>> {
-    let args = Arguments::from_args();
+    let args = parse_args();
     let test_files = collect_test_files()?;
-    let tests = test_files.into_iter().flat_map(TestCase::new).collect();
+    let mut tests: Vec<_> = test_files.into_iter().flat_map(TestCase::new).collect();
+    tests.push(Test {
+        name: "bless self-test".to_owned(),
+        kind: "harness".to_owned(),
+        is_ignored: false,
+        is_bench: false,
+        data: TestCase {
+            path: env::temp_dir().join(format!("slide-bless-{}.slide", std::process::id())),
+            variant: TestCaseVariant::BlessSelfTest,
+        },
+    });
     run_tests(&args, tests, TestCase::drive_test).exit();
 }
 
+/// Parses the arguments of the test runner.
+///
+/// The arguments libtest-mimic accepts are fixed, so `--bless` is taken out of the arguments here
+/// and passed on as `BLESS=1`, which it is equivalent to.
+fn parse_args() -> Arguments {
+    let mut args: Vec<_> = env::args().collect();
+    let num_args = args.len();
+    args.retain(|arg| arg != "--bless");
+    if args.len() != num_args {
+        env::set_var("BLESS", "1");
+    }
+    Arguments::from_iter(args)
+}
+
 /// Collects all `.slide` system test files, starting from slide/src/test and visiting all nested
 /// directories.
 fn collect_test_files() -> Result<Vec<Test<PathBuf>>, Box<dyn Error>> {
@@ -72,8 +98,9 @@ impl TestCase {
                 let closure: &Printer = read_lk.get(&outcome).unwrap().as_ref();
                 unsafe { fail!(&*(closure as *const Printer)) }
             }
-            TestCaseVariant::SlideEmit(tc) => tc.drive_test(test_name, test_path),
+            TestCaseVariant::SlideEmit(tc) => tc.drive_test(test_name, test_path, *BLESS),
             TestCaseVariant::LaTeXEmit(tc) => tc.drive_test(test_name, test_path),
+            TestCaseVariant::BlessSelfTest => bless_self_test(test_name, test_path),
         }
     }
 
@@ -85,7 +112,7 @@ impl TestCase {
 
         let mut builder = TestCaseBuilder::new(&mut cases, &test_file);
 
-        let slide_emit_test = match SlideEmitTest::new(&test_file, content) {
+        let slide_emit_test = match SlideEmitTest::new(&test_file, content, *BLESS) {
             Ok(test) => test,
             Err(failed_test) => {
                 builder.add(failed_test);
@@ -93,8 +120,7 @@ impl TestCase {
             }
         };
 
-        if *TEST_LATEX_EMIT
-            && (slide_emit_test.args.contains(" latex") || slide_emit_test.args.contains("=latex"))
+        if (slide_emit_test.args.contains(" latex") || slide_emit_test.args.contains("=latex"))
             && slide_emit_test.exitcode.trim() == "0"
         {
             // The check could be better, but this will do for now.
@@ -142,6 +168,8 @@ enum TestCaseVariant {
     SlideEmit(SlideEmitTest),
     LaTeXEmit(LaTeXEmitTest),
     FailedTestConstruction(ErrorMsgRef),
+    /// Checks that blessing a test file makes it pass.
+    BlessSelfTest,
 }
 
 macro_rules! variant_from_test {
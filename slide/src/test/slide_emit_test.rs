@@ -3,7 +3,7 @@ use super::*;
 use libtest_mimic::{LinePrinter, Outcome, Test};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Describes a test case for testing the slide emit of a program.
 #[derive(Clone)]
@@ -12,15 +12,27 @@ pub struct SlideEmitTest {
     pub annotations: HashMap<String, String>,
     pub annotation_order: Vec<String>,
     pub args: String,
+    /// The test input, including any inline diagnostic annotations.
     pub input: String,
+    /// The program run by the test, which is the input without its diagnostic annotations.
+    pub program: String,
+    pub expected_diagnostics: Vec<DiagnosticAnnotation>,
     pub stdout: String,
-    pub stderr: String,
+    /// The expected stderr. Tests with diagnostic annotations may omit it, in which case the
+    /// diagnostics are only checked against the annotations.
+    pub stderr: Option<String>,
     pub exitcode: String,
 }
 
 impl SlideEmitTest {
-    /// Creates a slide emit test case from a .slide test file.
-    pub fn new(test_file: &Test<PathBuf>, mut content: String) -> Result<Self, ErrorMsgRef> {
+    /// Creates a slide emit test case from a .slide test file. If the test is to be blessed,
+    /// missing blessable clauses are taken to be empty, except for a stderr clause omitted in
+    /// favor of diagnostic annotations.
+    pub fn new(
+        test_file: &Test<PathBuf>,
+        mut content: String,
+        bless: bool,
+    ) -> Result<Self, ErrorMsgRef> {
         // First we need to parse out annotations at the top of the file.
         let mut annotations = HashMap::<String, String>::new();
         let mut annotation_order = Vec::new();
@@ -41,6 +53,8 @@ impl SlideEmitTest {
         // Next we get all the clauses.
         let clause_names = ["args", "in", "stdout", "stderr", "exitcode"];
         let mut clauses = Vec::with_capacity(clause_names.len());
+        let mut program = String::new();
+        let mut expected_diagnostics = Vec::new();
         for clause in clause_names.iter() {
             let mut splits: Vec<_> = content
                 .split(&format!("{}\n", get_clause_delim(clause)))
@@ -48,11 +62,17 @@ impl SlideEmitTest {
                 .collect();
 
             if splits.len() != 3 {
-                if clause == &"args" || (*BLESS && can_be_blessed(clause)) {
+                if clause == &"stderr" && !expected_diagnostics.is_empty() {
+                    // Diagnostics are checked against the annotations instead.
+                    clauses.push(None);
+                    content = splits.pop().unwrap();
+                    continue;
+                }
+                if clause == &"args" || (bless && can_be_blessed(clause)) {
                     // Args are optional, so we can skip them if not found.
                     // If running in bless mode, blessable clauses will get updated later, so just
                     // make them empty for now.
-                    clauses.push("".into());
+                    clauses.push(Some("".into()));
                     content = splits.pop().unwrap();
                     continue;
                 }
@@ -71,44 +91,54 @@ impl SlideEmitTest {
                 //          ^ newline here
                 // ===in
                 clause_content.pop();
+
+                let (stripped, diagnostics) = parse_diagnostic_annotations(&clause_content)
+                    .map_err(Self::annotation_failure)?;
+                program = stripped;
+                expected_diagnostics = diagnostics;
             }
-            clauses.push(clause_content);
+            clauses.push(Some(clause_content));
         }
         let mut clauses = clauses.into_iter();
+        let mut next_clause = || clauses.next().unwrap();
 
         Ok(SlideEmitTest {
             annotations,
             annotation_order,
-            args: clauses.next().unwrap(),
-            input: clauses.next().unwrap(),
-            stdout: clauses.next().unwrap(),
-            stderr: clauses.next().unwrap(),
-            exitcode: clauses.next().unwrap(),
+            args: next_clause().unwrap(),
+            input: next_clause().unwrap(),
+            program,
+            expected_diagnostics,
+            stdout: next_clause().unwrap(),
+            stderr: next_clause(),
+            exitcode: next_clause().unwrap(),
         })
     }
 
     /// Executes a slide emit test, ensuring the stdout and stderr of the slide program is as
-    /// expected from the test file.
-    /// If run in bless mode, the test file is updated with the actual stdout and stderr.
+    /// expected from the test file, and that the diagnostics it emits match its diagnostic
+    /// annotations.
+    /// If `bless` is set, a test file whose expectations do not match is updated with the actual
+    /// stdout, stderr, and exit code instead of failing. Tests that panic are never blessed, and
+    /// neither are diagnostic annotations.
     /// If run in fail-todo mode, the test fails on any @TODO annotations.
-    pub fn drive_test(self, test_name: String, test_path: PathBuf) -> Outcome {
+    pub fn drive_test(self, test_name: String, test_path: PathBuf, bless: bool) -> Outcome {
         if *FAIL_TODO && self.annotations.contains_key("@TODO") {
             return print_fail! {
                 Failure: "Test is marked @TODO, which the test runner is set to fail on.";
             };
         }
 
-        let (stdout, stderr, exitcode) = match run_slide(&self.args, &self.input) {
+        // A panic fails the test before it can be blessed, since there is no output to bless.
+        let (stdout, stderr, exitcode) = match run_slide(&self.args, &self.program, &test_path) {
             Ok(res) => res,
             Err(outcome) => return outcome,
         };
 
-        if *BLESS {
-            let blessed = self.make_bless_file(&stdout, &stderr, &exitcode);
-            return match fs::write(test_path, blessed) {
-                Ok(_) => Outcome::Passed,
-                Err(e) => print_fail! { Failure: "{}", e; },
-            };
+        if !self.expected_diagnostics.is_empty() {
+            if let outcome @ Outcome::Failed { .. } = self.check_diagnostics(&test_path) {
+                return outcome;
+            }
         }
 
         // Right ends of bless content may be inaccurate because we always force a newline, so
@@ -120,18 +150,28 @@ impl SlideEmitTest {
             };
         }
 
-        if t!(stdout) != t!(self.stdout)
-            || t!(stderr) != t!(self.stderr)
-            || t!(exitcode) != t!(self.exitcode)
-        {
+        let stderr_matches = match &self.stderr {
+            Some(expected) => t!(stderr) == t!(expected),
+            None => true,
+        };
+
+        if t!(stdout) != t!(self.stdout) || !stderr_matches || t!(exitcode) != t!(self.exitcode) {
+            if bless {
+                let blessed = self.make_bless_file(&stdout, &stderr, &exitcode);
+                return match fs::write(test_path, blessed) {
+                    Ok(_) => Outcome::Passed,
+                    Err(e) => print_fail! { Failure: "{}", e; },
+                };
+            }
+
             return fail! { move |printer: &mut dyn LinePrinter| {
                 if t!(stdout) != t!(self.stdout) {
                     printer! { printer Text: "Mismatch in stdout:"; };
                     print_diff(printer, &t!(self.stdout), &t!(stdout));
                 }
-                if t!(stderr) != t!(self.stderr) {
+                if !stderr_matches {
                     printer! { printer Text: "Mismatch in stderr:"; };
-                    print_diff(printer, &t!(self.stderr), &t!(stderr));
+                    print_diff(printer, &t!(self.stderr.as_ref().unwrap()), &t!(stderr));
                 }
                 if t!(exitcode) != t!(self.exitcode) {
                     printer! { printer Text: "Mismatch in exit code:"; };
@@ -147,6 +187,43 @@ impl SlideEmitTest {
         Outcome::Passed
     }
 
+    /// Runs the program of the test again with JSON diagnostics, and matches the diagnostics it
+    /// emits against the diagnostic annotations of the test.
+    fn check_diagnostics(&self, test_path: &Path) -> Outcome {
+        let args = format!("{}\n--diagnostic-format json", self.args);
+        let (_, stderr, _) = match run_slide(&args, &self.program, test_path) {
+            Ok(res) => res,
+            Err(outcome) => return outcome,
+        };
+        let diagnostics: Vec<serde_json::Value> = if stderr.trim().is_empty() {
+            vec![]
+        } else {
+            match serde_json::from_str(&stderr) {
+                Ok(diagnostics) => diagnostics,
+                Err(e) => {
+                    return print_fail! {
+                        Failure: "Failed to read the diagnostics of the test: {}", e;
+                        Text: "{}", stderr;
+                    }
+                }
+            }
+        };
+
+        let mismatches = match_diagnostic_annotations(&self.expected_diagnostics, &diagnostics);
+        if mismatches.is_empty() {
+            return Outcome::Passed;
+        }
+        fail! { move |printer: &mut dyn LinePrinter| {
+            printer! { printer Text: "Mismatch in diagnostic annotations:"; };
+            for mismatch in mismatches.iter() {
+                printer! { printer Failure: "  {}", mismatch; };
+            }
+            printer! { printer
+                Suggestion: "Diagnostic annotations are not blessed; update them by hand.";
+            };
+        }}
+    }
+
     /// Generates the actual ("bless"ed) contents of a test case.
     fn make_bless_file(&self, stdout: &str, stderr: &str, exitcode: &str) -> String {
         let mut content = String::with_capacity(256);
@@ -179,7 +256,9 @@ impl SlideEmitTest {
         }
         push("in", &self.input);
         push("stdout", stdout);
-        push("stderr", stderr);
+        if self.stderr.is_some() {
+            push("stderr", stderr);
+        }
         push("exitcode", exitcode);
         content.pop(); // drop trailing newline
         content
@@ -209,15 +288,101 @@ impl SlideEmitTest {
                 };
             }
         };
+        Self::register_failure(Box::new(printer))
+    }
+
+    /// Creates an error for a malformed diagnostic annotation in a test file and returns a
+    /// reference to where it is stored in TEST_CONSTRUCTION_FAIL.
+    fn annotation_failure(msg: String) -> ErrorMsgRef {
+        let printer = move |printer: &mut dyn LinePrinter| {
+            printer! { printer
+                Failure:    "{}", msg;
+                Suggestion: "Diagnostic annotations have the form `//~ KIND CODE: message`, like \
+                            `//~ ERROR S0001: invalid token`. The message is optional.";
+            };
+        };
+        Self::register_failure(Box::new(printer))
+    }
+
+    /// Stores a test construction failure in TEST_CONSTRUCTION_FAIL, returning a reference to it.
+    fn register_failure(printer: Box<Printer>) -> ErrorMsgRef {
         let mut outcome_lk = TEST_CONSTRUCTION_FAIL
             .write()
             .expect("Test outcome map poisoned.");
         let i = outcome_lk.len();
-        outcome_lk.insert(i, Box::new(printer));
+        outcome_lk.insert(i, printer);
         i
     }
 }
 
+/// A test file whose expected output is out of date, to be blessed by [bless_self_test].
+const BLESS_FIXTURE: &str = r"@fixture: kept when blessed
+
+!!!args
+-o s-expression
+!!!args
+
+===in
+1 + 2 + x
+===in
+
+~~~stdout
+(+ 3 y)
+~~~stdout
+
+~~~stderr
+~~~stderr
+
+~~~exitcode
+1
+~~~exitcode
+";
+
+/// Blesses a copy of [BLESS_FIXTURE] at `test_path`, ensuring that the fixture fails before it is
+/// blessed, passes after, and keeps its annotations and arguments.
+pub fn bless_self_test(test_name: String, test_path: PathBuf) -> Outcome {
+    let outcome = bless_fixture(test_name, &test_path);
+    let _ = fs::remove_file(&test_path);
+    outcome
+}
+
+fn bless_fixture(test_name: String, test_path: &Path) -> Outcome {
+    let test_file = Test {
+        name: test_name.clone(),
+        kind: "harness".to_owned(),
+        is_ignored: false,
+        is_bench: false,
+        data: test_path.to_path_buf(),
+    };
+    let drive = |bless: bool| {
+        let content = fs::read_to_string(test_path).unwrap();
+        match SlideEmitTest::new(&test_file, content, bless) {
+            Ok(test) => test.drive_test(test_name.clone(), test_path.to_path_buf(), bless),
+            Err(_) => print_fail! { Failure: "Failed to read the bless fixture."; },
+        }
+    };
+
+    if let Err(e) = fs::write(test_path, BLESS_FIXTURE) {
+        return print_fail! { Failure: "{}", e; };
+    }
+    if let Outcome::Passed = drive(false) {
+        return print_fail! { Failure: "The bless fixture passed before it was blessed."; };
+    }
+    for bless in &[true, false] {
+        if let outcome @ Outcome::Failed { .. } = drive(*bless) {
+            return outcome;
+        }
+    }
+
+    let blessed = fs::read_to_string(test_path).unwrap();
+    if !blessed.starts_with("@fixture: kept when blessed\n")
+        || !blessed.contains("!!!args\n-o s-expression\n!!!args")
+    {
+        return print_fail! { Failure: "Blessing dropped the headers of the fixture:\n{}", blessed; };
+    }
+    Outcome::Passed
+}
+
 /// Returns the delimiter for a test case clause in a .slide test file.
 fn get_clause_delim(clause: &str) -> String {
     let prefix = match clause {